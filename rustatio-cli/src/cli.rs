@@ -7,6 +7,10 @@ use std::path::PathBuf;
 #[command(name = "rustatio")]
 #[command(author, version, about = "BitTorrent ratio faker CLI", long_about = None)]
 pub struct Cli {
+    /// Directory for config.toml and saved sessions (overrides RUSTATIO_DATA_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -28,25 +32,33 @@ pub enum Commands {
         #[arg(value_name = "TORRENT_FILE")]
         torrent: PathBuf,
 
-        /// Client to emulate
-        #[arg(short, long, value_enum, default_value = "transmission")]
-        client: ClientArg,
+        /// Client to emulate [default: the profile's, transmission]
+        #[arg(short, long, value_enum)]
+        client: Option<ClientArg>,
 
-        /// Client version string (e.g., "5.1.4")
+        /// Client version string (e.g., "5.1.4"), one of those listed by `rustatio clients`
         #[arg(long, value_name = "VERSION")]
         client_version: Option<String>,
 
-        /// Upload rate in KB/s
-        #[arg(short, long, default_value = "0.0", value_name = "KB/s")]
-        upload_rate: f64,
+        /// Custom User-Agent, replacing the client preset
+        #[arg(long, value_name = "USER_AGENT")]
+        user_agent: Option<String>,
 
-        /// Download rate in KB/s
-        #[arg(short, long, default_value = "700.0", value_name = "KB/s")]
-        download_rate: f64,
+        /// Custom peer ID prefix (1-19 chars, e.g. "-XX1234-"), replacing the client preset
+        #[arg(long, value_name = "PREFIX")]
+        peer_id_prefix: Option<String>,
 
-        /// Port to announce
-        #[arg(short, long, default_value = "59859")]
-        port: u16,
+        /// Upload rate in KB/s [default: the profile or config file's, 700]
+        #[arg(short, long, value_name = "KB/s")]
+        upload_rate: Option<f64>,
+
+        /// Download rate in KB/s [default: the profile or config file's, 0]
+        #[arg(short, long, value_name = "KB/s")]
+        download_rate: Option<f64>,
+
+        /// Port to announce [default: the profile or config file's, 59859]
+        #[arg(short, long)]
+        port: Option<u16>,
 
         /// Initial completion percentage (0-100)
         #[arg(long, default_value = "100.0", value_name = "PERCENT")]
@@ -76,17 +88,34 @@ pub enum Commands {
         #[arg(long, value_name = "HOURS", default_value = "744.0")]
         stop_time: Option<f64>,
 
-        /// Stop when there are no leechers
-        #[arg(long, action = clap::ArgAction::SetTrue, default_value_t = false)]
-        stop_when_no_leechers: bool,
+        /// Stop when there are no leechers (`--stop-when-no-leechers=false` overrides a profile)
+        #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+        stop_when_no_leechers: Option<bool>,
+
+        /// Once a stop condition is met, keep announcing without transferring instead of stopping
+        #[arg(long)]
+        idle_seed: bool,
+
+        /// Measure the ratio/uploaded/downloaded stop targets on this session or on the totals
+        #[arg(long, value_enum, default_value = "session")]
+        stop_basis: RatioBasisArg,
+
+        /// Never report a total ratio above this; uploading pauses at the cap
+        #[arg(long, value_name = "RATIO")]
+        max_ratio: Option<f64>,
+
+        /// Upload at most this many times the torrent size per hour between announces, keeping
+        /// reported deltas believable (lower is safer but slower)
+        #[arg(long, value_name = "COPIES")]
+        max_uploads_per_hour: Option<f64>,
 
         /// Disable rate randomization
         #[arg(long)]
         no_randomize: bool,
 
-        /// Randomization range percentage (default: 20%)
-        #[arg(long, default_value = "50.0", value_name = "PERCENT")]
-        random_range: f64,
+        /// Randomization range percentage [default: the preset or config file's, 50]
+        #[arg(long, value_name = "PERCENT")]
+        random_range: Option<f64>,
 
         /// Enable progressive rate adjustment
         #[arg(long)]
@@ -104,6 +133,14 @@ pub enum Commands {
         #[arg(long, default_value = "1.0", value_name = "HOURS")]
         progressive_duration: f64,
 
+        /// Shape of the progressive ramp
+        #[arg(long, value_enum, default_value = "linear")]
+        progressive_curve: CurveArg,
+
+        /// Only transfer during these local hours, e.g. "22-6,12-14" (announces continue outside)
+        #[arg(long, value_name = "RANGES", value_delimiter = ',', value_parser = parse_hour_range)]
+        active_hours: Option<Vec<(u8, u8)>>,
+
         /// How many times to retry an announce on failure
         #[arg(long, default_value = "10", value_name = "COUNT")]
         announce_max_retries: u32,
@@ -116,22 +153,69 @@ pub enum Commands {
         #[arg(long, default_value = "1800", value_name = "SECONDS")]
         announce_interval: u64,
 
+        /// Never announce less often than every this many seconds, even if the tracker asks for longer
+        /// (the tracker's min interval still applies)
+        #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..=86400))]
+        max_announce_interval: Option<u64>,
+
+        /// Never announce more often than every this many seconds
+        #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..=86400))]
+        min_announce_interval: Option<u64>,
+
+        /// Randomly shift each periodic announce by up to this percent of the interval (0-50)
+        /// [default: the preset's, 0]
+        #[arg(long, value_name = "PERCENT")]
+        announce_jitter: Option<f64>,
+
+        /// Extra query parameter appended to every announce, e.g. "uid=42" (repeatable)
+        #[arg(long = "announce-param", value_name = "KEY=VALUE", value_parser = parse_announce_param)]
+        announce_params: Vec<(String, String)>,
+
+        /// Announce to every http(s) tracker of the torrent, not only the first one
+        #[arg(long)]
+        announce_all_trackers: bool,
+
+        /// How seeders/leechers from several trackers are combined (with --announce-all-trackers)
+        #[arg(long, value_enum, default_value = "max")]
+        swarm_aggregation: SwarmAggregationArg,
+
         /// Stats update interval in seconds (background loop)
         #[arg(long, default_value = "5", value_name = "SECONDS")]
         update_interval: u64,
 
+        /// Tracker request timeout in seconds (1-300)
+        #[arg(long, default_value = "30", value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..=300))]
+        timeout: u64,
+
         /// Path to config file
         #[arg(long, value_name = "FILE")]
         config: Option<PathBuf>,
 
+        /// Named profile from the config file, or a built-in preset (conservative, balanced, aggressive)
+        /// filling the rate options left unset
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// Output JSON Lines instead of TUI (for integrations)
         #[arg(long)]
         json: bool,
 
+        /// Send this single announce, print the tracker's response and exit
+        #[arg(long, value_enum, value_name = "EVENT")]
+        once: Option<OnceEventArg>,
+
         /// Stats update interval in seconds (JSON mode only)
         #[arg(long, default_value = "1", value_name = "SECONDS")]
         interval: u64,
 
+        /// Heartbeat interval in seconds, 0 to disable (JSON mode only)
+        #[arg(long, default_value = "5", value_name = "SECONDS")]
+        heartbeat_interval: u64,
+
+        /// Decimals to round reported rates to (JSON mode only)
+        #[arg(long, default_value = "1", value_name = "DECIMALS", value_parser = clap::value_parser!(u32).range(0..=6))]
+        rate_decimals: u32,
+
         /// Resume from saved session (if exists for this torrent)
         #[arg(long)]
         resume: bool,
@@ -144,6 +228,10 @@ pub enum Commands {
         #[arg(long)]
         no_save_session: bool,
 
+        /// Also save session progress every this many seconds while running, 0 to only save on exit
+        #[arg(long, default_value = "30", value_name = "SECONDS")]
+        session_save_interval: u64,
+
         #[arg(long)]
         infinite_retry_after_max: bool,
     },
@@ -178,9 +266,60 @@ pub enum Commands {
         #[arg(long, default_value = "1", value_name = "SECONDS")]
         interval: u64,
 
+        /// Heartbeat interval in seconds, 0 to disable (JSON mode only)
+        #[arg(long, default_value = "5", value_name = "SECONDS")]
+        heartbeat_interval: u64,
+
+        /// Decimals to round reported rates to (JSON mode only)
+        #[arg(long, default_value = "1", value_name = "DECIMALS", value_parser = clap::value_parser!(u32).range(0..=6))]
+        rate_decimals: u32,
+
         /// Don't save session progress on exit
         #[arg(long)]
         no_save_session: bool,
+
+        /// Also save session progress every this many seconds while running, 0 to only save on exit
+        #[arg(long, default_value = "30", value_name = "SECONDS")]
+        session_save_interval: u64,
+    },
+
+    /// Run every torrent in a directory with a live multi-instance dashboard
+    Dash {
+        /// Directory containing the .torrent files
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+
+        /// Client to emulate
+        #[arg(short, long, value_enum, default_value = "transmission")]
+        client: ClientArg,
+
+        /// Client version string (e.g., "5.1.4"), one of those listed by `rustatio clients`
+        #[arg(long, value_name = "VERSION")]
+        client_version: Option<String>,
+
+        /// Upload rate in KB/s, per torrent
+        #[arg(short, long, default_value = "0.0", value_name = "KB/s")]
+        upload_rate: f64,
+
+        /// Download rate in KB/s, per torrent
+        #[arg(short, long, default_value = "700.0", value_name = "KB/s")]
+        download_rate: f64,
+
+        /// Port to announce (shared by all torrents, like a real client)
+        #[arg(short, long, default_value = "59859")]
+        port: u16,
+
+        /// Initial completion percentage (0-100)
+        #[arg(long, default_value = "100.0", value_name = "PERCENT")]
+        completion: f64,
+
+        /// Stop a torrent when its session ratio reaches this value
+        #[arg(long, value_name = "RATIO")]
+        stop_ratio: Option<f64>,
+
+        /// Stop a torrent after uploading this many gigabytes
+        #[arg(long, value_name = "GB")]
+        stop_uploaded: Option<f64>,
     },
 
     /// Display information about a torrent file
@@ -194,6 +333,21 @@ pub enum Commands {
         json: bool,
     },
 
+    /// Check a torrent file and configuration without starting
+    Validate {
+        /// Path to the .torrent file
+        #[arg(value_name = "TORRENT_FILE")]
+        torrent: PathBuf,
+
+        /// Path to config file
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// List supported BitTorrent clients
     Clients {
         /// Output as JSON
@@ -234,6 +388,26 @@ pub enum Commands {
         #[arg(long)]
         path: bool,
 
+        /// Write all sessions to this file as a JSON array
+        #[arg(long, value_name = "FILE", conflicts_with = "import")]
+        export: Option<PathBuf>,
+
+        /// Read sessions from a file written by --export into the sessions directory
+        #[arg(long, value_name = "FILE")]
+        import: Option<PathBuf>,
+
+        /// With --import, overwrite sessions that already exist instead of skipping them
+        #[arg(long, requires = "import")]
+        force: bool,
+
+        /// Show totals across all sessions
+        #[arg(long)]
+        stats: bool,
+
+        /// Compare a saved session with its torrent file and the tracker's current swarm (read-only)
+        #[arg(long, value_name = "INFO_HASH")]
+        diff: Option<String>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -253,6 +427,19 @@ pub enum ClientArg {
     Utorrent,
     Transmission,
     Deluge,
+    Biglybt,
+}
+
+impl From<rustatio_core::ClientType> for ClientArg {
+    fn from(client: rustatio_core::ClientType) -> Self {
+        match client {
+            rustatio_core::ClientType::QBittorrent => ClientArg::Qbittorrent,
+            rustatio_core::ClientType::UTorrent => ClientArg::Utorrent,
+            rustatio_core::ClientType::Transmission => ClientArg::Transmission,
+            rustatio_core::ClientType::Deluge => ClientArg::Deluge,
+            rustatio_core::ClientType::BiglyBT => ClientArg::Biglybt,
+        }
+    }
 }
 
 impl From<ClientArg> for rustatio_core::ClientType {
@@ -262,10 +449,159 @@ impl From<ClientArg> for rustatio_core::ClientType {
             ClientArg::Utorrent => rustatio_core::ClientType::UTorrent,
             ClientArg::Transmission => rustatio_core::ClientType::Transmission,
             ClientArg::Deluge => rustatio_core::ClientType::Deluge,
+            ClientArg::Biglybt => rustatio_core::ClientType::BiglyBT,
         }
     }
 }
 
+/// Parse an hour range like "22-6"
+fn parse_hour_range(s: &str) -> Result<(u8, u8), String> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| format!("expected START-END, got '{}'", s))?;
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|h| *h <= 23)
+            .ok_or_else(|| format!("'{}' is not an hour between 0 and 23", v))
+    };
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// Parse a custom announce parameter like "uid=42"
+fn parse_announce_param(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", s))?;
+    let param = (name.to_string(), value.to_string());
+    rustatio_core::validate_announce_params(std::slice::from_ref(&param)).map_err(|e| e.to_string())?;
+    Ok(param)
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CurveArg {
+    Linear,
+    EaseInOut,
+    Logarithmic,
+    Exponential,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OnceEventArg {
+    Started,
+    Stopped,
+    Completed,
+}
+
+impl From<OnceEventArg> for rustatio_core::protocol::TrackerEvent {
+    fn from(event: OnceEventArg) -> Self {
+        match event {
+            OnceEventArg::Started => rustatio_core::protocol::TrackerEvent::Started,
+            OnceEventArg::Stopped => rustatio_core::protocol::TrackerEvent::Stopped,
+            OnceEventArg::Completed => rustatio_core::protocol::TrackerEvent::Completed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SwarmAggregationArg {
+    Max,
+    Sum,
+}
+
+impl From<SwarmAggregationArg> for rustatio_core::SwarmAggregation {
+    fn from(aggregation: SwarmAggregationArg) -> Self {
+        match aggregation {
+            SwarmAggregationArg::Max => rustatio_core::SwarmAggregation::Max,
+            SwarmAggregationArg::Sum => rustatio_core::SwarmAggregation::Sum,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RatioBasisArg {
+    Total,
+    Session,
+}
+
+impl From<RatioBasisArg> for rustatio_core::RatioBasis {
+    fn from(basis: RatioBasisArg) -> Self {
+        match basis {
+            RatioBasisArg::Total => rustatio_core::RatioBasis::Total,
+            RatioBasisArg::Session => rustatio_core::RatioBasis::Session,
+        }
+    }
+}
+
+impl From<CurveArg> for rustatio_core::Curve {
+    fn from(curve: CurveArg) -> Self {
+        match curve {
+            CurveArg::Linear => rustatio_core::Curve::Linear,
+            CurveArg::EaseInOut => rustatio_core::Curve::EaseInOut,
+            CurveArg::Logarithmic => rustatio_core::Curve::Logarithmic,
+            CurveArg::Exponential => rustatio_core::Curve::Exponential,
+        }
+    }
+}
+
+/// Rate and randomization options of `start` that a built-in preset can fill, `None` when the
+/// flag was left out
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateOptions {
+    /// Upload rate in KB/s
+    pub upload_rate: Option<f64>,
+    pub randomize: Option<bool>,
+    /// Randomization range percentage
+    pub random_range: Option<f64>,
+    /// Announce jitter percentage
+    pub announce_jitter: Option<f64>,
+}
+
+impl RateOptions {
+    /// Built-in `--profile` presets, used when the config file has no profile of that name
+    pub fn preset(name: &str) -> Option<RateOptions> {
+        let (upload_rate, randomize, random_range, announce_jitter) = match name {
+            "conservative" => (50.0, true, 30.0, 10.0),
+            "balanced" => (200.0, true, 50.0, 5.0),
+            "aggressive" => (1000.0, false, 20.0, 0.0),
+            _ => return None,
+        };
+        Some(RateOptions {
+            upload_rate: Some(upload_rate),
+            randomize: Some(randomize),
+            random_range: Some(random_range),
+            announce_jitter: Some(announce_jitter),
+        })
+    }
+
+    /// Fill the options left unset from `preset`, so explicit flags win
+    pub fn with_preset(self, preset: &RateOptions) -> RateOptions {
+        RateOptions {
+            upload_rate: self.upload_rate.or(preset.upload_rate),
+            randomize: self.randomize.or(preset.randomize),
+            random_range: self.random_range.or(preset.random_range),
+            announce_jitter: self.announce_jitter.or(preset.announce_jitter),
+        }
+    }
+
+    /// Fill the options still unset from the config file. Applied after `with_preset`, so the order
+    /// is flags, then preset, then config file
+    pub fn with_config_defaults(self, faker: &rustatio_core::FakerSettings) -> RateOptions {
+        RateOptions {
+            upload_rate: self.upload_rate.or(Some(faker.default_upload_rate)),
+            randomize: self.randomize.or(Some(faker.default_randomize)),
+            random_range: self.random_range.or(Some(faker.default_random_range_percent)),
+            ..self
+        }
+    }
+}
+
+/// Stop ratio from `--stop-ratio`, falling back to the config file's default when enabled there
+pub fn effective_stop_ratio(stop_ratio: Option<f64>, faker: &rustatio_core::FakerSettings) -> Option<f64> {
+    stop_ratio.or(faker.default_stop_ratio_enabled.then_some(faker.default_stop_ratio))
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum ShellArg {
     Bash,
@@ -286,3 +622,126 @@ impl From<ShellArg> for Shell {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_start(args: &[&str]) -> Commands {
+        let args = ["rustatio", "start", "file.torrent"].iter().chain(args);
+        Cli::try_parse_from(args).unwrap().command
+    }
+
+    #[test]
+    fn test_start_flags_left_out_are_none() {
+        let Commands::Start {
+            client,
+            download_rate,
+            port,
+            stop_when_no_leechers,
+            ..
+        } = parse_start(&[])
+        else {
+            panic!("expected start");
+        };
+        assert!(client.is_none() && download_rate.is_none() && port.is_none());
+        assert_eq!(stop_when_no_leechers, None);
+
+        // Explicit values are kept even when they equal the built-in defaults
+        let Commands::Start {
+            client,
+            port,
+            stop_when_no_leechers,
+            ..
+        } = parse_start(&["--client", "transmission", "--port", "59859", "--stop-when-no-leechers"])
+        else {
+            panic!("expected start");
+        };
+        assert!(matches!(client, Some(ClientArg::Transmission)));
+        assert_eq!(port, Some(59859));
+        assert_eq!(stop_when_no_leechers, Some(true));
+
+        let Commands::Start {
+            stop_when_no_leechers, ..
+        } = parse_start(&["--stop-when-no-leechers=false"])
+        else {
+            panic!("expected start");
+        };
+        assert_eq!(stop_when_no_leechers, Some(false));
+    }
+
+    #[test]
+    fn test_presets() {
+        let cases = [
+            ("conservative", 50.0, true, 30.0, 10.0),
+            ("balanced", 200.0, true, 50.0, 5.0),
+            ("aggressive", 1000.0, false, 20.0, 0.0),
+        ];
+        for (name, upload_rate, randomize, random_range, announce_jitter) in cases {
+            let rates = RateOptions::default().with_preset(&RateOptions::preset(name).unwrap());
+            assert_eq!(rates.upload_rate, Some(upload_rate), "{name}");
+            assert_eq!(rates.randomize, Some(randomize), "{name}");
+            assert_eq!(rates.random_range, Some(random_range), "{name}");
+            assert_eq!(rates.announce_jitter, Some(announce_jitter), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_preset_keeps_explicit_flags() {
+        // Explicit flags win, even when they equal the built-in defaults
+        let explicit = RateOptions {
+            upload_rate: Some(75.0),
+            random_range: Some(50.0),
+            announce_jitter: Some(0.0),
+            ..Default::default()
+        };
+        let rates = explicit.with_preset(&RateOptions::preset("conservative").unwrap());
+        assert_eq!(rates.upload_rate, Some(75.0));
+        assert_eq!(rates.random_range, Some(50.0));
+        assert_eq!(rates.announce_jitter, Some(0.0));
+        assert_eq!(rates.randomize, Some(true));
+        assert!(RateOptions::preset("unknown").is_none());
+    }
+
+    #[test]
+    fn test_config_defaults_fill_unset_flags() {
+        let faker = rustatio_core::FakerSettings {
+            default_randomize: false,
+            default_random_range_percent: 15.0,
+            default_stop_ratio: 3.0,
+            default_stop_ratio_enabled: true,
+            ..Default::default()
+        };
+
+        let rates = RateOptions::default().with_config_defaults(&faker);
+        assert_eq!(rates.randomize, Some(false));
+        assert_eq!(rates.random_range, Some(15.0));
+        assert_eq!(rates.upload_rate, Some(faker.default_upload_rate));
+        assert_eq!(rates.announce_jitter, None);
+        assert_eq!(effective_stop_ratio(None, &faker), Some(3.0));
+
+        // Explicit flags win over the config file
+        let explicit = RateOptions {
+            random_range: Some(40.0),
+            ..Default::default()
+        };
+        assert_eq!(explicit.with_config_defaults(&faker).random_range, Some(40.0));
+        assert_eq!(effective_stop_ratio(Some(1.5), &faker), Some(1.5));
+
+        // A disabled default stop ratio is not applied
+        // A preset wins over the config file, even where it matches the built-in default
+        let rates = RateOptions::default()
+            .with_preset(&RateOptions::preset("balanced").unwrap())
+            .with_config_defaults(&faker);
+        assert_eq!(rates.random_range, Some(50.0));
+        assert_eq!(rates.randomize, Some(true));
+        assert_eq!(rates.upload_rate, Some(200.0));
+
+        let faker = rustatio_core::FakerSettings::default();
+        assert_eq!(effective_stop_ratio(None, &faker), None);
+        assert_eq!(
+            RateOptions::default().with_config_defaults(&faker).randomize,
+            Some(true)
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use serde::Deserialize;
 
-/// Commands that can be sent via stdin in JSON mode
+/// Commands that can be sent via stdin in JSON mode, one JSON object per line,
+/// e.g. `{"command":"pause"}` or `{"command":"set_rate","upload":100}`
 #[derive(Debug, Deserialize)]
 #[serde(tag = "command", rename_all = "snake_case")]
 pub enum InputCommand {
@@ -18,6 +19,9 @@ pub enum InputCommand {
 
     /// Get current stats (triggers immediate stats event)
     Stats,
+
+    /// Change the upload and/or download rate (KB/s) without restarting
+    SetRate { upload: Option<f64>, download: Option<f64> },
 }
 
 impl InputCommand {
@@ -60,4 +64,27 @@ mod tests {
         let cmd = InputCommand::parse(r#"{"command":"stats"}"#).unwrap();
         assert!(matches!(cmd, InputCommand::Stats));
     }
+
+    #[test]
+    fn test_parse_set_rate() {
+        let cmd = InputCommand::parse(r#"{"command":"set_rate","upload":100}"#).unwrap();
+        assert!(matches!(
+            cmd,
+            InputCommand::SetRate {
+                upload: Some(u),
+                download: None
+            } if u == 100.0
+        ));
+
+        let cmd = InputCommand::parse(r#"{"command":"set_rate","upload":50.5,"download":0}"#).unwrap();
+        assert!(matches!(
+            cmd,
+            InputCommand::SetRate {
+                upload: Some(_),
+                download: Some(d)
+            } if d == 0.0
+        ));
+
+        assert!(InputCommand::parse(r#"{"command":"set_rate","upload":"fast"}"#).is_err());
+    }
 }
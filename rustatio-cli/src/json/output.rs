@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
-use rustatio_core::{FakerState, FakerStats, TorrentInfo};
+use rustatio_core::protocol::TrackerError;
+use rustatio_core::{round_to_decimals, ClientFingerprint, FakerError, FakerState, FakerStats, TorrentInfo};
 use serde::Serialize;
 
 /// All JSON output events
@@ -30,6 +31,12 @@ pub enum OutputEvent {
     /// Scrape response
     Scrape(ScrapeEvent),
 
+    /// Rates changed via a `set_rate` command
+    RatesChanged(RatesChangedEvent),
+
+    /// Keepalive emitted on a short fixed cadence
+    Heartbeat(HeartbeatEvent),
+
     /// Faker stopped
     Stopped(StoppedEvent),
 
@@ -78,6 +85,7 @@ pub struct StartedEvent {
     pub client: String,
     pub client_version: String,
     pub port: u16,
+    pub profile: Option<String>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -144,8 +152,9 @@ pub struct StatsEvent {
     pub timestamp: DateTime<Utc>,
 }
 
-impl From<&FakerStats> for StatsEvent {
-    fn from(stats: &FakerStats) -> Self {
+impl StatsEvent {
+    /// Project faker stats into a stats event, rounding rates to `rate_decimals` decimals
+    pub fn from_stats(stats: &FakerStats, rate_decimals: u32) -> Self {
         StatsEvent {
             uploaded: stats.uploaded,
             downloaded: stats.downloaded,
@@ -154,10 +163,10 @@ impl From<&FakerStats> for StatsEvent {
             session_ratio: stats.session_ratio,
             session_uploaded: stats.session_uploaded,
             session_downloaded: stats.session_downloaded,
-            upload_rate: stats.current_upload_rate,
-            download_rate: stats.current_download_rate,
-            avg_upload_rate: stats.average_upload_rate,
-            avg_download_rate: stats.average_download_rate,
+            upload_rate: round_to_decimals(stats.current_upload_rate, rate_decimals),
+            download_rate: round_to_decimals(stats.current_download_rate, rate_decimals),
+            avg_upload_rate: round_to_decimals(stats.average_upload_rate, rate_decimals),
+            avg_download_rate: round_to_decimals(stats.average_download_rate, rate_decimals),
             seeders: stats.seeders,
             leechers: stats.leechers,
             upload_progress: stats.upload_progress,
@@ -181,6 +190,8 @@ fn format_state(state: &FakerState) -> String {
         FakerState::Paused => "paused".to_string(),
         FakerState::Stopped => "stopped".to_string(),
         FakerState::Completed => "completed".to_string(),
+        FakerState::Seeding => "seeding".to_string(),
+        FakerState::Errored => "errored".to_string(),
     }
 }
 
@@ -194,6 +205,20 @@ pub struct ResumedEvent {
     pub timestamp: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct RatesChangedEvent {
+    pub upload_rate: f64,
+    pub download_rate: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeartbeatEvent {
+    pub uptime_secs: u64,
+    pub state: String,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ScrapeEvent {
     pub seeders: i64,
@@ -230,10 +255,40 @@ pub enum StopReason {
 
 #[derive(Debug, Serialize)]
 pub struct ErrorEvent {
+    pub code: ErrorCode,
     pub message: String,
     pub timestamp: DateTime<Utc>,
 }
 
+/// Machine-readable category of an error event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The .torrent file could not be read or parsed
+    TorrentParse,
+    /// The tracker rejected the request or sent an unusable response
+    TrackerFailure,
+    /// The tracker did not answer in time
+    TrackerTimeout,
+    /// Invalid option, config or command
+    Validation,
+    /// A file, session or config that was asked for doesn't exist
+    NotFound,
+    /// Anything else
+    Internal,
+}
+
+impl From<&FakerError> for ErrorCode {
+    fn from(e: &FakerError) -> Self {
+        match e {
+            FakerError::TrackerError(TrackerError::HttpError(e)) if e.is_timeout() => ErrorCode::TrackerTimeout,
+            FakerError::TrackerError(_) => ErrorCode::TrackerFailure,
+            FakerError::ConfigError(_) => ErrorCode::Validation,
+            FakerError::InvalidState(_) => ErrorCode::Internal,
+        }
+    }
+}
+
 impl OutputEvent {
     /// Serialize event to JSON and print to stdout
     pub fn emit(&self) {
@@ -251,13 +306,28 @@ impl OutputEvent {
     }
 
     /// Helper to emit error event
-    pub fn error(message: impl Into<String>) -> Self {
+    pub fn error(code: ErrorCode, message: impl Into<String>) -> Self {
         OutputEvent::Error(ErrorEvent {
+            code,
             message: message.into(),
             timestamp: Utc::now(),
         })
     }
 
+    /// Helper to emit an error event for a faker error, categorized by its type
+    pub fn faker_error(context: &str, e: &FakerError) -> Self {
+        Self::error(e.into(), format!("{}: {}", context, e))
+    }
+
+    /// Helper to emit heartbeat event
+    pub fn heartbeat(uptime_secs: u64, state: &FakerState) -> Self {
+        OutputEvent::Heartbeat(HeartbeatEvent {
+            uptime_secs,
+            state: format_state(state),
+            timestamp: Utc::now(),
+        })
+    }
+
     /// Helper to emit paused event
     pub fn paused() -> Self {
         OutputEvent::Paused(PausedEvent { timestamp: Utc::now() })
@@ -267,6 +337,15 @@ impl OutputEvent {
     pub fn resumed() -> Self {
         OutputEvent::Resumed(ResumedEvent { timestamp: Utc::now() })
     }
+
+    /// Helper to emit rates changed event
+    pub fn rates_changed(upload_rate: f64, download_rate: f64) -> Self {
+        OutputEvent::RatesChanged(RatesChangedEvent {
+            upload_rate,
+            download_rate,
+            timestamp: Utc::now(),
+        })
+    }
 }
 
 /// Output for the `info` subcommand
@@ -286,6 +365,7 @@ pub struct TorrentInfoOutput {
     pub creation_date: Option<String>,
     pub created_by: Option<String>,
     pub comment: Option<String>,
+    pub source: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -324,6 +404,7 @@ impl From<&TorrentInfo> for TorrentInfoOutput {
             }),
             created_by: torrent.created_by.clone(),
             comment: torrent.comment.clone(),
+            source: torrent.source.clone(),
         }
     }
 }
@@ -331,41 +412,13 @@ impl From<&TorrentInfo> for TorrentInfoOutput {
 /// Output for the `clients` subcommand
 #[derive(Debug, Serialize)]
 pub struct ClientsOutput {
-    pub clients: Vec<ClientInfo>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct ClientInfo {
-    pub id: String,
-    pub name: String,
-    pub default_version: String,
+    pub clients: Vec<ClientFingerprint>,
 }
 
 impl ClientsOutput {
     pub fn new() -> Self {
         ClientsOutput {
-            clients: vec![
-                ClientInfo {
-                    id: "qbittorrent".to_string(),
-                    name: "qBittorrent".to_string(),
-                    default_version: "5.1.4".to_string(),
-                },
-                ClientInfo {
-                    id: "utorrent".to_string(),
-                    name: "uTorrent".to_string(),
-                    default_version: "3.5.5".to_string(),
-                },
-                ClientInfo {
-                    id: "transmission".to_string(),
-                    name: "Transmission".to_string(),
-                    default_version: "4.0.5".to_string(),
-                },
-                ClientInfo {
-                    id: "deluge".to_string(),
-                    name: "Deluge".to_string(),
-                    default_version: "2.1.1".to_string(),
-                },
-            ],
+            clients: ClientFingerprint::all(),
         }
     }
 }
@@ -376,6 +429,53 @@ impl Default for ClientsOutput {
     }
 }
 
+/// Output for the `validate` subcommand
+#[derive(Debug, Serialize)]
+pub struct ValidationOutput {
+    pub valid: bool,
+    pub torrent: String,
+    pub config: Option<String>,
+    pub checks: Vec<ValidationCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationCheck {
+    pub name: String,
+    pub passed: bool,
+    /// Passed, but likely not what the user wants
+    pub warning: bool,
+    pub message: String,
+}
+
+impl ValidationCheck {
+    pub fn pass(name: &str, message: impl Into<String>) -> Self {
+        ValidationCheck {
+            name: name.to_string(),
+            passed: true,
+            warning: false,
+            message: message.into(),
+        }
+    }
+
+    pub fn warn(name: &str, message: impl Into<String>) -> Self {
+        ValidationCheck {
+            name: name.to_string(),
+            passed: true,
+            warning: true,
+            message: message.into(),
+        }
+    }
+
+    pub fn fail(name: &str, message: impl Into<String>) -> Self {
+        ValidationCheck {
+            name: name.to_string(),
+            passed: false,
+            warning: false,
+            message: message.into(),
+        }
+    }
+}
+
 /// Format bytes to human readable string
 pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -410,3 +510,55 @@ pub fn format_duration(secs: u64) -> String {
         format!("{}s", seconds)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustatio_core::{FakerConfig, RatioFaker};
+
+    #[tokio::test]
+    async fn test_stats_event_rounds_rates() {
+        let torrent = TorrentInfo::fixture("http://tracker.example/announce");
+        let faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        let mut stats = faker.get_stats().await;
+        stats.current_upload_rate = 119.99873;
+        stats.average_upload_rate = 42.04999;
+
+        let json = serde_json::to_value(StatsEvent::from_stats(&stats, 1)).unwrap();
+        assert_eq!(json["upload_rate"], 120.0);
+        assert_eq!(json["avg_upload_rate"], 42.0);
+
+        let json = serde_json::to_value(StatsEvent::from_stats(&stats, 3)).unwrap();
+        assert_eq!(json["upload_rate"], 119.999);
+
+        // The stats the event was built from keep full precision
+        assert_eq!(stats.current_upload_rate, 119.99873);
+        assert_eq!(stats.average_upload_rate, 42.04999);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let failure = FakerError::TrackerError(TrackerError::TrackerFailure("unregistered torrent".to_string()));
+        assert_eq!(ErrorCode::from(&failure), ErrorCode::TrackerFailure);
+        assert_eq!(
+            ErrorCode::from(&FakerError::ConfigError("bad rate".to_string())),
+            ErrorCode::Validation
+        );
+
+        let json = serde_json::to_value(OutputEvent::faker_error("Update error", &failure)).unwrap();
+        assert_eq!(json["event"], "error");
+        assert_eq!(json["code"], "tracker_failure");
+        assert_eq!(
+            json["message"],
+            "Update error: Tracker error: Tracker returned error: unregistered torrent"
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_event() {
+        let json = serde_json::to_value(OutputEvent::heartbeat(42, &FakerState::Seeding)).unwrap();
+        assert_eq!(json["event"], "heartbeat");
+        assert_eq!(json["uptime_secs"], 42);
+        assert_eq!(json["state"], "seeding");
+    }
+}
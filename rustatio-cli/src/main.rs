@@ -3,13 +3,14 @@ mod json;
 mod runner;
 mod session;
 mod tui;
+mod validate;
 
 use anyhow::Result;
 use clap::Parser;
 use cli::{Cli, Commands};
-use json::{format_bytes, ClientsOutput, TorrentInfoOutput};
+use json::{format_bytes, ClientsOutput, ErrorCode, TorrentInfoOutput, ValidationOutput};
 use runner::RunnerConfig;
-use session::Session;
+use session::{Session, SessionDiff, SessionStats};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -18,11 +19,20 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
+    // Every config and session path is resolved from --data-dir, then RUSTATIO_DATA_DIR
+    let data_dir = cli
+        .data_dir
+        .clone()
+        .or_else(rustatio_core::AppConfig::data_dir_override);
+    let sessions_dir = Session::sessions_dir(data_dir.as_deref());
+
     match cli.command {
         Commands::Start {
             torrent,
             client,
             client_version,
+            user_agent,
+            peer_id_prefix,
             upload_rate,
             download_rate,
             port,
@@ -34,28 +44,50 @@ async fn main() -> Result<()> {
             stop_downloaded,
             stop_time,
             stop_when_no_leechers,
+            idle_seed,
+            stop_basis,
+            max_ratio,
+            max_uploads_per_hour,
             no_randomize,
             random_range,
             progressive,
             target_upload,
             target_download,
             progressive_duration,
+            progressive_curve,
+            active_hours,
             config: config_file,
+            profile,
             json,
+            once,
             interval,
+            heartbeat_interval,
+            rate_decimals,
             resume,
             save_session,
             no_save_session,
+            session_save_interval,
             announce_max_retries,
             announce_retry_delay_seconds,
             announce_interval,
+            min_announce_interval,
+            max_announce_interval,
+            announce_jitter,
+            announce_params,
+            announce_all_trackers,
+            swarm_aggregation,
             update_interval,
+            timeout,
             infinite_retry_after_max,
         } => {
             // Validate torrent file exists
             if !torrent.exists() {
                 if json {
-                    json::OutputEvent::error(format!("Torrent file not found: {}", torrent.display())).emit();
+                    json::OutputEvent::error(
+                        ErrorCode::NotFound,
+                        format!("Torrent file not found: {}", torrent.display()),
+                    )
+                    .emit();
                 } else {
                     eprintln!("Error: Torrent file not found: {}", torrent.display());
                 }
@@ -63,7 +95,48 @@ async fn main() -> Result<()> {
             }
 
             // Load config file (if specified) or use defaults
-            let app_config = load_config(config_file.as_ref(), json);
+            let app_config = load_config(config_file.as_ref(), data_dir.as_deref(), json);
+
+            // A config profile wins over a built-in preset of the same name
+            let preset = profile
+                .as_deref()
+                .filter(|name| app_config.profile(name).is_none())
+                .and_then(cli::RateOptions::preset);
+
+            // Merge the selected profile over the config defaults
+            let (app_config, profile_config) = match profile {
+                Some(ref name) if preset.is_none() => match app_config.with_profile(name) {
+                    Ok(merged) => {
+                        let faker_config = merged.faker.to_faker_config(&merged.client);
+                        (merged, Some(faker_config))
+                    }
+                    Err(e) => {
+                        if json {
+                            json::OutputEvent::error(ErrorCode::Validation, format!("Failed to apply profile: {}", e))
+                                .emit();
+                        } else {
+                            eprintln!("Error: Failed to apply profile: {}", e);
+                        }
+                        std::process::exit(1);
+                    }
+                },
+                _ => (app_config, None),
+            };
+
+            // Built-in presets only fill the rate options left unset
+            let rates = cli::RateOptions {
+                upload_rate,
+                randomize: no_randomize.then_some(false),
+                random_range,
+                announce_jitter,
+            };
+            let rates = match preset {
+                Some(ref p) => rates.with_preset(p),
+                None => rates,
+            };
+            // Flags win over the preset, which wins over the config file
+            let rates = rates.with_config_defaults(&app_config.faker);
+            let stop_ratio = cli::effective_stop_ratio(stop_ratio, &app_config.faker);
 
             // Load torrent to get info_hash for session lookup
             let torrent_info = runner::load_torrent(&torrent)?;
@@ -71,7 +144,7 @@ async fn main() -> Result<()> {
 
             // Try to load existing session if --resume is set
             let existing_session = if resume {
-                Session::load_for_hash(&info_hash)
+                Session::load_for_hash(&sessions_dir, &info_hash)
             } else {
                 None
             };
@@ -90,32 +163,40 @@ async fn main() -> Result<()> {
                 (initial_uploaded, initial_downloaded)
             };
 
-            // Apply config defaults where CLI args use defaults
-            let effective_upload_rate = if upload_rate == 700.0 {
-                app_config.faker.default_upload_rate
-            } else {
-                upload_rate
+            // Options left unset on the command line fall back to the profile
+            let (client, stop_ratio, stop_uploaded, stop_downloaded, stop_when_no_leechers) = match profile_config {
+                Some(ref p) => (
+                    client.unwrap_or_else(|| p.client_type.clone().into()),
+                    stop_ratio.or(p.stop_at_ratio),
+                    stop_uploaded.or(p.stop_at_uploaded.map(bytes_to_gb)),
+                    stop_downloaded.or(p.stop_at_downloaded.map(bytes_to_gb)),
+                    stop_when_no_leechers.unwrap_or(p.stop_when_no_leechers),
+                ),
+                None => (
+                    client.unwrap_or(cli::ClientArg::Transmission),
+                    stop_ratio,
+                    stop_uploaded,
+                    stop_downloaded,
+                    stop_when_no_leechers.unwrap_or(false),
+                ),
             };
-
-            let effective_download_rate = if download_rate == 0.0 {
-                app_config.faker.default_download_rate
+            let on_target_reached = if idle_seed {
+                rustatio_core::TargetAction::IdleSeed
             } else {
-                download_rate
+                profile_config.as_ref().map(|p| p.on_target_reached).unwrap_or_default()
             };
-
-            let effective_port = if port == 59859 {
-                app_config.client.default_port
-            } else {
-                port
+            let (progressive, target_upload, target_download) = match profile_config {
+                Some(ref p) if !progressive => (p.progressive_rates, p.target_upload_rate, p.target_download_rate),
+                _ => (progressive, target_upload, target_download),
             };
 
             let config = RunnerConfig {
                 torrent_path: torrent,
                 client,
                 client_version: client_version.or(app_config.client.default_version.clone()),
-                upload_rate: effective_upload_rate,
-                download_rate: effective_download_rate,
-                port: effective_port,
+                upload_rate: rates.upload_rate.unwrap_or(app_config.faker.default_upload_rate),
+                download_rate: download_rate.unwrap_or(app_config.faker.default_download_rate),
+                port: port.unwrap_or(app_config.client.default_port),
                 completion,
                 initial_uploaded: effective_uploaded,
                 initial_downloaded: effective_downloaded,
@@ -124,26 +205,48 @@ async fn main() -> Result<()> {
                 stop_downloaded,
                 stop_time,
                 stop_when_no_leechers,
-                no_randomize,
-                random_range,
+                on_target_reached,
+                stop_ratio_basis: stop_basis.into(),
+                max_ratio_cap: max_ratio,
+                max_uploads_per_hour,
+                no_randomize: !rates.randomize.unwrap_or(true),
+                random_range: rates.random_range.unwrap_or(50.0),
                 progressive,
                 target_upload,
                 target_download,
                 progressive_duration,
+                progressive_curve: progressive_curve.into(),
+                active_schedule: active_hours,
                 json_mode: json,
                 stats_interval: interval,
+                heartbeat_interval,
+                rate_decimals,
                 save_session: save_session && !no_save_session,
+                session_save_interval,
+                sessions_dir: sessions_dir.clone(),
                 info_hash: info_hash.clone(),
                 torrent_name: torrent_info.name.clone(),
                 torrent_size: torrent_info.total_size,
                 announce_max_retries,
                 announce_retry_delay_seconds,
                 announce_interval,
+                announce_interval_min_secs: min_announce_interval,
+                announce_interval_max_secs: max_announce_interval,
+                announce_jitter_percent: rates.announce_jitter.unwrap_or(0.0),
+                extra_announce_params: announce_params,
+                announce_all_trackers,
+                swarm_aggregation: swarm_aggregation.into(),
                 update_interval,
+                request_timeout_secs: Some(timeout),
+                user_agent_override: user_agent,
+                peer_id_prefix_override: peer_id_prefix,
                 infinite_retry_after_max,
+                profile,
             };
 
-            if json {
+            if let Some(event) = once {
+                runner::run_once(config, event.into()).await?;
+            } else if json {
                 runner::run_json_mode(config).await?;
             } else {
                 tui::run_tui_mode(config).await?;
@@ -158,14 +261,18 @@ async fn main() -> Result<()> {
             stop_uploaded,
             json,
             interval,
+            heartbeat_interval,
+            rate_decimals,
             no_save_session,
+            session_save_interval,
         } => {
             // Look up the session
-            let session = match Session::load_for_hash(&info_hash) {
+            let session = match Session::load_for_hash(&sessions_dir, &info_hash) {
                 Some(s) => s,
                 None => {
                     if json {
-                        json::OutputEvent::error(format!("Session not found: {}", info_hash)).emit();
+                        json::OutputEvent::error(ErrorCode::NotFound, format!("Session not found: {}", info_hash))
+                            .emit();
                     } else {
                         eprintln!("Error: No saved session found for hash: {}", info_hash);
                         eprintln!();
@@ -179,7 +286,11 @@ async fn main() -> Result<()> {
             let torrent_path = std::path::PathBuf::from(&session.torrent_path);
             if !torrent_path.exists() {
                 if json {
-                    json::OutputEvent::error(format!("Torrent file no longer exists: {}", session.torrent_path)).emit();
+                    json::OutputEvent::error(
+                        ErrorCode::NotFound,
+                        format!("Torrent file no longer exists: {}", session.torrent_path),
+                    )
+                    .emit();
                 } else {
                     eprintln!("Error: Torrent file no longer exists: {}", session.torrent_path);
                     eprintln!();
@@ -201,13 +312,7 @@ async fn main() -> Result<()> {
             }
 
             // Parse client type from session
-            let client = match session.client.to_lowercase().as_str() {
-                "qbittorrent" => cli::ClientArg::Qbittorrent,
-                "utorrent" => cli::ClientArg::Utorrent,
-                "transmission" => cli::ClientArg::Transmission,
-                "deluge" => cli::ClientArg::Deluge,
-                _ => cli::ClientArg::Qbittorrent,
-            };
+            let client = session_client(&session);
 
             let config = RunnerConfig {
                 torrent_path,
@@ -224,23 +329,43 @@ async fn main() -> Result<()> {
                 stop_downloaded: None,
                 stop_time: Some(744.0),
                 stop_when_no_leechers: false,
+                on_target_reached: rustatio_core::TargetAction::Stop,
+                stop_ratio_basis: rustatio_core::RatioBasis::Session,
+                max_ratio_cap: None,
+                max_uploads_per_hour: None,
                 no_randomize: false,
                 random_range: 50.0,
                 progressive: false,
                 target_upload: None,
                 target_download: None,
                 progressive_duration: 1.0,
+                progressive_curve: rustatio_core::Curve::Linear,
+                active_schedule: None,
                 json_mode: json,
                 stats_interval: interval,
+                heartbeat_interval,
+                rate_decimals,
                 save_session: !no_save_session,
+                session_save_interval,
+                sessions_dir: sessions_dir.clone(),
                 info_hash: session.info_hash.clone(),
                 torrent_name: session.torrent_name.clone(),
                 torrent_size: session.torrent_size,
                 announce_max_retries: 3,
                 announce_retry_delay_seconds: 5,
                 announce_interval: 1800,
+                announce_interval_min_secs: None,
+                announce_interval_max_secs: None,
+                announce_jitter_percent: 0.0,
+                extra_announce_params: Vec::new(),
+                announce_all_trackers: false,
+                swarm_aggregation: rustatio_core::SwarmAggregation::Max,
                 update_interval: 5,
+                request_timeout_secs: None,
+                user_agent_override: None,
+                peer_id_prefix_override: None,
                 infinite_retry_after_max: false,
+                profile: None,
             };
 
             if json {
@@ -250,17 +375,52 @@ async fn main() -> Result<()> {
             }
         }
 
+        Commands::Dash {
+            dir,
+            client,
+            client_version,
+            upload_rate,
+            download_rate,
+            port,
+            completion,
+            stop_ratio,
+            stop_uploaded,
+        } => {
+            if !dir.is_dir() {
+                eprintln!("Error: Directory not found: {}", dir.display());
+                std::process::exit(1);
+            }
+
+            tui::run_dash_mode(tui::DashConfig {
+                dir,
+                client,
+                client_version,
+                upload_rate,
+                download_rate,
+                port,
+                completion,
+                stop_ratio,
+                stop_uploaded,
+            })
+            .await?;
+        }
+
         Commands::Info { torrent, json } => {
             if !torrent.exists() {
                 if json {
-                    json::OutputEvent::error(format!("Torrent file not found: {}", torrent.display())).emit();
+                    json::OutputEvent::error(
+                        ErrorCode::NotFound,
+                        format!("Torrent file not found: {}", torrent.display()),
+                    )
+                    .emit();
                 } else {
                     eprintln!("Error: Torrent file not found: {}", torrent.display());
                 }
                 std::process::exit(1);
             }
 
-            let torrent_info = runner::load_torrent(&torrent)?;
+            // Only displayed, so show the tracker URLs without their passkeys
+            let torrent_info = runner::load_torrent(&torrent)?.redacted();
 
             if json {
                 let output = TorrentInfoOutput::from(&torrent_info);
@@ -270,6 +430,20 @@ async fn main() -> Result<()> {
             }
         }
 
+        Commands::Validate { torrent, config, json } => {
+            let report = validate::validate(&torrent, config.as_deref(), data_dir.as_deref());
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_validation_report(&report);
+            }
+
+            if !report.valid {
+                std::process::exit(1);
+            }
+        }
+
         Commands::Clients { json } => {
             let clients = ClientsOutput::new();
 
@@ -283,6 +457,14 @@ async fn main() -> Result<()> {
                         "  {:14} {} (default: {})",
                         client.id, client.name, client.default_version
                     );
+                    println!(
+                        "  {:14} peer ID {}, User-Agent \"{}\", crypto {}",
+                        "",
+                        client.peer_id_prefix,
+                        client.user_agent,
+                        if client.supports_crypto { "yes" } else { "no" }
+                    );
+                    println!("  {:14} versions: {}", "", client.versions.join(", "));
                 }
                 println!();
                 println!("Use --client <id> to select a client.");
@@ -295,7 +477,7 @@ async fn main() -> Result<()> {
             show,
             json: json_output,
         } => {
-            let config_path = rustatio_core::AppConfig::default_path();
+            let config_path = rustatio_core::AppConfig::default_path_in(data_dir.as_deref());
 
             if path {
                 if json_output {
@@ -306,7 +488,7 @@ async fn main() -> Result<()> {
             } else if init {
                 if config_path.exists() {
                     if json_output {
-                        json::OutputEvent::error("Config file already exists").emit();
+                        json::OutputEvent::error(ErrorCode::Validation, "Config file already exists").emit();
                     } else {
                         eprintln!("Config file already exists at: {}", config_path.display());
                         eprintln!("Use --show to view current config.");
@@ -337,7 +519,7 @@ async fn main() -> Result<()> {
             } else if show {
                 if !config_path.exists() {
                     if json_output {
-                        json::OutputEvent::error("No config file found").emit();
+                        json::OutputEvent::error(ErrorCode::NotFound, "No config file found").emit();
                     } else {
                         eprintln!("No config file found at: {}", config_path.display());
                         eprintln!("Use --init to create one.");
@@ -369,22 +551,107 @@ async fn main() -> Result<()> {
             delete,
             clear,
             path,
+            export,
+            import,
+            force,
+            stats,
+            diff,
             json: json_output,
         } => {
             if path {
-                let sessions_dir = Session::sessions_dir();
                 if json_output {
                     println!("{}", serde_json::json!({ "path": sessions_dir.display().to_string() }));
                 } else {
                     println!("{}", sessions_dir.display());
                 }
+            } else if let Some(file) = export {
+                let count = Session::export_all(&sessions_dir, &file)?;
+                if json_output {
+                    println!(
+                        "{}",
+                        serde_json::json!({ "exported": count, "path": file.display().to_string() })
+                    );
+                } else {
+                    println!("Exported {} session(s) to {}", count, file.display());
+                }
+            } else if let Some(file) = import {
+                let import = Session::read_export(&file)?;
+                let mut imported = Vec::new();
+                let mut skipped = Vec::new();
+
+                for session in import.sessions {
+                    if !force && Session::path_for_hash(&sessions_dir, &session.info_hash).exists() {
+                        skipped.push(session.info_hash);
+                        continue;
+                    }
+                    session.save_session(&sessions_dir)?;
+                    imported.push(session.info_hash);
+                }
+
+                if json_output {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "imported": imported,
+                            "skipped": skipped,
+                            "warnings": import.warnings,
+                        })
+                    );
+                } else {
+                    for warning in &import.warnings {
+                        eprintln!("Warning: {}", warning);
+                    }
+                    println!("Imported {} session(s)", imported.len());
+                    if !skipped.is_empty() {
+                        println!(
+                            "Skipped {} existing session(s), use --force to overwrite them",
+                            skipped.len()
+                        );
+                    }
+                }
+            } else if let Some(hash) = diff {
+                let Some(session) = Session::load_for_hash(&sessions_dir, &hash) else {
+                    if json_output {
+                        json::OutputEvent::error(ErrorCode::NotFound, format!("Session not found: {}", hash)).emit();
+                    } else {
+                        eprintln!("Session not found: {}", hash);
+                    }
+                    std::process::exit(1);
+                };
+
+                let torrent = rustatio_core::TorrentInfo::from_file(&session.torrent_path).ok();
+                let mut diff = SessionDiff::compare(&session, torrent.as_ref(), chrono::Utc::now());
+                if let Some(ref torrent) = torrent {
+                    match scrape_swarm(&session, torrent).await {
+                        Ok(swarm) => diff.swarm = Some(swarm),
+                        Err(e) => diff.scrape_error = Some(e),
+                    }
+                }
+
+                if json_output {
+                    println!("{}", serde_json::to_string_pretty(&diff)?);
+                } else {
+                    print_session_diff(&diff);
+                }
+            } else if stats {
+                let sessions: Vec<Session> = Session::list_all(&sessions_dir)?
+                    .iter()
+                    .filter_map(|summary| Session::load_for_hash(&sessions_dir, &summary.info_hash))
+                    .collect();
+                let stats = SessionStats::from_sessions(&sessions);
+
+                if json_output {
+                    println!("{}", serde_json::to_string_pretty(&stats)?);
+                } else {
+                    print_session_stats(&stats);
+                }
             } else if clear {
-                let sessions = Session::list_all()?;
+                let sessions = Session::list_all(&sessions_dir)?;
                 let count = sessions.len();
 
                 for summary in sessions {
-                    if let Some(session) = Session::load_for_hash(&summary.info_hash) {
-                        let _ = session.delete();
+                    if let Some(session) = Session::load_for_hash(&sessions_dir, &summary.info_hash) {
+                        let _ = session.delete(&sessions_dir);
                     }
                 }
 
@@ -394,8 +661,8 @@ async fn main() -> Result<()> {
                     println!("Deleted {} session(s)", count);
                 }
             } else if let Some(hash) = delete {
-                if let Some(session) = Session::load_for_hash(&hash) {
-                    session.delete()?;
+                if let Some(session) = Session::load_for_hash(&sessions_dir, &hash) {
+                    session.delete(&sessions_dir)?;
                     if json_output {
                         println!("{}", serde_json::json!({ "deleted": true, "info_hash": hash }));
                     } else {
@@ -403,7 +670,7 @@ async fn main() -> Result<()> {
                     }
                 } else {
                     if json_output {
-                        json::OutputEvent::error(format!("Session not found: {}", hash)).emit();
+                        json::OutputEvent::error(ErrorCode::NotFound, format!("Session not found: {}", hash)).emit();
                     } else {
                         eprintln!("Session not found: {}", hash);
                     }
@@ -411,7 +678,7 @@ async fn main() -> Result<()> {
                 }
             } else {
                 // List all sessions
-                let sessions = Session::list_all()?;
+                let sessions = Session::list_all(&sessions_dir)?;
 
                 if json_output {
                     println!("{}", serde_json::to_string_pretty(&sessions)?);
@@ -473,6 +740,7 @@ async fn main() -> Result<()> {
                     println!("  rustatio resume <hash>            Resume a session by hash");
                     println!("  rustatio start <file> --resume    Resume by torrent file");
                     println!("  rustatio sessions --delete <hash> Delete a session");
+                    println!("  rustatio sessions --export <file> Export all sessions (--import to restore)");
                 }
             }
         }
@@ -485,14 +753,22 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+fn bytes_to_gb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
+
 /// Load configuration from file or use defaults
-fn load_config(config_path: Option<&std::path::PathBuf>, json_mode: bool) -> rustatio_core::AppConfig {
+fn load_config(
+    config_path: Option<&std::path::PathBuf>,
+    data_dir: Option<&std::path::Path>,
+    json_mode: bool,
+) -> rustatio_core::AppConfig {
     if let Some(path) = config_path {
         match rustatio_core::AppConfig::load(path) {
             Ok(config) => config,
             Err(e) => {
                 if json_mode {
-                    json::OutputEvent::error(format!("Failed to load config: {}", e)).emit();
+                    json::OutputEvent::error(ErrorCode::Validation, format!("Failed to load config: {}", e)).emit();
                 } else {
                     eprintln!("Warning: Failed to load config from {:?}: {}", path, e);
                     eprintln!("Using default configuration.");
@@ -502,7 +778,7 @@ fn load_config(config_path: Option<&std::path::PathBuf>, json_mode: bool) -> rus
         }
     } else {
         // Try to load from default path, but don't fail if not found
-        rustatio_core::AppConfig::load_or_default()
+        rustatio_core::AppConfig::load_or_default_in(data_dir)
     }
 }
 
@@ -548,6 +824,10 @@ fn print_torrent_info(torrent: &rustatio_core::TorrentInfo) {
         println!("Comment:     {}", comment);
     }
 
+    if let Some(ref source) = torrent.source {
+        println!("Source:      {}", source);
+    }
+
     println!();
 
     if torrent.is_single_file {
@@ -563,6 +843,138 @@ fn print_torrent_info(torrent: &rustatio_core::TorrentInfo) {
     }
 }
 
+fn print_validation_report(report: &ValidationOutput) {
+    println!("Validation Report");
+    println!("=================");
+    println!();
+    println!("Torrent:     {}", report.torrent);
+    if let Some(ref config) = report.config {
+        println!("Config:      {}", config);
+    }
+    println!();
+
+    for check in &report.checks {
+        let mark = match (check.passed, check.warning) {
+            (false, _) => "✗",
+            (true, true) => "!",
+            (true, false) => "✓",
+        };
+        println!("  {} {:22} {}", mark, check.name, check.message);
+    }
+
+    println!();
+    let failed = report.checks.iter().filter(|c| !c.passed).count();
+    let warnings = report.checks.iter().filter(|c| c.warning).count();
+    if failed == 0 && warnings > 0 {
+        println!("Result: PASSED ({} checks, {} warnings)", report.checks.len(), warnings);
+    } else if failed == 0 {
+        println!("Result: PASSED ({} checks)", report.checks.len());
+    } else {
+        println!("Result: FAILED ({} of {} checks failed)", failed, report.checks.len());
+    }
+}
+
+fn print_session_stats(stats: &SessionStats) {
+    println!("Session Statistics");
+    println!("==================");
+    println!();
+    println!("Sessions:    {}", stats.session_count);
+    println!("Uploaded:    {}", format_bytes(stats.total_uploaded));
+    println!("Downloaded:  {}", format_bytes(stats.total_downloaded));
+    println!("Seed Time:   {}", json::format_duration(stats.total_seed_time_secs));
+    match stats.average_ratio {
+        Some(ratio) => println!("Avg Ratio:   {:.3}", ratio),
+        None => println!("Avg Ratio:   -"),
+    }
+
+    if !stats.by_client.is_empty() {
+        println!();
+        println!("By Client:");
+        for (client, count) in &stats.by_client {
+            println!("  {:14} {}", client, count);
+        }
+    }
+
+    if !stats.top_uploaded.is_empty() {
+        println!();
+        println!("Top Uploaded:");
+        for (i, session) in stats.top_uploaded.iter().enumerate() {
+            println!(
+                "  {}. {:>12}  ratio {:>7}  {}",
+                i + 1,
+                format_bytes(session.uploaded),
+                session.ratio_display,
+                session.torrent_name
+            );
+        }
+    }
+}
+
+/// Client a session was saved with (unknown names fall back to qBittorrent)
+fn session_client(session: &Session) -> cli::ClientArg {
+    match session.client.to_lowercase().as_str() {
+        "qbittorrent" => cli::ClientArg::Qbittorrent,
+        "utorrent" => cli::ClientArg::Utorrent,
+        "transmission" => cli::ClientArg::Transmission,
+        "deluge" => cli::ClientArg::Deluge,
+        "biglybt" => cli::ClientArg::Biglybt,
+        _ => cli::ClientArg::Qbittorrent,
+    }
+}
+
+/// Scrape the torrent's first HTTP(S) tracker, identifying as the session's client
+async fn scrape_swarm(
+    session: &Session,
+    torrent: &rustatio_core::TorrentInfo,
+) -> std::result::Result<rustatio_core::protocol::ScrapeResponse, String> {
+    let mut urls = vec![torrent.announce.clone()];
+    if let Some(ref tiers) = torrent.announce_list {
+        urls.extend(tiers.iter().flatten().cloned());
+    }
+    let url = urls
+        .into_iter()
+        .find(|url| rustatio_core::validate_tracker_url(url).is_ok())
+        .ok_or_else(|| "No HTTP(S) tracker to scrape".to_string())?;
+
+    let client_config =
+        rustatio_core::ClientConfig::get(session_client(session).into(), session.client_version.clone());
+    let client = rustatio_core::protocol::TrackerClient::new(client_config).map_err(|e| e.to_string())?;
+    client.scrape(&url, &torrent.info_hash).await.map_err(|e| e.to_string())
+}
+
+fn print_session_diff(diff: &SessionDiff) {
+    println!("Session Diff: {}", diff.torrent_name);
+    println!("==============");
+    println!();
+    println!("Info Hash:   {}", diff.info_hash);
+    println!("Uploaded:    {}", format_bytes(diff.uploaded));
+    println!("Last Run:    +{}", format_bytes(diff.last_run_uploaded));
+    println!(
+        "Last Saved:  {} ({} ago)",
+        diff.updated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        json::format_duration(diff.elapsed_since_update_secs)
+    );
+    println!();
+    println!("Torrent:     {}", diff.torrent_path);
+    match (diff.torrent_exists, diff.torrent_matches) {
+        (false, _) => println!("File:        missing"),
+        (true, None) => println!("File:        unreadable"),
+        (true, Some(true)) => println!("File:        ok"),
+        (true, Some(false)) => println!("File:        info hash changed"),
+    }
+
+    println!();
+    match (&diff.swarm, &diff.scrape_error) {
+        (Some(swarm), _) => {
+            println!("Seeders:     {}", swarm.complete);
+            println!("Leechers:    {}", swarm.incomplete);
+            println!("Completed:   {}", swarm.downloaded);
+        }
+        (None, Some(e)) => println!("Swarm:       scrape failed ({})", e),
+        (None, None) => println!("Swarm:       not scraped"),
+    }
+}
+
 /// Format a datetime as relative time (e.g., "2h ago", "3d ago")
 fn format_relative_time(dt: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
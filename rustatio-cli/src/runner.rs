@@ -1,18 +1,22 @@
 use crate::cli::ClientArg;
 use crate::json::{
-    AnnounceEvent, AnnounceType, InputCommand, OutputEvent, ScrapeEvent, StartedEvent, StatsEvent, StopReason,
-    StoppedEvent,
+    AnnounceEvent, AnnounceType, ErrorCode, InputCommand, OutputEvent, ScrapeEvent, StartedEvent, StatsEvent,
+    StopReason, StoppedEvent,
 };
 use crate::session::Session;
 use anyhow::{Context, Result};
 use chrono::Utc;
-use rustatio_core::{ClientConfig, ClientType, FakerConfig, FakerState, RatioFaker, TorrentInfo};
+use rustatio_core::protocol::TrackerEvent;
+use rustatio_core::{
+    ClientConfig, ClientType, Curve, FakerConfig, FakerError, FakerState, FakerStats, RatioBasis, RatioFaker,
+    SwarmAggregation, TargetAction, TorrentInfo,
+};
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
+use tokio::time::{interval, Duration, Instant};
 
 /// Configuration for the runner
 #[allow(dead_code)]
@@ -31,23 +35,45 @@ pub struct RunnerConfig {
     pub stop_downloaded: Option<f64>,
     pub stop_time: Option<f64>,
     pub stop_when_no_leechers: bool,
+    pub on_target_reached: TargetAction,
+    pub stop_ratio_basis: RatioBasis,
+    pub max_ratio_cap: Option<f64>,
+    pub max_uploads_per_hour: Option<f64>,
     pub no_randomize: bool,
     pub random_range: f64,
     pub progressive: bool,
     pub target_upload: Option<f64>,
     pub target_download: Option<f64>,
     pub progressive_duration: f64,
+    pub progressive_curve: Curve,
+    pub active_schedule: Option<Vec<(u8, u8)>>,
     pub json_mode: bool,
     pub stats_interval: u64,
+    pub heartbeat_interval: u64,
+    pub rate_decimals: u32,
     pub save_session: bool,
+    pub session_save_interval: u64,
+    /// Directory the session is saved in
+    pub sessions_dir: std::path::PathBuf,
     pub info_hash: String,
     pub torrent_name: String,
     pub torrent_size: u64,
     pub announce_max_retries: u32,
     pub announce_retry_delay_seconds: u64,
     pub announce_interval: u64,
+    pub announce_interval_min_secs: Option<u64>,
+    pub announce_interval_max_secs: Option<u64>,
+    pub announce_jitter_percent: f64,
+    pub extra_announce_params: Vec<(String, String)>,
+    pub announce_all_trackers: bool,
+    pub swarm_aggregation: SwarmAggregation,
     pub update_interval: u64,
+    pub request_timeout_secs: Option<u64>,
+    pub user_agent_override: Option<String>,
+    pub peer_id_prefix_override: Option<String>,
     pub infinite_retry_after_max: bool,
+    /// Config profile or built-in preset applied on top of the defaults
+    pub profile: Option<String>,
 }
 
 /// Internal command for controlling the runner
@@ -58,17 +84,22 @@ pub enum RunnerCommand {
     Stop,
     Scrape,
     Stats,
+    SetRate { upload: Option<f64>, download: Option<f64> },
     Shutdown,
 }
 
 /// Run the faker in JSON mode
 pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
+    let started_at = Instant::now();
+
     // Emit init event
     OutputEvent::init().emit();
 
     // Load torrent
-    let torrent = load_torrent(&config.torrent_path)?;
-    OutputEvent::TorrentLoaded((&torrent).into()).emit();
+    let torrent = load_torrent(&config.torrent_path).inspect_err(|e| {
+        OutputEvent::error(ErrorCode::TorrentParse, format!("{:#}", e)).emit();
+    })?;
+    OutputEvent::TorrentLoaded((&torrent.redacted()).into()).emit();
 
     // Create faker config
     let faker_config = create_faker_config(&config);
@@ -78,21 +109,24 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
     let client_config = ClientConfig::get(client_type.clone(), config.client_version.clone());
 
     // Create faker
-    let mut faker =
-        RatioFaker::new(torrent, faker_config).map_err(|e| anyhow::anyhow!("Failed to create faker: {}", e))?;
+    let mut faker = RatioFaker::new(torrent, faker_config).map_err(|e| {
+        OutputEvent::faker_error("Failed to create faker", &e).emit();
+        anyhow::anyhow!("Failed to create faker: {}", e)
+    })?;
 
     // Start faker
-    faker
-        .start()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to start faker: {}", e))?;
+    faker.start().await.map_err(|e| {
+        OutputEvent::faker_error("Failed to start faker", &e).emit();
+        anyhow::anyhow!("Failed to start faker: {}", e)
+    })?;
 
     // Emit started event
     OutputEvent::Started(StartedEvent {
-        peer_id: client_config.generate_peer_id(),
+        peer_id: faker.peer_id().to_string(),
         client: format!("{:?}", client_type),
         client_version: client_config.version.clone(),
         port: config.port,
+        profile: config.profile.clone(),
         timestamp: Utc::now(),
     })
     .emit();
@@ -103,26 +137,20 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
         announce_type: AnnounceType::Started,
         seeders: stats.seeders,
         leechers: stats.leechers,
-        interval: 1800, // Default, will be updated
+        interval: faker.announce_interval().as_secs(),
         timestamp: Utc::now(),
     })
     .emit();
+    let mut announce_count = stats.announce_count;
 
     // Setup channels
     let (cmd_tx, mut cmd_rx) = mpsc::channel::<RunnerCommand>(32);
 
     // Setup shutdown flag
     let shutdown = Arc::new(AtomicBool::new(false));
-    let shutdown_clone = shutdown.clone();
 
-    // Setup Ctrl+C handler
-    let cmd_tx_ctrlc = cmd_tx.clone();
-    tokio::spawn(async move {
-        if tokio::signal::ctrl_c().await.is_ok() {
-            shutdown_clone.store(true, Ordering::SeqCst);
-            let _ = cmd_tx_ctrlc.send(RunnerCommand::Shutdown).await;
-        }
-    });
+    // Setup Ctrl+C / SIGTERM handler
+    spawn_shutdown_listener(shutdown.clone(), Some(cmd_tx.clone()));
 
     // Setup stdin reader for commands
     let cmd_tx_stdin = cmd_tx.clone();
@@ -138,6 +166,7 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
                     InputCommand::Stop => RunnerCommand::Stop,
                     InputCommand::Scrape => RunnerCommand::Scrape,
                     InputCommand::Stats => RunnerCommand::Stats,
+                    InputCommand::SetRate { upload, download } => RunnerCommand::SetRate { upload, download },
                 };
                 if cmd_tx_stdin.blocking_send(runner_cmd).is_err() {
                     break;
@@ -148,6 +177,14 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
 
     // Main loop
     let mut stats_ticker = interval(Duration::from_secs(config.stats_interval));
+    // A heartbeat interval of 0 disables the branch below; the ticker still needs a non-zero period
+    let mut heartbeat_ticker = interval(Duration::from_secs(config.heartbeat_interval.max(1)));
+    heartbeat_ticker.reset();
+    let auto_save = config.save_session && config.session_save_interval > 0;
+    let mut session_save_ticker = interval(Duration::from_secs(config.session_save_interval.max(1)));
+    session_save_ticker.reset();
+    // Current rates, which `set_rate` may change, for the saved session
+    let mut rates = (config.upload_rate, config.download_rate);
     let mut stop_reason = StopReason::UserInterrupt;
 
     loop {
@@ -158,8 +195,9 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
                 }
 
                 // Update stats
+                let was_completed = matches!(faker.get_stats().await.state, FakerState::Completed);
                 if let Err(e) = faker.update().await {
-                    OutputEvent::error(format!("Update error: {}", e)).emit();
+                    OutputEvent::faker_error("Update error", &e).emit();
                 }
 
                 let stats = faker.get_stats().await;
@@ -170,25 +208,59 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
                     break;
                 }
 
+                // Report announces made during the update
+                if stats.announce_count > announce_count {
+                    let announce_type = if !was_completed && matches!(stats.state, FakerState::Completed) {
+                        AnnounceType::Completed
+                    } else {
+                        AnnounceType::Periodic
+                    };
+                    OutputEvent::Announce(AnnounceEvent {
+                        announce_type,
+                        seeders: stats.seeders,
+                        leechers: stats.leechers,
+                        interval: faker.announce_interval().as_secs(),
+                        timestamp: Utc::now(),
+                    })
+                    .emit();
+                }
+                announce_count = stats.announce_count;
+
                 // Emit stats event
-                OutputEvent::Stats(StatsEvent::from(&stats)).emit();
+                OutputEvent::Stats(StatsEvent::from_stats(&stats, config.rate_decimals)).emit();
+            }
+
+            _ = heartbeat_ticker.tick(), if config.heartbeat_interval > 0 => {
+                let state = faker.get_stats().await.state;
+                if !matches!(state, FakerState::Paused) {
+                    OutputEvent::heartbeat(started_at.elapsed().as_secs(), &state).emit();
+                }
+            }
+
+            _ = session_save_ticker.tick(), if auto_save => {
+                let session = build_session(&config, &faker.get_stats().await, rates);
+                if let Err(e) = session.save_session(&config.sessions_dir) {
+                    OutputEvent::error(ErrorCode::Internal, format!("Failed to auto-save session: {}", e)).emit();
+                }
             }
 
             Some(cmd) = cmd_rx.recv() => {
                 match cmd {
                     RunnerCommand::Pause => {
                         if let Err(e) = faker.pause().await {
-                            OutputEvent::error(format!("Pause error: {}", e)).emit();
+                            OutputEvent::faker_error("Pause error", &e).emit();
                         } else {
                             OutputEvent::paused().emit();
                         }
                     }
                     RunnerCommand::Resume => {
                         if let Err(e) = faker.resume().await {
-                            OutputEvent::error(format!("Resume error: {}", e)).emit();
+                            OutputEvent::faker_error("Resume error", &e).emit();
                         } else {
                             OutputEvent::resumed().emit();
                         }
+                        // Resume may re-announce; that's reported as `resumed`, not as a periodic announce
+                        announce_count = faker.get_stats().await.announce_count;
                     }
                     RunnerCommand::Stop => {
                         stop_reason = StopReason::UserCommand;
@@ -205,13 +277,24 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
                                 }).emit();
                             }
                             Err(e) => {
-                                OutputEvent::error(format!("Scrape error: {}", e)).emit();
+                                OutputEvent::faker_error("Scrape error", &e).emit();
                             }
                         }
                     }
                     RunnerCommand::Stats => {
                         let stats = faker.get_stats().await;
-                        OutputEvent::Stats(StatsEvent::from(&stats)).emit();
+                        OutputEvent::Stats(StatsEvent::from_stats(&stats, config.rate_decimals)).emit();
+                    }
+                    RunnerCommand::SetRate { upload, download } => {
+                        match faker.set_rates(upload, download) {
+                            Ok((upload_rate, download_rate)) => {
+                                rates = (upload_rate, download_rate);
+                                OutputEvent::rates_changed(upload_rate, download_rate).emit();
+                            }
+                            Err(e) => {
+                                OutputEvent::faker_error("Set rate error", &e).emit();
+                            }
+                        }
                     }
                     RunnerCommand::Shutdown => {
                         stop_reason = StopReason::UserInterrupt;
@@ -226,34 +309,14 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
     let final_stats = faker.get_stats().await;
 
     if let Err(e) = faker.stop().await {
-        OutputEvent::error(format!("Stop error: {}", e)).emit();
+        OutputEvent::faker_error("Stop error", &e).emit();
     }
 
     // Save session if enabled
     if config.save_session {
-        let client_type: ClientType = config.client.into();
-        let mut session = Session::new(
-            &config.info_hash,
-            &config.torrent_name,
-            &config.torrent_path.to_string_lossy(),
-            config.torrent_size,
-            &format!("{:?}", client_type),
-            config.client_version.clone(),
-        );
-        session.upload_rate = config.upload_rate;
-        session.download_rate = config.download_rate;
-        session.port = config.port;
-        session.completion_percent = config.completion;
-        session.stop_at_ratio = config.stop_ratio;
-        session.stop_at_uploaded_gb = config.stop_uploaded;
-        session.update(
-            final_stats.uploaded,
-            final_stats.downloaded,
-            final_stats.elapsed_time.as_secs(),
-        );
-
-        if let Err(e) = session.save_session() {
-            OutputEvent::error(format!("Failed to save session: {}", e)).emit();
+        let session = build_session(&config, &final_stats, rates);
+        if let Err(e) = session.save_session(&config.sessions_dir) {
+            OutputEvent::error(ErrorCode::Internal, format!("Failed to save session: {}", e)).emit();
         }
     }
 
@@ -273,6 +336,111 @@ pub async fn run_json_mode(config: RunnerConfig) -> Result<()> {
     Ok(())
 }
 
+/// Send a single announce with `event` and print the tracker's response (as JSON in JSON mode).
+/// No stats loop runs and no session is saved.
+pub async fn run_once(config: RunnerConfig, event: TrackerEvent) -> Result<()> {
+    let report = |context: &str, e: &FakerError| {
+        if config.json_mode {
+            OutputEvent::faker_error(context, e).emit();
+        }
+        anyhow::anyhow!("{}: {}", context, e)
+    };
+
+    let torrent = load_torrent(&config.torrent_path).inspect_err(|e| {
+        if config.json_mode {
+            OutputEvent::error(ErrorCode::TorrentParse, format!("{:#}", e)).emit();
+        }
+    })?;
+    let mut faker =
+        RatioFaker::new(torrent, create_faker_config(&config)).map_err(|e| report("Failed to create faker", &e))?;
+    let response = faker
+        .announce_once(event.clone())
+        .await
+        .map_err(|e| report("Announce failed", &e))?;
+
+    if config.json_mode {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+
+    println!("Tracker response to {} announce:", event.as_str().unwrap_or("periodic"));
+    match response.min_interval {
+        Some(min) => println!("  Interval:   {}s (min {}s)", response.interval, min),
+        None => println!("  Interval:   {}s", response.interval),
+    }
+    println!("  Seeders:    {}", response.complete);
+    println!("  Leechers:   {}", response.incomplete);
+    if let Some(ref id) = response.tracker_id {
+        println!("  Tracker ID: {}", id);
+    }
+    if let Some(ref warning) = response.warning {
+        println!("  Warning:    {}", warning);
+    }
+
+    Ok(())
+}
+
+/// Build the session to save from the runner config and current stats.
+/// `rates` are the upload/download rates in effect, which may differ from the config after live changes.
+pub fn build_session(config: &RunnerConfig, stats: &FakerStats, rates: (f64, f64)) -> Session {
+    let client_type: ClientType = config.client.into();
+    let mut session = Session::new(
+        &config.info_hash,
+        &config.torrent_name,
+        &config.torrent_path.to_string_lossy(),
+        config.torrent_size,
+        &format!("{:?}", client_type),
+        config.client_version.clone(),
+    );
+    (session.upload_rate, session.download_rate) = rates;
+    session.port = config.port;
+    session.completion_percent = config.completion;
+    session.stop_at_ratio = config.stop_ratio;
+    session.stop_at_uploaded_gb = config.stop_uploaded;
+    session.update(stats.uploaded, stats.downloaded, stats.elapsed_time.as_secs());
+    session.last_run_uploaded = stats.session_uploaded;
+    session
+}
+
+/// Resolves on Ctrl+C, or on SIGTERM (`docker stop`, supervisors) on unix.
+/// The SIGTERM handler is installed when this is called, not when the future is first polled.
+pub fn shutdown_signal() -> impl std::future::Future<Output = ()> {
+    #[cfg(unix)]
+    let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).ok();
+
+    async move {
+        let ctrl_c = async {
+            // Without a Ctrl+C handler, only SIGTERM can end the run
+            if tokio::signal::ctrl_c().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+
+        #[cfg(unix)]
+        if let Some(sigterm) = sigterm.as_mut() {
+            tokio::select! {
+                _ = ctrl_c => {}
+                _ = sigterm.recv() => {}
+            }
+            return;
+        }
+
+        ctrl_c.await;
+    }
+}
+
+/// Set `shutdown` (and send `RunnerCommand::Shutdown` to `cmd_tx`, if given) once a shutdown signal arrives
+pub fn spawn_shutdown_listener(shutdown: Arc<AtomicBool>, cmd_tx: Option<mpsc::Sender<RunnerCommand>>) {
+    let signal = shutdown_signal();
+    tokio::spawn(async move {
+        signal.await;
+        shutdown.store(true, Ordering::SeqCst);
+        if let Some(cmd_tx) = cmd_tx {
+            let _ = cmd_tx.send(RunnerCommand::Shutdown).await;
+        }
+    });
+}
+
 /// Load torrent file from path
 pub fn load_torrent(path: &Path) -> Result<TorrentInfo> {
     TorrentInfo::from_file(path).context("Failed to parse torrent file")
@@ -289,7 +457,9 @@ pub fn create_faker_config(config: &RunnerConfig) -> FakerConfig {
         initial_uploaded: config.initial_uploaded,
         initial_downloaded: config.initial_downloaded,
         completion_percent: config.completion,
+        completion_jitter_secs: 0,
         num_want: 50,
+        num_want_steady: None,
         randomize_rates: !config.no_randomize,
         random_range_percent: config.random_range,
         stop_at_ratio: config.stop_ratio,
@@ -297,36 +467,54 @@ pub fn create_faker_config(config: &RunnerConfig) -> FakerConfig {
         stop_at_downloaded: config.stop_downloaded.map(|gb| (gb * 1024.0 * 1024.0 * 1024.0) as u64),
         stop_at_seed_time: config.stop_time.map(|hours| (hours * 3600.0) as u64),
         stop_when_no_leechers: config.stop_when_no_leechers,
+        on_target_reached: config.on_target_reached,
+        stop_ratio_basis: config.stop_ratio_basis,
+        max_ratio_cap: config.max_ratio_cap,
+        max_uploads_per_hour: config.max_uploads_per_hour,
         progressive_rates: config.progressive,
         target_upload_rate: config.target_upload,
         target_download_rate: config.target_download,
         progressive_duration: (config.progressive_duration * 3600.0) as u64,
+        progressive_curve: config.progressive_curve,
+        active_schedule: config.active_schedule.clone(),
         announce_max_retries: config.announce_max_retries,
         announce_retry_delay_seconds: config.announce_retry_delay_seconds,
         announce_interval: config.announce_interval,
+        announce_interval_min_secs: config.announce_interval_min_secs,
+        announce_interval_max_secs: config.announce_interval_max_secs,
+        announce_jitter_percent: config.announce_jitter_percent,
+        extra_announce_params: config.extra_announce_params.clone(),
+        announce_all_trackers: config.announce_all_trackers,
+        swarm_aggregation: config.swarm_aggregation,
         update_interval: config.update_interval,
         infinite_retry_after_max: config.infinite_retry_after_max,
+        announce_on_resume: false,
+        request_timeout_secs: config.request_timeout_secs,
+        user_agent_override: config.user_agent_override.clone(),
+        peer_id_prefix_override: config.peer_id_prefix_override.clone(),
     }
 }
 
 /// Determine why the faker stopped based on config and final stats
 fn determine_stop_reason(config: &RunnerConfig, stats: &rustatio_core::FakerStats) -> StopReason {
+    let basis = config.stop_ratio_basis;
+
     if let Some(target_ratio) = config.stop_ratio {
-        if stats.session_ratio >= target_ratio - 0.001 {
+        if basis.ratio(stats) >= target_ratio - 0.001 {
             return StopReason::TargetRatio;
         }
     }
 
     if let Some(target_gb) = config.stop_uploaded {
         let target_bytes = (target_gb * 1024.0 * 1024.0 * 1024.0) as u64;
-        if stats.session_uploaded >= target_bytes {
+        if basis.uploaded(stats) >= target_bytes {
             return StopReason::TargetUploaded;
         }
     }
 
     if let Some(target_gb) = config.stop_downloaded {
         let target_bytes = (target_gb * 1024.0 * 1024.0 * 1024.0) as u64;
-        if stats.session_downloaded >= target_bytes {
+        if basis.downloaded(stats) >= target_bytes {
             return StopReason::TargetDownloaded;
         }
     }
@@ -344,3 +532,30 @@ fn determine_stop_reason(config: &RunnerConfig, stats: &rustatio_core::FakerStat
 
     StopReason::UserInterrupt
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sigterm_sets_shutdown_flag() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (cmd_tx, mut cmd_rx) = mpsc::channel(1);
+        spawn_shutdown_listener(shutdown.clone(), Some(cmd_tx));
+        assert!(!shutdown.load(Ordering::SeqCst));
+
+        // The handler is installed by now, so this doesn't kill the test process
+        let status = std::process::Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let cmd = tokio::time::timeout(Duration::from_secs(5), cmd_rx.recv())
+            .await
+            .unwrap();
+        assert!(matches!(cmd, Some(RunnerCommand::Shutdown)));
+        assert!(shutdown.load(Ordering::SeqCst));
+    }
+}
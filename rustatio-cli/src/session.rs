@@ -6,7 +6,9 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 /// Session data that persists across restarts
@@ -37,6 +39,10 @@ pub struct Session {
     /// Total uploaded bytes (cumulative across all sessions)
     pub uploaded: u64,
 
+    /// Uploaded bytes added by the run that last saved the session
+    #[serde(default)]
+    pub last_run_uploaded: u64,
+
     /// Total downloaded bytes (cumulative)
     pub downloaded: u64,
 
@@ -91,6 +97,7 @@ impl Session {
             client: client.to_string(),
             client_version,
             uploaded: 0,
+            last_run_uploaded: 0,
             downloaded: 0,
             upload_rate: 700.0,
             download_rate: 0.0,
@@ -148,28 +155,40 @@ impl Session {
         }
 
         let content = serde_json::to_string_pretty(self).with_context(|| "Failed to serialize session")?;
-        fs::write(path.as_ref(), content)
-            .with_context(|| format!("Failed to write session file: {:?}", path.as_ref()))?;
+
+        // Write next to the target and rename over it, so a crash mid-write never leaves a truncated session
+        let mut tmp_path = path.as_ref().as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let mut file =
+            fs::File::create(&tmp_path).with_context(|| format!("Failed to create session file: {:?}", tmp_path))?;
+        file.write_all(content.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| format!("Failed to write session file: {:?}", tmp_path))?;
+        fs::rename(&tmp_path, path.as_ref())
+            .with_context(|| format!("Failed to replace session file: {:?}", path.as_ref()))?;
         Ok(())
     }
 
-    /// Get the default sessions directory
-    pub fn sessions_dir() -> PathBuf {
-        if let Ok(home) = std::env::var("HOME") {
+    /// Get the sessions directory (`<data dir>/sessions` when a data directory is given)
+    pub fn sessions_dir(data_dir: Option<&Path>) -> PathBuf {
+        if let Some(data_dir) = data_dir {
+            data_dir.join("sessions")
+        } else if let Ok(home) = std::env::var("HOME") {
             PathBuf::from(home).join(".config").join("rustatio").join("sessions")
         } else {
             PathBuf::from("sessions")
         }
     }
 
-    /// Get the session file path for an info hash
-    pub fn path_for_hash(info_hash: &str) -> PathBuf {
-        Self::sessions_dir().join(format!("{}.json", info_hash))
+    /// Get the session file path for an info hash in `dir`
+    pub fn path_for_hash(dir: &Path, info_hash: &str) -> PathBuf {
+        dir.join(format!("{}.json", info_hash))
     }
 
-    /// Load session by info hash (if exists)
-    pub fn load_for_hash(info_hash: &str) -> Option<Self> {
-        let path = Self::path_for_hash(info_hash);
+    /// Load session by info hash from `dir` (if exists)
+    pub fn load_for_hash(dir: &Path, info_hash: &str) -> Option<Self> {
+        let path = Self::path_for_hash(dir, info_hash);
         if path.exists() {
             Self::load(&path).ok()
         } else {
@@ -177,30 +196,28 @@ impl Session {
         }
     }
 
-    /// Save session (uses info_hash as filename)
-    pub fn save_session(&self) -> Result<()> {
-        let path = Self::path_for_hash(&self.info_hash);
-        self.save(path)
+    /// Save session in `dir` (uses info_hash as filename)
+    pub fn save_session(&self, dir: &Path) -> Result<()> {
+        self.save(Self::path_for_hash(dir, &self.info_hash))
     }
 
-    /// Delete session file
-    pub fn delete(&self) -> Result<()> {
-        let path = Self::path_for_hash(&self.info_hash);
+    /// Delete session file from `dir`
+    pub fn delete(&self, dir: &Path) -> Result<()> {
+        let path = Self::path_for_hash(dir, &self.info_hash);
         if path.exists() {
             fs::remove_file(&path).with_context(|| format!("Failed to delete session file: {:?}", path))?;
         }
         Ok(())
     }
 
-    /// List all saved sessions
-    pub fn list_all() -> Result<Vec<SessionSummary>> {
-        let dir = Self::sessions_dir();
+    /// List all sessions saved in `dir`
+    pub fn list_all(dir: &Path) -> Result<Vec<SessionSummary>> {
         if !dir.exists() {
             return Ok(vec![]);
         }
 
         let mut sessions = Vec::new();
-        for entry in fs::read_dir(&dir)? {
+        for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
@@ -211,9 +228,161 @@ impl Session {
         }
 
         // Sort by last updated (most recent first)
-        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
         Ok(sessions)
     }
+
+    /// Write every session saved in `dir` to `path` as a single JSON array. Returns how many were exported.
+    pub fn export_all<P: AsRef<Path>>(dir: &Path, path: P) -> Result<usize> {
+        let sessions: Vec<Session> = Self::list_all(dir)?
+            .iter()
+            .filter_map(|summary| Self::load_for_hash(dir, &summary.info_hash))
+            .collect();
+
+        let content = serde_json::to_string_pretty(&sessions).with_context(|| "Failed to serialize sessions")?;
+        fs::write(path.as_ref(), content)
+            .with_context(|| format!("Failed to write export file: {:?}", path.as_ref()))?;
+        Ok(sessions.len())
+    }
+
+    /// Read the sessions from a file written by `export_all`
+    pub fn read_export<P: AsRef<Path>>(path: P) -> Result<SessionImport> {
+        let content = fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read export file: {:?}", path.as_ref()))?;
+        Self::parse_export(&content)
+    }
+
+    /// Parse an export file. Entries that aren't valid sessions, or whose info hash can't be used as a
+    /// file name, are dropped; sessions from another format version are kept. Both come with a warning.
+    fn parse_export(content: &str) -> Result<SessionImport> {
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(content).with_context(|| "Export file is not a JSON array of sessions")?;
+
+        let mut import = SessionImport::default();
+        for (i, entry) in entries.into_iter().enumerate() {
+            let session: Session = match serde_json::from_value(entry) {
+                Ok(session) => session,
+                Err(e) => {
+                    import
+                        .warnings
+                        .push(format!("Entry {}: not a valid session ({})", i + 1, e));
+                    continue;
+                }
+            };
+
+            if session.info_hash.len() != 40 || !session.info_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                import
+                    .warnings
+                    .push(format!("Entry {}: invalid info hash {:?}", i + 1, session.info_hash));
+                continue;
+            }
+
+            if session.version != Self::VERSION {
+                import.warnings.push(format!(
+                    "Session {} uses format version {}, this version of rustatio writes {}",
+                    session.info_hash,
+                    session.version,
+                    Self::VERSION
+                ));
+            }
+
+            import.sessions.push(session);
+        }
+
+        Ok(import)
+    }
+}
+
+/// How many torrents `SessionStats` lists by uploaded amount
+const TOP_SESSIONS: usize = 5;
+
+/// Aggregate figures across sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStats {
+    pub session_count: usize,
+    pub total_uploaded: u64,
+    pub total_downloaded: u64,
+    pub total_seed_time_secs: u64,
+    /// Mean ratio over sessions with a finite ratio (None if there are none)
+    pub average_ratio: Option<f64>,
+    /// Number of sessions per emulated client
+    pub by_client: BTreeMap<String, usize>,
+    /// Sessions with the most uploaded, highest first
+    pub top_uploaded: Vec<SessionSummary>,
+}
+
+impl SessionStats {
+    pub fn from_sessions(sessions: &[Session]) -> Self {
+        let ratios: Vec<f64> = sessions.iter().map(Session::ratio).filter(|r| r.is_finite()).collect();
+        let average_ratio = (!ratios.is_empty()).then(|| ratios.iter().sum::<f64>() / ratios.len() as f64);
+
+        let mut by_client = BTreeMap::new();
+        for session in sessions {
+            *by_client.entry(session.client.clone()).or_insert(0) += 1;
+        }
+
+        let mut top: Vec<&Session> = sessions.iter().collect();
+        top.sort_by_key(|s| std::cmp::Reverse(s.uploaded));
+
+        SessionStats {
+            session_count: sessions.len(),
+            total_uploaded: sessions.iter().map(|s| s.uploaded).sum(),
+            total_downloaded: sessions.iter().map(|s| s.downloaded).sum(),
+            total_seed_time_secs: sessions.iter().map(|s| s.total_seed_time_secs).sum(),
+            average_ratio,
+            by_client,
+            top_uploaded: top.into_iter().take(TOP_SESSIONS).map(SessionSummary::from).collect(),
+        }
+    }
+}
+
+/// A saved session compared against its torrent file and the tracker's current swarm
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDiff {
+    pub info_hash: String,
+    pub torrent_name: String,
+    /// Total uploaded that a resume would report
+    pub uploaded: u64,
+    /// Uploaded bytes added by the run that last saved the session
+    pub last_run_uploaded: u64,
+    pub updated_at: DateTime<Utc>,
+    /// Seconds since the session was last saved
+    pub elapsed_since_update_secs: u64,
+    pub torrent_path: String,
+    pub torrent_exists: bool,
+    /// Whether the torrent file still has the session's info hash (None if it can't be read)
+    pub torrent_matches: Option<bool>,
+    /// Tracker's swarm right now (None if not scraped)
+    pub swarm: Option<rustatio_core::protocol::ScrapeResponse>,
+    /// Why the tracker couldn't be scraped
+    pub scrape_error: Option<String>,
+}
+
+impl SessionDiff {
+    /// Compare `session` with the torrent file read from its path (None if unreadable) at `now`
+    pub fn compare(session: &Session, torrent: Option<&rustatio_core::TorrentInfo>, now: DateTime<Utc>) -> Self {
+        SessionDiff {
+            info_hash: session.info_hash.clone(),
+            torrent_name: session.torrent_name.clone(),
+            uploaded: session.uploaded,
+            last_run_uploaded: session.last_run_uploaded,
+            updated_at: session.updated_at,
+            elapsed_since_update_secs: (now - session.updated_at).num_seconds().max(0) as u64,
+            torrent_path: session.torrent_path.clone(),
+            torrent_exists: Path::new(&session.torrent_path).exists(),
+            torrent_matches: torrent.map(|t| t.info_hash_hex().eq_ignore_ascii_case(&session.info_hash)),
+            swarm: None,
+            scrape_error: None,
+        }
+    }
+}
+
+/// Sessions read from an export file
+#[derive(Debug, Default)]
+pub struct SessionImport {
+    pub sessions: Vec<Session>,
+    /// Problems found while reading, one per affected entry
+    pub warnings: Vec<String>,
 }
 
 /// Summary information about a session for listing
@@ -283,6 +452,44 @@ mod tests {
         assert_eq!(session.ratio(), 2.0);
     }
 
+    #[test]
+    fn test_session_diff() {
+        let dir = tempdir().unwrap();
+        let torrent_path = dir.path().join("test.torrent");
+        let mut session = Session::new(
+            "0101010101010101010101010101010101010101",
+            "Test Torrent",
+            &torrent_path.to_string_lossy(),
+            1024,
+            "qbittorrent",
+            None,
+        );
+        session.uploaded = 4096;
+        session.last_run_uploaded = 1024;
+        let now = session.updated_at + chrono::Duration::hours(2);
+
+        let diff = SessionDiff::compare(&session, None, now);
+        assert_eq!(diff.uploaded, 4096);
+        assert_eq!(diff.last_run_uploaded, 1024);
+        assert_eq!(diff.elapsed_since_update_secs, 7200);
+        assert!(!diff.torrent_exists);
+        assert_eq!(diff.torrent_matches, None);
+
+        fs::write(&torrent_path, b"d4:infoe").unwrap();
+        let mut torrent = rustatio_core::TorrentInfo {
+            name: "Test Torrent".to_string(),
+            ..rustatio_core::TorrentInfo::fixture("http://tracker.example/announce")
+        };
+        let diff = SessionDiff::compare(&session, Some(&torrent), now);
+        assert!(diff.torrent_exists);
+        assert_eq!(diff.torrent_matches, Some(true));
+
+        // Replaced by a different torrent at the same path
+        torrent.info_hash = [2u8; 20];
+        let diff = SessionDiff::compare(&session, Some(&torrent), now);
+        assert_eq!(diff.torrent_matches, Some(false));
+    }
+
     #[test]
     fn test_session_save_load() {
         let dir = tempdir().unwrap();
@@ -305,4 +512,105 @@ mod tests {
         assert_eq!(loaded.torrent_name, session.torrent_name);
         assert_eq!(loaded.torrent_size, session.torrent_size);
     }
+
+    #[test]
+    fn test_session_resave_replaces_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test_session.json");
+
+        let mut session = Session::new("abcdef", "Test", "/path/to/test.torrent", 1024, "transmission", None);
+        session.save(&path).unwrap();
+
+        session.update(2048, 0, 30);
+        session.save(&path).unwrap();
+
+        assert_eq!(Session::load(&path).unwrap().uploaded, 2048);
+        // Only the session itself is left, no temp file
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_failed_save_keeps_existing_session() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test_session.json");
+
+        let mut session = Session::new("abcdef", "Test", "/path/to/test.torrent", 1024, "transmission", None);
+        session.update(1000, 0, 30);
+        session.save(&path).unwrap();
+
+        // A temp file that can't be written stands in for a save interrupted mid-write
+        let tmp_path = dir.path().join("test_session.json.tmp");
+        std::fs::create_dir(&tmp_path).unwrap();
+        session.update(2000, 0, 30);
+        assert!(session.save(&path).is_err());
+        assert_eq!(Session::load(&path).unwrap().uploaded, 1000);
+
+        // A truncated temp file left by a crash is simply overwritten by the next save
+        std::fs::remove_dir(&tmp_path).unwrap();
+        std::fs::write(&tmp_path, "{\"version\": 1, \"info_").unwrap();
+        assert_eq!(Session::load(&path).unwrap().uploaded, 1000);
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap().uploaded, 2000);
+        assert!(!tmp_path.exists());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let hash = "a".repeat(40);
+        let mut session = Session::new(&hash, "Test", "/path/to/test.torrent", 1024, "transmission", None);
+        let mut future = session.clone();
+        future.info_hash = "b".repeat(40);
+        future.version = Session::VERSION + 1;
+        let mut bad_hash = session.clone();
+        bad_hash.info_hash = "../../etc/passwd".to_string();
+        session.update(2048, 0, 30);
+
+        let content = serde_json::to_string(&serde_json::json!([session, future, bad_hash, {"version": 1}])).unwrap();
+        let import = Session::parse_export(&content).unwrap();
+
+        let hashes: Vec<_> = import.sessions.iter().map(|s| s.info_hash.as_str()).collect();
+        assert_eq!(hashes, vec![hash.as_str(), future.info_hash.as_str()]);
+        assert_eq!(import.sessions[0].uploaded, 2048);
+        assert_eq!(import.warnings.len(), 3);
+        assert!(import.warnings[0].contains("format version 2"));
+
+        assert!(Session::parse_export("{}").is_err());
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let session = |hash: &str, client: &str, uploaded: u64, size: u64| {
+            let mut s = Session::new(hash, hash, "/path/to/test.torrent", size, client, None);
+            s.update(uploaded, 0, 60);
+            s
+        };
+        let sessions = vec![
+            session("a", "Transmission", 100, 100),
+            session("b", "QBittorrent", 300, 100),
+            session("c", "Transmission", 50, 0), // infinite ratio, left out of the average
+        ];
+
+        let stats = SessionStats::from_sessions(&sessions);
+        assert_eq!(stats.session_count, 3);
+        assert_eq!(stats.total_uploaded, 450);
+        assert_eq!(stats.total_seed_time_secs, 180);
+        assert_eq!(stats.average_ratio, Some(2.0));
+        assert_eq!(stats.by_client["Transmission"], 2);
+        assert_eq!(stats.by_client["QBittorrent"], 1);
+        let top: Vec<_> = stats.top_uploaded.iter().map(|s| s.info_hash.as_str()).collect();
+        assert_eq!(top, vec!["b", "a", "c"]);
+
+        assert_eq!(SessionStats::from_sessions(&[]).average_ratio, None);
+    }
+
+    #[test]
+    fn test_sessions_dir_in_data_dir() {
+        let dir = Session::sessions_dir(Some(Path::new("/tmp/rustatio-data")));
+        assert_eq!(dir, PathBuf::from("/tmp/rustatio-data/sessions"));
+        assert_eq!(
+            Session::path_for_hash(&dir, "abc"),
+            PathBuf::from("/tmp/rustatio-data/sessions/abc.json")
+        );
+        assert!(Session::sessions_dir(None).ends_with("sessions"));
+    }
 }
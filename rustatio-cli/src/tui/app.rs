@@ -1,6 +1,5 @@
 use crate::json::{format_bytes, format_duration};
-use crate::runner::RunnerConfig;
-use crate::session::Session;
+use crate::runner::{build_session, RunnerConfig};
 use anyhow::Result;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
@@ -12,16 +11,22 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{Block, Borders, Gauge, Paragraph, Sparkline},
     Frame, Terminal,
 };
-use rustatio_core::{ClientConfig, ClientType, FakerState, FakerStats, RatioFaker, TorrentInfo};
+use rustatio_core::{
+    validate_rate, ClientConfig, ClientType, FakerState, FakerStats, RatioFaker, TorrentInfo, MAX_RATE, MIN_RATE,
+};
 use std::io;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration as StdDuration, Instant};
 use tokio::time::{interval, Duration};
 
+/// Rate change per key press (KB/s)
+const RATE_STEP: f64 = 10.0;
+
 /// TUI Application state
 pub struct App {
     pub torrent: TorrentInfo,
@@ -44,6 +49,9 @@ pub struct App {
 
     // Track announce count to detect new announces
     pub last_announce_count: u32,
+
+    // Rate history graph toggled with [g]
+    pub show_graph: bool,
 }
 
 impl App {
@@ -66,6 +74,7 @@ impl App {
             target_uploaded: config.stop_uploaded,
             target_time: config.stop_time,
             last_announce_count: 0,
+            show_graph: false,
         }
     }
 
@@ -84,13 +93,66 @@ impl App {
 }
 
 /// Keyboard commands
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum KeyCommand {
     Quit,
     Pause,
     Resume,
     Stop,
     Scrape,
+    ToggleGraph,
+    /// Change the upload/download rate by the given delta (KB/s)
+    AdjustRate {
+        upload: f64,
+        download: f64,
+    },
+}
+
+/// Map a pressed key to its command
+fn key_command(code: KeyCode) -> Option<KeyCommand> {
+    match code {
+        KeyCode::Char('q') | KeyCode::Esc => Some(KeyCommand::Quit),
+        KeyCode::Char('p') => Some(KeyCommand::Pause),
+        KeyCode::Char('r') => Some(KeyCommand::Resume),
+        KeyCode::Char('x') => Some(KeyCommand::Stop),
+        KeyCode::Char('s') => Some(KeyCommand::Scrape),
+        KeyCode::Char('g') => Some(KeyCommand::ToggleGraph),
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(KeyCommand::AdjustRate {
+            upload: RATE_STEP,
+            download: 0.0,
+        }),
+        KeyCode::Char('-') => Some(KeyCommand::AdjustRate {
+            upload: -RATE_STEP,
+            download: 0.0,
+        }),
+        KeyCode::Char(']') => Some(KeyCommand::AdjustRate {
+            upload: 0.0,
+            download: RATE_STEP,
+        }),
+        KeyCode::Char('[') => Some(KeyCommand::AdjustRate {
+            upload: 0.0,
+            download: -RATE_STEP,
+        }),
+        _ => None,
+    }
+}
+
+/// Apply a rate change from the keyboard to the faker and the displayed rates
+fn adjust_rates(app: &mut App, faker: &mut RatioFaker, upload: f64, download: f64) {
+    // Clamp to the accepted range instead of rejecting the key press
+    let clamp = |rate: f64, field| validate_rate(rate.clamp(MIN_RATE, MAX_RATE), field).ok();
+    let upload = clamp(app.upload_rate + upload, "upload_rate");
+    let download = clamp(app.download_rate + download, "download_rate");
+    match faker.set_rates(upload, download) {
+        Ok((upload_rate, download_rate)) => {
+            app.upload_rate = upload_rate;
+            app.download_rate = download_rate;
+            app.set_status(format!("Rates: ↑ {:.0} KB/s  ↓ {:.0} KB/s", upload_rate, download_rate));
+        }
+        Err(e) => {
+            app.set_status(format!("Rate change failed: {}", e));
+        }
+    }
 }
 
 /// Run the TUI mode
@@ -126,6 +188,10 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
 
     app.set_status("Running");
 
+    // Raw mode swallows Ctrl+C as a key press, so this mostly catches SIGTERM
+    let shutdown = Arc::new(AtomicBool::new(false));
+    crate::runner::spawn_shutdown_listener(shutdown.clone(), None);
+
     // Setup keyboard event channel - use std::sync::mpsc for thread communication
     let (key_tx, key_rx) = mpsc::channel::<KeyCommand>();
 
@@ -136,16 +202,7 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
             if event::poll(StdDuration::from_millis(100)).unwrap_or(false) {
                 if let Ok(Event::Key(key)) = event::read() {
                     if key.kind == KeyEventKind::Press {
-                        let cmd = match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => Some(KeyCommand::Quit),
-                            KeyCode::Char('p') => Some(KeyCommand::Pause),
-                            KeyCode::Char('r') => Some(KeyCommand::Resume),
-                            KeyCode::Char('x') => Some(KeyCommand::Stop),
-                            KeyCode::Char('s') => Some(KeyCommand::Scrape),
-                            _ => None,
-                        };
-
-                        if let Some(cmd) = cmd {
+                        if let Some(cmd) = key_command(key.code) {
                             if key_tx.send(cmd).is_err() {
                                 break; // Channel closed, exit thread
                             }
@@ -158,8 +215,16 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
 
     // Main loop
     let mut stats_ticker = interval(Duration::from_millis(500));
+    let auto_save = config.save_session && config.session_save_interval > 0;
+    let mut last_session_save = Instant::now();
 
     loop {
+        // Shut down the same way as [q] on a signal
+        if shutdown.load(Ordering::SeqCst) {
+            app.should_quit = true;
+            app.set_status("Shutdown signal received, stopping...");
+        }
+
         // Check for keyboard commands (non-blocking)
         while let Ok(cmd) = key_rx.try_recv() {
             match cmd {
@@ -170,7 +235,7 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
                 }
                 KeyCommand::Pause => {
                     if let Some(ref stats) = app.stats {
-                        if matches!(stats.state, FakerState::Running) {
+                        if matches!(stats.state, FakerState::Running | FakerState::Seeding) {
                             if let Err(e) = faker.pause().await {
                                 app.set_status(format!("Pause failed: {}", e));
                             } else {
@@ -215,6 +280,14 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
                         }
                     }
                 }
+                KeyCommand::ToggleGraph => {
+                    app.show_graph = !app.show_graph;
+                    terminal.draw(|f| ui(f, &app))?;
+                }
+                KeyCommand::AdjustRate { upload, download } => {
+                    adjust_rates(&mut app, &mut faker, upload, download);
+                    terminal.draw(|f| ui(f, &app))?;
+                }
             }
         }
 
@@ -228,8 +301,8 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
         // Get current stats first to check state
         let stats = faker.get_stats().await;
 
-        // Only update if running (not paused)
-        if matches!(stats.state, FakerState::Running) {
+        // Only update if running (not paused); idle seeding still announces
+        if matches!(stats.state, FakerState::Running | FakerState::Seeding) {
             // Use update() which handles periodic announces
             if let Err(e) = faker.update().await {
                 app.set_status(format!("Update error: {}", e));
@@ -263,6 +336,15 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
             break;
         }
 
+        // Periodically save so a crash doesn't lose the whole session
+        if auto_save && last_session_save.elapsed() >= StdDuration::from_secs(config.session_save_interval) {
+            last_session_save = Instant::now();
+            let session = build_session(&config, &stats, (app.upload_rate, app.download_rate));
+            if let Err(e) = session.save_session(&config.sessions_dir) {
+                app.set_status(format!("Session auto-save failed: {}", e));
+            }
+        }
+
         app.update_stats(stats);
         terminal.draw(|f| ui(f, &app))?;
     }
@@ -270,7 +352,7 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
     // Stop faker gracefully if not already stopped
     if !matches!(
         app.stats.as_ref().map(|s| &s.state),
-        Some(FakerState::Stopped) | Some(FakerState::Completed)
+        Some(FakerState::Stopped) | Some(FakerState::Completed) | Some(FakerState::Errored)
     ) {
         app.set_status("Stopping...");
         terminal.draw(|f| ui(f, &app))?;
@@ -283,24 +365,8 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
     // Save session if enabled
     if config.save_session {
         if let Some(ref stats) = app.stats {
-            let client_type: ClientType = config.client.into();
-            let mut session = Session::new(
-                &config.info_hash,
-                &config.torrent_name,
-                &config.torrent_path.to_string_lossy(),
-                config.torrent_size,
-                &format!("{:?}", client_type),
-                config.client_version.clone(),
-            );
-            session.upload_rate = config.upload_rate;
-            session.download_rate = config.download_rate;
-            session.port = config.port;
-            session.completion_percent = config.completion;
-            session.stop_at_ratio = config.stop_ratio;
-            session.stop_at_uploaded_gb = config.stop_uploaded;
-            session.update(stats.uploaded, stats.downloaded, stats.elapsed_time.as_secs());
-
-            if let Err(e) = session.save_session() {
+            let session = build_session(&config, stats, (app.upload_rate, app.download_rate));
+            if let Err(e) = session.save_session(&config.sessions_dir) {
                 eprintln!("Warning: Failed to save session: {}", e);
             } else {
                 println!("Session saved. Use --resume to continue later.");
@@ -320,7 +386,7 @@ pub async fn run_tui_mode(config: RunnerConfig) -> Result<()> {
     Ok(())
 }
 
-fn cleanup_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+pub(super) fn cleanup_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
     terminal.show_cursor()?;
@@ -331,30 +397,24 @@ fn cleanup_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Re
 fn ui(frame: &mut Frame, app: &App) {
     let size = frame.area();
 
-    // Determine layout based on whether we have stop conditions
+    // Determine layout based on whether we have stop conditions and the graph is shown
     let has_progress = app.has_stop_condition();
 
     // Create main layout
-    let constraints = if has_progress {
-        vec![
-            Constraint::Length(3), // Header
-            Constraint::Length(6), // Torrent info (expanded)
-            Constraint::Length(3), // Status bar
-            Constraint::Length(8), // Stats (expanded)
-            Constraint::Length(3), // Tracker/Announce info
-            Constraint::Length(5), // Progress section
-            Constraint::Min(3),    // Help
-        ]
-    } else {
-        vec![
-            Constraint::Length(3), // Header
-            Constraint::Length(6), // Torrent info (expanded)
-            Constraint::Length(3), // Status bar
-            Constraint::Length(8), // Stats (expanded)
-            Constraint::Length(3), // Tracker/Announce info
-            Constraint::Min(3),    // Help
-        ]
-    };
+    let mut constraints = vec![
+        Constraint::Length(3), // Header
+        Constraint::Length(6), // Torrent info (expanded)
+        Constraint::Length(3), // Status bar
+        Constraint::Length(8), // Stats (expanded)
+    ];
+    if app.show_graph {
+        constraints.push(Constraint::Length(8)); // Rate graph
+    }
+    constraints.push(Constraint::Length(3)); // Tracker/Announce info
+    if has_progress {
+        constraints.push(Constraint::Length(5)); // Progress section
+    }
+    constraints.push(Constraint::Min(3)); // Help
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -363,13 +423,7 @@ fn ui(frame: &mut Frame, app: &App) {
         .split(size);
 
     // Header
-    let header = Paragraph::new(format!(
-        " rustatio v{} - BitTorrent Ratio Faker",
-        env!("CARGO_PKG_VERSION")
-    ))
-    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-    .block(Block::default().borders(Borders::ALL));
-    frame.render_widget(header, chunks[0]);
+    render_header(frame, chunks[0]);
 
     // Torrent info (expanded with more details)
     render_torrent_info(frame, app, chunks[1]);
@@ -380,15 +434,47 @@ fn ui(frame: &mut Frame, app: &App) {
     // Stats
     render_stats(frame, app, chunks[3]);
 
+    let mut next = 4;
+
+    // Rate graph (if toggled on)
+    if app.show_graph {
+        render_rate_graph(frame, app, chunks[next]);
+        next += 1;
+    }
+
     // Tracker/Announce info
-    render_tracker_info(frame, app, chunks[4]);
+    render_tracker_info(frame, app, chunks[next]);
+    next += 1;
 
     // Progress section (if stop conditions set)
     if has_progress {
-        render_progress(frame, app, chunks[5]);
-        render_help(frame, chunks[6]);
-    } else {
-        render_help(frame, chunks[5]);
+        render_progress(frame, app, chunks[next]);
+        next += 1;
+    }
+
+    render_help(frame, chunks[next]);
+}
+
+pub(super) fn render_header(frame: &mut Frame, area: Rect) {
+    let header = Paragraph::new(format!(
+        " rustatio v{} - BitTorrent Ratio Faker",
+        env!("CARGO_PKG_VERSION")
+    ))
+    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(header, area);
+}
+
+/// Status text and color for a faker state
+pub(super) fn state_label(state: &FakerState) -> (&'static str, Color) {
+    match state {
+        FakerState::Running => ("● Running", Color::Green),
+        FakerState::Paused => ("⏸ Paused", Color::Yellow),
+        FakerState::Stopped => ("■ Stopped", Color::Red),
+        FakerState::Completed => ("✓ Completed", Color::Cyan),
+        FakerState::Seeding => ("◐ Seeding (target reached)", Color::Blue),
+        FakerState::Idle => ("○ Idle", Color::Gray),
+        FakerState::Errored => ("✗ Errored", Color::Red),
     }
 }
 
@@ -401,7 +487,10 @@ fn render_torrent_info(frame: &mut Frame, app: &App, area: Rect) {
         ]),
         Line::from(vec![
             Span::styled("Tracker: ", Style::default().fg(Color::Gray)),
-            Span::styled(&app.torrent.announce, Style::default().fg(Color::Yellow)),
+            Span::styled(
+                rustatio_core::redact_tracker_url(&app.torrent.announce),
+                Style::default().fg(Color::Yellow),
+            ),
         ]),
         Line::from(vec![
             Span::styled("Size:    ", Style::default().fg(Color::Gray)),
@@ -454,13 +543,7 @@ fn render_torrent_info(frame: &mut Frame, app: &App, area: Rect) {
 
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let (status_text, status_color) = if let Some(ref stats) = app.stats {
-        match stats.state {
-            FakerState::Running => ("● Running", Color::Green),
-            FakerState::Paused => ("⏸ Paused", Color::Yellow),
-            FakerState::Stopped => ("■ Stopped", Color::Red),
-            FakerState::Completed => ("✓ Completed", Color::Cyan),
-            FakerState::Idle => ("○ Idle", Color::Gray),
-        }
+        state_label(&stats.state)
     } else {
         ("○ Initializing", Color::Gray)
     };
@@ -484,6 +567,12 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         status_spans.push(Span::styled(format!("[{}]", msg), Style::default().fg(Color::Magenta)));
     }
 
+    // Why the faker gave up
+    if let Some(error) = app.stats.as_ref().and_then(|s| s.error.as_ref()) {
+        status_spans.push(Span::raw("   "));
+        status_spans.push(Span::styled(error.clone(), Style::default().fg(Color::Red)));
+    }
+
     let status_line = Line::from(status_spans);
     let status_bar = Paragraph::new(status_line).block(Block::default().borders(Borders::ALL).title(" Status "));
     frame.render_widget(status_bar, area);
@@ -565,6 +654,55 @@ fn render_stats(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
+fn render_rate_graph(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Rate History (KB/s) ");
+
+    let Some(stats) = app
+        .stats
+        .as_ref()
+        .filter(|s| !s.upload_rate_history.is_empty() || !s.download_rate_history.is_empty())
+    else {
+        frame.render_widget(Paragraph::new(" Waiting for rate samples...").block(block), area);
+        return;
+    };
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Both graphs share the observed max so their heights are comparable
+    let max = stats
+        .upload_rate_history
+        .iter()
+        .chain(&stats.download_rate_history)
+        .fold(0.0_f64, |max, &rate| max.max(rate));
+
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+
+    for (history, label, color, area) in [
+        (&stats.upload_rate_history, "↑", Color::Green, halves[0]),
+        (&stats.download_rate_history, "↓", Color::Blue, halves[1]),
+    ] {
+        let data = sparkline_data(history, area.width);
+        let peak = history.iter().fold(0.0_f64, |max, &rate| max.max(rate));
+
+        let sparkline = Sparkline::default()
+            .block(Block::default().title(format!(" {} peak {:.1} ", label, peak)))
+            .data(&data)
+            .max((max.round() as u64).max(1))
+            .style(Style::default().fg(color));
+        frame.render_widget(sparkline, area);
+    }
+}
+
+/// Keep the most recent rate samples that fit the width, rounded for the sparkline
+fn sparkline_data(history: &[f64], width: u16) -> Vec<u64> {
+    let skip = history.len().saturating_sub(width as usize);
+    history[skip..].iter().map(|rate| rate.round() as u64).collect()
+}
+
 fn render_tracker_info(frame: &mut Frame, app: &App, area: Rect) {
     let mut spans = vec![Span::styled(" Tracker: ", Style::default().fg(Color::Gray))];
 
@@ -716,8 +854,146 @@ fn render_progress(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_help(frame: &mut Frame, area: Rect) {
-    let help = Paragraph::new(" [q] Quit   [p] Pause   [r] Resume   [x] Stop   [s] Scrape")
-        .style(Style::default().fg(Color::DarkGray))
-        .block(Block::default().borders(Borders::TOP));
+    let help = Paragraph::new(
+        " [q] Quit   [p] Pause   [r] Resume   [x] Stop   [s] Scrape   [g] Graph   [+/-] Upload rate   [[/]] Download rate",
+    )
+    .style(Style::default().fg(Color::DarkGray))
+    .block(Block::default().borders(Borders::TOP));
     frame.render_widget(help, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use rustatio_core::FakerConfig;
+
+    fn test_faker() -> RatioFaker {
+        let config = FakerConfig {
+            upload_rate: 50.0,
+            download_rate: 100.0,
+            ..FakerConfig::default()
+        };
+        RatioFaker::new(TorrentInfo::fixture("http://127.0.0.1:1/announce"), config).unwrap()
+    }
+
+    fn test_app() -> App {
+        App {
+            torrent: TorrentInfo::fixture("http://127.0.0.1:1/announce"),
+            client_type: ClientType::QBittorrent,
+            client_version: "5.0.0".to_string(),
+            stats: None,
+            status_message: None,
+            should_quit: false,
+            completion: 100.0,
+            upload_rate: 50.0,
+            download_rate: 100.0,
+            port: 6881,
+            target_ratio: None,
+            target_uploaded: None,
+            target_time: None,
+            last_announce_count: 0,
+            show_graph: false,
+        }
+    }
+
+    fn render(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 60)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_key_command() {
+        assert_eq!(key_command(KeyCode::Char('q')), Some(KeyCommand::Quit));
+        assert_eq!(key_command(KeyCode::Esc), Some(KeyCommand::Quit));
+        assert_eq!(key_command(KeyCode::Char('p')), Some(KeyCommand::Pause));
+        assert_eq!(key_command(KeyCode::Char('r')), Some(KeyCommand::Resume));
+        assert_eq!(key_command(KeyCode::Char('x')), Some(KeyCommand::Stop));
+        assert_eq!(key_command(KeyCode::Char('s')), Some(KeyCommand::Scrape));
+        assert_eq!(key_command(KeyCode::Char('g')), Some(KeyCommand::ToggleGraph));
+
+        let upload = |delta| {
+            Some(KeyCommand::AdjustRate {
+                upload: delta,
+                download: 0.0,
+            })
+        };
+        let download = |delta| {
+            Some(KeyCommand::AdjustRate {
+                upload: 0.0,
+                download: delta,
+            })
+        };
+        assert_eq!(key_command(KeyCode::Char('+')), upload(RATE_STEP));
+        assert_eq!(key_command(KeyCode::Char('=')), upload(RATE_STEP));
+        assert_eq!(key_command(KeyCode::Char('-')), upload(-RATE_STEP));
+        assert_eq!(key_command(KeyCode::Char(']')), download(RATE_STEP));
+        assert_eq!(key_command(KeyCode::Char('[')), download(-RATE_STEP));
+
+        assert_eq!(key_command(KeyCode::Char('z')), None);
+        assert_eq!(key_command(KeyCode::Enter), None);
+    }
+
+    #[test]
+    fn test_adjust_rates() {
+        let mut app = test_app();
+        let mut faker = test_faker();
+
+        adjust_rates(&mut app, &mut faker, RATE_STEP, -RATE_STEP);
+        assert_eq!((app.upload_rate, app.download_rate), (60.0, 90.0));
+        assert_eq!(app.status_message.as_deref(), Some("Rates: ↑ 60 KB/s  ↓ 90 KB/s"));
+
+        // Stepping below the minimum clamps instead of failing
+        for _ in 0..10 {
+            adjust_rates(&mut app, &mut faker, -RATE_STEP, 0.0);
+        }
+        assert_eq!(app.upload_rate, MIN_RATE);
+
+        app.download_rate = MAX_RATE - 1.0;
+        adjust_rates(&mut app, &mut faker, 0.0, RATE_STEP);
+        assert_eq!(app.download_rate, MAX_RATE);
+    }
+
+    #[test]
+    fn test_sparkline_data() {
+        assert!(sparkline_data(&[], 10).is_empty());
+        assert_eq!(sparkline_data(&[1.4, 2.6], 10), vec![1, 3]);
+
+        // Only the most recent samples that fit are kept
+        assert_eq!(sparkline_data(&[1.0, 2.0, 3.0, 4.0], 2), vec![3, 4]);
+        assert!(sparkline_data(&[1.0, 2.0], 0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rate_graph_toggle() {
+        let mut app = test_app();
+        assert!(!render(&app).contains("Rate History"));
+
+        app.show_graph = true;
+        assert!(render(&app).contains("Waiting for rate samples..."));
+
+        let mut stats = test_faker().get_stats().await;
+        stats.upload_rate_history = vec![10.0, 42.5, 20.0];
+        stats.download_rate_history = vec![5.0];
+        app.update_stats(stats);
+        let screen = render(&app);
+        assert!(screen.contains("Rate History"));
+        assert!(screen.contains("↑ peak 42.5"));
+        assert!(screen.contains("↓ peak 5.0"));
+    }
+
+    #[test]
+    fn test_has_stop_condition() {
+        let mut app = test_app();
+        assert!(!app.has_stop_condition());
+        app.target_time = Some(1.0);
+        assert!(app.has_stop_condition());
+    }
+}
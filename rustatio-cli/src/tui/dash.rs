@@ -0,0 +1,424 @@
+use super::app::{cleanup_terminal, render_header, state_label};
+use crate::cli::ClientArg;
+use crate::json::format_bytes;
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{self, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{enable_raw_mode, EnterAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use rustatio_core::{FakerConfig, FakerState, FakerStats, RatioFaker};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration as StdDuration;
+use tokio::time::{interval, Duration};
+
+/// Configuration for the multi-instance dashboard
+pub struct DashConfig {
+    pub dir: PathBuf,
+    pub client: ClientArg,
+    pub client_version: Option<String>,
+    pub upload_rate: f64,
+    pub download_rate: f64,
+    pub port: u16,
+    pub completion: f64,
+    pub stop_ratio: Option<f64>,
+    pub stop_uploaded: Option<f64>,
+}
+
+impl DashConfig {
+    /// FakerConfig shared by every instance on the dashboard
+    fn faker_config(&self) -> FakerConfig {
+        FakerConfig {
+            upload_rate: self.upload_rate,
+            download_rate: self.download_rate,
+            port: self.port,
+            client_type: self.client.into(),
+            client_version: self.client_version.clone(),
+            completion_percent: self.completion,
+            stop_at_ratio: self.stop_ratio,
+            stop_at_uploaded: self.stop_uploaded.map(|gb| (gb * 1024.0 * 1024.0 * 1024.0) as u64),
+            ..FakerConfig::default()
+        }
+    }
+}
+
+/// One torrent on the dashboard
+struct DashRow {
+    name: String,
+    faker: RatioFaker,
+    stats: FakerStats,
+}
+
+/// Dashboard state
+struct Dash {
+    rows: Vec<DashRow>,
+    table_state: TableState,
+    status_message: Option<String>,
+    should_quit: bool,
+}
+
+impl Dash {
+    fn selected(&mut self) -> Option<&mut DashRow> {
+        let index = self.table_state.selected()?;
+        self.rows.get_mut(index)
+    }
+
+    fn set_status(&mut self, msg: impl Into<String>) {
+        self.status_message = Some(msg.into());
+    }
+}
+
+/// Keyboard commands
+#[derive(Debug)]
+enum DashKey {
+    Quit,
+    Up,
+    Down,
+    Pause,
+    Resume,
+    Stop,
+    Scrape,
+}
+
+/// List the .torrent files directly inside `dir`, sorted by path
+fn find_torrents(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut torrents: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("torrent")))
+        .collect();
+    torrents.sort();
+    Ok(torrents)
+}
+
+/// Run every torrent in a directory with a live table of instances
+pub async fn run_dash_mode(config: DashConfig) -> Result<()> {
+    let paths = find_torrents(&config.dir)?;
+    if paths.is_empty() {
+        anyhow::bail!("No .torrent files found in {}", config.dir.display());
+    }
+
+    let mut rows = Vec::with_capacity(paths.len());
+    let mut skipped = Vec::new();
+    for path in &paths {
+        let faker = crate::runner::load_torrent(path)
+            .and_then(|torrent| RatioFaker::new(torrent, config.faker_config()).map_err(|e| anyhow::anyhow!("{}", e)));
+        match faker {
+            Ok(faker) => rows.push(DashRow {
+                name: faker.get_torrent().name.clone(),
+                stats: faker.get_stats().await,
+                faker,
+            }),
+            Err(e) => skipped.push(format!("{}: {:#}", path.display(), e)),
+        }
+    }
+    if rows.is_empty() {
+        anyhow::bail!(
+            "No usable torrents in {}:\n  {}",
+            config.dir.display(),
+            skipped.join("\n  ")
+        );
+    }
+
+    let mut dash = Dash {
+        rows,
+        table_state: TableState::default().with_selected(Some(0)),
+        status_message: None,
+        should_quit: false,
+    };
+
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Start fakers one by one so the table fills in as they come up
+    let total = dash.rows.len();
+    let mut start_failed = false;
+    for i in 0..total {
+        dash.set_status(format!("Starting {}/{}...", i + 1, total));
+        terminal.draw(|f| ui(f, &mut dash))?;
+
+        let row = &mut dash.rows[i];
+        let result = row.faker.start().await;
+        row.stats = row.faker.get_stats().await;
+        if let Err(e) = result {
+            let msg = format!("{}: start failed: {}", row.name, e);
+            dash.set_status(msg);
+            start_failed = true;
+        }
+    }
+    // Keep the last start error visible instead of overwriting it
+    if !start_failed {
+        dash.set_status(if skipped.is_empty() {
+            format!("Running {} torrents", total)
+        } else {
+            format!("Running {} torrents ({} skipped)", total, skipped.len())
+        });
+    }
+
+    // Raw mode swallows Ctrl+C as a key press, so this mostly catches SIGTERM
+    let shutdown = Arc::new(AtomicBool::new(false));
+    crate::runner::spawn_shutdown_listener(shutdown.clone(), None);
+
+    // Setup keyboard event channel - use std::sync::mpsc for thread communication
+    let (key_tx, key_rx) = mpsc::channel::<DashKey>();
+
+    // Spawn keyboard event reader thread
+    thread::spawn(move || loop {
+        if event::poll(StdDuration::from_millis(100)).unwrap_or(false) {
+            if let Ok(Event::Key(key)) = event::read() {
+                if key.kind == KeyEventKind::Press {
+                    let cmd = match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => Some(DashKey::Quit),
+                        KeyCode::Up | KeyCode::Char('k') => Some(DashKey::Up),
+                        KeyCode::Down | KeyCode::Char('j') => Some(DashKey::Down),
+                        KeyCode::Char('p') => Some(DashKey::Pause),
+                        KeyCode::Char('r') => Some(DashKey::Resume),
+                        KeyCode::Char('x') => Some(DashKey::Stop),
+                        KeyCode::Char('s') => Some(DashKey::Scrape),
+                        _ => None,
+                    };
+
+                    if let Some(cmd) = cmd {
+                        if key_tx.send(cmd).is_err() {
+                            break; // Channel closed, exit thread
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    // Main loop
+    let mut stats_ticker = interval(Duration::from_millis(500));
+
+    loop {
+        // Shut down the same way as [q] on a signal
+        if shutdown.load(Ordering::SeqCst) {
+            dash.should_quit = true;
+        }
+
+        // Check for keyboard commands (non-blocking)
+        while let Ok(cmd) = key_rx.try_recv() {
+            match cmd {
+                DashKey::Quit => {
+                    dash.should_quit = true;
+                    break;
+                }
+                DashKey::Up => dash.table_state.select_previous(),
+                DashKey::Down => {
+                    // select_next() doesn't know the row count, so clamp here
+                    let last = dash.rows.len() - 1;
+                    let next = dash.table_state.selected().map_or(0, |i| (i + 1).min(last));
+                    dash.table_state.select(Some(next));
+                }
+                DashKey::Pause => {
+                    let Some(row) = dash.selected() else { continue };
+                    if matches!(row.stats.state, FakerState::Running | FakerState::Seeding) {
+                        let msg = match row.faker.pause().await {
+                            Ok(()) => format!("{}: paused", row.name),
+                            Err(e) => format!("{}: pause failed: {}", row.name, e),
+                        };
+                        row.stats = row.faker.get_stats().await;
+                        dash.set_status(msg);
+                    }
+                }
+                DashKey::Resume => {
+                    let Some(row) = dash.selected() else { continue };
+                    let msg = match row.stats.state {
+                        // Still idle when its initial start failed, so start it again
+                        FakerState::Idle => match row.faker.start().await {
+                            Ok(()) => format!("{}: started", row.name),
+                            Err(e) => format!("{}: start failed: {}", row.name, e),
+                        },
+                        FakerState::Paused | FakerState::Stopped | FakerState::Errored => {
+                            match row.faker.resume().await {
+                                Ok(()) => format!("{}: resumed", row.name),
+                                Err(e) => format!("{}: resume failed: {}", row.name, e),
+                            }
+                        }
+                        _ => continue,
+                    };
+                    row.stats = row.faker.get_stats().await;
+                    dash.set_status(msg);
+                }
+                DashKey::Stop => {
+                    let Some(row) = dash.selected() else { continue };
+                    if !matches!(row.stats.state, FakerState::Stopped | FakerState::Errored) {
+                        let msg = match row.faker.stop().await {
+                            Ok(()) => format!("{}: stopped", row.name),
+                            Err(e) => format!("{}: stop failed: {}", row.name, e),
+                        };
+                        row.stats = row.faker.get_stats().await;
+                        dash.set_status(msg);
+                    }
+                }
+                DashKey::Scrape => {
+                    let Some(row) = dash.selected() else { continue };
+                    let msg = match row.faker.scrape().await {
+                        Ok(resp) => format!(
+                            "{}: scrape {} seeders, {} leechers",
+                            row.name, resp.complete, resp.incomplete
+                        ),
+                        Err(e) => format!("{}: scrape failed: {}", row.name, e),
+                    };
+                    dash.set_status(msg);
+                }
+            }
+            terminal.draw(|f| ui(f, &mut dash))?;
+        }
+
+        if dash.should_quit {
+            break;
+        }
+
+        // Wait for next tick
+        stats_ticker.tick().await;
+
+        // Only update running instances (idle seeding still announces)
+        for row in &mut dash.rows {
+            if matches!(row.stats.state, FakerState::Running | FakerState::Seeding) {
+                if let Err(e) = row.faker.update().await {
+                    dash.status_message = Some(format!("{}: update error: {}", row.name, e));
+                }
+            }
+            row.stats = row.faker.get_stats().await;
+        }
+
+        terminal.draw(|f| ui(f, &mut dash))?;
+    }
+
+    // Stop every faker that is still registered with the tracker
+    dash.set_status("Stopping...");
+    terminal.draw(|f| ui(f, &mut dash))?;
+    for row in &mut dash.rows {
+        if !matches!(
+            row.stats.state,
+            FakerState::Stopped | FakerState::Idle | FakerState::Errored
+        ) {
+            let _ = row.faker.stop().await;
+            row.stats = row.faker.get_stats().await;
+        }
+    }
+
+    // Cleanup
+    cleanup_terminal(&mut terminal)?;
+
+    // Print final stats
+    println!("\nFinal Statistics:");
+    for row in &dash.rows {
+        println!(
+            "  {}: ↑ {}  ↓ {}  ratio {:.3}",
+            row.name,
+            format_bytes(row.stats.uploaded),
+            format_bytes(row.stats.downloaded),
+            row.stats.ratio
+        );
+    }
+    for skip in &skipped {
+        println!("  skipped {}", skip);
+    }
+
+    Ok(())
+}
+
+/// Render the dashboard
+fn ui(frame: &mut Frame, dash: &mut Dash) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(5),    // Instance table
+            Constraint::Length(3), // Status bar
+            Constraint::Length(2), // Help
+        ])
+        .split(frame.area());
+
+    render_header(frame, chunks[0]);
+
+    let header = Row::new(["Name", "State", "Ratio", "↑ KB/s", "↓ KB/s", "Uploaded", "Peers"])
+        .style(Style::default().fg(Color::Gray).add_modifier(Modifier::BOLD));
+
+    let rows = dash.rows.iter().map(|row| {
+        let stats = &row.stats;
+        let (state, color) = state_label(&stats.state);
+        Row::new([
+            Cell::from(row.name.clone()),
+            Cell::from(state).style(Style::default().fg(color)),
+            Cell::from(format!("{:.3}", stats.ratio)).style(Style::default().fg(Color::Cyan)),
+            Cell::from(format!("{:.1}", stats.current_upload_rate)).style(Style::default().fg(Color::Green)),
+            Cell::from(format!("{:.1}", stats.current_download_rate)).style(Style::default().fg(Color::Blue)),
+            Cell::from(format_bytes(stats.uploaded)),
+            Cell::from(format!("{} S / {} L", stats.seeders, stats.leechers)),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(28),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(14),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Instances ({}) ", dash.rows.len())),
+    )
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol("▶ ");
+    frame.render_stateful_widget(table, chunks[1], &mut dash.table_state);
+
+    let status = Paragraph::new(Line::from(Span::styled(
+        format!(" {}", dash.status_message.as_deref().unwrap_or("")),
+        Style::default().fg(Color::Magenta),
+    )))
+    .block(Block::default().borders(Borders::ALL).title(" Status "));
+    frame.render_widget(status, chunks[2]);
+
+    let help = Paragraph::new(" [↑/↓] Select   [p] Pause   [r] Resume/Start   [x] Stop   [s] Scrape   [q] Quit")
+        .style(Style::default().fg(Color::DarkGray))
+        .block(Block::default().borders(Borders::TOP));
+    frame.render_widget(help, chunks[3]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_torrents() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.torrent"), b"").unwrap();
+        std::fs::write(dir.path().join("a.TORRENT"), b"").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"").unwrap();
+        std::fs::create_dir(dir.path().join("sub.torrent")).unwrap();
+
+        let found = find_torrents(dir.path()).unwrap();
+        assert_eq!(found, vec![dir.path().join("a.TORRENT"), dir.path().join("b.torrent")]);
+
+        assert!(find_torrents(&dir.path().join("missing")).is_err());
+    }
+}
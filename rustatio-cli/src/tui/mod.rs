@@ -1,3 +1,5 @@
 pub mod app;
+pub mod dash;
 
 pub use app::run_tui_mode;
+pub use dash::{run_dash_mode, DashConfig};
@@ -0,0 +1,237 @@
+use crate::json::{format_bytes, ValidationCheck, ValidationOutput};
+use rustatio_core::validation::{
+    validate_client_version, validate_percentage, validate_port, validate_rate, validate_request_timeout,
+    validate_torrent_path, validate_tracker_url, validate_update_interval,
+};
+use rustatio_core::{AppConfig, FakerConfig, TorrentInfo};
+use std::path::Path;
+
+/// Run preflight checks on a torrent file and the config it would be started with. Without
+/// `config_path`, the config file in `data_dir` (or the platform default) is checked.
+pub fn validate(torrent_path: &Path, config_path: Option<&Path>, data_dir: Option<&Path>) -> ValidationOutput {
+    let mut checks = Vec::new();
+
+    // Torrent file
+    let torrent = check_torrent(torrent_path, &mut checks);
+    if let Some(ref torrent) = torrent {
+        check_trackers(torrent, &mut checks);
+    }
+
+    // Config (an explicit config file must load, the default one falls back to defaults).
+    // Only read, so validating never migrates or creates a config file.
+    let app_config = match config_path {
+        Some(path) => match AppConfig::read(path) {
+            Ok(config) => {
+                checks.push(ValidationCheck::pass("config", format!("Loaded {}", path.display())));
+                Some(config)
+            }
+            Err(e) => {
+                checks.push(ValidationCheck::fail(
+                    "config",
+                    format!("Failed to load {}: {}", path.display(), e),
+                ));
+                None
+            }
+        },
+        None => {
+            let path = AppConfig::default_path_in(data_dir);
+            if path.exists() {
+                Some(AppConfig::read(&path).unwrap_or_else(|e| {
+                    checks.push(ValidationCheck::warn(
+                        "config",
+                        format!("Failed to load {}, using defaults: {}", path.display(), e),
+                    ));
+                    AppConfig::default()
+                }))
+            } else {
+                Some(AppConfig::default())
+            }
+        }
+    };
+
+    if let Some(app_config) = app_config {
+        let torrent_size = torrent.as_ref().map(|t| t.total_size);
+        check_faker_config(&resolve_faker_config(&app_config), torrent_size, &mut checks);
+    }
+
+    ValidationOutput {
+        valid: checks.iter().all(|c| c.passed),
+        torrent: torrent_path.display().to_string(),
+        config: config_path.map(|p| p.display().to_string()),
+        checks,
+    }
+}
+
+/// Build the FakerConfig a torrent would start with from the config file defaults
+fn resolve_faker_config(app_config: &AppConfig) -> FakerConfig {
+    app_config.faker.to_faker_config(&app_config.client)
+}
+
+/// Check the torrent path and parse it, returning the torrent if it loaded
+fn check_torrent(path: &Path, checks: &mut Vec<ValidationCheck>) -> Option<TorrentInfo> {
+    if let Err(e) = validate_torrent_path(&path.to_string_lossy()) {
+        checks.push(ValidationCheck::fail("torrent", e.to_string()));
+        return None;
+    }
+
+    // Strict parsing also catches a pieces list that doesn't match the torrent size
+    match std::fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| TorrentInfo::from_bytes_strict(&data).map_err(|e| e.to_string()))
+    {
+        Ok(torrent) => {
+            checks.push(ValidationCheck::pass(
+                "torrent",
+                format!(
+                    "{} ({}, {} pieces)",
+                    torrent.name,
+                    format_bytes(torrent.total_size),
+                    torrent.num_pieces
+                ),
+            ));
+            Some(torrent)
+        }
+        Err(e) => {
+            checks.push(ValidationCheck::fail(
+                "torrent",
+                format!("Failed to parse torrent file: {}", e),
+            ));
+            None
+        }
+    }
+}
+
+/// Check every tracker URL of the torrent, reporting them with passkeys redacted
+fn check_trackers(torrent: &TorrentInfo, checks: &mut Vec<ValidationCheck>) {
+    for url in torrent.redacted().get_all_tracker_urls() {
+        push_check(checks, "tracker", validate_tracker_url(&url).map(|_| url.clone()));
+    }
+}
+
+/// Run all validators against a resolved FakerConfig, with the torrent size when it loaded
+fn check_faker_config(config: &FakerConfig, torrent_size: Option<u64>, checks: &mut Vec<ValidationCheck>) {
+    push_check(checks, "upload_rate", validate_rate(config.upload_rate, "upload_rate"));
+    push_check(
+        checks,
+        "download_rate",
+        validate_rate(config.download_rate, "download_rate"),
+    );
+    push_check(checks, "port", validate_port(config.port));
+    if let Some(version) = &config.client_version {
+        push_check(
+            checks,
+            "client_version",
+            validate_client_version(&config.client_type, version).map(|()| version.clone()),
+        );
+    }
+    push_check(
+        checks,
+        "completion_percent",
+        validate_percentage(config.completion_percent, "completion_percent"),
+    );
+    push_check(
+        checks,
+        "random_range_percent",
+        validate_percentage(config.random_range_percent, "random_range_percent"),
+    );
+    push_check(
+        checks,
+        "update_interval",
+        validate_update_interval(config.update_interval),
+    );
+    push_check(
+        checks,
+        "request_timeout_secs",
+        validate_request_timeout(
+            config
+                .request_timeout_secs
+                .unwrap_or(rustatio_core::DEFAULT_REQUEST_TIMEOUT_SECS),
+        ),
+    );
+
+    if let Some(rate) = config.target_upload_rate {
+        push_check(checks, "target_upload_rate", validate_rate(rate, "target_upload_rate"));
+    }
+    if let Some(rate) = config.target_download_rate {
+        push_check(
+            checks,
+            "target_download_rate",
+            validate_rate(rate, "target_download_rate"),
+        );
+    }
+
+    for warning in config.stop_condition_warnings(torrent_size) {
+        checks.push(ValidationCheck::warn("stop_conditions", warning));
+    }
+}
+
+/// Record a validator result, using the validated value or the error as the message
+fn push_check<T: ToString, E: ToString>(checks: &mut Vec<ValidationCheck>, name: &str, result: Result<T, E>) {
+    checks.push(match result {
+        Ok(value) => ValidationCheck::pass(name, value.to_string()),
+        Err(e) => ValidationCheck::fail(name, e.to_string()),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faker_config_checks() {
+        let mut checks = Vec::new();
+        check_faker_config(&resolve_faker_config(&AppConfig::default()), None, &mut checks);
+        assert!(checks.iter().all(|c| c.passed));
+
+        let mut app_config = AppConfig::default();
+        app_config.client.default_port = 80;
+        app_config.faker.default_completion_percent = 150.0;
+
+        let mut checks = Vec::new();
+        check_faker_config(&resolve_faker_config(&app_config), None, &mut checks);
+        let failed: Vec<&str> = checks.iter().filter(|c| !c.passed).map(|c| c.name.as_str()).collect();
+        assert_eq!(failed, vec!["port", "completion_percent"]);
+    }
+
+    #[test]
+    fn test_unreachable_stop_condition_warns() {
+        let mut app_config = AppConfig::default();
+        app_config.faker.default_upload_rate = 0.0;
+        app_config.faker.default_stop_uploaded_enabled = true;
+
+        let mut checks = Vec::new();
+        check_faker_config(&resolve_faker_config(&app_config), None, &mut checks);
+        let warning = checks.iter().find(|c| c.warning).unwrap();
+        assert_eq!(warning.name, "stop_conditions");
+        assert!(warning.message.contains("stop_at_uploaded"));
+        assert!(checks.iter().all(|c| c.passed), "warnings don't fail validation");
+    }
+
+    #[test]
+    fn test_tracker_checks_redact_passkeys() {
+        let passkey = "abcdef0123456789abcdef0123456789";
+        let announce = format!("https://tracker.example.com/{}/announce", passkey);
+        let data = format!(
+            "d8:announce{}:{}4:infod6:lengthi1024e4:name4:test12:piece lengthi1024e6:pieces20:{}ee",
+            announce.len(),
+            announce,
+            "x".repeat(20)
+        );
+        let torrent = TorrentInfo::from_bytes(data.as_bytes()).unwrap();
+
+        let mut checks = Vec::new();
+        check_trackers(&torrent, &mut checks);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].passed);
+        assert!(!checks[0].message.contains(passkey), "{}", checks[0].message);
+    }
+
+    #[test]
+    fn test_missing_torrent_fails() {
+        let mut checks = Vec::new();
+        assert!(check_torrent(Path::new("/nonexistent/file.torrent"), &mut checks).is_none());
+        assert_eq!(checks.len(), 1);
+        assert!(!checks[0].passed);
+        assert!(checks[0].message.contains("does not exist"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Time source for the faker, so timing (rate accumulation, announce scheduling, seed time)
+//! can be tested without waiting on the real clock.
+
+use instant::Instant;
+#[cfg(any(test, feature = "test-support"))]
+use std::sync::{Arc, Mutex};
+#[cfg(any(test, feature = "test-support"))]
+use std::time::Duration;
+
+/// Source of the current time for a `RatioFaker`
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// Hour of the local wall-clock time (0-23), for the active schedule
+    fn local_hour(&self) -> u8 {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use chrono::Timelike;
+            chrono::Local::now().hour() as u8
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            js_sys::Date::new_0().get_hours() as u8
+        }
+    }
+}
+
+/// The real monotonic clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when advanced. Clones share the same time, so a test can keep one
+/// and hand the other to the faker.
+#[cfg(any(test, feature = "test-support"))]
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+    local_hour: Arc<Mutex<u8>>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl MockClock {
+    /// A clock frozen at the current instant, with the local time at noon
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+            local_hour: Arc::new(Mutex::new(12)),
+        }
+    }
+
+    /// Set the local hour seen by the active schedule
+    pub fn set_local_hour(&self, hour: u8) {
+        *self.local_hour.lock().unwrap_or_else(|e| e.into_inner()) = hour;
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += duration;
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn local_hour(&self) -> u8 {
+        *self.local_hour.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::faker::FakerConfig;
 use crate::torrent::ClientType;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -12,12 +13,28 @@ pub enum ConfigError {
     TomlError(#[from] toml::de::Error),
     #[error("TOML serialize error: {0}")]
     TomlSerializeError(#[from] toml::ser::Error),
+    #[error("Unsupported config version {found} (this build supports up to version {supported})")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("Unknown profile: {0}")]
+    UnknownProfile(String),
 }
 
 pub type Result<T> = std::result::Result<T, ConfigError>;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Current config schema version.
+/// Config files without a `version` field are treated as version 0.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Environment variable that overrides the directory holding the config file
+/// (and, for the CLI, the sessions directory)
+pub const DATA_DIR_ENV: &str = "RUSTATIO_DATA_DIR";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// Config schema version (missing in files written before versioning, i.e. version 0)
+    #[serde(default)]
+    pub version: u32,
+
     #[serde(default)]
     pub client: ClientSettings,
 
@@ -30,6 +47,10 @@ pub struct AppConfig {
     #[serde(default)]
     pub instances: Vec<InstanceConfig>,
 
+    /// Named setting bundles that can be applied over the defaults
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+
     #[serde(default)]
     pub active_instance_id: Option<usize>,
 }
@@ -63,6 +84,27 @@ pub struct InstanceConfig {
     pub progressive_duration_hours: f64,
 }
 
+/// Named bundle of faker settings (e.g. "aggressive", "stealth", "overnight")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Client to emulate (None keeps the default client)
+    #[serde(default)]
+    pub client_type: Option<ClientType>,
+
+    /// Client version (None keeps the default version)
+    #[serde(default)]
+    pub client_version: Option<String>,
+
+    /// `FakerSettings` fields to override, anything left out comes from `[faker]`
+    #[serde(default)]
+    pub faker: toml::Table,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientSettings {
     /// Default client type to emulate
@@ -79,6 +121,10 @@ pub struct ClientSettings {
     /// Default number of peers to request
     #[serde(default = "default_num_want")]
     pub default_num_want: u32,
+
+    /// Default number of peers to taper down to after the first announce (None keeps `default_num_want`)
+    #[serde(default)]
+    pub default_num_want_steady: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,6 +191,9 @@ pub struct FakerSettings {
     #[serde(default = "default_stop_seed_time_enabled")]
     pub default_stop_seed_time_enabled: bool,
 
+    #[serde(default = "default_randomize")]
+    pub default_randomize: bool,
+
     #[serde(default = "default_random_range_percent")]
     pub default_random_range_percent: f64,
 
@@ -156,6 +205,15 @@ pub struct FakerSettings {
 
     #[serde(default = "default_infinite_retry_after_max")]
     pub default_infinite_retry_after_max: bool,
+
+    /// Maximum random delay in seconds before an auto-started instance's first announce (0 = none)
+    #[serde(default = "default_startup_jitter_secs")]
+    pub startup_jitter_secs: u64,
+
+    /// Consecutive failed announces after which an instance restored in a running state
+    /// gives up and moves to `Errored` (0 = keep retrying)
+    #[serde(default = "default_restore_max_failed_announces")]
+    pub restore_max_failed_announces: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -282,6 +340,10 @@ fn default_stop_seed_time_enabled() -> bool {
     true
 }
 
+fn default_randomize() -> bool {
+    true
+}
+
 fn default_random_range_percent() -> f64 {
     50.0
 }
@@ -298,6 +360,14 @@ fn default_infinite_retry_after_max() -> bool {
     false
 }
 
+fn default_startup_jitter_secs() -> u64 {
+    0
+}
+
+fn default_restore_max_failed_announces() -> u32 {
+    5
+}
+
 impl Default for ClientSettings {
     fn default() -> Self {
         ClientSettings {
@@ -305,6 +375,7 @@ impl Default for ClientSettings {
             default_version: None,
             default_port: default_port(),
             default_num_want: default_num_want(),
+            default_num_want_steady: None,
         }
     }
 }
@@ -331,10 +402,27 @@ impl Default for FakerSettings {
             default_stop_uploaded_enabled: default_stop_uploaded_enabled(),
             default_stop_downloaded_enabled: default_stop_downloaded_enabled(),
             default_stop_seed_time_enabled: default_stop_seed_time_enabled(),
+            default_randomize: default_randomize(),
             default_random_range_percent: default_random_range_percent(),
             default_announce_max_retries: default_announce_max_retries(),
             default_announce_retry_delay_seconds: default_announce_retry_delay_seconds(),
             default_infinite_retry_after_max: default_infinite_retry_after_max(),
+            startup_jitter_secs: default_startup_jitter_secs(),
+            restore_max_failed_announces: default_restore_max_failed_announces(),
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            version: CONFIG_VERSION,
+            client: ClientSettings::default(),
+            faker: FakerSettings::default(),
+            ui: UiSettings::default(),
+            instances: Vec::new(),
+            active_instance_id: None,
+            profiles: Vec::new(),
         }
     }
 }
@@ -350,14 +438,136 @@ impl Default for UiSettings {
     }
 }
 
+impl FakerSettings {
+    /// Build a faker config from these defaults
+    pub fn to_faker_config(&self, client: &ClientSettings) -> FakerConfig {
+        FakerConfig {
+            upload_rate: self.default_upload_rate,
+            download_rate: self.default_download_rate,
+            port: client.default_port,
+            client_type: client.default_type.clone(),
+            client_version: client.default_version.clone(),
+            completion_percent: self.default_completion_percent,
+            num_want: client.default_num_want,
+            num_want_steady: client.default_num_want_steady,
+            randomize_rates: self.default_randomize,
+            random_range_percent: self.default_random_range_percent,
+            stop_at_ratio: self.default_stop_ratio_enabled.then_some(self.default_stop_ratio),
+            stop_at_uploaded: self
+                .default_stop_uploaded_enabled
+                .then_some((self.default_stop_uploaded_gb * 1024.0 * 1024.0 * 1024.0) as u64),
+            stop_at_downloaded: self
+                .default_stop_downloaded_enabled
+                .then_some((self.default_stop_downloaded_gb * 1024.0 * 1024.0 * 1024.0) as u64),
+            stop_at_seed_time: self
+                .default_stop_seed_time_enabled
+                .then_some((self.default_stop_seed_time_hours * 3600.0) as u64),
+            stop_when_no_leechers: self.default_stop_when_no_leechers,
+            progressive_rates: self.default_progressive_rates_enabled,
+            target_upload_rate: self
+                .default_progressive_rates_enabled
+                .then_some(self.default_target_upload_rate),
+            target_download_rate: self
+                .default_progressive_rates_enabled
+                .then_some(self.default_target_download_rate),
+            progressive_duration: (self.default_progressive_duration_hours * 3600.0) as u64,
+            announce_max_retries: self.default_announce_max_retries,
+            announce_retry_delay_seconds: self.default_announce_retry_delay_seconds,
+            announce_interval: self.default_announce_interval,
+            update_interval: self.update_interval,
+            infinite_retry_after_max: self.default_infinite_retry_after_max,
+            ..FakerConfig::default()
+        }
+    }
+}
+
 impl AppConfig {
+    /// Find a profile by name
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Copy of this config with the named profile merged over the client and faker defaults
+    pub fn with_profile(&self, name: &str) -> Result<AppConfig> {
+        let profile = self
+            .profile(name)
+            .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))?;
+
+        let mut merged = self.clone();
+        if let Some(ref client_type) = profile.client_type {
+            merged.client.default_type = client_type.clone();
+        }
+        if profile.client_version.is_some() {
+            merged.client.default_version = profile.client_version.clone();
+        }
+
+        let mut faker = toml::Table::try_from(&self.faker)?;
+        faker.extend(profile.faker.clone());
+        merged.faker = faker.try_into()?;
+
+        Ok(merged)
+    }
+
+    /// Faker config for the named profile merged over the defaults
+    pub fn profile_faker_config(&self, name: &str) -> Result<FakerConfig> {
+        let merged = self.with_profile(name)?;
+        Ok(merged.faker.to_faker_config(&merged.client))
+    }
+
     /// Load configuration from a TOML file
+    /// Older config files are migrated to the current version and rewritten in place.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let mut config: AppConfig = toml::from_str(&content)?;
+
+        let from_version = config.version;
+        if config.migrate()? {
+            log::info!(
+                "Migrated config {:?} from version {} to {}",
+                path,
+                from_version,
+                config.version
+            );
+            if let Err(e) = config.save(path) {
+                log::warn!("Failed to rewrite migrated config: {}", e);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load configuration from a TOML file, migrating it in memory only; the file is never written
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let config: AppConfig = toml::from_str(&content)?;
+        let mut config: AppConfig = toml::from_str(&content)?;
+        config.migrate()?;
         Ok(config)
     }
 
+    /// Upgrade a config loaded from an older schema version to `CONFIG_VERSION`.
+    /// New fields are already filled with defaults by serde, so each step only needs to
+    /// handle renamed or reinterpreted settings. Returns true if the config was changed.
+    pub fn migrate(&mut self) -> Result<bool> {
+        if self.version > CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion {
+                found: self.version,
+                supported: CONFIG_VERSION,
+            });
+        }
+
+        if self.version == CONFIG_VERSION {
+            return Ok(false);
+        }
+
+        // v0 -> v1: version field introduced, no settings changed
+        if self.version == 0 {
+            self.version = 1;
+        }
+
+        Ok(true)
+    }
+
     /// Save configuration to a TOML file
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let toml_string = toml::to_string_pretty(self)?;
@@ -365,9 +575,23 @@ impl AppConfig {
         Ok(())
     }
 
-    /// Get the default config file path
+    /// Data directory set through `RUSTATIO_DATA_DIR`, if any (empty values are ignored)
+    pub fn data_dir_override() -> Option<PathBuf> {
+        std::env::var_os(DATA_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Get the default config file path (`<data dir>/config.toml` when `RUSTATIO_DATA_DIR` is set)
     pub fn default_path() -> PathBuf {
-        if let Some(config_dir) = dirs::config_dir() {
+        Self::default_path_in(Self::data_dir_override().as_deref())
+    }
+
+    /// Get the config file path inside `data_dir`, or the platform default when it is `None`
+    pub fn default_path_in(data_dir: Option<&Path>) -> PathBuf {
+        if let Some(data_dir) = data_dir {
+            data_dir.join("config.toml")
+        } else if let Some(config_dir) = dirs::config_dir() {
             config_dir.join("rustatio").join("config.toml")
         } else {
             PathBuf::from("rustatio.toml")
@@ -376,7 +600,12 @@ impl AppConfig {
 
     /// Load from default path or create default config if not exists
     pub fn load_or_default() -> Self {
-        let path = Self::default_path();
+        Self::load_or_default_in(Self::data_dir_override().as_deref())
+    }
+
+    /// Like `load_or_default`, with the config file inside `data_dir` when given
+    pub fn load_or_default_in(data_dir: Option<&Path>) -> Self {
+        let path = Self::default_path_in(data_dir);
 
         if path.exists() {
             Self::load(&path).unwrap_or_else(|e| {
@@ -443,5 +672,139 @@ mod tests {
         let parsed: AppConfig = toml::from_str(&toml).unwrap();
 
         assert_eq!(config.faker.default_upload_rate, parsed.faker.default_upload_rate);
+        assert_eq!(parsed.version, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn test_stop_and_randomize_defaults_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let mut config = AppConfig::default();
+        config.faker.default_stop_ratio = 3.5;
+        config.faker.default_stop_ratio_enabled = true;
+        config.faker.default_randomize = false;
+        config.faker.default_random_range_percent = 15.0;
+        config.save(&path).unwrap();
+
+        let loaded = AppConfig::load(&path).unwrap();
+        assert_eq!(loaded.faker.default_stop_ratio, 3.5);
+        assert!(loaded.faker.default_stop_ratio_enabled);
+        assert!(!loaded.faker.default_randomize);
+        assert_eq!(loaded.faker.default_random_range_percent, 15.0);
+
+        let faker_config = loaded.faker.to_faker_config(&loaded.client);
+        assert_eq!(faker_config.stop_at_ratio, Some(3.5));
+        assert!(!faker_config.randomize_rates);
+
+        // Older files without the setting keep randomizing
+        let parsed: AppConfig = toml::from_str("[faker]\ndefault_upload_rate = 100.0").unwrap();
+        assert!(parsed.faker.default_randomize);
+    }
+
+    #[test]
+    fn test_migrate_v0_config() {
+        // Written before versioning: no version field and some settings missing
+        let v0 = r#"
+            [client]
+            default_type = "qbittorrent"
+            default_port = 6881
+
+            [faker]
+            default_upload_rate = 123.0
+        "#;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, v0).unwrap();
+
+        // Reading only migrates in memory
+        let config = AppConfig::read(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(fs::read_to_string(&path).unwrap(), v0);
+
+        let config = AppConfig::load(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.client.default_type, ClientType::QBittorrent);
+        assert_eq!(config.client.default_port, 6881);
+        assert_eq!(config.faker.default_upload_rate, 123.0);
+        assert_eq!(config.faker.default_stop_ratio, default_stop_ratio());
+
+        // File was rewritten with the current version and round-trips unchanged
+        let rewritten = AppConfig::load(&path).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("version = 1"));
+        assert_eq!(rewritten.version, CONFIG_VERSION);
+        assert_eq!(rewritten.client.default_port, 6881);
+        assert_eq!(rewritten.faker.default_upload_rate, 123.0);
+    }
+
+    #[test]
+    fn test_profile_merged_over_defaults() {
+        let toml = r#"
+            [client]
+            default_type = "qbittorrent"
+            default_port = 6881
+
+            [faker]
+            default_upload_rate = 300.0
+            default_download_rate = 50.0
+
+            [[profiles]]
+            name = "overnight"
+            client_type = "deluge"
+
+            [profiles.faker]
+            default_upload_rate = 80.0
+            default_stop_ratio_enabled = true
+            default_stop_ratio = 3.5
+        "#;
+        let config: AppConfig = toml::from_str(toml).unwrap();
+
+        let faker_config = config.profile_faker_config("overnight").unwrap();
+        // From the profile
+        assert_eq!(faker_config.upload_rate, 80.0);
+        assert_eq!(faker_config.stop_at_ratio, Some(3.5));
+        assert_eq!(faker_config.client_type, ClientType::Deluge);
+        // From [faker] and [client]
+        assert_eq!(faker_config.download_rate, 50.0);
+        assert_eq!(faker_config.port, 6881);
+        // Built-in defaults
+        assert_eq!(faker_config.update_interval, default_update_interval());
+        assert_eq!(
+            faker_config.stop_at_seed_time,
+            Some((default_stop_seed_time_hours() * 3600.0) as u64)
+        );
+
+        assert!(matches!(
+            config.profile_faker_config("missing"),
+            Err(ConfigError::UnknownProfile(ref name)) if name == "missing"
+        ));
+
+        // Profiles survive a save round-trip
+        let parsed: AppConfig = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(parsed.profile("overnight").unwrap().faker.len(), 3);
+    }
+
+    #[test]
+    fn test_newer_config_version_rejected() {
+        let mut config = AppConfig {
+            version: CONFIG_VERSION + 1,
+            ..AppConfig::default()
+        };
+
+        assert!(matches!(
+            config.migrate(),
+            Err(ConfigError::UnsupportedVersion { found, supported })
+                if found == CONFIG_VERSION + 1 && supported == CONFIG_VERSION
+        ));
+    }
+
+    #[test]
+    fn test_default_path_in_data_dir() {
+        assert_eq!(
+            AppConfig::default_path_in(Some(Path::new("/tmp/rustatio-data"))),
+            PathBuf::from("/tmp/rustatio-data/config.toml")
+        );
+        assert!(AppConfig::default_path_in(None).ends_with("config.toml"));
     }
 }
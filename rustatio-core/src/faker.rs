@@ -1,6 +1,13 @@
-use crate::protocol::{AnnounceRequest, AnnounceResponse, TrackerClient, TrackerError, TrackerEvent};
-use crate::torrent::{ClientConfig, ClientType, TorrentInfo};
-use crate::{log_debug, log_info, log_trace};
+use crate::clock::{Clock, SystemClock};
+use crate::protocol::{AnnounceRequest, AnnounceResponse, Tracker, TrackerClient, TrackerError, TrackerEvent};
+use crate::torrent::{redact_tracker_url, ClientConfig, ClientType, TorrentInfo, DEFAULT_REQUEST_TIMEOUT_SECS};
+use crate::validation::{
+    validate_active_schedule, validate_announce_interval_bounds, validate_announce_jitter, validate_announce_params,
+    validate_client_version, validate_completion_jitter, validate_percentage, validate_port, validate_positive,
+    validate_rate, validate_request_timeout, validate_tracker_url, ValidationError,
+};
+use crate::{log_debug, log_error, log_info, log_trace, log_warn};
+use futures_util::future::join_all;
 use instant::Instant;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -89,9 +96,20 @@ pub struct FakerConfig {
     /// Percentage already downloaded (0-100)
     pub completion_percent: f64,
 
+    /// Maximum random delay in seconds before sending `completed` once the download finishes
+    /// (0 sends it right away)
+    #[serde(default)]
+    pub completion_jitter_secs: u64,
+
     /// Number of peers to request
     pub num_want: u32,
 
+    /// Number of peers to settle on after the `started` announce (optional). When set, later
+    /// announces halve the gap between `num_want` and this value each time, like a client whose
+    /// peer list is filling up
+    #[serde(default)]
+    pub num_want_steady: Option<u32>,
+
     /// Enable randomization of rates
     #[serde(default = "default_randomize_rates")]
     pub randomize_rates: bool,
@@ -117,6 +135,25 @@ pub struct FakerConfig {
     #[serde(default)]
     pub stop_when_no_leechers: bool,
 
+    /// What to do once a stop condition is met (default: stop)
+    #[serde(default)]
+    pub on_target_reached: TargetAction,
+
+    /// Whether the ratio, uploaded and downloaded targets count this session only
+    /// or include the carried-over totals (default: session)
+    #[serde(default)]
+    pub stop_ratio_basis: RatioBasis,
+
+    /// Never report a total ratio above this (optional). Uploading pauses at the cap while
+    /// announces continue, guarding against implausible values from a mistyped rate.
+    #[serde(default)]
+    pub max_ratio_cap: Option<f64>,
+
+    /// Keep each announce's uploaded delta believable: at most this many times the torrent size per
+    /// hour since the previous announce (optional). Lower is safer but slows the ratio gain.
+    #[serde(default)]
+    pub max_uploads_per_hour: Option<f64>,
+
     // Progressive rate adjustment
     /// Enable progressive rate adjustment
     #[serde(default)]
@@ -132,6 +169,10 @@ pub struct FakerConfig {
     #[serde(default = "default_progressive_duration")]
     pub progressive_duration: u64,
 
+    /// Shape of the ramp from the start rates to the target rates
+    #[serde(default)]
+    pub progressive_curve: Curve,
+
     /// How many times to retry an announce on failure (default 10)
     #[serde(default = "default_announce_max_retries")]
     pub announce_max_retries: u32,
@@ -143,11 +184,164 @@ pub struct FakerConfig {
     #[serde(default = "default_announce_interval")]
     pub announce_interval: u64,
 
+    /// Lower bound in seconds for the tracker-provided announce interval (optional)
+    #[serde(default)]
+    pub announce_interval_min_secs: Option<u64>,
+
+    /// Upper bound in seconds for the tracker-provided announce interval, to announce more often
+    /// than the tracker asks. Never goes below the tracker's `min interval` (optional)
+    #[serde(default)]
+    pub announce_interval_max_secs: Option<u64>,
+
+    /// Random variation applied to each periodic announce delay, in percent of the interval
+    /// (0-50, default 0). Never goes below the tracker's `min interval`
+    #[serde(default)]
+    pub announce_jitter_percent: f64,
+
     #[serde(default = "default_update_interval")]
     pub update_interval: u64,
 
     #[serde(default = "default_infinite_retry_after_max")]
     pub infinite_retry_after_max: bool,
+
+    /// Send a regular announce when resuming from pause, so a tracker that timed us out while
+    /// paused registers us again (default false)
+    #[serde(default)]
+    pub announce_on_resume: bool,
+
+    /// Tracker request timeout in seconds (1-300, None uses the default of 30)
+    #[serde(default)]
+    pub request_timeout_secs: Option<u64>,
+
+    /// Custom User-Agent replacing the client preset (optional)
+    #[serde(default)]
+    pub user_agent_override: Option<String>,
+
+    /// Custom peer ID prefix replacing the client preset, e.g. "-XX1234-" (optional)
+    #[serde(default)]
+    pub peer_id_prefix_override: Option<String>,
+
+    /// Local-time hour ranges `(start, end)` during which data is transferred (optional).
+    /// End is exclusive, `(22, 6)` wraps past midnight. Announces continue outside the window.
+    #[serde(default)]
+    pub active_schedule: Option<Vec<(u8, u8)>>,
+
+    /// Extra `(name, value)` query parameters appended to every announce, after the standard ones.
+    /// Both are percent-encoded, e.g. for private trackers that want more than the passkey.
+    #[serde(default)]
+    pub extra_announce_params: Vec<(String, String)>,
+
+    /// Also announce to every other http(s) tracker of the torrent, like clients do for trackers
+    /// in separate tiers. Those get a single attempt each, the announce fails only if all trackers fail.
+    #[serde(default)]
+    pub announce_all_trackers: bool,
+
+    /// How seeder/leecher counts are combined across trackers with `announce_all_trackers`
+    #[serde(default)]
+    pub swarm_aggregation: SwarmAggregation,
+}
+
+/// How seeder/leecher counts from several trackers are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwarmAggregation {
+    /// The largest count any tracker reported, for trackers sharing one swarm
+    #[default]
+    Max,
+    /// The counts added up, for independent swarms
+    Sum,
+}
+
+impl SwarmAggregation {
+    fn combine(self, a: i64, b: i64) -> i64 {
+        match self {
+            SwarmAggregation::Max => a.max(b),
+            SwarmAggregation::Sum => a.saturating_add(b),
+        }
+    }
+}
+
+/// What a faker does once one of its stop conditions is met
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetAction {
+    /// Announce `stopped` and end the session
+    #[default]
+    Stop,
+    /// Keep announcing periodically without transferring anything (`Seeding` state)
+    IdleSeed,
+}
+
+/// Which counters the ratio, uploaded and downloaded stop conditions are measured against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RatioBasis {
+    /// Lifetime totals, including `initial_uploaded`/`initial_downloaded` carried over from earlier runs
+    Total,
+    /// Only what was transferred since the session started
+    #[default]
+    Session,
+}
+
+impl RatioBasis {
+    /// Uploaded bytes on this basis
+    pub fn uploaded(self, stats: &FakerStats) -> u64 {
+        match self {
+            RatioBasis::Total => stats.uploaded,
+            RatioBasis::Session => stats.session_uploaded,
+        }
+    }
+
+    /// Downloaded bytes on this basis
+    pub fn downloaded(self, stats: &FakerStats) -> u64 {
+        match self {
+            RatioBasis::Total => stats.downloaded,
+            RatioBasis::Session => stats.session_downloaded,
+        }
+    }
+
+    /// Ratio (uploaded / torrent size) on this basis
+    pub fn ratio(self, stats: &FakerStats) -> f64 {
+        match self {
+            RatioBasis::Total => stats.ratio,
+            RatioBasis::Session => stats.session_ratio,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            RatioBasis::Total => "total",
+            RatioBasis::Session => "session",
+        }
+    }
+}
+
+/// Easing curve for progressive rate ramps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Curve {
+    /// Constant change per second
+    #[default]
+    Linear,
+    /// Slow start and slow finish (smoothstep)
+    EaseInOut,
+    /// Fast start that levels off near the target
+    Logarithmic,
+    /// Slow start that accelerates towards the target
+    Exponential,
+}
+
+impl Curve {
+    /// Map linear progress (0.0-1.0) onto this curve, keeping 0 and 1 fixed
+    pub fn apply(self, progress: f64) -> f64 {
+        let p = progress.clamp(0.0, 1.0);
+        match self {
+            Curve::Linear => p,
+            Curve::EaseInOut => p * p * (3.0 - 2.0 * p),
+            Curve::Logarithmic => (1.0 + 9.0 * p).log10(),
+            Curve::Exponential => (10f64.powf(p) - 1.0) / 9.0,
+        }
+    }
 }
 
 fn default_randomize_rates() -> bool {
@@ -193,7 +387,9 @@ impl Default for FakerConfig {
             initial_uploaded: 0,
             initial_downloaded: 0,
             completion_percent: 100.0,
+            completion_jitter_secs: 0,
             num_want: 50,
+            num_want_steady: None,
             randomize_rates: true,
             random_range_percent: 50.0,
             stop_at_ratio: None,
@@ -201,16 +397,118 @@ impl Default for FakerConfig {
             stop_at_downloaded: None,
             stop_at_seed_time: Some(2678400),
             stop_when_no_leechers: false,
+            on_target_reached: TargetAction::Stop,
+            stop_ratio_basis: RatioBasis::Session,
+            max_ratio_cap: None,
+            max_uploads_per_hour: None,
             progressive_rates: false,
             target_upload_rate: None,
             target_download_rate: None,
             progressive_duration: 3600,
+            progressive_curve: Curve::Linear,
             announce_max_retries: 10,
             announce_retry_delay_seconds: 5,
             announce_interval: 1800,
+            announce_interval_min_secs: None,
+            announce_interval_max_secs: None,
+            announce_jitter_percent: 0.0,
             update_interval: 5,
             infinite_retry_after_max: false,
+            announce_on_resume: false,
+            request_timeout_secs: None,
+            user_agent_override: None,
+            peer_id_prefix_override: None,
+            active_schedule: None,
+            extra_announce_params: Vec::new(),
+            announce_all_trackers: false,
+            swarm_aggregation: SwarmAggregation::Max,
+        }
+    }
+}
+
+impl FakerConfig {
+    /// Validate the user-facing fields (rates, port, completion, randomization range, client version)
+    /// before starting a faker. The remaining fields are checked by `RatioFaker` itself.
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        validate_rate(self.upload_rate, "upload_rate")?;
+        validate_rate(self.download_rate, "download_rate")?;
+        validate_port(self.port)?;
+        validate_percentage(self.completion_percent, "completion_percent")?;
+        validate_completion_jitter(self.completion_jitter_secs)?;
+        if self.randomize_rates {
+            validate_percentage(self.random_range_percent, "random_range_percent")?;
+        }
+        if let Some(version) = &self.client_version {
+            validate_client_version(&self.client_type, version)?;
+        }
+        Ok(())
+    }
+
+    /// Validate the fields `RatioFaker` checks whenever it's created or reconfigured
+    fn validate_faker_settings(&self) -> std::result::Result<(), ValidationError> {
+        if let Some(ref schedule) = self.active_schedule {
+            validate_active_schedule(schedule)?;
+        }
+        validate_announce_interval_bounds(self.announce_interval_min_secs, self.announce_interval_max_secs)?;
+        validate_announce_jitter(self.announce_jitter_percent)?;
+        validate_completion_jitter(self.completion_jitter_secs)?;
+        validate_announce_params(&self.extra_announce_params)?;
+        if let Some(cap) = self.max_ratio_cap {
+            validate_positive(cap, "max_ratio_cap")?;
+        }
+        if let Some(per_hour) = self.max_uploads_per_hour {
+            validate_positive(per_hour, "max_uploads_per_hour")?;
+        }
+        Ok(())
+    }
+
+    /// Stop conditions that can't be met with the configured rates, e.g. an upload target with a zero
+    /// upload rate. These are warnings rather than errors since rates can be changed while running.
+    /// On a `total` basis, targets the carried-over totals already meet are not reported; a ratio
+    /// target is only checked against them when `torrent_size` is known.
+    pub fn stop_condition_warnings(&self, torrent_size: Option<u64>) -> Vec<String> {
+        let peak_rate = |rate: f64, target: Option<f64>| match target {
+            Some(target) if self.progressive_rates => rate.max(target),
+            _ => rate,
+        };
+        let uploads = peak_rate(self.upload_rate, self.target_upload_rate) > 0.0;
+        let downloads =
+            peak_rate(self.download_rate, self.target_download_rate) > 0.0 && self.completion_percent < 100.0;
+        let carried_over = |initial: u64, target: u64| self.stop_ratio_basis == RatioBasis::Total && initial >= target;
+        let ratio_carried_over = |ratio: f64| match torrent_size {
+            Some(size) => carried_over(self.initial_uploaded, (ratio * size as f64) as u64),
+            None => carried_over(self.initial_uploaded, 1),
+        };
+
+        let mut warnings = Vec::new();
+        if !uploads {
+            if self
+                .stop_at_ratio
+                .is_some_and(|ratio| ratio > 0.0 && !ratio_carried_over(ratio))
+            {
+                warnings.push("stop_at_ratio can never be reached: the upload rate is 0".to_string());
+            }
+            if self
+                .stop_at_uploaded
+                .is_some_and(|bytes| bytes > 0 && !carried_over(self.initial_uploaded, bytes))
+            {
+                warnings.push("stop_at_uploaded can never be reached: the upload rate is 0".to_string());
+            }
+        }
+        if !downloads
+            && self
+                .stop_at_downloaded
+                .is_some_and(|bytes| bytes > 0 && !carried_over(self.initial_downloaded, bytes))
+        {
+            let reason = if self.completion_percent >= 100.0 {
+                "the torrent is already complete"
+            } else {
+                "the download rate is 0"
+            };
+            warnings.push(format!("stop_at_downloaded can never be reached: {}", reason));
         }
+
+        warnings
     }
 }
 
@@ -221,8 +519,15 @@ pub enum FakerState {
     Paused,
     Stopped,
     Completed,
+    /// Target reached: still announcing, but no longer transferring
+    Seeding,
+    /// Given up, e.g. on a tracker that stays unreachable; the reason is in `FakerStats::error`
+    Errored,
 }
 
+/// Maximum number of points kept in each stats history vec (graphs)
+pub const HISTORY_MAX_POINTS: usize = 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FakerStats {
     // === CUMULATIVE STATS (lifetime totals for display) ===
@@ -248,7 +553,7 @@ pub struct FakerStats {
     pub average_upload_rate: f64,   // Average upload rate KB/s (session)
     pub average_download_rate: f64, // Average download rate KB/s (session)
 
-    // === PROGRESS (session-based for stop conditions) ===
+    // === PROGRESS (toward stop conditions, on their `stop_ratio_basis`) ===
     pub upload_progress: f64,    // 0-100% toward stop_at_uploaded
     pub download_progress: f64,  // 0-100% toward stop_at_downloaded
     pub ratio_progress: f64,     // 0-100% toward stop_at_ratio
@@ -271,13 +576,118 @@ pub struct FakerStats {
     #[serde(skip)]
     pub next_announce: Option<Instant>,
     pub announce_count: u32,
+    /// Announce interval in use, after applying the configured bounds
+    #[serde(default)]
+    pub announce_interval_secs: u64,
+
+    /// Whether the current local time is within `active_schedule` (always true without one)
+    #[serde(default = "default_schedule_active")]
+    pub schedule_active: bool,
+
+    /// How much the uploaded total we reported grew since the announce before the last periodic
+    /// one, whatever its event. Trackers don't echo what they credited, so compare this with the
+    /// tracker's own stats to spot one that discounts reports.
+    #[serde(default)]
+    pub uploaded_delta_last_announce: u64,
+    /// Same as `uploaded_delta_last_announce`, for the downloaded total
+    #[serde(default)]
+    pub downloaded_delta_last_announce: u64,
+
+    /// Why the session moved to `Errored`, cleared by the next successful start
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+fn default_schedule_active() -> bool {
+    true
+}
+
+/// Whether a local hour falls within any of the schedule's ranges
+pub fn is_hour_active(schedule: &[(u8, u8)], hour: u8) -> bool {
+    schedule.iter().any(|&(start, end)| match start.cmp(&end) {
+        std::cmp::Ordering::Less => (start..end).contains(&hour),
+        std::cmp::Ordering::Greater => hour >= start || hour < end,
+        std::cmp::Ordering::Equal => true,
+    })
+}
+
+/// Clamp a tracker-provided announce interval to the configured bounds.
+/// The tracker's `min interval` always wins, so the result never goes below it.
+pub fn clamp_announce_interval(interval: i64, min_interval: Option<i64>, min: Option<u64>, max: Option<u64>) -> u64 {
+    let mut secs = interval.max(0) as u64;
+    if let Some(max) = max {
+        secs = secs.min(max);
+    }
+    if let Some(min) = min {
+        secs = secs.max(min);
+    }
+    if let Some(tracker_min) = min_interval {
+        secs = secs.max(tracker_min.max(0) as u64);
+    }
+    secs
+}
+
+/// Delay before the next periodic announce: `interval` shifted by a random amount within
+/// ±`jitter_percent`, never shorter than the tracker's `min interval` or the configured `min`
+pub fn jittered_announce_delay(
+    interval: Duration,
+    jitter_percent: f64,
+    min_interval: Option<i64>,
+    min: Option<u64>,
+) -> Duration {
+    if jitter_percent <= 0.0 {
+        return interval;
+    }
+    let spread = jitter_percent.min(50.0) / 100.0;
+    let factor = 1.0 + rand::rng().random_range(-spread..=spread);
+    let delay = interval.mul_f64(factor);
+    let floor = (min_interval.unwrap_or(0).max(0) as u64).max(min.unwrap_or(0));
+    delay.max(Duration::from_secs(floor))
+}
+
+/// numwant for an announce after `started`: halves the gap between `start` and `steady` on
+/// each announce sent so far, never going below `steady`
+pub fn taper_num_want(start: u32, steady: u32, announces: u32) -> u32 {
+    if steady >= start {
+        return steady;
+    }
+    steady + ((start - steady) >> announces.min(31))
+}
+
+/// Default number of decimals for rates in reported stats
+pub const DEFAULT_RATE_DECIMALS: u32 = 1;
+
+/// Round a value to the given number of decimals
+pub fn round_to_decimals(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+impl FakerStats {
+    /// Copy of these stats with rates (and rate history) rounded for reporting.
+    /// Internal accumulation keeps using the full-precision values.
+    pub fn with_rounded_rates(&self, decimals: u32) -> FakerStats {
+        let round = |v: &f64| round_to_decimals(*v, decimals);
+        FakerStats {
+            current_upload_rate: round(&self.current_upload_rate),
+            current_download_rate: round(&self.current_download_rate),
+            average_upload_rate: round(&self.average_upload_rate),
+            average_download_rate: round(&self.average_download_rate),
+            upload_rate_history: self.upload_rate_history.iter().map(round).collect(),
+            download_rate_history: self.download_rate_history.iter().map(round).collect(),
+            ..self.clone()
+        }
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub struct RatioFaker {
+pub struct RatioFaker<T: Tracker = TrackerClient, C: Clock = SystemClock> {
     torrent: TorrentInfo,
     config: FakerConfig,
-    tracker_client: TrackerClient,
+    tracker_client: T,
+    tracker_url: String,
+    /// Tracker picked at start, before any redirect
+    original_tracker_url: String,
 
     // Runtime state
     state: Arc<RwLock<FakerState>>,
@@ -287,18 +697,37 @@ pub struct RatioFaker {
     peer_id: String,
     key: String,
     tracker_id: Option<String>,
+    /// Announces sent since the last `started`, drives the numwant taper
+    announces_since_started: u32,
+    /// Uploaded total sent in the last announce, the base of `max_uploads_per_hour`
+    uploaded_at_last_announce: u64,
+    /// Downloaded total sent in the last announce
+    downloaded_at_last_announce: u64,
 
     // Timing
-    start_time: Instant,
+    /// Source of the current time, mocked in tests
+    clock: C,
+    /// Start of the current session, the base for elapsed time and average rates
+    session_start: Instant,
     last_update: Instant,
     announce_interval: Duration,
+    /// Delay until the next periodic announce: the interval with `announce_jitter_percent` applied
+    next_announce_delay: Duration,
+    /// Total time spent paused or stopped since start, excluded from elapsed time
+    paused_duration: Duration,
+    paused_at: Option<Instant>,
+    /// When to send the `completed` event of a download that already finished
+    pending_completion: Option<Instant>,
 }
 
 #[cfg(target_arch = "wasm32")]
-pub struct RatioFaker {
+pub struct RatioFaker<T: Tracker = TrackerClient, C: Clock = SystemClock> {
     torrent: TorrentInfo,
     config: FakerConfig,
-    tracker_client: TrackerClient,
+    tracker_client: T,
+    tracker_url: String,
+    /// Tracker picked at start, before any redirect
+    original_tracker_url: String,
 
     // Runtime state (RefCell for single-threaded WASM)
     state: RefCell<FakerState>,
@@ -308,15 +737,102 @@ pub struct RatioFaker {
     peer_id: String,
     key: String,
     tracker_id: Option<String>,
+    /// Announces sent since the last `started`, drives the numwant taper
+    announces_since_started: u32,
+    /// Uploaded total sent in the last announce, the base of `max_uploads_per_hour`
+    uploaded_at_last_announce: u64,
+    /// Downloaded total sent in the last announce
+    downloaded_at_last_announce: u64,
 
     // Timing
-    start_time: Instant,
+    /// Source of the current time, mocked in tests
+    clock: C,
+    /// Start of the current session, the base for elapsed time and average rates
+    session_start: Instant,
     last_update: Instant,
     announce_interval: Duration,
+    /// Delay until the next periodic announce: the interval with `announce_jitter_percent` applied
+    next_announce_delay: Duration,
+    /// Total time spent paused or stopped since start, excluded from elapsed time
+    paused_duration: Duration,
+    paused_at: Option<Instant>,
+    /// When to send the `completed` event of a download that already finished
+    pending_completion: Option<Instant>,
+}
+
+/// Pick the tracker to announce to.
+///
+/// UDP and WebSocket trackers are not supported yet, so when the primary announce URL
+/// isn't http(s) the first http(s) tracker from the announce-list is used instead.
+fn select_tracker_url(torrent: &TorrentInfo) -> Result<String> {
+    let primary = torrent.get_tracker_url();
+    let error = match validate_tracker_url(primary) {
+        Ok(_) => return Ok(primary.to_string()),
+        Err(e) => e,
+    };
+
+    let fallback = torrent
+        .announce_list
+        .iter()
+        .flatten()
+        .flatten()
+        .find(|url| validate_tracker_url(url).is_ok());
+
+    if let Some(url) = fallback {
+        log_warn!(
+            "Primary tracker {} is not supported, using {} from the announce-list",
+            redact_tracker_url(primary),
+            redact_tracker_url(url)
+        );
+        return Ok(url.clone());
+    }
+
+    Err(match error {
+        ValidationError::UnsupportedTrackerScheme(scheme) => FakerError::ConfigError(format!(
+            "{}:// trackers are not yet supported; pick an http(s):// tracker from the announce-list",
+            scheme
+        )),
+        _ => FakerError::ConfigError(format!("Invalid tracker URL: {}", redact_tracker_url(primary))),
+    })
 }
 
 impl RatioFaker {
     pub fn new(torrent: TorrentInfo, config: FakerConfig) -> Result<Self> {
+        // Create client configuration
+        let mut client_config = ClientConfig::get(config.client_type.clone(), config.client_version.clone());
+        client_config.request_timeout_secs =
+            validate_request_timeout(config.request_timeout_secs.unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS))
+                .map_err(|e| FakerError::ConfigError(e.to_string()))?;
+        client_config
+            .apply_overrides(
+                config.user_agent_override.as_deref(),
+                config.peer_id_prefix_override.as_deref(),
+            )
+            .map_err(|e| FakerError::ConfigError(e.to_string()))?;
+
+        // Create tracker client
+        let tracker_client = TrackerClient::new(client_config).map_err(|e| FakerError::ConfigError(e.to_string()))?;
+
+        Self::with_tracker(torrent, config, tracker_client)
+    }
+}
+
+impl<T: Tracker> RatioFaker<T> {
+    /// Create a faker that talks to the given tracker. The emulated client comes from the tracker's
+    /// client configuration, `client_type` and the overrides in `config` are not applied again.
+    pub fn with_tracker(torrent: TorrentInfo, config: FakerConfig, tracker_client: T) -> Result<Self> {
+        Self::with_tracker_and_clock(torrent, config, tracker_client, SystemClock)
+    }
+}
+
+impl<T: Tracker, C: Clock> RatioFaker<T, C> {
+    /// Create a faker that talks to the given tracker and reads the time from `clock`
+    pub fn with_tracker_and_clock(
+        torrent: TorrentInfo,
+        config: FakerConfig,
+        tracker_client: T,
+        clock: C,
+    ) -> Result<Self> {
         log_debug!(
             "Creating RatioFaker for '{}' (size: {} bytes)",
             torrent.name,
@@ -329,18 +845,21 @@ impl RatioFaker {
             config.client_type
         );
 
-        // Create client configuration
-        let client_config = ClientConfig::get(config.client_type.clone(), config.client_version.clone());
-
         // Generate session identifiers
+        let client_config = tracker_client.client_config();
         let peer_id = client_config.generate_peer_id();
-        let key = ClientConfig::generate_key();
+        let key = client_config.generate_client_key();
 
         log_trace!("Generated peer_id: {}, key: {}", peer_id, key);
 
-        // Create tracker client
-        let tracker_client =
-            TrackerClient::new(client_config.clone()).map_err(|e| FakerError::ConfigError(e.to_string()))?;
+        config
+            .validate_faker_settings()
+            .map_err(|e| FakerError::ConfigError(e.to_string()))?;
+
+        let tracker_url = select_tracker_url(&torrent)?;
+        for warning in config.stop_condition_warnings(Some(torrent.total_size)) {
+            log_warn!("'{}': {}", torrent.name, warning);
+        }
 
         // Calculate how much of THIS torrent is already downloaded
         let completion = config.completion_percent.clamp(0.0, 100.0) / 100.0;
@@ -396,22 +915,41 @@ impl RatioFaker {
             last_announce: None,
             next_announce: None,
             announce_count: 0,
+            announce_interval_secs: 0,
+            schedule_active: true,
+            uploaded_delta_last_announce: 0,
+            downloaded_delta_last_announce: 0,
+            error: None,
         };
 
+        let initial_uploaded = stats.uploaded;
+        let initial_downloaded = stats.downloaded;
+        let now = clock.now();
+
         #[cfg(not(target_arch = "wasm32"))]
         {
             Ok(RatioFaker {
                 torrent,
                 config,
                 tracker_client,
+                original_tracker_url: tracker_url.clone(),
+                tracker_url,
                 state: Arc::new(RwLock::new(FakerState::Idle)),
                 stats: Arc::new(RwLock::new(stats)),
                 peer_id,
                 key,
                 tracker_id: None,
-                start_time: Instant::now(),
-                last_update: Instant::now(),
+                announces_since_started: 0,
+                uploaded_at_last_announce: initial_uploaded,
+                downloaded_at_last_announce: initial_downloaded,
+                clock,
+                session_start: now,
+                last_update: now,
                 announce_interval: Duration::from_secs(1800), // Default 30 minutes
+                next_announce_delay: Duration::from_secs(1800),
+                paused_duration: Duration::ZERO,
+                paused_at: None,
+                pending_completion: None,
             })
         }
 
@@ -421,40 +959,54 @@ impl RatioFaker {
                 torrent,
                 config,
                 tracker_client,
+                original_tracker_url: tracker_url.clone(),
+                tracker_url,
                 state: RefCell::new(FakerState::Idle),
                 stats: RefCell::new(stats),
                 peer_id,
                 key,
                 tracker_id: None,
-                start_time: Instant::now(),
-                last_update: Instant::now(),
+                announces_since_started: 0,
+                uploaded_at_last_announce: initial_uploaded,
+                downloaded_at_last_announce: initial_downloaded,
+                clock,
+                session_start: now,
+                last_update: now,
                 announce_interval: Duration::from_secs(1800), // Default 30 minutes
+                next_announce_delay: Duration::from_secs(1800),
+                paused_duration: Duration::ZERO,
+                paused_at: None,
+                pending_completion: None,
             })
         }
     }
 
+    /// Send a single announce with `event` and return the tracker's response.
+    /// No session is started: state, stats and the announce schedule are left as they are.
+    pub async fn announce_once(&mut self, event: TrackerEvent) -> Result<AnnounceResponse> {
+        log_info!("Sending single {:?} announce", event);
+        self.announce(event).await
+    }
+
     /// Start the ratio faking session
     pub async fn start(&mut self) -> Result<()> {
         log_info!("Starting ratio faker for torrent: {}", self.torrent.name);
 
         // Update state
         *write_lock!(self.state) = FakerState::Running;
-        self.start_time = Instant::now();
-        self.last_update = Instant::now();
+        self.begin_session();
 
         // Send started event
         let response = match self.announce(TrackerEvent::Started).await {
             Ok(r) => r,
             Err(e) => {
                 // UI doit savoir que le start a échoué
-                log_info!("Initial announce failed: {}", e);
-                *write_lock!(self.state) = FakerState::Stopped;
+                self.mark_errored(format!("Initial announce failed: {}", e)).await;
                 return Err(e);
             }
         };
 
-        // Update announce interval
-        self.announce_interval = Duration::from_secs(response.interval as u64);
+        self.set_announce_interval(&response);
 
         // Store tracker ID if provided
         self.tracker_id = response.tracker_id;
@@ -462,10 +1014,13 @@ impl RatioFaker {
         // Update stats with tracker response
         let mut stats = write_lock!(self.stats);
         stats.state = FakerState::Running; // Ensure state is synced
+        stats.error = None;
         stats.seeders = response.complete;
         stats.leechers = response.incomplete;
-        stats.last_announce = Some(Instant::now());
-        stats.next_announce = Some(Instant::now() + self.announce_interval);
+        let announced_at = self.clock.now();
+        stats.last_announce = Some(announced_at);
+        stats.next_announce = Some(announced_at + self.next_announce_delay);
+        stats.announce_interval_secs = self.announce_interval.as_secs();
         stats.announce_count += 1;
 
         log_info!(
@@ -482,27 +1037,55 @@ impl RatioFaker {
     pub async fn stop(&mut self) -> Result<()> {
         log_info!("Stopping ratio faker");
 
+        // A delayed completed event is dropped, the stopped announce already reports left=0
+        self.pending_completion = None;
+
         // Send stopped event
         self.announce(TrackerEvent::Stopped).await?;
 
+        self.stop_without_announce().await;
+        write_lock!(self.stats).announce_count += 1;
+
+        Ok(())
+    }
+
+    /// End the session without telling the tracker, e.g. when it mustn't be contacted.
+    /// The tracker drops the peer on its own once the announce interval runs out.
+    pub async fn stop_without_announce(&mut self) {
+        self.pending_completion = None;
+
         // Update state
         *write_lock!(self.state) = FakerState::Stopped;
+        self.paused_at.get_or_insert_with(|| self.clock.now());
 
         // CRITICAL: Also update the state in stats so frontend can detect the stop
-        let mut stats = write_lock!(self.stats);
-        stats.state = FakerState::Stopped;
-        stats.announce_count += 1;
+        write_lock!(self.stats).state = FakerState::Stopped;
+    }
 
-        Ok(())
+    /// Give up on the session without announcing: moves to `Errored` with `reason`,
+    /// kept in the stats until the next successful start
+    pub async fn mark_errored(&mut self, reason: String) {
+        log_error!("Giving up on '{}': {}", self.torrent.name, reason);
+        self.pending_completion = None;
+
+        *write_lock!(self.state) = FakerState::Errored;
+        self.paused_at.get_or_insert_with(|| self.clock.now());
+
+        let mut stats = write_lock!(self.stats);
+        stats.state = FakerState::Errored;
+        stats.current_upload_rate = 0.0;
+        stats.current_download_rate = 0.0;
+        stats.error = Some(reason);
     }
 
     /// Update the fake stats (call this periodically)
     pub async fn update(&mut self) -> Result<()> {
-        let now = Instant::now();
+        let now = self.clock.now();
         let elapsed = now.duration_since(self.last_update);
         self.last_update = now;
 
         let mut stats = write_lock!(self.stats);
+        stats.schedule_active = self.is_schedule_active();
 
         // Calculate and apply rates
         let (upload_rate, download_rate) = self.calculate_current_rates(&stats);
@@ -522,9 +1105,9 @@ impl RatioFaker {
 
         let completed = self.update_transfer_stats(&mut stats, upload_delta, download_delta);
 
-        if completed {
+        if completed || self.pending_completion.is_some() {
             drop(stats);
-            self.on_completed().await?;
+            self.handle_completion(completed, now).await?;
             stats = write_lock!(self.stats);
         }
 
@@ -532,11 +1115,16 @@ impl RatioFaker {
         self.update_derived_stats(&mut stats, now);
 
         // Check stop conditions
-        if self.check_stop_conditions(&stats) {
-            log_info!("Stop condition met, stopping faker");
-            drop(stats);
-            self.stop().await?;
-            return Ok(());
+        if stats.state != FakerState::Seeding && self.check_stop_conditions(&stats) {
+            if self.config.on_target_reached == TargetAction::IdleSeed {
+                log_info!("Stop condition met, idle seeding");
+                self.enter_seeding(&mut stats).await;
+            } else {
+                log_info!("Stop condition met, stopping faker");
+                drop(stats);
+                self.stop().await?;
+                return Ok(());
+            }
         }
 
         // Check if we need to announce
@@ -552,11 +1140,12 @@ impl RatioFaker {
 
     /// Update only the stats without announcing to tracker (for live updates)
     pub async fn update_stats_only(&mut self) -> Result<()> {
-        let now = Instant::now();
+        let now = self.clock.now();
         let elapsed = now.duration_since(self.last_update);
         self.last_update = now;
 
         let mut stats = write_lock!(self.stats);
+        stats.schedule_active = self.is_schedule_active();
 
         // Calculate and apply rates
         let (upload_rate, download_rate) = self.calculate_current_rates(&stats);
@@ -568,9 +1157,9 @@ impl RatioFaker {
 
         let completed = self.update_transfer_stats(&mut stats, upload_delta, download_delta);
 
-        if completed {
+        if completed || self.pending_completion.is_some() {
             drop(stats);
-            self.on_completed().await?;
+            self.handle_completion(completed, now).await?;
             stats = write_lock!(self.stats);
         }
 
@@ -578,11 +1167,16 @@ impl RatioFaker {
         self.update_derived_stats(&mut stats, now);
 
         // Check stop conditions
-        if self.check_stop_conditions(&stats) {
-            log_info!("Stop condition met, stopping faker");
-            drop(stats);
-            self.stop().await?;
-            return Ok(());
+        if stats.state != FakerState::Seeding && self.check_stop_conditions(&stats) {
+            if self.config.on_target_reached == TargetAction::IdleSeed {
+                log_info!("Stop condition met, idle seeding");
+                self.enter_seeding(&mut stats).await;
+            } else {
+                log_info!("Stop condition met, stopping faker");
+                drop(stats);
+                self.stop().await?;
+                return Ok(());
+            }
         }
 
         // NOTE: We don't check for periodic announce here - that's handled by update()
@@ -595,13 +1189,144 @@ impl RatioFaker {
         read_lock!(self.stats).clone()
     }
 
+    /// Get current stats synchronously (WASM stats live in a `RefCell`, so no await is needed)
+    #[cfg(target_arch = "wasm32")]
+    pub fn stats_snapshot(&self) -> FakerStats {
+        self.stats.borrow().clone()
+    }
+
+    /// Restore graph history from a previous run (e.g. after a server restart).
+    /// Only the most recent `HISTORY_MAX_POINTS` points of each history are kept.
+    pub async fn restore_history(
+        &mut self,
+        upload_rate_history: Vec<f64>,
+        download_rate_history: Vec<f64>,
+        ratio_history: Vec<f64>,
+        history_timestamps: Vec<u64>,
+    ) {
+        fn keep_recent<T>(mut history: Vec<T>) -> Vec<T> {
+            let excess = history.len().saturating_sub(HISTORY_MAX_POINTS);
+            history.drain(..excess);
+            history
+        }
+
+        let mut stats = write_lock!(self.stats);
+        stats.upload_rate_history = keep_recent(upload_rate_history);
+        stats.download_rate_history = keep_recent(download_rate_history);
+        stats.ratio_history = keep_recent(ratio_history);
+        stats.history_timestamps = keep_recent(history_timestamps);
+    }
+
+    /// Replace the current stats snapshot wholesale, without touching the tracker.
+    /// Used to drive a faker from recorded stats (e.g. demo replays).
+    pub async fn replace_stats(&mut self, stats: FakerStats) {
+        *write_lock!(self.state) = stats.state.clone();
+        *write_lock!(self.stats) = stats;
+    }
+
+    /// Apply a new config without starting a new session.
+    ///
+    /// Rates, randomization, progressive settings, stop conditions, retries and the active schedule
+    /// take effect on the next update; stats, timing and the tracker session are kept. Settings
+    /// that define the announced peer (client, port, timeout, overrides) and the starting
+    /// counters stay as they are until a new faker is created. An idle-seeding faker goes back to
+    /// running so that raised targets are picked up.
+    pub async fn apply_config(&mut self, config: FakerConfig) -> Result<()> {
+        config
+            .validate_faker_settings()
+            .map_err(|e| FakerError::ConfigError(e.to_string()))?;
+
+        let current = std::mem::replace(&mut self.config, config);
+        self.config.port = current.port;
+        self.config.client_type = current.client_type;
+        self.config.client_version = current.client_version;
+        self.config.initial_uploaded = current.initial_uploaded;
+        self.config.initial_downloaded = current.initial_downloaded;
+        self.config.completion_percent = current.completion_percent;
+        self.config.request_timeout_secs = current.request_timeout_secs;
+        self.config.user_agent_override = current.user_agent_override;
+        self.config.peer_id_prefix_override = current.peer_id_prefix_override;
+
+        if *read_lock!(self.state) == FakerState::Seeding {
+            *write_lock!(self.state) = FakerState::Running;
+            write_lock!(self.stats).state = FakerState::Running;
+        }
+
+        log_info!(
+            "Applied new config to '{}': upload_rate={} KB/s, download_rate={} KB/s",
+            self.torrent.name,
+            self.config.upload_rate,
+            self.config.download_rate
+        );
+
+        Ok(())
+    }
+
+    /// Change the upload and/or download rate (KB/s) in place, leaving the other one as is.
+    /// Both rates are validated before either is applied. Returns the rates now in effect.
+    pub fn set_rates(&mut self, upload: Option<f64>, download: Option<f64>) -> Result<(f64, f64)> {
+        let validate = |rate: Option<f64>, field: &str| {
+            rate.map(|r| validate_rate(r, field))
+                .transpose()
+                .map_err(|e| FakerError::ConfigError(e.to_string()))
+        };
+        let upload = validate(upload, "upload_rate")?;
+        let download = validate(download, "download_rate")?;
+
+        if let Some(rate) = upload {
+            self.config.upload_rate = rate;
+        }
+        if let Some(rate) = download {
+            self.config.download_rate = rate;
+        }
+
+        log_info!(
+            "Rates changed: upload_rate={} KB/s, download_rate={} KB/s",
+            self.config.upload_rate,
+            self.config.download_rate
+        );
+
+        Ok((self.config.upload_rate, self.config.download_rate))
+    }
+
+    /// Announce interval from the tracker's last response
+    pub fn announce_interval(&self) -> Duration {
+        self.announce_interval
+    }
+
+    /// Peer ID announced for this session
+    pub fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
     /// Get torrent info
     pub fn get_torrent(&self) -> &TorrentInfo {
         &self.torrent
     }
 
+    /// Config in effect, including the settings `apply_config` keeps
+    pub fn get_config(&self) -> &FakerConfig {
+        &self.config
+    }
+
+    /// Tracker URL announces currently go to
+    pub fn tracker_url(&self) -> &str {
+        &self.tracker_url
+    }
+
     /// Build announce request (helper)
     fn build_announce_request(&self, stats: &FakerStats, event: TrackerEvent) -> AnnounceRequest {
+        // Some clients only send numwant on certain events and let the tracker decide otherwise
+        let num_want = match (&event, self.config.num_want_steady) {
+            (TrackerEvent::Started, _) | (_, None) => self.config.num_want,
+            (_, Some(steady)) => taper_num_want(self.config.num_want, steady, self.announces_since_started),
+        };
+        let numwant = self
+            .tracker_client
+            .client_config()
+            .sends_num_want(&event)
+            .then_some(num_want);
+
         AnnounceRequest {
             info_hash: self.torrent.info_hash,
             peer_id: self.peer_id.clone(),
@@ -610,12 +1335,14 @@ impl RatioFaker {
             downloaded: stats.downloaded,
             left: stats.left,
             compact: true,
-            no_peer_id: false,
+            // Only sent by clients whose announce layout includes it
+            no_peer_id: true,
             event,
             ip: None,
-            numwant: Some(self.config.num_want),
+            numwant,
             key: Some(self.key.clone()),
             tracker_id: self.tracker_id.clone(),
+            extra_params: self.config.extra_announce_params.clone(),
         }
     }
 
@@ -632,19 +1359,112 @@ impl RatioFaker {
         );
 
         let request = self.build_announce_request(&stats, event.clone());
+        let uploaded = request.uploaded;
+        let downloaded = request.downloaded;
 
         drop(stats); // Release lock before async call
 
+        if matches!(event, TrackerEvent::Started) {
+            self.announces_since_started = 0;
+        }
+
         // Pour ne pas bloquer l'UI lors de l'ajout de torrent, on ne fait PAS
         // de retry sur l'announce initial (Started). On renvoie l'erreur tout de suite.
         let response = match event {
-            TrackerEvent::Started => self.send_announce_with_retry(request).await?,
-            _ => self.send_announce_with_retry(request).await?,
+            TrackerEvent::Started => self.send_announce_with_retry(request.clone()).await,
+            _ => self.send_announce_with_retry(request.clone()).await,
         };
+        let response = self.announce_other_trackers(&request, response).await?;
+        self.announces_since_started = self.announces_since_started.saturating_add(1);
+        self.uploaded_at_last_announce = uploaded;
+        self.downloaded_at_last_announce = downloaded;
+
+        // Tracker moved: announce and scrape against the new URL from now on
+        if let Some(url) = response.redirected_to.as_deref() {
+            if url != self.tracker_url && validate_tracker_url(url).is_ok() {
+                log_info!(
+                    "Tracker redirected {} to {}, using it for this session",
+                    redact_tracker_url(&self.tracker_url),
+                    redact_tracker_url(url)
+                );
+                self.tracker_url = url.to_string();
+            }
+        }
 
         Ok(response)
     }
 
+    /// Trackers announced to besides the current one with `announce_all_trackers`: the first
+    /// supported URL of every other tier. The tier of the current tracker, or of the one it was
+    /// redirected from, only holds backups of it.
+    fn other_tracker_urls(&self) -> Vec<String> {
+        if !self.config.announce_all_trackers {
+            return Vec::new();
+        }
+        let single_tier = [vec![self.torrent.announce.clone()]];
+        let tiers = self.torrent.announce_list.as_deref().unwrap_or(&single_tier);
+        let ours = |url: &String| *url == self.tracker_url || *url == self.original_tracker_url;
+
+        let mut urls: Vec<String> = Vec::new();
+        for tier in tiers.iter().filter(|tier| !tier.iter().any(ours)) {
+            if let Some(url) = tier.iter().find(|url| validate_tracker_url(url).is_ok()) {
+                if !urls.contains(url) {
+                    urls.push(url.clone());
+                }
+            }
+        }
+        urls
+    }
+
+    /// Send the same announce to the other trackers, all at once, and fold their swarm counts into
+    /// `primary`. When the current tracker failed, the first other tracker that answered stands in for it.
+    async fn announce_other_trackers(
+        &self,
+        request: &AnnounceRequest,
+        primary: Result<AnnounceResponse>,
+    ) -> Result<AnnounceResponse> {
+        let urls = self.other_tracker_urls();
+        if urls.is_empty() {
+            return primary;
+        }
+
+        // Tracker IDs are only meaningful to the tracker that handed them out
+        let request = AnnounceRequest {
+            tracker_id: None,
+            ..request.clone()
+        };
+        let aggregation = self.config.swarm_aggregation;
+
+        let responses = join_all(urls.iter().map(|url| self.tracker_client.announce(url, &request))).await;
+
+        let mut combined = primary;
+        for (url, response) in urls.iter().zip(responses) {
+            let response = match response {
+                Ok(response) => response,
+                Err(e) => {
+                    log_warn!("Announce to {} failed: {}", redact_tracker_url(url), e);
+                    continue;
+                }
+            };
+            combined = match combined {
+                Ok(mut base) => {
+                    base.complete = aggregation.combine(base.complete, response.complete);
+                    base.incomplete = aggregation.combine(base.incomplete, response.incomplete);
+                    Ok(base)
+                }
+                Err(e) => {
+                    log_warn!("{}, using the response of {}", e, redact_tracker_url(url));
+                    Ok(AnnounceResponse {
+                        tracker_id: None,
+                        redirected_to: None,
+                        ..response
+                    })
+                }
+            };
+        }
+        combined
+    }
+
     /// Send announce with retry/fixed-delay
     async fn send_announce_with_retry(&mut self, request: AnnounceRequest) -> Result<AnnounceResponse> {
         // Number of retries after the initial attempt
@@ -658,11 +1478,7 @@ impl RatioFaker {
         loop {
             attempt += 1;
 
-            match self
-                .tracker_client
-                .announce(self.torrent.get_tracker_url(), &request)
-                .await
-            {
+            match self.tracker_client.announce(&self.tracker_url, &request).await {
                 Ok(resp) => {
                     return Ok(resp);
                 }
@@ -725,18 +1541,23 @@ impl RatioFaker {
     async fn periodic_announce(&mut self) -> Result<()> {
         log_info!("Sending periodic announce");
 
+        let uploaded_before = self.uploaded_at_last_announce;
+        let downloaded_before = self.downloaded_at_last_announce;
         let response = self.announce(TrackerEvent::None).await?;
 
-        // Update interval if changed
-        self.announce_interval = Duration::from_secs(response.interval as u64);
+        self.set_announce_interval(&response);
 
         // Update stats
         let mut stats = write_lock!(self.stats);
         stats.seeders = response.complete;
         stats.leechers = response.incomplete;
-        stats.last_announce = Some(Instant::now());
-        stats.next_announce = Some(Instant::now() + self.announce_interval);
+        let announced_at = self.clock.now();
+        stats.last_announce = Some(announced_at);
+        stats.next_announce = Some(announced_at + self.next_announce_delay);
+        stats.announce_interval_secs = self.announce_interval.as_secs();
         stats.announce_count += 1;
+        stats.uploaded_delta_last_announce = self.uploaded_at_last_announce.saturating_sub(uploaded_before);
+        stats.downloaded_delta_last_announce = self.downloaded_at_last_announce.saturating_sub(downloaded_before);
 
         log_info!(
             "Periodic announce complete. Seeders: {}, Leechers: {}",
@@ -747,6 +1568,35 @@ impl RatioFaker {
         Ok(())
     }
 
+    /// Switch to `Seeding`: the session stays alive but nothing more is transferred
+    async fn enter_seeding(&self, stats: &mut FakerStats) {
+        *write_lock!(self.state) = FakerState::Seeding;
+        stats.state = FakerState::Seeding;
+        stats.current_upload_rate = 0.0;
+        stats.current_download_rate = 0.0;
+    }
+
+    /// Send `completed` for a download that just finished, or once its delay from
+    /// `completion_jitter_secs` is over
+    async fn handle_completion(&mut self, just_completed: bool, now: Instant) -> Result<()> {
+        if just_completed {
+            let jitter = self.config.completion_jitter_secs;
+            if jitter == 0 {
+                return self.on_completed().await;
+            }
+            let delay = rand::rng().random_range(Duration::ZERO..=Duration::from_secs(jitter));
+            log_debug!("Download finished, sending completed in {:.1}s", delay.as_secs_f64());
+            self.pending_completion = Some(now + delay);
+            return Ok(());
+        }
+
+        if self.pending_completion.is_some_and(|at| now >= at) {
+            self.pending_completion = None;
+            return self.on_completed().await;
+        }
+        Ok(())
+    }
+
     /// Handle completion event
     async fn on_completed(&mut self) -> Result<()> {
         log_info!("Torrent completed! Sending completed event");
@@ -772,7 +1622,7 @@ impl RatioFaker {
 
         let response = self
             .tracker_client
-            .scrape(self.torrent.get_tracker_url(), &self.torrent.info_hash)
+            .scrape(&self.tracker_url, &self.torrent.info_hash)
             .await?;
 
         log_info!(
@@ -785,26 +1635,139 @@ impl RatioFaker {
         Ok(response)
     }
 
+    /// Scrape the current tracker for several torrents in one request, e.g. every torrent
+    /// announced to it. Torrents the tracker doesn't report are missing from the result.
+    pub async fn scrape_many(
+        &self,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<std::collections::HashMap<[u8; 20], crate::protocol::ScrapeResponse>> {
+        Ok(self.tracker_client.scrape_many(&self.tracker_url, info_hashes).await?)
+    }
+
+    /// Refresh seeder/leecher counts from a scrape, without announcing or touching transfer stats
+    pub async fn apply_scrape(&mut self, response: &crate::protocol::ScrapeResponse) {
+        let mut stats = write_lock!(self.stats);
+        stats.seeders = response.complete;
+        stats.leechers = response.incomplete;
+    }
+
     /// Pause the faker
     pub async fn pause(&mut self) -> Result<()> {
         log_info!("Pausing ratio faker");
         *write_lock!(self.state) = FakerState::Paused;
         write_lock!(self.stats).state = FakerState::Paused;
+        self.paused_at.get_or_insert_with(|| self.clock.now());
         Ok(())
     }
 
-    /// Resume the faker
+    /// Resume the faker.
+    ///
+    /// What is sent to the tracker depends on the state being resumed from:
+    /// - Paused: nothing, the tracker still considers us active, unless `announce_on_resume`
+    ///   is set (a regular announce, which also restarts the announce interval)
+    /// - Completed: a regular announce as a seeder (`completed` is never re-sent)
+    /// - Stopped/Idle/Errored: `started`, since the tracker has dropped (or never seen) us
+    /// - Running/Seeding: no-op
     pub async fn resume(&mut self) -> Result<()> {
-        log_info!("Resuming ratio faker");
+        let previous = read_lock!(self.state).clone();
+
+        match previous {
+            FakerState::Running | FakerState::Seeding => return Ok(()),
+            FakerState::Paused if self.config.announce_on_resume => {
+                log_info!("Resuming ratio faker, re-announcing to the tracker");
+                self.announce_and_run(TrackerEvent::None).await?;
+            }
+            FakerState::Paused => {
+                log_info!("Resuming ratio faker");
+                *write_lock!(self.state) = FakerState::Running;
+                write_lock!(self.stats).state = FakerState::Running;
+            }
+            FakerState::Completed => {
+                log_info!("Resuming completed torrent, re-announcing as seeder");
+                self.announce_and_run(TrackerEvent::None).await?;
+            }
+            FakerState::Idle => {
+                // Never started (e.g. restored after a restart): this is a fresh session
+                log_info!("Resuming idle ratio faker, announcing started");
+                self.begin_session();
+                self.announce_and_run(TrackerEvent::Started).await?;
+            }
+            FakerState::Stopped | FakerState::Errored => {
+                log_info!("Resuming {:?} ratio faker, re-announcing started", previous);
+                self.announce_and_run(TrackerEvent::Started).await?;
+            }
+        }
+
+        // Paused/stopped time doesn't count toward seed time or average rates
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_duration += self.clock.now().duration_since(paused_at);
+        }
+
+        self.last_update = self.clock.now(); // Reset to avoid large delta
+        Ok(())
+    }
+
+    /// Use the tracker's interval, clamped to the configured bounds, for the next announces
+    fn set_announce_interval(&mut self, response: &AnnounceResponse) {
+        let secs = clamp_announce_interval(
+            response.interval,
+            response.min_interval,
+            self.config.announce_interval_min_secs,
+            self.config.announce_interval_max_secs,
+        );
+        if secs != response.interval.max(0) as u64 {
+            log_debug!("Tracker interval {}s clamped to {}s", response.interval, secs);
+        }
+        self.announce_interval = Duration::from_secs(secs);
+        self.next_announce_delay = jittered_announce_delay(
+            self.announce_interval,
+            self.config.announce_jitter_percent,
+            response.min_interval,
+            self.config.announce_interval_min_secs,
+        );
+    }
+
+    /// Reset session timing so elapsed time and averages only cover this session
+    fn begin_session(&mut self) {
+        self.session_start = self.clock.now();
+        self.last_update = self.session_start;
+        self.paused_duration = Duration::ZERO;
+        self.paused_at = None;
+    }
+
+    /// Announce `event` and switch to Running with the tracker's response
+    async fn announce_and_run(&mut self, event: TrackerEvent) -> Result<()> {
+        let response = self.announce(event).await?;
+
+        self.set_announce_interval(&response);
+        if response.tracker_id.is_some() {
+            self.tracker_id = response.tracker_id;
+        }
+
         *write_lock!(self.state) = FakerState::Running;
-        write_lock!(self.stats).state = FakerState::Running;
-        self.last_update = Instant::now(); // Reset to avoid large delta
+
+        let mut stats = write_lock!(self.stats);
+        stats.state = FakerState::Running;
+        stats.error = None;
+        stats.seeders = response.complete;
+        stats.leechers = response.incomplete;
+        let announced_at = self.clock.now();
+        stats.last_announce = Some(announced_at);
+        stats.next_announce = Some(announced_at + self.next_announce_delay);
+        stats.announce_interval_secs = self.announce_interval.as_secs();
+        stats.announce_count += 1;
+
         Ok(())
     }
 
     /// Check if any stop conditions are met
     /// Calculate current upload and download rates with progressive and random adjustments
     fn calculate_current_rates(&self, stats: &FakerStats) -> (f64, f64) {
+        // Outside active hours or once idle seeding, nothing is transferred but the session stays alive
+        if !stats.schedule_active || stats.state == FakerState::Seeding {
+            return (0.0, 0.0);
+        }
+
         let base_upload_rate = if self.config.progressive_rates {
             self.calculate_progressive_rate(
                 self.config.upload_rate,
@@ -836,11 +1799,22 @@ impl RatioFaker {
             download_rate = 0.0;
         }
 
-        // Can't upload if there are no leechers
-        if stats.leechers <= 0 {
+        // Can't upload if there are no leechers, or once the ratio cap is reached
+        if stats.leechers <= 0 || self.ratio_cap_limit().is_some_and(|limit| stats.uploaded >= limit) {
             upload_rate = 0.0;
         }
 
+        // Past the believable amount for this announce window, slow down to the plausible rate
+        if let Some(per_hour) = self.config.max_uploads_per_hour {
+            if self
+                .plausible_upload_limit(stats)
+                .is_some_and(|limit| stats.uploaded >= limit)
+            {
+                let ceiling = per_hour * self.torrent.total_size as f64 / 3600.0 / 1024.0;
+                upload_rate = upload_rate.min(ceiling);
+            }
+        }
+
         (upload_rate, download_rate)
     }
 
@@ -863,21 +1837,58 @@ impl RatioFaker {
 
         // Record timestamp for this data point (Unix millis)
         let timestamp = Self::current_timestamp_millis();
-        Self::add_to_history_u64(&mut stats.history_timestamps, timestamp, 60);
+        Self::add_to_history_u64(&mut stats.history_timestamps, timestamp, HISTORY_MAX_POINTS);
+
+        Self::add_to_history(&mut stats.upload_rate_history, upload_rate, HISTORY_MAX_POINTS);
+        Self::add_to_history(&mut stats.download_rate_history, download_rate, HISTORY_MAX_POINTS);
+    }
+
+    /// Total uploaded bytes allowed by `max_ratio_cap`, if set
+    fn ratio_cap_limit(&self) -> Option<u64> {
+        self.config
+            .max_ratio_cap
+            .map(|cap| (cap * self.torrent.total_size as f64) as u64)
+    }
 
-        Self::add_to_history(&mut stats.upload_rate_history, upload_rate, 60);
-        Self::add_to_history(&mut stats.download_rate_history, download_rate, 60);
+    /// Total uploaded bytes believable under `max_uploads_per_hour`, if set: the last announced
+    /// total plus that many torrent sizes per hour since the last announce
+    fn plausible_upload_limit(&self, stats: &FakerStats) -> Option<u64> {
+        let per_hour = self.config.max_uploads_per_hour?;
+        let window = self
+            .clock
+            .now()
+            .duration_since(stats.last_announce.unwrap_or(self.session_start));
+        let allowed = per_hour * self.torrent.total_size as f64 * window.as_secs_f64() / 3600.0;
+        Some(self.uploaded_at_last_announce.saturating_add(allowed as u64))
     }
 
     /// Update transfer stats (uploaded, downloaded, left). Returns true if just completed.
     fn update_transfer_stats(&self, stats: &mut FakerStats, upload_delta: u64, download_delta: u64) -> bool {
-        stats.uploaded += upload_delta;
-        stats.session_uploaded += upload_delta;
+        let mut upload_delta = upload_delta;
+        if let Some(limit) = self.ratio_cap_limit() {
+            if stats.uploaded.saturating_add(upload_delta) >= limit {
+                if upload_delta > 0 && stats.uploaded < limit {
+                    log_warn!(
+                        "'{}' reached the ratio cap of {:.2}, uploading paused",
+                        self.torrent.name,
+                        self.config.max_ratio_cap.unwrap_or_default()
+                    );
+                }
+                upload_delta = limit.saturating_sub(stats.uploaded);
+            }
+        }
+        if let Some(limit) = self.plausible_upload_limit(stats) {
+            upload_delta = upload_delta.min(limit.saturating_sub(stats.uploaded));
+        }
+
+        // Saturate rather than wrap: resumed totals near u64::MAX plus huge rates must not overflow
+        stats.uploaded = stats.uploaded.saturating_add(upload_delta);
+        stats.session_uploaded = stats.session_uploaded.saturating_add(upload_delta);
 
         if stats.left > 0 {
             let actual_download = download_delta.min(stats.left);
-            stats.downloaded += actual_download;
-            stats.session_downloaded += actual_download;
+            stats.downloaded = stats.downloaded.saturating_add(actual_download);
+            stats.session_downloaded = stats.session_downloaded.saturating_add(actual_download);
             stats.left = stats.left.saturating_sub(actual_download);
 
             stats.left == 0
@@ -895,16 +1906,16 @@ impl RatioFaker {
             0.0
         };
         stats.ratio = current_ratio;
-        Self::add_to_history(&mut stats.ratio_history, current_ratio, 60);
+        Self::add_to_history(&mut stats.ratio_history, current_ratio, HISTORY_MAX_POINTS);
 
-        // Session ratio (for stop conditions) = session_uploaded / torrent_size
+        // Session ratio = session_uploaded / torrent_size
         stats.session_ratio = if self.torrent.total_size > 0 {
             stats.session_uploaded as f64 / self.torrent.total_size as f64
         } else {
             0.0
         };
 
-        stats.elapsed_time = now.duration_since(self.start_time);
+        stats.elapsed_time = self.active_elapsed(now);
 
         let elapsed_secs = stats.elapsed_time.as_secs_f64();
         if elapsed_secs > 0.0 {
@@ -915,6 +1926,16 @@ impl RatioFaker {
         self.update_progress_and_eta(stats);
     }
 
+    /// Time since the session started, excluding time spent paused or stopped
+    fn active_elapsed(&self, now: Instant) -> Duration {
+        let paused_now = self
+            .paused_at
+            .map(|paused_at| now.duration_since(paused_at))
+            .unwrap_or_default();
+        now.duration_since(self.session_start)
+            .saturating_sub(self.paused_duration + paused_now)
+    }
+
     /// Add a value to a history vec, keeping only the last `max_len` items
     fn add_to_history(history: &mut Vec<f64>, value: f64, max_len: usize) {
         history.push(value);
@@ -931,6 +1952,14 @@ impl RatioFaker {
         }
     }
 
+    /// Whether the current local hour is within the active schedule (always true without one)
+    fn is_schedule_active(&self) -> bool {
+        match self.config.active_schedule {
+            Some(ref schedule) => is_hour_active(schedule, self.clock.local_hour()),
+            None => true,
+        }
+    }
+
     /// Get current timestamp in milliseconds (cross-platform)
     fn current_timestamp_millis() -> u64 {
         #[cfg(not(target_arch = "wasm32"))]
@@ -948,37 +1977,45 @@ impl RatioFaker {
     }
 
     fn check_stop_conditions(&self, stats: &FakerStats) -> bool {
-        // Check ratio target (use session ratio, not cumulative)
+        let basis = self.config.stop_ratio_basis;
+
+        // Check ratio target
         if let Some(target_ratio) = self.config.stop_at_ratio {
-            if stats.session_ratio >= target_ratio - 0.001 {
+            let ratio = basis.ratio(stats);
+            if ratio >= target_ratio - 0.001 {
                 log_info!(
-                    "Target ratio reached: {:.3} >= {:.3} (session)",
-                    stats.session_ratio,
-                    target_ratio
+                    "Target ratio reached: {:.3} >= {:.3} ({})",
+                    ratio,
+                    target_ratio,
+                    basis.label()
                 );
                 return true;
             }
         }
 
-        // Check uploaded target (session uploaded, not total)
+        // Check uploaded target
         if let Some(target_uploaded) = self.config.stop_at_uploaded {
-            if stats.session_uploaded >= target_uploaded {
+            let uploaded = basis.uploaded(stats);
+            if uploaded >= target_uploaded {
                 log_info!(
-                    "Target uploaded reached: {} >= {} bytes (session)",
-                    stats.session_uploaded,
-                    target_uploaded
+                    "Target uploaded reached: {} >= {} bytes ({})",
+                    uploaded,
+                    target_uploaded,
+                    basis.label()
                 );
                 return true;
             }
         }
 
-        // Check downloaded target (session downloaded, not total)
+        // Check downloaded target
         if let Some(target_downloaded) = self.config.stop_at_downloaded {
-            if stats.session_downloaded >= target_downloaded {
+            let downloaded = basis.downloaded(stats);
+            if downloaded >= target_downloaded {
                 log_info!(
-                    "Target downloaded reached: {} >= {} bytes (session)",
-                    stats.session_downloaded,
-                    target_downloaded
+                    "Target downloaded reached: {} >= {} bytes ({})",
+                    downloaded,
+                    target_downloaded,
+                    basis.label()
                 );
                 return true;
             }
@@ -1005,7 +2042,7 @@ impl RatioFaker {
         false
     }
 
-    /// Calculate progressive rate (linear interpolation)
+    /// Calculate progressive rate, interpolating along the configured curve
     fn calculate_progressive_rate(
         &self,
         start_rate: f64,
@@ -1017,19 +2054,26 @@ impl RatioFaker {
             return target_rate;
         }
 
-        let progress = elapsed_secs as f64 / duration_secs as f64;
+        let progress = self
+            .config
+            .progressive_curve
+            .apply(elapsed_secs as f64 / duration_secs as f64);
         start_rate + (target_rate - start_rate) * progress
     }
 
     /// Update progress percentages and ETAs
     fn update_progress_and_eta(&self, stats: &mut FakerStats) {
-        // Upload progress (based on session uploaded)
+        // Progress is measured on the same basis as the stop conditions
+        let basis = self.config.stop_ratio_basis;
+        let uploaded = basis.uploaded(stats);
+
+        // Upload progress
         if let Some(target) = self.config.stop_at_uploaded {
-            stats.upload_progress = ((stats.session_uploaded as f64 / target as f64) * 100.0).min(100.0);
+            stats.upload_progress = ((uploaded as f64 / target as f64) * 100.0).min(100.0);
 
             // Calculate ETA
             if stats.average_upload_rate > 0.0 {
-                let remaining = target.saturating_sub(stats.session_uploaded);
+                let remaining = target.saturating_sub(uploaded);
                 let eta_secs = (remaining as f64 / 1024.0) / stats.average_upload_rate;
                 stats.eta_uploaded = Some(Duration::from_secs_f64(eta_secs));
             }
@@ -1038,21 +2082,21 @@ impl RatioFaker {
             stats.eta_uploaded = None;
         }
 
-        // Download progress (based on session downloaded)
+        // Download progress
         if let Some(target) = self.config.stop_at_downloaded {
-            stats.download_progress = ((stats.session_downloaded as f64 / target as f64) * 100.0).min(100.0);
+            stats.download_progress = ((basis.downloaded(stats) as f64 / target as f64) * 100.0).min(100.0);
         } else {
             stats.download_progress = 0.0;
         }
 
-        // Ratio progress (use session ratio for progress tracking)
+        // Ratio progress
         if let Some(target_ratio) = self.config.stop_at_ratio {
-            stats.ratio_progress = ((stats.session_ratio / target_ratio) * 100.0).min(100.0);
+            stats.ratio_progress = ((basis.ratio(stats) / target_ratio) * 100.0).min(100.0);
 
-            // Calculate ETA for ratio (based on session stats)
+            // Calculate ETA for ratio
             if stats.average_upload_rate > 0.0 && self.torrent.total_size > 0 {
-                let target_session_uploaded = (target_ratio * self.torrent.total_size as f64) as u64;
-                let remaining = target_session_uploaded.saturating_sub(stats.session_uploaded);
+                let target_uploaded = (target_ratio * self.torrent.total_size as f64) as u64;
+                let remaining = target_uploaded.saturating_sub(uploaded);
                 let eta_secs = (remaining as f64 / 1024.0) / stats.average_upload_rate;
                 stats.eta_ratio = Some(Duration::from_secs_f64(eta_secs));
             }
@@ -1078,6 +2122,9 @@ impl RatioFaker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
+    use crate::protocol::ScrapeResponse;
+    use crate::torrent::NumWantPolicy;
 
     #[test]
     fn test_faker_config_default() {
@@ -1085,4 +2132,1297 @@ mod tests {
         assert_eq!(config.upload_rate, 700.0);
         assert_eq!(config.download_rate, 0.0);
     }
+
+    fn udp_torrent(announce_list: Option<Vec<Vec<String>>>) -> TorrentInfo {
+        TorrentInfo {
+            announce_list,
+            ..TorrentInfo::fixture("udp://tracker.example:1337/announce")
+        }
+    }
+
+    /// Spawn a tracker that answers every announce and records each request line
+    fn spawn_recording_tracker() -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        std::thread::spawn(move || {
+            let body = b"d8:completei1e10:incompletei2e8:intervali1800ee";
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                if let Some(line) = request.lines().next() {
+                    recorded.lock().unwrap().push(line.to_string());
+                }
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(body);
+            }
+        });
+
+        (format!("http://{}/announce", addr), requests)
+    }
+
+    /// Tracker double: answers announces from a script, then with a 30 minute interval, and records every request
+    struct MockTracker {
+        client_config: ClientConfig,
+        responses: std::sync::Mutex<std::collections::VecDeque<AnnounceResponse>>,
+        requests: std::sync::Mutex<Vec<AnnounceRequest>>,
+    }
+
+    impl MockTracker {
+        fn new(responses: Vec<AnnounceResponse>) -> Self {
+            MockTracker {
+                client_config: ClientConfig::get(FakerConfig::default().client_type, None),
+                responses: std::sync::Mutex::new(responses.into()),
+                requests: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        /// Events of the announces received so far, `none` for periodic ones
+        fn events(&self) -> Vec<String> {
+            let requests = self.requests.lock().unwrap();
+            requests
+                .iter()
+                .map(|r| r.event.as_str().unwrap_or("none").to_string())
+                .collect()
+        }
+    }
+
+    impl Tracker for MockTracker {
+        fn client_config(&self) -> &ClientConfig {
+            &self.client_config
+        }
+
+        async fn announce(
+            &self,
+            _tracker_url: &str,
+            request: &AnnounceRequest,
+        ) -> crate::protocol::tracker::Result<AnnounceResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            let scripted = self.responses.lock().unwrap().pop_front();
+            Ok(scripted.unwrap_or_else(|| mock_response(1800)))
+        }
+
+        async fn scrape(
+            &self,
+            _tracker_url: &str,
+            _info_hash: &[u8; 20],
+        ) -> crate::protocol::tracker::Result<ScrapeResponse> {
+            Ok(ScrapeResponse {
+                complete: 1,
+                incomplete: 2,
+                downloaded: 0,
+                name: None,
+            })
+        }
+    }
+
+    fn mock_response(interval: i64) -> AnnounceResponse {
+        AnnounceResponse {
+            interval,
+            min_interval: None,
+            tracker_id: None,
+            complete: 1,
+            incomplete: 2,
+            warning: None,
+            redirected_to: None,
+        }
+    }
+
+    /// Tracker double for several trackers: seeders and leechers per URL, `None` for one that is down
+    struct SwarmTracker {
+        client_config: ClientConfig,
+        swarms: std::collections::HashMap<String, Option<(i64, i64)>>,
+        urls: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl SwarmTracker {
+        fn new(swarms: &[(&str, Option<(i64, i64)>)]) -> Self {
+            SwarmTracker {
+                client_config: ClientConfig::get(FakerConfig::default().client_type, None),
+                swarms: swarms.iter().map(|(url, swarm)| (url.to_string(), *swarm)).collect(),
+                urls: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Tracker for SwarmTracker {
+        fn client_config(&self) -> &ClientConfig {
+            &self.client_config
+        }
+
+        async fn announce(
+            &self,
+            tracker_url: &str,
+            _request: &AnnounceRequest,
+        ) -> crate::protocol::tracker::Result<AnnounceResponse> {
+            self.urls.lock().unwrap().push(tracker_url.to_string());
+            match self.swarms.get(tracker_url).copied().flatten() {
+                Some((complete, incomplete)) => Ok(AnnounceResponse {
+                    complete,
+                    incomplete,
+                    ..mock_response(1800)
+                }),
+                None => Err(TrackerError::TrackerFailure("down".to_string())),
+            }
+        }
+
+        async fn scrape(
+            &self,
+            _tracker_url: &str,
+            _info_hash: &[u8; 20],
+        ) -> crate::protocol::tracker::Result<ScrapeResponse> {
+            Err(TrackerError::TrackerFailure("no scrape".to_string()))
+        }
+    }
+
+    fn mock_faker(config: FakerConfig, responses: Vec<AnnounceResponse>) -> RatioFaker<MockTracker> {
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        RatioFaker::with_tracker(torrent, config, MockTracker::new(responses)).unwrap()
+    }
+
+    /// Faker on a mock tracker whose time only moves with the returned clock
+    fn clocked_faker(
+        config: FakerConfig,
+        responses: Vec<AnnounceResponse>,
+    ) -> (RatioFaker<MockTracker, MockClock>, MockClock) {
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        let clock = MockClock::new();
+        let faker =
+            RatioFaker::with_tracker_and_clock(torrent, config, MockTracker::new(responses), clock.clone()).unwrap();
+        (faker, clock)
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_upload_accumulation() {
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        faker.start().await.unwrap();
+
+        clock.advance(Duration::from_secs(10));
+        faker.update().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.session_uploaded, 100 * 1024 * 10);
+        assert_eq!(stats.elapsed_time, Duration::from_secs(10));
+
+        clock.advance(Duration::from_millis(500));
+        faker.update().await.unwrap();
+        assert_eq!(faker.get_stats().await.uploaded, 100 * 1024 * 10 + 51_200);
+
+        // Paused time neither uploads nor counts toward the elapsed time
+        faker.pause().await.unwrap();
+        clock.advance(Duration::from_secs(60));
+        faker.resume().await.unwrap();
+        clock.advance(Duration::from_secs(1));
+        faker.update().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.uploaded, 100 * 1024 * 10 + 51_200 + 100 * 1024);
+        assert_eq!(stats.elapsed_time, Duration::from_millis(11_500));
+    }
+
+    #[tokio::test]
+    async fn test_byte_counters_saturate() {
+        let config = FakerConfig {
+            upload_rate: 1_000_000.0,
+            download_rate: 1_000_000.0,
+            randomize_rates: false,
+            completion_percent: 0.0,
+            initial_uploaded: u64::MAX - 1024,
+            initial_downloaded: u64::MAX - 1024,
+            stop_at_seed_time: None,
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        faker.start().await.unwrap();
+        faker.stats.write().await.left = u64::MAX;
+
+        clock.advance(Duration::from_secs(60));
+        faker.update_stats_only().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.uploaded, u64::MAX);
+        assert_eq!(stats.downloaded, u64::MAX);
+        assert_eq!(stats.session_uploaded, 1_000_000 * 1024 * 60);
+
+        // Already at the limit, the next update stays there instead of wrapping or panicking
+        faker.stats.write().await.session_uploaded = u64::MAX - 1;
+        clock.advance(Duration::from_secs(60));
+        faker.update().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.uploaded, u64::MAX);
+        assert_eq!(stats.session_uploaded, u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_announce_schedule() {
+        let config = FakerConfig {
+            randomize_rates: false,
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, vec![mock_response(600)]);
+        faker.start().await.unwrap();
+
+        clock.advance(Duration::from_secs(599));
+        faker.update().await.unwrap();
+        assert_eq!(faker.tracker_client.events(), ["started"]);
+
+        clock.advance(Duration::from_secs(1));
+        faker.update().await.unwrap();
+        assert_eq!(faker.tracker_client.events(), ["started", "none"]);
+
+        // The next announce follows the interval of the latest response
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.announce_count, 2);
+        assert_eq!(
+            stats.next_announce.unwrap() - stats.last_announce.unwrap(),
+            Duration::from_secs(1800)
+        );
+        clock.advance(Duration::from_secs(1799));
+        faker.update().await.unwrap();
+        assert_eq!(faker.tracker_client.events().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_stop_at_seed_time() {
+        let config = FakerConfig {
+            randomize_rates: false,
+            stop_at_seed_time: Some(3600),
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        faker.start().await.unwrap();
+
+        clock.advance(Duration::from_secs(3599));
+        faker.update().await.unwrap();
+        assert_eq!(faker.get_stats().await.state, FakerState::Running);
+
+        clock.advance(Duration::from_secs(1));
+        faker.update().await.unwrap();
+        assert_eq!(faker.get_stats().await.state, FakerState::Stopped);
+        assert_eq!(faker.tracker_client.events().last().unwrap(), "stopped");
+    }
+
+    #[tokio::test]
+    async fn test_mock_tracker_completion() {
+        let config = FakerConfig {
+            download_rate: 100.0,
+            completion_percent: 50.0,
+            ..FakerConfig::default()
+        };
+        let mut faker = mock_faker(config, Vec::new());
+        faker.start().await.unwrap();
+
+        // 60s at 100 KB/s finishes the remaining 512 bytes
+        faker.last_update = Instant::now() - Duration::from_secs(60);
+        faker.update().await.unwrap();
+
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.left, 0);
+        assert_eq!(stats.state, FakerState::Completed);
+        assert_eq!(faker.tracker_client.events(), ["started", "completed"]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_tracker_ratio_stop() {
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            stop_at_ratio: Some(1.0),
+            ..FakerConfig::default()
+        };
+        let mut faker = mock_faker(config, Vec::new());
+        faker.start().await.unwrap();
+
+        faker.last_update = Instant::now() - Duration::from_secs(60);
+        faker.update().await.unwrap();
+
+        assert_eq!(faker.get_stats().await.state, FakerState::Stopped);
+        assert_eq!(faker.tracker_client.events(), ["started", "stopped"]);
+        let requests = faker.tracker_client.requests.lock().unwrap();
+        assert!(
+            requests[1].uploaded >= 1024,
+            "the stopped event reports the final upload"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_announce_once() {
+        let config = FakerConfig {
+            initial_uploaded: 4096,
+            ..FakerConfig::default()
+        };
+        let mut faker = mock_faker(config, vec![mock_response(900)]);
+        let response = faker.announce_once(TrackerEvent::Stopped).await.unwrap();
+        assert_eq!(response.interval, 900);
+
+        assert_eq!(faker.tracker_client.events(), ["stopped"]);
+        assert_eq!(faker.tracker_client.requests.lock().unwrap()[0].uploaded, 4096);
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.state, FakerState::Idle);
+        assert_eq!(stats.announce_count, 0);
+        assert!(stats.next_announce.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_tracker_announce_schedule() {
+        let mut faker = mock_faker(FakerConfig::default(), vec![mock_response(1800), mock_response(900)]);
+        faker.start().await.unwrap();
+        let started = faker.get_stats().await;
+        assert_eq!(started.announce_interval_secs, 1800);
+
+        // Not due yet: nothing is sent
+        faker.update().await.unwrap();
+        assert_eq!(faker.tracker_client.events(), ["started"]);
+
+        // Due: the periodic announce picks up the tracker's new interval
+        let mut stats = faker.get_stats().await;
+        stats.next_announce = Some(Instant::now());
+        faker.replace_stats(stats).await;
+        faker.update().await.unwrap();
+
+        let stats = faker.get_stats().await;
+        assert_eq!(faker.tracker_client.events(), ["started", "none"]);
+        assert_eq!(stats.announce_interval_secs, 900);
+        assert_eq!(stats.announce_count, started.announce_count + 1);
+        let scheduled = stats.next_announce.unwrap() - stats.last_announce.unwrap();
+        assert!(scheduled.abs_diff(Duration::from_secs(900)) < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_resume_announce_events() {
+        let (tracker_url, requests) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+        let last_request = || requests.lock().unwrap().last().cloned().unwrap();
+
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        faker.start().await.unwrap();
+        assert!(last_request().contains("event=started"));
+
+        // Paused -> Running: the tracker still has us, nothing is sent
+        faker.pause().await.unwrap();
+        let sent = requests.lock().unwrap().len();
+        faker.resume().await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), sent);
+        assert_eq!(faker.get_stats().await.state, FakerState::Running);
+
+        // Completed -> Running: plain seeder announce, completed is not re-sent
+        let mut stats = faker.get_stats().await;
+        stats.left = 0;
+        stats.state = FakerState::Completed;
+        faker.replace_stats(stats).await;
+        faker.resume().await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), sent + 1);
+        assert!(!last_request().contains("event="));
+        assert!(last_request().contains("left=0"));
+        assert_eq!(faker.get_stats().await.state, FakerState::Running);
+
+        // Stopped -> Running: the tracker dropped us, announce started again
+        faker.stop().await.unwrap();
+        assert!(last_request().contains("event=stopped"));
+        faker.resume().await.unwrap();
+        assert!(last_request().contains("event=started"));
+        assert_eq!(faker.get_stats().await.state, FakerState::Running);
+    }
+
+    #[tokio::test]
+    async fn test_announce_on_resume() {
+        let (tracker_url, requests) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+        let config = FakerConfig {
+            announce_on_resume: true,
+            ..FakerConfig::default()
+        };
+
+        let mut faker = RatioFaker::new(torrent, config).unwrap();
+        faker.start().await.unwrap();
+        faker.pause().await.unwrap();
+        let before = faker.get_stats().await;
+
+        faker.resume().await.unwrap();
+        let after = faker.get_stats().await;
+        assert_eq!(after.announce_count, before.announce_count + 1);
+        assert_eq!(after.state, FakerState::Running);
+        assert!(after.next_announce > before.next_announce);
+        let last = requests.lock().unwrap().last().cloned().unwrap();
+        assert!(!last.contains("event="));
+    }
+
+    #[tokio::test]
+    async fn test_periodic_announce_counted() {
+        let (tracker_url, requests) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        faker.start().await.unwrap();
+        let count = faker.get_stats().await.announce_count;
+
+        // Make the next announce due
+        let mut stats = faker.get_stats().await;
+        stats.next_announce = Some(Instant::now());
+        faker.replace_stats(stats).await;
+        faker.update().await.unwrap();
+
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.announce_count, count + 1);
+        assert_eq!((stats.seeders, stats.leechers), (1, 2));
+        assert_eq!(faker.announce_interval(), Duration::from_secs(1800));
+        assert!(!requests.lock().unwrap().last().unwrap().contains("event="));
+    }
+
+    #[tokio::test]
+    async fn test_announce_interval_bounds() {
+        // Tracker interval 3600s, min interval 300s
+        assert_eq!(clamp_announce_interval(3600, Some(300), None, Some(600)), 600);
+        assert_eq!(clamp_announce_interval(3600, Some(300), None, Some(120)), 300);
+        assert_eq!(clamp_announce_interval(60, None, Some(900), None), 900);
+        assert_eq!(clamp_announce_interval(1800, None, Some(60), Some(3600)), 1800);
+
+        let (tracker_url, _) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+        let config = FakerConfig {
+            announce_interval_max_secs: Some(600),
+            ..FakerConfig::default()
+        };
+
+        let mut faker = RatioFaker::new(torrent.clone(), config).unwrap();
+        faker.start().await.unwrap();
+        assert_eq!(faker.announce_interval(), Duration::from_secs(600));
+        assert_eq!(faker.get_stats().await.announce_interval_secs, 600);
+
+        let config = FakerConfig {
+            announce_interval_min_secs: Some(900),
+            announce_interval_max_secs: Some(600),
+            ..FakerConfig::default()
+        };
+        assert!(RatioFaker::new(torrent, config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_elapsed_time_excludes_paused_time() {
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+
+        // Ran for 60s, then paused for 30s
+        faker.session_start = Instant::now() - Duration::from_secs(90);
+        faker.pause().await.unwrap();
+        faker.paused_at = Some(Instant::now() - Duration::from_secs(30));
+
+        // Time spent paused is excluded while still paused...
+        faker.update_stats_only().await.unwrap();
+        assert_eq!(faker.get_stats().await.elapsed_time.as_secs(), 60);
+
+        // ...and after resuming
+        faker.resume().await.unwrap();
+        faker.update_stats_only().await.unwrap();
+        assert_eq!(faker.get_stats().await.elapsed_time.as_secs(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_average_rate_matches_steady_rate() {
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        faker.stats.write().await.leechers = 10;
+
+        // Idle long before the session starts, that time must not count
+        clock.advance(Duration::from_secs(60));
+        faker.begin_session();
+
+        for _ in 0..5 {
+            clock.advance(Duration::from_secs(1));
+            faker.update_stats_only().await.unwrap();
+        }
+
+        let stats = faker.get_stats().await;
+        assert!(
+            (stats.average_upload_rate - 100.0).abs() < 1e-9,
+            "average {} should match the configured 100 KB/s",
+            stats.average_upload_rate
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_config_keeps_session() {
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config.clone(), Vec::new());
+        faker.stats.write().await.leechers = 10;
+        faker.begin_session();
+        clock.advance(Duration::from_secs(1));
+        faker.update_stats_only().await.unwrap();
+
+        let session_start = faker.session_start;
+        let peer_id = faker.peer_id.clone();
+        let uploaded = faker.get_stats().await.uploaded;
+        assert_eq!(uploaded, 100 * 1024);
+
+        faker
+            .apply_config(FakerConfig {
+                upload_rate: 200.0,
+                port: config.port + 1,
+                ..config
+            })
+            .await
+            .unwrap();
+        clock.advance(Duration::from_secs(1));
+        faker.update_stats_only().await.unwrap();
+
+        let stats = faker.get_stats().await;
+        assert_eq!(faker.session_start, session_start);
+        assert_eq!(faker.peer_id, peer_id);
+        assert_eq!(faker.config.port, config.port, "port is part of the tracker session");
+        assert_eq!(
+            stats.uploaded,
+            uploaded + 200 * 1024,
+            "counters continue from the running session"
+        );
+        assert!((stats.current_upload_rate - 200.0).abs() < 1e-9);
+
+        let invalid = FakerConfig {
+            active_schedule: Some(vec![(25, 3)]),
+            ..FakerConfig::default()
+        };
+        assert!(faker.apply_config(invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_idle_seed_after_target_ratio() {
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            stop_at_ratio: Some(1.0),
+            on_target_reached: TargetAction::IdleSeed,
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        faker.stats.write().await.leechers = 10;
+        faker.begin_session();
+
+        // 100 KB/s against a 1 KB torrent passes ratio 1.0 on the first tick
+        clock.advance(Duration::from_secs(1));
+        faker.update_stats_only().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.state, FakerState::Seeding);
+        assert_eq!(*faker.state.read().await, FakerState::Seeding);
+
+        clock.advance(Duration::from_secs(1));
+        faker.update_stats_only().await.unwrap();
+        let later = faker.get_stats().await;
+        assert_eq!(later.state, FakerState::Seeding);
+        assert_eq!(later.uploaded, stats.uploaded, "nothing is uploaded while idle seeding");
+        assert_eq!(later.current_upload_rate, 0.0);
+    }
+
+    #[test]
+    fn test_set_rates() {
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        let download = faker.config.download_rate;
+
+        assert_eq!(faker.set_rates(Some(100.0), None).unwrap(), (100.0, download));
+
+        // An invalid rate rejects the whole change
+        assert!(faker.set_rates(Some(250.0), Some(-1.0)).is_err());
+        assert!(faker.set_rates(Some(f64::NAN), None).is_err());
+        assert_eq!(faker.config.upload_rate, 100.0);
+        assert_eq!(faker.config.download_rate, download);
+    }
+
+    #[tokio::test]
+    async fn test_max_ratio_cap() {
+        // 100 KB/s against a 1 KB torrent passes ratio 2.0 on the first tick
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            max_ratio_cap: Some(2.0),
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        faker.stats.write().await.leechers = 10;
+        faker.begin_session();
+
+        clock.advance(Duration::from_secs(1));
+        faker.update_stats_only().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.uploaded, 2048);
+        assert_eq!(stats.ratio, 2.0);
+
+        // Capped: the session goes on, but nothing more is uploaded
+        clock.advance(Duration::from_secs(1));
+        faker.update_stats_only().await.unwrap();
+        let later = faker.get_stats().await;
+        assert_eq!(later.state, stats.state);
+        assert_eq!(later.uploaded, 2048);
+        assert_eq!(later.current_upload_rate, 0.0);
+
+        let invalid = FakerConfig {
+            max_ratio_cap: Some(0.0),
+            ..FakerConfig::default()
+        };
+        assert!(faker.apply_config(invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_max_uploads_per_hour() {
+        // 60 copies of a 1 KB torrent per hour: 1 KB per minute, far below 100 KB/s
+        let config = FakerConfig {
+            upload_rate: 100.0,
+            randomize_rates: false,
+            max_uploads_per_hour: Some(60.0),
+            ..FakerConfig::default()
+        };
+        let mut faker = mock_faker(config, Vec::new());
+        faker.start().await.unwrap();
+
+        // A minute after the started announce
+        let minute_ago = Instant::now() - Duration::from_secs(60);
+        let mut stats = faker.get_stats().await;
+        stats.last_announce = Some(minute_ago);
+        faker.replace_stats(stats).await;
+        faker.last_update = minute_ago;
+        faker.update().await.unwrap();
+        let uploaded = faker.get_stats().await.uploaded;
+        assert!((1024..1030).contains(&uploaded), "uploaded {} in a minute", uploaded);
+
+        // Throttled to the plausible rate instead of stopping
+        faker.update().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert!(stats.current_upload_rate > 0.0 && stats.current_upload_rate < 0.02);
+
+        // The next announce reports the capped total and starts a new window
+        let mut stats = faker.get_stats().await;
+        stats.next_announce = Some(Instant::now());
+        faker.replace_stats(stats).await;
+        faker.update().await.unwrap();
+        let announced = faker.tracker_client.requests.lock().unwrap()[1].uploaded;
+        assert!(announced < 1030);
+        assert_eq!(faker.uploaded_at_last_announce, announced);
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_is_errored() {
+        let torrent = udp_torrent(Some(vec![vec!["http://127.0.0.1:1/announce".to_string()]]));
+        let config = FakerConfig {
+            announce_max_retries: 0,
+            ..FakerConfig::default()
+        };
+        let mut faker = RatioFaker::new(torrent, config).unwrap();
+        assert!(faker.start().await.is_err());
+
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.state, FakerState::Errored);
+        assert!(stats.error.unwrap().starts_with("Initial announce failed"));
+    }
+
+    #[tokio::test]
+    async fn test_mark_errored_and_retry() {
+        let mut faker = mock_faker(FakerConfig::default(), Vec::new());
+        faker.start().await.unwrap();
+
+        faker.mark_errored("tracker unreachable".to_string()).await;
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.state, FakerState::Errored);
+        assert_eq!(stats.error.as_deref(), Some("tracker unreachable"));
+        assert_eq!(stats.current_upload_rate, 0.0);
+
+        // A manual retry announces started again and clears the reason
+        faker.resume().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.state, FakerState::Running);
+        assert!(stats.error.is_none());
+        assert_eq!(faker.tracker_client.events().last().unwrap(), "started");
+    }
+
+    #[tokio::test]
+    async fn test_stop_without_announce() {
+        let mut faker = mock_faker(FakerConfig::default(), Vec::new());
+        faker.start().await.unwrap();
+
+        faker.stop_without_announce().await;
+        assert_eq!(faker.get_stats().await.state, FakerState::Stopped);
+        assert_eq!(faker.tracker_client.events(), vec!["started"]);
+
+        faker.stop().await.unwrap();
+        assert_eq!(faker.tracker_client.events(), vec!["started", "stopped"]);
+    }
+
+    #[tokio::test]
+    async fn test_announce_all_trackers() {
+        const A: &str = "http://a.example/announce";
+        const B: &str = "http://b.example/announce";
+        let torrent = udp_torrent(Some(vec![vec![A.to_string()], vec![B.to_string()]]));
+        let swarm = |config: FakerConfig, a, b| {
+            let tracker = SwarmTracker::new(&[(A, a), (B, b)]);
+            RatioFaker::with_tracker(torrent.clone(), config, tracker).unwrap()
+        };
+        let config = |swarm_aggregation| FakerConfig {
+            announce_all_trackers: true,
+            swarm_aggregation,
+            announce_max_retries: 0,
+            ..FakerConfig::default()
+        };
+
+        // Both trackers get the same announce, counts combined
+        let mut faker = swarm(config(SwarmAggregation::Max), Some((10, 3)), Some((4, 7)));
+        faker.start().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!((stats.seeders, stats.leechers), (10, 7));
+        assert_eq!(*faker.tracker_client.urls.lock().unwrap(), vec![A, B]);
+
+        let mut faker = swarm(config(SwarmAggregation::Sum), Some((10, 3)), Some((4, 7)));
+        faker.start().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!((stats.seeders, stats.leechers), (14, 10));
+
+        // One tracker down: the other one's counts are used
+        let mut faker = swarm(config(SwarmAggregation::Sum), Some((10, 3)), None);
+        faker.start().await.unwrap();
+        assert_eq!(faker.get_stats().await.seeders, 10);
+
+        let mut faker = swarm(config(SwarmAggregation::Sum), None, Some((4, 7)));
+        faker.start().await.unwrap();
+        assert_eq!(faker.get_stats().await.seeders, 4);
+
+        // Every tracker down fails the announce
+        let mut faker = swarm(config(SwarmAggregation::Max), None, None);
+        assert!(faker.start().await.is_err());
+
+        // Off by default: only the selected tracker is announced to
+        let mut faker = swarm(FakerConfig::default(), Some((10, 3)), Some((4, 7)));
+        faker.start().await.unwrap();
+        assert_eq!(*faker.tracker_client.urls.lock().unwrap(), vec![A]);
+    }
+
+    #[test]
+    fn test_other_trackers_one_per_tier() {
+        let tiers = vec![
+            vec![
+                "http://a.example/announce".to_string(),
+                "http://a2.example/announce".to_string(),
+            ],
+            vec![
+                "udp://b.example:80".to_string(),
+                "http://b2.example/announce".to_string(),
+            ],
+            vec![
+                "http://c.example/announce".to_string(),
+                "http://c2.example/announce".to_string(),
+            ],
+        ];
+        let config = FakerConfig {
+            announce_all_trackers: true,
+            ..FakerConfig::default()
+        };
+        let mut faker =
+            RatioFaker::with_tracker(udp_torrent(Some(tiers)), config, MockTracker::new(Vec::new())).unwrap();
+
+        // Backups in the current tier are skipped, other tiers get their first supported tracker
+        assert_eq!(
+            faker.other_tracker_urls(),
+            vec!["http://b2.example/announce", "http://c.example/announce"]
+        );
+
+        // A redirect doesn't make the original tracker one of the others
+        faker.tracker_url = "http://moved.example/announce".to_string();
+        assert_eq!(
+            faker.other_tracker_urls(),
+            vec!["http://b2.example/announce", "http://c.example/announce"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_announce_deltas() {
+        let mut faker = mock_faker(FakerConfig::default(), Vec::new());
+        faker.start().await.unwrap();
+
+        let mut stats = faker.get_stats().await;
+        stats.uploaded += 5000;
+        stats.downloaded += 700;
+        faker.replace_stats(stats).await;
+        faker.periodic_announce().await.unwrap();
+
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.uploaded_delta_last_announce, 5000);
+        assert_eq!(stats.downloaded_delta_last_announce, 700);
+
+        // Nothing transferred since: the next delta is empty
+        faker.periodic_announce().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert_eq!(stats.uploaded_delta_last_announce, 0);
+        assert_eq!(stats.downloaded_delta_last_announce, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stop_ratio_basis() {
+        // A resumed instance: 2 KB carried over on a 1 KB torrent, nothing sent this session yet
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        let reached = |basis| {
+            let config = FakerConfig {
+                initial_uploaded: 2048,
+                stop_at_ratio: Some(1.5),
+                stop_ratio_basis: basis,
+                ..FakerConfig::default()
+            };
+            let faker = RatioFaker::new(torrent.clone(), config).unwrap();
+            async move {
+                let mut stats = faker.get_stats().await;
+                faker.update_derived_stats(&mut stats, Instant::now());
+                (faker.check_stop_conditions(&stats), stats.ratio_progress)
+            }
+        };
+
+        let (total_reached, total_progress) = reached(RatioBasis::Total).await;
+        assert!(total_reached, "carried-over uploads count toward a total ratio target");
+        assert_eq!(total_progress, 100.0);
+
+        let (session_reached, session_progress) = reached(RatioBasis::Session).await;
+        assert!(!session_reached, "a session target starts from zero on every run");
+        assert_eq!(session_progress, 0.0);
+        assert_eq!(FakerConfig::default().stop_ratio_basis, RatioBasis::Session);
+    }
+
+    #[test]
+    fn test_progressive_curves() {
+        for curve in [Curve::Linear, Curve::EaseInOut, Curve::Logarithmic, Curve::Exponential] {
+            let config = FakerConfig {
+                progressive_curve: curve,
+                ..FakerConfig::default()
+            };
+            let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+            let faker = RatioFaker::new(torrent, config).unwrap();
+
+            let at = |elapsed| faker.calculate_progressive_rate(100.0, 500.0, elapsed, 3600);
+            assert!(
+                (at(0) - 100.0).abs() < 1e-9,
+                "{:?} should start at the start rate",
+                curve
+            );
+            assert_eq!(at(3600), 500.0, "{:?} should reach the target", curve);
+            assert_eq!(at(7200), 500.0, "{:?} should stay clamped to the target", curve);
+        }
+
+        // EaseInOut never goes backwards and stays within [0, 1]
+        let mut previous = 0.0;
+        for step in 0..=100 {
+            let value = Curve::EaseInOut.apply(step as f64 / 100.0);
+            assert!(value >= previous && value <= 1.0);
+            previous = value;
+        }
+
+        // Logarithmic ramps up faster than linear, exponential slower
+        assert!(Curve::Logarithmic.apply(0.25) > 0.25);
+        assert!(Curve::Exponential.apply(0.25) < 0.25);
+    }
+
+    #[test]
+    fn test_is_hour_active() {
+        assert!(is_hour_active(&[(9, 17)], 9));
+        assert!(is_hour_active(&[(9, 17)], 16));
+        assert!(!is_hour_active(&[(9, 17)], 17));
+        assert!(!is_hour_active(&[(9, 17)], 3));
+
+        // Wrapping window
+        assert!(is_hour_active(&[(22, 6)], 23));
+        assert!(is_hour_active(&[(22, 6)], 0));
+        assert!(!is_hour_active(&[(22, 6)], 6));
+        assert!(!is_hour_active(&[(22, 6)], 12));
+
+        // Several ranges, whole day
+        assert!(is_hour_active(&[(1, 2), (12, 14)], 13));
+        assert!(is_hour_active(&[(5, 5)], 20));
+    }
+
+    #[tokio::test]
+    async fn test_no_transfer_outside_active_hours() {
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        assert!(RatioFaker::new(
+            torrent,
+            FakerConfig {
+                active_schedule: Some(vec![(0, 24)]),
+                ..FakerConfig::default()
+            }
+        )
+        .is_err());
+
+        // Active from 9 to 17, local time pinned to 20
+        let config = FakerConfig {
+            active_schedule: Some(vec![(9, 17)]),
+            ..FakerConfig::default()
+        };
+        let (mut faker, clock) = clocked_faker(config, Vec::new());
+        clock.set_local_hour(20);
+        faker.stats.write().await.leechers = 10;
+        clock.advance(Duration::from_secs(60));
+        faker.update_stats_only().await.unwrap();
+
+        let stats = faker.get_stats().await;
+        assert!(!stats.schedule_active);
+        assert_eq!(stats.session_uploaded, 0);
+        assert_eq!(stats.current_upload_rate, 0.0);
+
+        // Back inside the window: uploading resumes
+        clock.set_local_hour(10);
+        clock.advance(Duration::from_secs(60));
+        faker.update_stats_only().await.unwrap();
+        let stats = faker.get_stats().await;
+        assert!(stats.schedule_active);
+        assert!(stats.session_uploaded > 0);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(FakerConfig::default().validate().is_ok());
+
+        let config = FakerConfig {
+            port: 80,
+            ..FakerConfig::default()
+        };
+        assert!(matches!(config.validate(), Err(ValidationError::InvalidPort(80))));
+
+        let config = FakerConfig {
+            completion_percent: 500.0,
+            ..FakerConfig::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("completion_percent must be between 0 and 100"));
+
+        // A huge completion delay would overflow the completion time
+        let config = FakerConfig {
+            completion_jitter_secs: u64::MAX,
+            ..FakerConfig::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(RatioFaker::new(TorrentInfo::fixture("http://tracker.example/announce"), config).is_err());
+
+        // Unknown client versions are rejected instead of silently replaced
+        let config = FakerConfig {
+            client_type: ClientType::Deluge,
+            client_version: Some("1.3.15".to_string()),
+            ..FakerConfig::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ValidationError::UnknownClientVersion { .. })
+        ));
+        let config = FakerConfig {
+            client_version: Some(ClientConfig::available_versions(&config.client_type)[0].clone()),
+            ..config
+        };
+        assert!(config.validate().is_ok());
+
+        // The random range only matters when randomization is on
+        let config = FakerConfig {
+            randomize_rates: false,
+            random_range_percent: 150.0,
+            ..FakerConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        let config = FakerConfig {
+            randomize_rates: true,
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_stop_condition_warnings() {
+        let config = FakerConfig {
+            upload_rate: 0.0,
+            stop_at_uploaded: Some(1024),
+            ..FakerConfig::default()
+        };
+        let warnings = config.stop_condition_warnings(None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("stop_at_uploaded"));
+
+        // Reachable through the progressive ramp
+        let ramped = FakerConfig {
+            progressive_rates: true,
+            target_upload_rate: Some(100.0),
+            ..config.clone()
+        };
+        assert!(ramped.stop_condition_warnings(None).is_empty());
+
+        // Met by the uploaded total carried over, but only when it covers the target
+        let carried = |initial_uploaded| FakerConfig {
+            initial_uploaded,
+            stop_ratio_basis: RatioBasis::Total,
+            ..config.clone()
+        };
+        assert!(carried(2048).stop_condition_warnings(None).is_empty());
+        assert_eq!(carried(512).stop_condition_warnings(None).len(), 1);
+        let session = FakerConfig {
+            stop_ratio_basis: RatioBasis::Session,
+            ..carried(2048)
+        };
+        assert_eq!(session.stop_condition_warnings(None).len(), 1);
+
+        let ratio = FakerConfig {
+            stop_at_uploaded: None,
+            stop_at_ratio: Some(2.0),
+            ..config
+        };
+        assert!(ratio.stop_condition_warnings(None)[0].contains("stop_at_ratio"));
+        let carried_ratio = FakerConfig {
+            initial_uploaded: 1500,
+            stop_ratio_basis: RatioBasis::Total,
+            ..ratio
+        };
+        assert!(carried_ratio.stop_condition_warnings(Some(500)).is_empty());
+        assert_eq!(carried_ratio.stop_condition_warnings(Some(1000)).len(), 1);
+        assert!(FakerConfig::default().stop_condition_warnings(None).is_empty());
+    }
+
+    #[test]
+    fn test_round_to_decimals() {
+        assert_eq!(round_to_decimals(119.99873, 1), 120.0);
+        assert_eq!(round_to_decimals(119.94, 1), 119.9);
+        assert_eq!(round_to_decimals(119.99873, 2), 120.0);
+        assert_eq!(round_to_decimals(12.3456, 3), 12.346);
+        assert_eq!(round_to_decimals(12.6, 0), 13.0);
+    }
+
+    #[test]
+    fn test_udp_only_torrent_rejected() {
+        let torrent = udp_torrent(Some(vec![vec!["wss://tracker.example/announce".to_string()]]));
+
+        match RatioFaker::new(torrent, FakerConfig::default()) {
+            Err(FakerError::ConfigError(msg)) => assert_eq!(
+                msg,
+                "udp:// trackers are not yet supported; pick an http(s):// tracker from the announce-list"
+            ),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("udp-only torrent should be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_http_tracker_from_announce_list_used() {
+        let torrent = udp_torrent(Some(vec![
+            vec!["udp://backup.example:6969/announce".to_string()],
+            vec!["https://tracker.example/announce".to_string()],
+        ]));
+
+        let faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        assert_eq!(faker.tracker_url, "https://tracker.example/announce");
+    }
+
+    #[tokio::test]
+    async fn test_numwant_omitted_on_periodic_announce() {
+        let torrent = TorrentInfo::fixture("http://tracker.example/announce");
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+
+        let mut client_config = ClientConfig::get(ClientType::QBittorrent, None);
+        client_config.num_want_policy = NumWantPolicy::StartedOnly;
+        faker.tracker_client = TrackerClient::new(client_config).unwrap();
+
+        let stats = faker.get_stats().await;
+        let tracker_url = "http://tracker.example/announce";
+
+        let request = faker.build_announce_request(&stats, TrackerEvent::None);
+        let url = faker.tracker_client.build_announce_url(tracker_url, &request).unwrap();
+        assert!(!url.contains("numwant="));
+
+        let request = faker.build_announce_request(&stats, TrackerEvent::Started);
+        let url = faker.tracker_client.build_announce_url(tracker_url, &request).unwrap();
+        assert!(url.contains("numwant=50"));
+    }
+
+    #[tokio::test]
+    async fn test_follows_tracker_redirect() {
+        use std::io::{Read, Write};
+
+        let (new_url, requests) = spawn_recording_tracker();
+
+        // Old tracker answers every request with a redirect to the new one, keeping the query
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let old_url = format!("http://{}/announce", listener.local_addr().unwrap());
+        let redirect_base = new_url.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let target = request.lines().next().unwrap_or("").split(' ').nth(1).unwrap_or("");
+                let query = target.split_once('?').map(|(_, q)| q).unwrap_or("");
+                let header = format!(
+                    "HTTP/1.1 302 Found\r\nLocation: {}?{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    redirect_base, query
+                );
+                let _ = stream.write_all(header.as_bytes());
+            }
+        });
+
+        let mut torrent = udp_torrent(None);
+        torrent.announce = old_url;
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        faker.start().await.unwrap();
+        assert_eq!(faker.tracker_url, new_url);
+
+        // Scrapes go straight to the new host
+        let _ = faker.scrape().await;
+        let last = requests.lock().unwrap().last().cloned().unwrap();
+        assert!(last.starts_with("GET /scrape?info_hash="));
+    }
+
+    #[tokio::test]
+    async fn test_apply_scrape_only_updates_swarm() {
+        let torrent = udp_torrent(Some(vec![vec!["http://tracker.example/announce".to_string()]]));
+        let mut faker = RatioFaker::new(torrent, FakerConfig::default()).unwrap();
+        let before = faker.get_stats().await;
+
+        let response = crate::protocol::ScrapeResponse {
+            complete: 12,
+            incomplete: 3,
+            downloaded: 40,
+            name: None,
+        };
+        faker.apply_scrape(&response).await;
+
+        let after = faker.get_stats().await;
+        assert_eq!((after.seeders, after.leechers), (12, 3));
+        assert_eq!(after.uploaded, before.uploaded);
+        assert_eq!(after.announce_count, before.announce_count);
+        assert_eq!(after.next_announce, before.next_announce);
+    }
+
+    #[tokio::test]
+    async fn test_delayed_completed_event() {
+        let (tracker_url, requests) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+        let config = FakerConfig {
+            download_rate: 100.0,
+            completion_percent: 50.0,
+            completion_jitter_secs: 30,
+            ..FakerConfig::default()
+        };
+        let sent_completed = || requests.lock().unwrap().iter().any(|r| r.contains("event=completed"));
+
+        // Finish the download on the next update
+        let finish = |faker: &mut RatioFaker| {
+            faker.last_update = Instant::now() - Duration::from_secs(60);
+        };
+
+        let mut faker = RatioFaker::new(torrent.clone(), config.clone()).unwrap();
+        faker.start().await.unwrap();
+        finish(&mut faker);
+        faker.update().await.unwrap();
+        assert_eq!(faker.get_stats().await.left, 0);
+        assert_eq!(faker.get_stats().await.state, FakerState::Running);
+        assert!(faker.pending_completion.is_some());
+        assert!(!sent_completed());
+
+        // Sent by the first update after the delay
+        faker.pending_completion = Some(Instant::now());
+        faker.update().await.unwrap();
+        assert!(sent_completed());
+        assert!(faker.pending_completion.is_none());
+        assert_eq!(faker.get_stats().await.state, FakerState::Completed);
+
+        // Stopping within the delay drops the completed event
+        requests.lock().unwrap().clear();
+        let mut faker = RatioFaker::new(torrent, config).unwrap();
+        faker.start().await.unwrap();
+        finish(&mut faker);
+        faker.update().await.unwrap();
+        faker.stop().await.unwrap();
+        assert!(faker.pending_completion.is_none());
+        assert!(!sent_completed());
+        assert_eq!(faker.get_stats().await.state, FakerState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_announce_jitter() {
+        let interval = Duration::from_secs(1800);
+        let delays: Vec<Duration> = (0..100)
+            .map(|_| jittered_announce_delay(interval, 5.0, None, None))
+            .collect();
+        assert!(delays
+            .iter()
+            .all(|d| (Duration::from_secs(1710)..=Duration::from_secs(1890)).contains(d)));
+        assert!(delays.iter().any(|d| *d != delays[0]));
+
+        // Off by default, and never below the tracker's min interval or the configured minimum
+        assert_eq!(jittered_announce_delay(interval, 0.0, None, None), interval);
+        assert!((0..100).all(|_| jittered_announce_delay(interval, 50.0, Some(1800), None) >= interval));
+        assert!((0..100).all(|_| jittered_announce_delay(interval, 50.0, None, Some(1800)) >= interval));
+
+        let (tracker_url, _) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+        let config = FakerConfig {
+            announce_jitter_percent: 5.0,
+            ..FakerConfig::default()
+        };
+        let mut faker = RatioFaker::new(torrent, config).unwrap();
+        faker.start().await.unwrap();
+        let stats = faker.get_stats().await;
+        let scheduled = stats.next_announce.unwrap() - stats.last_announce.unwrap();
+        assert!(scheduled >= Duration::from_secs(1709) && scheduled <= Duration::from_secs(1891));
+        assert_eq!(stats.announce_interval_secs, 1800);
+    }
+
+    #[tokio::test]
+    async fn test_numwant_tapers_after_started() {
+        assert_eq!(taper_num_want(200, 50, 1), 125);
+        assert_eq!(taper_num_want(200, 50, 3), 68);
+        assert_eq!(taper_num_want(200, 50, 40), 50);
+        assert_eq!(taper_num_want(30, 50, 1), 50);
+
+        let (tracker_url, requests) = spawn_recording_tracker();
+        let mut torrent = udp_torrent(None);
+        torrent.announce = tracker_url;
+        let config = FakerConfig {
+            num_want: 200,
+            num_want_steady: Some(50),
+            ..FakerConfig::default()
+        };
+        let mut faker = RatioFaker::new(torrent, config).unwrap();
+        let last_numwant = || {
+            let request = requests.lock().unwrap().last().cloned().unwrap();
+            let value = request.split("numwant=").nth(1).unwrap();
+            value.split(['&', ' ']).next().unwrap().parse::<u32>().unwrap()
+        };
+
+        faker.start().await.unwrap();
+        let started = last_numwant();
+        assert_eq!(started, 200);
+
+        let mut previous = started;
+        for _ in 0..3 {
+            let mut stats = faker.get_stats().await;
+            stats.next_announce = Some(Instant::now());
+            faker.replace_stats(stats).await;
+            faker.update().await.unwrap();
+
+            let periodic = last_numwant();
+            assert!(periodic < previous);
+            assert!(periodic >= 50);
+            previous = periodic;
+        }
+    }
 }
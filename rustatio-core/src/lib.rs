@@ -1,3 +1,4 @@
+pub mod clock;
 pub mod config;
 pub mod faker;
 pub mod logger;
@@ -6,7 +7,18 @@ pub mod torrent;
 pub mod validation;
 
 // Re-export main types explicitly to avoid ambiguous Result types
-pub use config::{AppConfig, ClientSettings, ConfigError, FakerSettings, InstanceConfig, UiSettings};
-pub use faker::{FakerConfig, FakerError, FakerState, FakerStats, RatioFaker};
-pub use torrent::{ClientConfig, ClientType, HttpVersion, TorrentError, TorrentFile, TorrentInfo};
+#[cfg(any(test, feature = "test-support"))]
+pub use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use config::{
+    AppConfig, ClientSettings, ConfigError, FakerSettings, InstanceConfig, Profile, UiSettings, DATA_DIR_ENV,
+};
+pub use faker::{
+    round_to_decimals, Curve, FakerConfig, FakerError, FakerState, FakerStats, RatioBasis, RatioFaker,
+    SwarmAggregation, TargetAction, DEFAULT_RATE_DECIMALS,
+};
+pub use torrent::{
+    redact_tracker_url, AnnounceParam, ClientConfig, ClientFingerprint, ClientType, ExtraParam, HttpVersion, KeyStyle,
+    NumWantPolicy, TorrentError, TorrentFile, TorrentInfo, DEFAULT_REQUEST_TIMEOUT_SECS,
+};
 pub use validation::*;
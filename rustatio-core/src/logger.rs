@@ -22,15 +22,14 @@ pub fn set_instance_context(instance_id: Option<u32>) {
     });
 }
 
+/// Get the instance ID set for the current thread, if any
+pub fn instance_context() -> Option<String> {
+    INSTANCE_CONTEXT.with(|ctx| ctx.borrow().clone())
+}
+
 /// Get the current instance context
 fn get_instance_prefix() -> String {
-    INSTANCE_CONTEXT.with(|ctx| {
-        if let Some(ref id) = *ctx.borrow() {
-            format!("[Instance {}] ", id)
-        } else {
-            String::new()
-        }
-    })
+    instance_context().map_or_else(String::new, |id| format!("[Instance {}] ", id))
 }
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "desktop"))]
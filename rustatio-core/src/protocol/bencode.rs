@@ -1,5 +1,6 @@
 use serde::Serialize;
 use std::collections::HashMap;
+use std::ops::Range;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -20,10 +21,79 @@ pub fn parse(data: &[u8]) -> Result<serde_bencode::value::Value> {
 }
 
 /// Encode data to bencode format
+///
+/// Dictionary keys are always written in sorted order, as the bencode spec requires,
+/// so `parse(&encode(&value)?)? == value` for any parsed value.
 pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
     serde_bencode::to_bytes(value).map_err(|e| BencodeError::ParseError(e.to_string()))
 }
 
+/// Find the byte range of a top-level dictionary value by key, without decoding it
+///
+/// The returned range covers the value exactly as it appears in `data`, which is what
+/// the info_hash must be computed over.
+pub fn dict_value_span(data: &[u8], key: &str) -> Result<Range<usize>> {
+    if data.first() != Some(&b'd') {
+        return Err(BencodeError::InvalidStructure("Root is not a dictionary".into()));
+    }
+
+    let mut pos = 1;
+    while data.get(pos) != Some(&b'e') {
+        let (entry_key, value_start) = read_string(data, pos)?;
+        let value_end = skip_value(data, value_start)?;
+        if entry_key == key.as_bytes() {
+            return Ok(value_start..value_end);
+        }
+        pos = value_end;
+    }
+
+    Err(BencodeError::InvalidStructure(format!("Missing key: {}", key)))
+}
+
+/// Read a `<len>:<bytes>` string starting at `pos`, returning its bytes and the offset just past it
+fn read_string(data: &[u8], pos: usize) -> Result<(&[u8], usize)> {
+    let colon = data[pos..]
+        .iter()
+        .position(|&b| b == b':')
+        .map(|i| pos + i)
+        .ok_or_else(|| BencodeError::ParseError(format!("Unterminated string length at byte {}", pos)))?;
+    let len = std::str::from_utf8(&data[pos..colon])
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or_else(|| BencodeError::ParseError(format!("Invalid string length at byte {}", pos)))?;
+    let start = colon + 1;
+    let end = start
+        .checked_add(len)
+        .filter(|&end| end <= data.len())
+        .ok_or_else(|| BencodeError::ParseError(format!("String at byte {} runs past the end of the data", pos)))?;
+    Ok((&data[start..end], end))
+}
+
+/// Skip over the value starting at `pos`, returning the offset just past it
+fn skip_value(data: &[u8], pos: usize) -> Result<usize> {
+    match data.get(pos) {
+        Some(b'i') => data[pos..]
+            .iter()
+            .position(|&b| b == b'e')
+            .map(|i| pos + i + 1)
+            .ok_or_else(|| BencodeError::ParseError(format!("Unterminated integer at byte {}", pos))),
+        Some(b'l') | Some(b'd') => {
+            // Dict keys are strings, so both containers are a run of values up to 'e'
+            let mut next = pos + 1;
+            while data.get(next) != Some(&b'e') {
+                next = skip_value(data, next)?;
+            }
+            Ok(next + 1)
+        }
+        Some(b'0'..=b'9') => read_string(data, pos).map(|(_, end)| end),
+        Some(b) => Err(BencodeError::ParseError(format!(
+            "Unexpected byte '{}' at {}",
+            *b as char, pos
+        ))),
+        None => Err(BencodeError::ParseError("Unexpected end of data".into())),
+    }
+}
+
 /// Extract a string value from a bencode dictionary
 pub fn get_string(dict: &HashMap<Vec<u8>, serde_bencode::value::Value>, key: &str) -> Result<String> {
     dict.get(key.as_bytes())
@@ -57,6 +127,7 @@ pub fn get_bytes(dict: &HashMap<Vec<u8>, serde_bencode::value::Value>, key: &str
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_bencode::value::Value;
 
     #[test]
     fn test_parse_simple_string() {
@@ -77,4 +148,76 @@ mod tests {
             _ => panic!("Expected int"),
         }
     }
+
+    fn bytes(s: &str) -> Value {
+        Value::Bytes(s.as_bytes().to_vec())
+    }
+
+    fn assert_round_trip(value: Value) {
+        let encoded = encode(&value).unwrap();
+        assert_eq!(
+            parse(&encoded).unwrap(),
+            value,
+            "round trip of {:?}",
+            String::from_utf8_lossy(&encoded)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_scalars() {
+        assert_round_trip(Value::Int(0));
+        assert_round_trip(Value::Int(-42));
+        assert_round_trip(Value::Int(i64::MAX));
+        assert_round_trip(Value::Int(i64::MIN));
+        assert_round_trip(bytes(""));
+        assert_round_trip(Value::Bytes(vec![0, 255, b':', b'e']));
+    }
+
+    #[test]
+    fn test_round_trip_nested() {
+        assert_round_trip(Value::List(vec![]));
+        assert_round_trip(Value::List(vec![
+            Value::List(vec![bytes("a"), Value::Int(-1)]),
+            Value::List(vec![Value::List(vec![bytes("")])]),
+        ]));
+
+        let mut inner = HashMap::new();
+        inner.insert(b"path".to_vec(), Value::List(vec![bytes("dir"), bytes("file.txt")]));
+        inner.insert(b"length".to_vec(), Value::Int(1024));
+        let mut outer = HashMap::new();
+        outer.insert(b"files".to_vec(), Value::List(vec![Value::Dict(inner)]));
+        outer.insert(b"".to_vec(), bytes(""));
+        assert_round_trip(Value::Dict(outer));
+        assert_round_trip(Value::Dict(HashMap::new()));
+    }
+
+    #[test]
+    fn test_out_of_order_keys_are_sorted() {
+        let data = b"d4:zeta1:z5:alphai-3e3:mid0:e";
+        let value = parse(data).unwrap();
+        assert_round_trip(value.clone());
+        assert_eq!(encode(&value).unwrap(), b"d5:alphai-3e3:mid0:4:zeta1:ze");
+    }
+
+    #[test]
+    fn test_dict_value_span() {
+        let data = b"d1:ali1ei2ee4:infod3:key5:valueee";
+        assert_eq!(&data[dict_value_span(data, "a").unwrap()], b"li1ei2ee");
+        assert_eq!(&data[dict_value_span(data, "info").unwrap()], b"d3:key5:valuee");
+        assert!(dict_value_span(data, "missing").is_err());
+
+        // Look-alikes inside string values are skipped, not matched
+        let data = b"d1:c6:4:info4:infoi7ee";
+        assert_eq!(&data[dict_value_span(data, "info").unwrap()], b"i7e");
+
+        assert!(dict_value_span(b"li1ee", "info").is_err());
+        assert!(dict_value_span(b"d4:infod1:a", "info").is_err());
+        assert!(dict_value_span(b"d4:info99:abce", "info").is_err());
+    }
+
+    #[test]
+    fn test_canonical_input_is_reencoded_verbatim() {
+        let data = b"d4:infod6:lengthi-7e4:name0:e4:listli1eli2eee1:s3:abce";
+        assert_eq!(encode(&parse(data).unwrap()).unwrap(), data);
+    }
 }
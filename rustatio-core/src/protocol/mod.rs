@@ -3,4 +3,7 @@ pub mod tracker;
 
 // Re-export common types
 pub use bencode::BencodeError;
-pub use tracker::{AnnounceRequest, AnnounceResponse, ScrapeResponse, TrackerClient, TrackerError, TrackerEvent};
+pub use tracker::{
+    AnnounceRequest, AnnounceResponse, ScrapeResponse, Tracker, TrackerClient, TrackerError, TrackerEvent,
+    PROXY_URL_STORAGE_KEY,
+};
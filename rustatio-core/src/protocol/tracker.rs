@@ -1,14 +1,15 @@
 use crate::protocol::bencode;
-use crate::torrent::ClientConfig;
+use crate::torrent::{redact_tracker_url, AnnounceParam, ClientConfig};
 use crate::{log_debug, log_error, log_info, log_trace, log_warn};
 use reqwest;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum TrackerError {
     #[error("HTTP error: {0}")]
-    HttpError(#[from] reqwest::Error),
+    HttpError(reqwest::Error),
     #[error("Bencode error: {0}")]
     BencodeError(#[from] crate::protocol::bencode::BencodeError),
     #[error("Tracker returned error: {0}")]
@@ -19,8 +20,18 @@ pub enum TrackerError {
     UrlError(#[from] url::ParseError),
 }
 
+impl From<reqwest::Error> for TrackerError {
+    /// Strip the request URL so passkeys never end up in error messages
+    fn from(e: reqwest::Error) -> Self {
+        TrackerError::HttpError(e.without_url())
+    }
+}
+
 pub type Result<T> = std::result::Result<T, TrackerError>;
 
+/// `localStorage` key holding the tracker proxy URL (WASM builds only)
+pub const PROXY_URL_STORAGE_KEY: &str = "rustatio-proxy-url";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TrackerEvent {
     Started,
@@ -55,6 +66,8 @@ pub struct AnnounceRequest {
     pub numwant: Option<u32>,
     pub key: Option<String>,
     pub tracker_id: Option<String>,
+    /// Custom `(name, value)` parameters appended after the client's own, percent-encoded
+    pub extra_params: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +92,10 @@ pub struct AnnounceResponse {
     /// Warning message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub warning: Option<String>,
+
+    /// Announce URL the tracker redirected this announce to, without the announce parameters
+    #[serde(skip)]
+    pub redirected_to: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +106,67 @@ pub struct ScrapeResponse {
     pub name: Option<String>,
 }
 
+/// Whether a response came from another URL than the one requested. Normalization alone (host case,
+/// default port, ...) is not a redirect.
+#[cfg(not(target_arch = "wasm32"))]
+fn was_redirected(requested: &str, response_url: &url::Url) -> bool {
+    url::Url::parse(requested).is_ok_and(|requested| &requested != response_url)
+}
+
+/// Announce URL to use after a redirect: the final URL without the parameters we appended.
+/// If the redirect rewrote them, only our parameters are dropped and the target's own (e.g. a passkey) are kept.
+#[cfg(not(target_arch = "wasm32"))]
+fn redirected_announce_url(final_url: &url::Url, query: &str) -> String {
+    let final_url = final_url.as_str();
+    if let Some(base) = final_url.strip_suffix(query) {
+        return base.trim_end_matches(['?', '&']).to_string();
+    }
+
+    let Some((base, target_query)) = final_url.split_once('?') else {
+        return final_url.to_string();
+    };
+    let key = |param: &str| param.split('=').next().unwrap_or(param).to_string();
+    let ours: Vec<String> = query.split('&').map(key).collect();
+    let kept: Vec<&str> = target_query
+        .split('&')
+        .filter(|param| !param.is_empty() && !ours.contains(&key(param)))
+        .collect();
+    if kept.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}?{}", base, kept.join("&"))
+    }
+}
+
+/// What the faker needs from a tracker: announce and scrape for the emulated client.
+///
+/// Implemented by [`TrackerClient`]; tests substitute a scripted tracker to drive the faker without network.
+#[allow(async_fn_in_trait)]
+pub trait Tracker {
+    /// Client configuration the requests are built for
+    fn client_config(&self) -> &ClientConfig;
+
+    /// Send an announce request to the tracker
+    async fn announce(&self, tracker_url: &str, request: &AnnounceRequest) -> Result<AnnounceResponse>;
+
+    /// Send a scrape request to the tracker
+    async fn scrape(&self, tracker_url: &str, info_hash: &[u8; 20]) -> Result<ScrapeResponse>;
+
+    /// Scrape several torrents on the same tracker, one request per torrent unless overridden.
+    /// Torrents the tracker doesn't report are missing from the result.
+    async fn scrape_many(
+        &self,
+        tracker_url: &str,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<HashMap<[u8; 20], ScrapeResponse>> {
+        let mut responses = HashMap::with_capacity(info_hashes.len());
+        for info_hash in info_hashes {
+            responses.insert(*info_hash, self.scrape(tracker_url, info_hash).await?);
+        }
+        Ok(responses)
+    }
+}
+
 pub struct TrackerClient {
     client: reqwest::Client,
     client_config: ClientConfig,
@@ -101,10 +179,11 @@ impl TrackerClient {
         #[cfg(not(target_arch = "wasm32"))]
         let client = reqwest::Client::builder()
             .user_agent(&client_config.user_agent)
-            .timeout(std::time::Duration::from_secs(30))
+            .timeout(std::time::Duration::from_secs(client_config.request_timeout_secs))
             .gzip(true)
             .build()?;
 
+        // The browser fetch backend has no timeout builder, request_timeout_secs is ignored
         #[cfg(target_arch = "wasm32")]
         let client = reqwest::Client::builder()
             .user_agent(&client_config.user_agent)
@@ -113,6 +192,11 @@ impl TrackerClient {
         Ok(TrackerClient { client, client_config })
     }
 
+    /// Get the client configuration this tracker client emulates
+    pub fn client_config(&self) -> &ClientConfig {
+        &self.client_config
+    }
+
     /// Send an announce request to the tracker
     pub async fn announce(&self, tracker_url: &str, request: &AnnounceRequest) -> Result<AnnounceResponse> {
         let announce_url = self.build_announce_url(tracker_url, request)?;
@@ -122,7 +206,7 @@ impl TrackerClient {
         let final_url = {
             if let Some(window) = web_sys::window() {
                 if let Ok(Some(storage)) = window.local_storage() {
-                    if let Ok(Some(proxy)) = storage.get_item("rustatio-proxy-url") {
+                    if let Ok(Some(proxy)) = storage.get_item(PROXY_URL_STORAGE_KEY) {
                         if !proxy.is_empty() {
                             // Encode the announce URL and prepend proxy
                             let encoded = percent_encoding::utf8_percent_encode(
@@ -148,8 +232,8 @@ impl TrackerClient {
         #[cfg(not(target_arch = "wasm32"))]
         let final_url = announce_url.clone();
 
-        log_info!("Announcing to tracker: {}", tracker_url);
-        log_debug!("Full announce URL: {}", final_url);
+        log_info!("Announcing to tracker: {}", redact_tracker_url(tracker_url));
+        log_debug!("Full announce URL: {}", redact_tracker_url(&announce_url));
 
         let response = self.client.get(&final_url).send().await?;
 
@@ -158,75 +242,112 @@ impl TrackerClient {
 
         if !status.is_success() {
             log_error!("Tracker request failed with status: {}", status);
-            return Err(TrackerError::HttpError(response.error_for_status().unwrap_err()));
+            return Err(response.error_for_status().unwrap_err().into());
         }
 
+        // Redirects are followed transparently, remember where we ended up (not behind the WASM proxy)
+        #[cfg(not(target_arch = "wasm32"))]
+        let redirected_to = was_redirected(&final_url, response.url()).then(|| {
+            let query = &announce_url[tracker_url.len() + 1..];
+            redirected_announce_url(response.url(), query)
+        });
+        #[cfg(target_arch = "wasm32")]
+        let redirected_to = None;
+
         let body = response.bytes().await?;
         log_debug!("Tracker response: {} bytes", body.len());
         log_trace!("Response body (hex): {:02X?}", &body[..body.len().min(100)]);
 
-        self.parse_announce_response(&body)
+        let mut parsed = self.parse_announce_response(&body)?;
+        parsed.redirected_to = redirected_to;
+        Ok(parsed)
     }
 
     /// Send a scrape request to the tracker
     pub async fn scrape(&self, tracker_url: &str, info_hash: &[u8; 20]) -> Result<ScrapeResponse> {
         let scrape_url = self.build_scrape_url(tracker_url, info_hash)?;
 
-        log_info!("Scraping tracker: {}", scrape_url);
+        log_info!("Scraping tracker: {}", redact_tracker_url(&scrape_url));
 
         let response = self.client.get(&scrape_url).send().await?;
 
         if !response.status().is_success() {
-            return Err(TrackerError::HttpError(response.error_for_status().unwrap_err()));
+            return Err(response.error_for_status().unwrap_err().into());
         }
 
         let body = response.bytes().await?;
         self.parse_scrape_response(&body, info_hash)
     }
 
-    /// Build announce URL with all parameters
-    fn build_announce_url(&self, tracker_url: &str, request: &AnnounceRequest) -> Result<String> {
-        // Build query parameters manually since info_hash needs special encoding
-        let info_hash_encoded: String = request.info_hash.iter().map(|b| format!("%{:02X}", b)).collect();
+    /// Scrape several torrents on the same tracker in one request (multiple `info_hash` parameters).
+    /// Torrents the tracker doesn't report are missing from the result.
+    pub async fn scrape_many(
+        &self,
+        tracker_url: &str,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<HashMap<[u8; 20], ScrapeResponse>> {
+        if info_hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
 
-        let mut params = vec![
-            format!("info_hash={}", info_hash_encoded),
-            format!("peer_id={}", request.peer_id),
-            format!("port={}", request.port),
-            format!("uploaded={}", request.uploaded),
-            format!("downloaded={}", request.downloaded),
-            format!("left={}", request.left),
-            format!("compact={}", if request.compact { "1" } else { "0" }),
-        ];
+        let scrape_url = self.build_scrape_url_many(tracker_url, info_hashes)?;
 
-        if request.no_peer_id {
-            params.push("no_peer_id=1".to_string());
-        }
+        log_info!(
+            "Scraping {} torrents from tracker: {}",
+            info_hashes.len(),
+            redact_tracker_url(tracker_url)
+        );
 
-        if let Some(event) = request.event.as_str() {
-            params.push(format!("event={}", event));
-        }
+        let response = self.client.get(&scrape_url).send().await?;
 
-        if let Some(ref ip) = request.ip {
-            params.push(format!("ip={}", ip));
+        if !response.status().is_success() {
+            return Err(response.error_for_status().unwrap_err().into());
         }
 
-        if let Some(numwant) = request.numwant {
-            params.push(format!("numwant={}", numwant));
-        }
+        let body = response.bytes().await?;
+        let mut responses = self.parse_scrape_files(&body)?;
+        responses.retain(|hash, _| info_hashes.contains(hash));
+        Ok(responses)
+    }
 
-        if let Some(ref key) = request.key {
-            params.push(format!("key={}", key));
-        }
+    /// Build announce URL with all parameters
+    pub(crate) fn build_announce_url(&self, tracker_url: &str, request: &AnnounceRequest) -> Result<String> {
+        // Build query parameters manually since info_hash needs special encoding
+        let info_hash_encoded: String = request.info_hash.iter().map(|b| format!("%{:02X}", b)).collect();
 
-        if let Some(ref tracker_id) = request.tracker_id {
-            params.push(format!("trackerid={}", tracker_id));
+        // Follow the emulated client's parameter order so the query matches its real layout
+        let mut params = Vec::with_capacity(self.client_config.announce_params.len());
+        for param in &self.client_config.announce_params {
+            let value = match param {
+                AnnounceParam::InfoHash => Some(format!("info_hash={}", info_hash_encoded)),
+                AnnounceParam::PeerId => Some(format!("peer_id={}", request.peer_id)),
+                AnnounceParam::Port => Some(format!("port={}", request.port)),
+                AnnounceParam::Uploaded => Some(format!("uploaded={}", request.uploaded)),
+                AnnounceParam::Downloaded => Some(format!("downloaded={}", request.downloaded)),
+                AnnounceParam::Left => Some(format!("left={}", request.left)),
+                AnnounceParam::Compact => Some(format!("compact={}", if request.compact { "1" } else { "0" })),
+                AnnounceParam::NoPeerId => request.no_peer_id.then(|| "no_peer_id=1".to_string()),
+                AnnounceParam::Event => request.event.as_str().map(|event| format!("event={}", event)),
+                AnnounceParam::Ip => request.ip.as_ref().map(|ip| format!("ip={}", ip)),
+                AnnounceParam::NumWant => request.numwant.map(|numwant| format!("numwant={}", numwant)),
+                AnnounceParam::Key => request.key.as_ref().map(|key| format!("key={}", key)),
+                AnnounceParam::TrackerId => request.tracker_id.as_ref().map(|id| format!("trackerid={}", id)),
+                AnnounceParam::SupportCrypto => self
+                    .client_config
+                    .supports_crypto
+                    .then(|| "supportcrypto=1".to_string()),
+                AnnounceParam::Extra(extra) => Some(format!("{}={}", extra.name, extra.value)),
+            };
+            params.extend(value);
         }
 
-        // Add client-specific parameters
-        if self.client_config.supports_crypto {
-            params.push("supportcrypto=1".to_string());
-        }
+        let encode = |s: &str| url::form_urlencoded::byte_serialize(s.as_bytes()).collect::<String>();
+        params.extend(
+            request
+                .extra_params
+                .iter()
+                .map(|(name, value)| format!("{}={}", encode(name), encode(value))),
+        );
 
         let query_string = params.join("&");
         let separator = if tracker_url.contains('?') { '&' } else { '?' };
@@ -236,15 +357,26 @@ impl TrackerClient {
 
     /// Build scrape URL from announce URL
     fn build_scrape_url(&self, tracker_url: &str, info_hash: &[u8; 20]) -> Result<String> {
+        self.build_scrape_url_many(tracker_url, std::slice::from_ref(info_hash))
+    }
+
+    /// Build scrape URL from announce URL with one `info_hash` parameter per torrent
+    fn build_scrape_url_many(&self, tracker_url: &str, info_hashes: &[[u8; 20]]) -> Result<String> {
         // Convert announce URL to scrape URL
         let scrape_url = tracker_url.replace("/announce", "/scrape");
 
         // URL encode info_hash (same format as announce)
-        let info_hash_encoded: String = info_hash.iter().map(|b| format!("%{:02X}", b)).collect();
+        let params: Vec<String> = info_hashes
+            .iter()
+            .map(|hash| {
+                let encoded: String = hash.iter().map(|b| format!("%{:02X}", b)).collect();
+                format!("info_hash={}", encoded)
+            })
+            .collect();
 
-        // Build URL with query parameter
+        // Build URL with query parameters
         let separator = if scrape_url.contains('?') { '&' } else { '?' };
-        Ok(format!("{}{}info_hash={}", scrape_url, separator, info_hash_encoded))
+        Ok(format!("{}{}{}", scrape_url, separator, params.join("&")))
     }
 
     /// Parse announce response from bencoded data
@@ -320,11 +452,19 @@ impl TrackerClient {
             complete,
             incomplete,
             warning,
+            redirected_to: None,
         })
     }
 
     /// Parse scrape response from bencoded data
     fn parse_scrape_response(&self, data: &[u8], info_hash: &[u8; 20]) -> Result<ScrapeResponse> {
+        self.parse_scrape_files(data)?
+            .remove(info_hash)
+            .ok_or_else(|| TrackerError::InvalidResponse("Torrent not found in scrape response".into()))
+    }
+
+    /// Parse every torrent entry of a scrape response's `files` dictionary
+    fn parse_scrape_files(&self, data: &[u8]) -> Result<HashMap<[u8; 20], ScrapeResponse>> {
         let value = match bencode::parse(data) {
             Ok(v) => v,
             Err(_) => {
@@ -353,29 +493,48 @@ impl TrackerClient {
             })
             .ok_or_else(|| TrackerError::InvalidResponse("Missing 'files' in scrape response".into()))?;
 
-        // Find our torrent's stats (the key is the raw info_hash bytes)
-        let stats = files
-            .get(info_hash.as_ref())
-            .and_then(|v| match v {
-                serde_bencode::value::Value::Dict(d) => Some(d),
+        // Keys are the raw info_hash bytes, anything else isn't a torrent entry. A malformed entry is
+        // skipped so it doesn't hide the others.
+        let mut responses = HashMap::with_capacity(files.len());
+        for (key, value) in files {
+            let Ok(info_hash) = <[u8; 20]>::try_from(key.as_slice()) else {
+                continue;
+            };
+            let serde_bencode::value::Value::Dict(stats) = value else {
+                continue;
+            };
+
+            let counts = bencode::get_int(stats, "complete").and_then(|complete| {
+                Ok((
+                    complete,
+                    bencode::get_int(stats, "incomplete")?,
+                    bencode::get_int(stats, "downloaded")?,
+                ))
+            });
+            let (complete, incomplete, downloaded) = match counts {
+                Ok(counts) => counts,
+                Err(e) => {
+                    log_warn!("Skipping malformed scrape entry: {}", e);
+                    continue;
+                }
+            };
+            let name = stats.get(b"name".as_ref()).and_then(|v| match v {
+                serde_bencode::value::Value::Bytes(b) => Some(String::from_utf8_lossy(b).to_string()),
                 _ => None,
-            })
-            .ok_or_else(|| TrackerError::InvalidResponse("Torrent not found in scrape response".into()))?;
-
-        let complete = bencode::get_int(stats, "complete")?;
-        let incomplete = bencode::get_int(stats, "incomplete")?;
-        let downloaded = bencode::get_int(stats, "downloaded")?;
-        let name = stats.get(b"name".as_ref()).and_then(|v| match v {
-            serde_bencode::value::Value::Bytes(b) => Some(String::from_utf8_lossy(b).to_string()),
-            _ => None,
-        });
+            });
+
+            responses.insert(
+                info_hash,
+                ScrapeResponse {
+                    complete,
+                    incomplete,
+                    downloaded,
+                    name,
+                },
+            );
+        }
 
-        Ok(ScrapeResponse {
-            complete,
-            incomplete,
-            downloaded,
-            name,
-        })
+        Ok(responses)
     }
 
     /// Format a preview of the response data for error messages
@@ -435,3 +594,230 @@ impl TrackerClient {
         }
     }
 }
+
+impl Tracker for TrackerClient {
+    fn client_config(&self) -> &ClientConfig {
+        TrackerClient::client_config(self)
+    }
+
+    async fn announce(&self, tracker_url: &str, request: &AnnounceRequest) -> Result<AnnounceResponse> {
+        TrackerClient::announce(self, tracker_url, request).await
+    }
+
+    async fn scrape(&self, tracker_url: &str, info_hash: &[u8; 20]) -> Result<ScrapeResponse> {
+        TrackerClient::scrape(self, tracker_url, info_hash).await
+    }
+
+    async fn scrape_many(
+        &self,
+        tracker_url: &str,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<HashMap<[u8; 20], ScrapeResponse>> {
+        TrackerClient::scrape_many(self, tracker_url, info_hashes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torrent::ClientType;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_request_timeout_on_slow_tracker() {
+        // Tracker that accepts the request but takes far longer than the timeout to answer
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let _ = stream.read(&mut [0u8; 1024]);
+                std::thread::sleep(Duration::from_secs(5));
+            }
+        });
+
+        let mut client_config = ClientConfig::get(ClientType::Transmission, None);
+        client_config.request_timeout_secs = 1;
+        let client = TrackerClient::new(client_config).unwrap();
+
+        let started = Instant::now();
+        let result = client.scrape(&format!("http://{}/announce", addr), &[0u8; 20]).await;
+
+        assert!(matches!(result, Err(TrackerError::HttpError(ref e)) if e.is_timeout()));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_redirected_announce_url() {
+        let query = "info_hash=%01&port=6881";
+        let url = |s: &str| url::Url::parse(s).unwrap();
+
+        assert_eq!(
+            redirected_announce_url(&url("http://new.example/announce?info_hash=%01&port=6881"), query),
+            "http://new.example/announce"
+        );
+        // Passkey in the announce URL is kept
+        assert_eq!(
+            redirected_announce_url(&url("http://new.example/ann?pk=abc&info_hash=%01&port=6881"), query),
+            "http://new.example/ann?pk=abc"
+        );
+        // Parameters dropped or rewritten by the redirect
+        assert_eq!(
+            redirected_announce_url(&url("https://new.example/announce"), query),
+            "https://new.example/announce"
+        );
+        assert_eq!(
+            redirected_announce_url(&url("https://new.example/announce?port=6881"), query),
+            "https://new.example/announce"
+        );
+        // The target's own parameters survive a rewritten query
+        assert_eq!(
+            redirected_announce_url(&url("https://new.example/announce?passkey=abc&port=1"), query),
+            "https://new.example/announce?passkey=abc"
+        );
+    }
+
+    #[test]
+    fn test_was_redirected() {
+        let url = |s: &str| url::Url::parse(s).unwrap();
+        let requested = "HTTP://Tracker.Example:80/announce?info_hash=%01";
+
+        assert!(!was_redirected(requested, &url(requested)));
+        assert!(!was_redirected(
+            requested,
+            &url("http://tracker.example/announce?info_hash=%01")
+        ));
+        assert!(was_redirected(
+            requested,
+            &url("http://other.example/announce?info_hash=%01")
+        ));
+    }
+
+    fn snapshot_request(event: TrackerEvent, numwant: Option<u32>) -> AnnounceRequest {
+        AnnounceRequest {
+            info_hash: [
+                0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x12,
+                0x34, 0x56, 0x78,
+            ],
+            peer_id: "PEERID".to_string(),
+            port: 6881,
+            uploaded: 1024,
+            downloaded: 0,
+            left: 0,
+            compact: true,
+            no_peer_id: true,
+            event,
+            ip: None,
+            numwant,
+            key: Some("1A2B3C4D".to_string()),
+            tracker_id: None,
+            extra_params: Vec::new(),
+        }
+    }
+
+    fn announce_url(client_type: ClientType, request: &AnnounceRequest) -> String {
+        let client = TrackerClient::new(ClientConfig::get(client_type, None)).unwrap();
+        let url = client
+            .build_announce_url("http://tracker.example/announce.php?passkey=abc", request)
+            .unwrap();
+        // Compare the query only, peer IDs are covered by the client tests
+        url.split_once("passkey=abc&").unwrap().1.to_string()
+    }
+
+    const INFO_HASH: &str = "%12%34%56%78%9A%BC%DE%F0%12%34%56%78%9A%BC%DE%F0%12%34%56%78";
+
+    #[test]
+    fn test_qbittorrent_announce_layout() {
+        // Captured from qBittorrent 5.1 (libtorrent 2.0)
+        let url = announce_url(
+            ClientType::QBittorrent,
+            &snapshot_request(TrackerEvent::Started, Some(200)),
+        );
+        assert_eq!(
+            url,
+            format!(
+                "info_hash={}&peer_id=PEERID&port=6881&uploaded=1024&downloaded=0&left=0&corrupt=0&key=1A2B3C4D\
+                 &event=started&numwant=200&compact=1&no_peer_id=1&supportcrypto=1&redundant=0",
+                INFO_HASH
+            )
+        );
+
+        // Regular announces omit the event
+        let url = announce_url(
+            ClientType::QBittorrent,
+            &snapshot_request(TrackerEvent::None, Some(200)),
+        );
+        assert!(url.contains("&key=1A2B3C4D&numwant=200&compact=1"));
+    }
+
+    #[test]
+    fn test_transmission_announce_layout() {
+        // Captured from Transmission 4.0
+        let url = announce_url(
+            ClientType::Transmission,
+            &snapshot_request(TrackerEvent::Started, Some(80)),
+        );
+        assert_eq!(
+            url,
+            format!(
+                "info_hash={}&peer_id=PEERID&port=6881&uploaded=1024&downloaded=0&left=0&numwant=80&key=1A2B3C4D\
+                 &compact=1&supportcrypto=1&event=started",
+                INFO_HASH
+            )
+        );
+    }
+
+    #[test]
+    fn test_extra_announce_params() {
+        let mut request = snapshot_request(TrackerEvent::Started, Some(80));
+        request.extra_params = vec![
+            ("uid".to_string(), "42".to_string()),
+            ("auth".to_string(), "a b&c=d".to_string()),
+        ];
+        let url = announce_url(ClientType::Transmission, &request);
+        assert!(url.ends_with("&event=started&uid=42&auth=a+b%26c%3Dd"));
+    }
+
+    #[test]
+    fn test_parse_multi_file_scrape() {
+        let client = TrackerClient::new(ClientConfig::get(ClientType::Transmission, None)).unwrap();
+        let (first, second) = ([1u8; 20], [2u8; 20]);
+
+        let url = client
+            .build_scrape_url_many("http://tracker.example/announce?passkey=abc", &[first, second])
+            .unwrap();
+        let first_param = format!("info_hash={}", "%01".repeat(20));
+        let second_param = format!("info_hash={}", "%02".repeat(20));
+        assert_eq!(
+            url,
+            format!(
+                "http://tracker.example/scrape?passkey=abc&{}&{}",
+                first_param, second_param
+            )
+        );
+
+        let mut body = b"d5:filesd20:".to_vec();
+        body.extend_from_slice(&first);
+        body.extend_from_slice(b"d8:completei5e10:downloadedi50e10:incompletei2e4:name5:firste20:");
+        body.extend_from_slice(&second);
+        body.extend_from_slice(b"d8:completei0e10:downloadedi3e10:incompletei7ee20:");
+        // A malformed entry is skipped without failing the others
+        body.extend_from_slice(&[3u8; 20]);
+        body.extend_from_slice(b"d8:complete3:badee");
+        body.extend_from_slice(b"ee");
+
+        let responses = client.parse_scrape_files(&body).unwrap();
+        assert_eq!(responses.len(), 2);
+        let stats = &responses[&first];
+        assert_eq!((stats.complete, stats.incomplete, stats.downloaded), (5, 2, 50));
+        assert_eq!(stats.name.as_deref(), Some("first"));
+        let stats = &responses[&second];
+        assert_eq!((stats.complete, stats.incomplete, stats.downloaded), (0, 7, 3));
+        assert!(stats.name.is_none());
+
+        // Single-torrent scrapes read their entry from the same dictionary
+        assert_eq!(client.parse_scrape_response(&body, &second).unwrap().incomplete, 7);
+        assert!(client.parse_scrape_response(&body, &[3u8; 20]).is_err());
+    }
+}
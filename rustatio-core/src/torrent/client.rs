@@ -1,3 +1,6 @@
+use crate::log_warn;
+use crate::protocol::TrackerEvent;
+use crate::validation::{validate_peer_id_prefix, validate_user_agent, ValidationError};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +14,43 @@ pub enum ClientType {
     Transmission,
     #[serde(rename = "deluge")]
     Deluge,
+    #[serde(rename = "biglybt")]
+    BiglyBT,
+}
+
+impl ClientType {
+    /// Every emulated client
+    pub fn all() -> [ClientType; 5] {
+        [
+            ClientType::UTorrent,
+            ClientType::QBittorrent,
+            ClientType::Transmission,
+            ClientType::Deluge,
+            ClientType::BiglyBT,
+        ]
+    }
+
+    /// Id used in configs, the CLI and the API (e.g. "qbittorrent")
+    pub fn id(&self) -> &'static str {
+        match self {
+            ClientType::UTorrent => "utorrent",
+            ClientType::QBittorrent => "qbittorrent",
+            ClientType::Transmission => "transmission",
+            ClientType::Deluge => "deluge",
+            ClientType::BiglyBT => "biglybt",
+        }
+    }
+
+    /// Human-readable client name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientType::UTorrent => "uTorrent",
+            ClientType::QBittorrent => "qBittorrent",
+            ClientType::Transmission => "Transmission",
+            ClientType::Deluge => "Deluge",
+            ClientType::BiglyBT => "BiglyBT",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -21,27 +61,233 @@ pub struct ClientConfig {
     pub user_agent: String,
     pub http_version: HttpVersion,
     pub num_want: u32,
+    pub num_want_policy: NumWantPolicy,
     pub supports_compact: bool,
     pub supports_crypto: bool,
+    pub key_style: KeyStyle,
+    /// Announce query parameters in the order this client sends them
+    pub announce_params: Vec<AnnounceParam>,
+    /// Tracker request timeout in seconds (ignored on WASM, which has no timeout support)
+    pub request_timeout_secs: u64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Length of a BitTorrent peer ID in bytes
+const PEER_ID_LEN: usize = 20;
+
+/// Default tracker request timeout in seconds
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum HttpVersion {
+    #[serde(rename = "HTTP/1.0")]
     Http10,
+    #[serde(rename = "HTTP/1.1")]
     Http11,
 }
 
+/// Format of the `key` announce parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStyle {
+    /// 8 uppercase hex characters (libtorrent/uTorrent/Transmission)
+    Hex,
+    /// 8 alphanumeric characters (Azureus/Vuze/BiglyBT)
+    Alphanumeric,
+}
+
+/// A parameter of the announce query string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnounceParam {
+    InfoHash,
+    PeerId,
+    Port,
+    Uploaded,
+    Downloaded,
+    Left,
+    Compact,
+    /// Sent only when the request asks for it
+    NoPeerId,
+    /// Sent only for started/stopped/completed announces
+    Event,
+    Ip,
+    NumWant,
+    Key,
+    TrackerId,
+    /// Sent only when the client supports encryption
+    SupportCrypto,
+    /// Fixed client-specific parameter (e.g. libtorrent's `corrupt=0`)
+    Extra(ExtraParam),
+}
+
+/// Fixed `name=value` pair a client always sends
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraParam {
+    pub name: String,
+    pub value: String,
+}
+
+impl AnnounceParam {
+    /// Query parameter name as sent to the tracker
+    pub fn name(&self) -> &str {
+        match self {
+            Self::InfoHash => "info_hash",
+            Self::PeerId => "peer_id",
+            Self::Port => "port",
+            Self::Uploaded => "uploaded",
+            Self::Downloaded => "downloaded",
+            Self::Left => "left",
+            Self::Compact => "compact",
+            Self::NoPeerId => "no_peer_id",
+            Self::Event => "event",
+            Self::Ip => "ip",
+            Self::NumWant => "numwant",
+            Self::Key => "key",
+            Self::TrackerId => "trackerid",
+            Self::SupportCrypto => "supportcrypto",
+            Self::Extra(extra) => &extra.name,
+        }
+    }
+
+    fn extra(name: &str, value: &str) -> Self {
+        AnnounceParam::Extra(ExtraParam {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// libtorrent order, shared by qBittorrent and Deluge
+    fn libtorrent_order() -> Vec<Self> {
+        vec![
+            Self::InfoHash,
+            Self::PeerId,
+            Self::Port,
+            Self::Uploaded,
+            Self::Downloaded,
+            Self::Left,
+            Self::extra("corrupt", "0"),
+            Self::Key,
+            Self::Event,
+            Self::NumWant,
+            Self::Compact,
+            Self::NoPeerId,
+            Self::SupportCrypto,
+            Self::extra("redundant", "0"),
+            Self::TrackerId,
+            Self::Ip,
+        ]
+    }
+
+    /// uTorrent order (libtorrent-like, without encryption or redundant hints)
+    fn utorrent_order() -> Vec<Self> {
+        vec![
+            Self::InfoHash,
+            Self::PeerId,
+            Self::Port,
+            Self::Uploaded,
+            Self::Downloaded,
+            Self::Left,
+            Self::extra("corrupt", "0"),
+            Self::Key,
+            Self::Event,
+            Self::NumWant,
+            Self::Compact,
+            Self::NoPeerId,
+            Self::TrackerId,
+            Self::Ip,
+        ]
+    }
+
+    /// Transmission order: numwant and key before compact, event near the end
+    fn transmission_order() -> Vec<Self> {
+        vec![
+            Self::InfoHash,
+            Self::PeerId,
+            Self::Port,
+            Self::Uploaded,
+            Self::Downloaded,
+            Self::Left,
+            Self::NumWant,
+            Self::Key,
+            Self::Compact,
+            Self::SupportCrypto,
+            Self::Event,
+            Self::TrackerId,
+            Self::Ip,
+        ]
+    }
+
+    /// Azureus/BiglyBT order: supportcrypto right after the peer ID
+    fn azureus_order() -> Vec<Self> {
+        vec![
+            Self::InfoHash,
+            Self::PeerId,
+            Self::SupportCrypto,
+            Self::Port,
+            Self::Uploaded,
+            Self::Downloaded,
+            Self::Left,
+            Self::extra("corrupt", "0"),
+            Self::Event,
+            Self::NumWant,
+            Self::NoPeerId,
+            Self::Compact,
+            Self::Key,
+            Self::extra("azver", "3"),
+            Self::TrackerId,
+            Self::Ip,
+        ]
+    }
+}
+
+/// Which announces include the `numwant` parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumWantPolicy {
+    /// Send `numwant` on every announce
+    Always,
+    /// Send `numwant` only on `started`, letting the tracker decide on later announces
+    StartedOnly,
+}
+
 impl ClientConfig {
-    /// Get configuration for a specific client
+    /// Get configuration for a specific client.
+    /// A version not in [`ClientConfig::available_versions`] is ignored with a warning, using the default.
     pub fn get(client_type: ClientType, version: Option<String>) -> Self {
+        let version = version.filter(|version| {
+            let known = Self::available_versions(&client_type).contains(version);
+            if !known {
+                log_warn!(
+                    "Unknown {} version '{}', using the default version",
+                    client_type.name(),
+                    version
+                );
+            }
+            known
+        });
+
         match client_type {
             ClientType::UTorrent => Self::utorrent(version),
             ClientType::QBittorrent => Self::qbittorrent(version),
             ClientType::Transmission => Self::transmission(version),
             ClientType::Deluge => Self::deluge(version),
+            ClientType::BiglyBT => Self::biglybt(version),
         }
     }
 
+    /// Real releases of a client that can be emulated, newest first
+    pub fn available_versions(client_type: &ClientType) -> Vec<String> {
+        let versions: &[&str] = match client_type {
+            ClientType::UTorrent => &["3.5.5", "3.5.4", "3.5.3", "3.5.0", "3.4.9", "3.4.8", "2.2.1"],
+            ClientType::QBittorrent => &[
+                "5.1.4", "5.1.3", "5.1.2", "5.0.5", "5.0.2", "4.6.7", "4.6.5", "4.5.5", "4.5.0", "4.4.5",
+            ],
+            ClientType::Transmission => &["4.0.6", "4.0.5", "4.0.4", "4.0.3", "3.00", "2.94", "2.93"],
+            ClientType::Deluge => &["2.1.1", "2.1.0", "2.0.5", "2.0.3"],
+            ClientType::BiglyBT => &["3.6.0.0", "3.5.0.0", "3.4.0.0"],
+        };
+        versions.iter().map(|v| v.to_string()).collect()
+    }
+
     /// uTorrent client configuration
     fn utorrent(version: Option<String>) -> Self {
         let version = version.unwrap_or_else(|| "3.5.5".to_string());
@@ -57,8 +303,12 @@ impl ClientConfig {
             user_agent: format!("uTorrent/{}", version),
             http_version: HttpVersion::Http11,
             num_want: 200,
+            num_want_policy: NumWantPolicy::Always,
             supports_compact: true,
             supports_crypto: true,
+            key_style: KeyStyle::Hex,
+            announce_params: AnnounceParam::utorrent_order(),
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
         }
     }
 
@@ -82,8 +332,12 @@ impl ClientConfig {
             user_agent: format!("qBittorrent/{}", version),
             http_version: HttpVersion::Http11,
             num_want: 200,
+            num_want_policy: NumWantPolicy::Always,
             supports_compact: true,
             supports_crypto: true,
+            key_style: KeyStyle::Hex,
+            announce_params: AnnounceParam::libtorrent_order(),
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
         }
     }
 
@@ -107,8 +361,12 @@ impl ClientConfig {
             user_agent: format!("Transmission/{}", version),
             http_version: HttpVersion::Http11,
             num_want: 80,
+            num_want_policy: NumWantPolicy::Always,
             supports_compact: true,
             supports_crypto: true,
+            key_style: KeyStyle::Hex,
+            announce_params: AnnounceParam::transmission_order(),
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
         }
     }
 
@@ -132,15 +390,73 @@ impl ClientConfig {
             user_agent: format!("Deluge/{}", version),
             http_version: HttpVersion::Http11,
             num_want: 200,
+            num_want_policy: NumWantPolicy::Always,
             supports_compact: true,
             supports_crypto: true,
+            key_style: KeyStyle::Hex,
+            announce_params: AnnounceParam::libtorrent_order(),
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
         }
     }
 
+    /// BiglyBT client configuration (Azureus-style peer ID, shared with Vuze)
+    fn biglybt(version: Option<String>) -> Self {
+        let version = version.unwrap_or_else(|| "3.6.0.0".to_string());
+        let parts: Vec<&str> = version.split('.').collect();
+        let version_code = if parts.len() >= 4 {
+            format!("{}{}{}{}", parts[0], parts[1], parts[2], parts[3])
+        } else {
+            "3600".to_string()
+        };
+
+        // Pad to exactly 4 characters
+        let padded_version = version_code.pad_to_width_with_char(4, '0');
+
+        ClientConfig {
+            client_type: ClientType::BiglyBT,
+            version: version.clone(),
+            peer_id_prefix: format!("-AZ{}-", padded_version),
+            user_agent: format!("BiglyBT {}", version),
+            http_version: HttpVersion::Http11,
+            num_want: 100,
+            num_want_policy: NumWantPolicy::Always,
+            supports_compact: true,
+            supports_crypto: true,
+            key_style: KeyStyle::Alphanumeric,
+            announce_params: AnnounceParam::azureus_order(),
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+        }
+    }
+
+    /// Whether an announce for `event` should include the `numwant` parameter
+    pub fn sends_num_want(&self, event: &TrackerEvent) -> bool {
+        match self.num_want_policy {
+            NumWantPolicy::Always => true,
+            NumWantPolicy::StartedOnly => matches!(event, TrackerEvent::Started),
+        }
+    }
+
+    /// Replace the preset fingerprint with a custom User-Agent and/or peer ID prefix
+    pub fn apply_overrides(
+        &mut self,
+        user_agent: Option<&str>,
+        peer_id_prefix: Option<&str>,
+    ) -> Result<(), ValidationError> {
+        if let Some(user_agent) = user_agent {
+            self.user_agent = validate_user_agent(user_agent)?;
+        }
+        if let Some(prefix) = peer_id_prefix {
+            self.peer_id_prefix = validate_peer_id_prefix(prefix)?;
+        }
+        Ok(())
+    }
+
     /// Generate a random peer ID based on this client config
     pub fn generate_peer_id(&self) -> String {
         let mut rng = rand::rng();
-        let random_suffix: String = (0..12)
+        // Fill up to the 20 bytes a peer ID must have
+        let suffix_len = PEER_ID_LEN.saturating_sub(self.peer_id_prefix.len());
+        let random_suffix: String = (0..suffix_len)
             .map(|_| {
                 let chars = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
                 chars[rng.random_range(0..chars.len())] as char
@@ -155,6 +471,70 @@ impl ClientConfig {
         let mut rng = rand::rng();
         (0..8).map(|_| format!("{:X}", rng.random_range(0..16))).collect()
     }
+
+    /// Generate a random key in this client's key style
+    pub fn generate_client_key(&self) -> String {
+        match self.key_style {
+            KeyStyle::Hex => Self::generate_key(),
+            KeyStyle::Alphanumeric => {
+                let mut rng = rand::rng();
+                let chars = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+                (0..8)
+                    .map(|_| chars[rng.random_range(0..chars.len())] as char)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Fingerprint of an emulated client at its default version, for listing clients in the UIs
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientFingerprint {
+    pub id: String,
+    pub name: String,
+    pub default_version: String,
+    /// Versions that can be emulated
+    pub versions: Vec<String>,
+    pub peer_id_prefix: String,
+    pub user_agent: String,
+    pub http_version: HttpVersion,
+    pub num_want: u32,
+    pub num_want_policy: NumWantPolicy,
+    pub supports_compact: bool,
+    pub supports_crypto: bool,
+    pub key_style: KeyStyle,
+    /// Announce query parameter names in the order the client sends them
+    pub announce_params: Vec<String>,
+}
+
+impl ClientFingerprint {
+    /// Fingerprints of every emulated client
+    pub fn all() -> Vec<Self> {
+        ClientType::all()
+            .into_iter()
+            .map(|client_type| Self::from(&ClientConfig::get(client_type, None)))
+            .collect()
+    }
+}
+
+impl From<&ClientConfig> for ClientFingerprint {
+    fn from(config: &ClientConfig) -> Self {
+        ClientFingerprint {
+            id: config.client_type.id().to_string(),
+            name: config.client_type.name().to_string(),
+            default_version: config.version.clone(),
+            versions: ClientConfig::available_versions(&config.client_type),
+            peer_id_prefix: config.peer_id_prefix.clone(),
+            user_agent: config.user_agent.clone(),
+            http_version: config.http_version.clone(),
+            num_want: config.num_want,
+            num_want_policy: config.num_want_policy,
+            supports_compact: config.supports_compact,
+            supports_crypto: config.supports_crypto,
+            key_style: config.key_style,
+            announce_params: config.announce_params.iter().map(|p| p.name().to_string()).collect(),
+        }
+    }
 }
 
 trait PadString {
@@ -217,6 +597,46 @@ mod tests {
         assert!(peer_id.starts_with("-DE"), "Deluge peer ID should start with -DE");
     }
 
+    #[test]
+    fn test_peer_id_generation_biglybt() {
+        let config = ClientConfig::get(ClientType::BiglyBT, None);
+        let peer_id = config.generate_peer_id();
+        assert_eq!(peer_id.len(), 20);
+        assert!(
+            peer_id.starts_with("-AZ3600-"),
+            "BiglyBT peer ID should use Azureus style -AZ3600-"
+        );
+        assert!(peer_id[3..7].chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(&peer_id[7..8], "-");
+
+        let config = ClientConfig::get(ClientType::BiglyBT, Some("3.5.0.0".to_string()));
+        assert!(config.generate_peer_id().starts_with("-AZ3500-"));
+
+        let key = config.generate_client_key();
+        assert_eq!(key.len(), 8);
+        assert!(key.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn test_fingerprint_overrides() {
+        let mut config = ClientConfig::get(ClientType::QBittorrent, None);
+        config
+            .apply_overrides(Some("CustomClient/9.9 (build 42)"), Some("-CC0990-x"))
+            .unwrap();
+        assert_eq!(config.user_agent, "CustomClient/9.9 (build 42)");
+
+        let peer_id = config.generate_peer_id();
+        assert_eq!(peer_id.len(), 20);
+        assert!(peer_id.starts_with("-CC0990-x"));
+
+        // Invalid overrides are rejected and leave the preset untouched
+        let mut config = ClientConfig::get(ClientType::QBittorrent, None);
+        assert!(config.apply_overrides(Some(""), None).is_err());
+        assert!(config.apply_overrides(None, Some(&"X".repeat(21))).is_err());
+        assert!(config.user_agent.starts_with("qBittorrent/"));
+        assert!(config.peer_id_prefix.starts_with("-qB"));
+    }
+
     #[test]
     fn test_peer_id_uniqueness() {
         let config = ClientConfig::get(ClientType::QBittorrent, None);
@@ -298,6 +718,63 @@ mod tests {
         assert!(config.user_agent.contains("4.5.0"));
     }
 
+    #[test]
+    fn test_available_versions() {
+        let versions = ClientConfig::available_versions(&ClientType::QBittorrent);
+        assert!(versions.len() > 1);
+        assert!(versions.contains(&"5.1.4".to_string()));
+
+        for client_type in ClientType::all() {
+            let default = ClientConfig::get(client_type.clone(), None).version;
+            assert!(ClientConfig::available_versions(&client_type).contains(&default));
+        }
+
+        // Older releases offered by the UI are known too
+        let config = ClientConfig::get(ClientType::Transmission, Some("2.94".to_string()));
+        assert_eq!(config.peer_id_prefix, "-TR2940-");
+        let config = ClientConfig::get(ClientType::UTorrent, Some("2.2.1".to_string()));
+        assert_eq!(config.peer_id_prefix, "-UT2210-");
+
+        // Unknown versions fall back to the default
+        let config = ClientConfig::get(ClientType::QBittorrent, Some("9.9.9".to_string()));
+        assert_eq!(config.version, "5.1.4");
+        assert_eq!(config.peer_id_prefix, "-qB5140-");
+    }
+
+    #[test]
+    fn test_sends_num_want() {
+        let mut config = ClientConfig::get(ClientType::QBittorrent, None);
+        assert!(config.sends_num_want(&TrackerEvent::Started));
+        assert!(config.sends_num_want(&TrackerEvent::None));
+
+        config.num_want_policy = NumWantPolicy::StartedOnly;
+        assert!(config.sends_num_want(&TrackerEvent::Started));
+        assert!(!config.sends_num_want(&TrackerEvent::None));
+        assert!(!config.sends_num_want(&TrackerEvent::Completed));
+        assert!(!config.sends_num_want(&TrackerEvent::Stopped));
+    }
+
+    #[test]
+    fn test_client_fingerprints() {
+        let fingerprints = ClientFingerprint::all();
+        let ids: Vec<&str> = fingerprints.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, ["utorrent", "qbittorrent", "transmission", "deluge", "biglybt"]);
+
+        let qbittorrent = &fingerprints[1];
+        assert_eq!(qbittorrent.name, "qBittorrent");
+        assert_eq!(qbittorrent.peer_id_prefix, "-qB5140-");
+        assert_eq!(qbittorrent.user_agent, "qBittorrent/5.1.4");
+        assert!(qbittorrent.supports_crypto);
+        assert!(qbittorrent.versions.contains(&qbittorrent.default_version));
+        assert_eq!(&qbittorrent.announce_params[6..8], ["corrupt", "key"]);
+
+        // The id matches the serde name used in configs
+        for client_type in ClientType::all() {
+            let json = serde_json::to_value(&client_type).unwrap();
+            assert_eq!(json, client_type.id());
+        }
+    }
+
     #[test]
     fn test_pad_string_trait() {
         assert_eq!("12".pad_to_width_with_char(4, '0'), "1200");
@@ -1,6 +1,6 @@
 use crate::protocol::bencode;
 use crate::protocol::BencodeError;
-use crate::{log_debug, log_error, log_trace};
+use crate::{log_debug, log_error, log_trace, log_warn};
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use std::path::Path;
@@ -18,6 +18,78 @@ pub enum TorrentError {
 
 pub type Result<T> = std::result::Result<T, TorrentError>;
 
+/// Placeholder substituted for credentials in redacted tracker URLs
+pub const REDACTED: &str = "REDACTED";
+
+/// Query parameters private trackers commonly use to carry per-user credentials
+const SECRET_QUERY_PARAMS: &[&str] = &[
+    "passkey",
+    "pk",
+    "authkey",
+    "auth",
+    "torrent_pass",
+    "token",
+    "secret",
+    "uid",
+    "key",
+];
+
+/// Minimum length of an alphanumeric path segment treated as a passkey (e.g. `/<passkey>/announce`)
+const PASSKEY_SEGMENT_MIN_LEN: usize = 16;
+
+/// Redact passkeys and other credentials from a tracker URL so it can be safely logged.
+///
+/// Credential query parameters (`passkey`, `authkey`, `key`, ...) and long alphanumeric
+/// path segments are replaced with `REDACTED`. Everything else, including encoded
+/// `info_hash` bytes, is left untouched. The result must only be used for display.
+pub fn redact_tracker_url(url: &str) -> String {
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (url, None),
+    };
+
+    // Keep scheme and authority as-is, only inspect the path
+    let path_start = match base.find("://") {
+        Some(pos) => base[pos + 3..].find('/').map(|p| pos + 3 + p),
+        None => base.find('/'),
+    };
+
+    let mut redacted = match path_start {
+        Some(start) => {
+            let path = base[start..]
+                .split('/')
+                .map(|segment| {
+                    if segment.len() >= PASSKEY_SEGMENT_MIN_LEN && segment.chars().all(|c| c.is_ascii_alphanumeric()) {
+                        REDACTED
+                    } else {
+                        segment
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            format!("{}{}", &base[..start], path)
+        }
+        None => base.to_string(),
+    };
+
+    if let Some(query) = query {
+        let params = query
+            .split('&')
+            .map(|param| match param.split_once('=') {
+                Some((name, _)) if SECRET_QUERY_PARAMS.contains(&name.to_ascii_lowercase().as_str()) => {
+                    format!("{}={}", name, REDACTED)
+                }
+                _ => param.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        redacted.push('?');
+        redacted.push_str(&params);
+    }
+
+    redacted
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TorrentInfo {
     /// SHA1 hash of the info dictionary (20 bytes)
@@ -54,6 +126,10 @@ pub struct TorrentInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_by: Option<String>,
 
+    /// Source tag in the info dict (set by some private trackers, changes the info_hash)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
     /// Is this a single-file or multi-file torrent
     pub is_single_file: bool,
 
@@ -76,7 +152,19 @@ impl TorrentInfo {
     }
 
     /// Parse a torrent from raw bytes
+    ///
+    /// A piece count that doesn't match the total size is only logged as a warning;
+    /// use [`TorrentInfo::from_bytes_strict`] to reject such torrents.
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::parse(data, false)
+    }
+
+    /// Parse a torrent from raw bytes, rejecting torrents whose piece count doesn't match their size
+    pub fn from_bytes_strict(data: &[u8]) -> Result<Self> {
+        Self::parse(data, true)
+    }
+
+    fn parse(data: &[u8], strict: bool) -> Result<Self> {
         log_trace!("Parsing torrent data ({} bytes)", data.len());
 
         let value = bencode::parse(data)?;
@@ -125,6 +213,19 @@ impl TorrentInfo {
             })
             .ok_or_else(|| TorrentError::InvalidStructure("Missing info dictionary".into()))?;
 
+        // v2 torrents describe content with "file tree" / "piece layers" and use a SHA-256 info hash.
+        // Hybrid torrents also carry the v1 fields and announce fine with their v1 hash, so only
+        // reject pure v2 torrents.
+        if bencode::get_int(info_dict, "meta version").is_ok_and(|version| version >= 2) {
+            if !info_dict.contains_key(b"pieces".as_ref()) {
+                log_error!("Invalid torrent: BitTorrent v2 only torrent");
+                return Err(TorrentError::InvalidStructure(
+                    "BitTorrent v2 torrents are not yet supported".into(),
+                ));
+            }
+            log_debug!("Hybrid v1/v2 torrent, using the v1 info hash");
+        }
+
         // Calculate info_hash (SHA1 of bencoded info dict)
         let info_hash = calculate_info_hash(data)?;
 
@@ -132,7 +233,14 @@ impl TorrentInfo {
         let name = bencode::get_string(info_dict, "name")?;
 
         // Extract piece length
-        let piece_length = bencode::get_int(info_dict, "piece length")? as u64;
+        let piece_length = bencode::get_int(info_dict, "piece length")?;
+        if piece_length <= 0 {
+            return Err(TorrentError::InvalidStructure(format!(
+                "Invalid piece length: {}",
+                piece_length
+            )));
+        }
+        let piece_length = piece_length as u64;
 
         // Extract pieces
         let pieces_bytes = bencode::get_bytes(info_dict, "pieces")?;
@@ -190,6 +298,13 @@ impl TorrentInfo {
             ));
         };
 
+        if let Err(msg) = check_piece_count(pieces_bytes.len(), total_size, piece_length) {
+            if strict {
+                return Err(TorrentError::InvalidStructure(msg));
+            }
+            log_warn!("Torrent '{}' looks malformed: {}", name, msg);
+        }
+
         // Extract optional fields
         let creation_date = dict.get(b"creation date".as_ref()).and_then(|v| match v {
             serde_bencode::value::Value::Int(i) => Some(*i),
@@ -203,13 +318,14 @@ impl TorrentInfo {
             serde_bencode::value::Value::Bytes(b) => Some(String::from_utf8_lossy(b).to_string()),
             _ => None,
         });
+        let source = bencode::get_string(info_dict, "source").ok();
 
         log_debug!(
             "Parsed torrent: name='{}', size={} bytes, pieces={}, tracker={}",
             name,
             total_size,
             num_pieces,
-            announce
+            redact_tracker_url(&announce)
         );
         log_trace!(
             "Info hash: {}",
@@ -227,11 +343,33 @@ impl TorrentInfo {
             creation_date,
             comment,
             created_by,
+            source,
             is_single_file,
             files,
         })
     }
 
+    /// Recompute the info_hash as if the info dict had its `source` key set to `source`
+    ///
+    /// Private trackers tag the info dict with a `source` key, so the same content gets a
+    /// different info_hash on each tracker (cross-seeding). The returned hash is what the
+    /// faker would announce for this torrent on the tracker using that tag.
+    pub fn info_hash_with_source(torrent_data: &[u8], source: &str) -> Result<[u8; 20]> {
+        let info_span = bencode::dict_value_span(torrent_data, "info")?;
+        let mut info_dict = match bencode::parse(&torrent_data[info_span])? {
+            serde_bencode::value::Value::Dict(d) => d,
+            _ => return Err(TorrentError::InvalidStructure("Info is not a dictionary".into())),
+        };
+        info_dict.insert(
+            b"source".to_vec(),
+            serde_bencode::value::Value::Bytes(source.as_bytes().to_vec()),
+        );
+
+        // Re-encoding writes the keys in canonical (sorted) order, as a tracker-side rewrite would
+        let info_bytes = bencode::encode(&serde_bencode::value::Value::Dict(info_dict))?;
+        Ok(Sha1::digest(&info_bytes).into())
+    }
+
     /// Get the primary tracker URL
     pub fn get_tracker_url(&self) -> &str {
         &self.announce
@@ -253,41 +391,79 @@ impl TorrentInfo {
             .collect()
     }
 
+    /// Copy of this torrent with passkeys redacted from all tracker URLs.
+    ///
+    /// Use this form whenever torrent info is logged or otherwise exposed for display;
+    /// the original keeps the functional URLs needed for announcing.
+    pub fn redacted(&self) -> Self {
+        let mut torrent = self.clone();
+        torrent.announce = redact_tracker_url(&self.announce);
+        torrent.announce_list = self.announce_list.as_ref().map(|tiers| {
+            tiers
+                .iter()
+                .map(|tier| tier.iter().map(|url| redact_tracker_url(url)).collect())
+                .collect()
+        });
+        torrent
+    }
+
     /// Format info_hash as hex string (for debugging)
     pub fn info_hash_hex(&self) -> String {
         self.info_hash.iter().map(|b| format!("{:02x}", b)).collect()
     }
 }
 
-/// Calculate the SHA1 info_hash from torrent bytes
-fn calculate_info_hash(torrent_data: &[u8]) -> Result<[u8; 20]> {
-    // Parse the torrent to find the info dictionary
-    let value = bencode::parse(torrent_data)?;
-    let _dict = match &value {
-        serde_bencode::value::Value::Dict(d) => d,
-        _ => return Err(TorrentError::InvalidStructure("Root is not a dictionary".into())),
-    };
-
-    // We need to find the raw bytes of the info dictionary in the original data
-    // This is a bit tricky because we need the exact bencoded representation
-
-    // Find "4:info" in the data to locate the info dict
-    let info_marker = b"4:info";
-    let info_start = torrent_data
-        .windows(info_marker.len())
-        .position(|window| window == info_marker)
-        .ok_or_else(|| TorrentError::InvalidStructure("Could not find info dictionary".into()))?
-        + info_marker.len();
+#[cfg(any(test, feature = "test-support"))]
+impl TorrentInfo {
+    /// Small single-file torrent announcing to `announce`, for tests
+    pub fn fixture(announce: impl Into<String>) -> Self {
+        Self {
+            info_hash: [1u8; 20],
+            announce: announce.into(),
+            announce_list: None,
+            name: "test".to_string(),
+            total_size: 1024,
+            piece_length: 256,
+            num_pieces: 4,
+            creation_date: None,
+            comment: None,
+            created_by: None,
+            source: None,
+            is_single_file: true,
+            files: Vec::new(),
+        }
+    }
+}
 
-    // Parse just the info dictionary to get its bencoded representation
-    let info_value = serde_bencode::from_bytes::<serde_bencode::value::Value>(&torrent_data[info_start..])
-        .map_err(|e| BencodeError::ParseError(e.to_string()))?;
+/// Check that the `pieces` hashes cover exactly `total_size` bytes at `piece_length` bytes per piece
+fn check_piece_count(pieces_len: usize, total_size: u64, piece_length: u64) -> std::result::Result<(), String> {
+    if !pieces_len.is_multiple_of(20) {
+        return Err(format!("pieces is {} bytes, not a multiple of 20", pieces_len));
+    }
+    let expected = total_size.div_ceil(piece_length);
+    let found = (pieces_len / 20) as u64;
+    if expected != found {
+        return Err(format!(
+            "{} bytes at {} bytes per piece needs {} pieces, found {}",
+            total_size, piece_length, expected, found
+        ));
+    }
+    Ok(())
+}
 
-    let info_bytes = serde_bencode::to_bytes(&info_value).map_err(|e| BencodeError::ParseError(e.to_string()))?;
+/// Calculate the SHA1 info_hash from torrent bytes
+fn calculate_info_hash(torrent_data: &[u8]) -> Result<[u8; 20]> {
+    // Hash the top-level info value exactly as it appears in the file. Locating it by walking
+    // the structure (rather than searching for "4:info") ignores look-alikes inside string values,
+    // and hashing the raw bytes keeps the hash correct even if the dict isn't canonically ordered.
+    let info_span = bencode::dict_value_span(torrent_data, "info")?;
+    if torrent_data.get(info_span.start) != Some(&b'd') {
+        return Err(TorrentError::InvalidStructure("Info is not a dictionary".into()));
+    }
 
     // Calculate SHA1
     let mut hasher = Sha1::new();
-    hasher.update(&info_bytes);
+    hasher.update(&torrent_data[info_span]);
     let result = hasher.finalize();
 
     let mut hash = [0u8; 20];
@@ -306,19 +482,146 @@ mod tests {
                 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x12,
                 0x34, 0x56, 0x78,
             ],
-            announce: "http://tracker.example.com/announce".to_string(),
-            announce_list: None,
-            name: "test".to_string(),
-            total_size: 1024,
-            piece_length: 256,
-            num_pieces: 4,
-            creation_date: None,
-            comment: None,
-            created_by: None,
-            is_single_file: true,
-            files: vec![],
+            ..TorrentInfo::fixture("http://tracker.example.com/announce")
         };
 
         assert_eq!(info.info_hash_hex(), "123456789abcdef0123456789abcdef012345678");
     }
+
+    /// Single-file torrent whose info dict SHA1 was computed independently
+    const FIXTURE_TORRENT: &[u8] = b"d8:announce35:http://tracker.example.com/announce\
+        4:infod6:lengthi1024e4:name8:test.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+    const FIXTURE_INFO_HASH: &str = "250fee1de8eb05c91bac04884c2784821a00753d";
+
+    #[test]
+    fn test_calculate_info_hash_fixture() {
+        let hash = calculate_info_hash(FIXTURE_TORRENT).unwrap();
+        let hash_hex: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hash_hex, FIXTURE_INFO_HASH);
+
+        let info = TorrentInfo::from_bytes(FIXTURE_TORRENT).unwrap();
+        assert_eq!(info.info_hash_hex(), FIXTURE_INFO_HASH);
+        assert_eq!(info.name, "test.txt");
+        assert_eq!(info.total_size, 1024);
+        assert_eq!(info.num_pieces, 1);
+    }
+
+    #[test]
+    fn test_source_field() {
+        let with_source = b"d8:announce35:http://tracker.example.com/announce\
+            4:infod6:lengthi1024e4:name8:test.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaa6:source3:TRKee";
+        let info = TorrentInfo::from_bytes(with_source).unwrap();
+        assert_eq!(info.source.as_deref(), Some("TRK"));
+        assert_eq!(info.info_hash_hex(), "e287e6edd03f1ca6712f57d4674e3e3a32d1cefe");
+        assert_eq!(TorrentInfo::from_bytes(FIXTURE_TORRENT).unwrap().source, None);
+
+        // Tagging the untagged fixture gives the tagged torrent's hash
+        let recomputed = TorrentInfo::info_hash_with_source(FIXTURE_TORRENT, "TRK").unwrap();
+        assert_eq!(recomputed, info.info_hash);
+        // Replacing an existing tag works the same way
+        let retagged = TorrentInfo::info_hash_with_source(with_source, "OTHER").unwrap();
+        assert_ne!(retagged, info.info_hash);
+        assert_eq!(
+            retagged,
+            TorrentInfo::info_hash_with_source(FIXTURE_TORRENT, "OTHER").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_v2_torrent_rejected() {
+        let v2 = b"d8:announce35:http://tracker.example.com/announce4:infod9:file treed8:test.txtd0:d\
+            6:lengthi1024e11:pieces root32:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbeee12:meta versioni2e\
+            4:name8:test.txt12:piece lengthi16384ee12:piece layersdee";
+        match TorrentInfo::from_bytes(v2) {
+            Err(TorrentError::InvalidStructure(msg)) => {
+                assert_eq!(msg, "BitTorrent v2 torrents are not yet supported")
+            }
+            other => panic!("Expected v2 rejection, got {:?}", other),
+        }
+
+        // Hybrid torrents keep the v1 fields and parse as v1
+        let hybrid = b"d8:announce35:http://tracker.example.com/announce4:infod9:file treed8:test.txtd0:d\
+            6:lengthi1024e11:pieces root32:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbeee6:lengthi1024e12:meta versioni2e\
+            4:name8:test.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+        let info = TorrentInfo::from_bytes(hybrid).unwrap();
+        assert_eq!(info.total_size, 1024);
+        assert_eq!(info.num_pieces, 1);
+    }
+
+    #[test]
+    fn test_piece_count_consistency() {
+        // pieces truncated to 19 bytes
+        let truncated = b"d8:announce35:http://tracker.example.com/announce\
+            4:infod6:lengthi1024e4:name8:test.txt12:piece lengthi16384e6:pieces19:aaaaaaaaaaaaaaaaaaaee";
+        assert!(TorrentInfo::from_bytes(truncated).is_ok());
+        assert!(matches!(
+            TorrentInfo::from_bytes_strict(truncated),
+            Err(TorrentError::InvalidStructure(msg)) if msg.contains("multiple of 20")
+        ));
+
+        // Two piece hashes for a single-piece file
+        let extra_piece = b"d8:announce35:http://tracker.example.com/announce\
+            4:infod6:lengthi1024e4:name8:test.txt12:piece lengthi16384e\
+            6:pieces40:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaee";
+        assert!(matches!(
+            TorrentInfo::from_bytes_strict(extra_piece),
+            Err(TorrentError::InvalidStructure(msg)) if msg.contains("needs 1 pieces, found 2")
+        ));
+
+        assert!(TorrentInfo::from_bytes_strict(FIXTURE_TORRENT).is_ok());
+        assert!(check_piece_count(40, 16385, 16384).is_ok());
+        assert!(check_piece_count(0, 0, 16384).is_ok());
+    }
+
+    #[test]
+    fn test_info_marker_in_comment_ignored() {
+        // "4:info" appears inside the comment, before the real info key
+        let torrent = b"d8:announce35:http://tracker.example.com/announce7:comment13:see 4:infod1e\
+            4:infod6:lengthi1024e4:name8:test.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+
+        let info = TorrentInfo::from_bytes(torrent).unwrap();
+        assert_eq!(info.info_hash_hex(), FIXTURE_INFO_HASH);
+        assert_eq!(info.comment.as_deref(), Some("see 4:infod1e"));
+    }
+
+    #[test]
+    fn test_redact_tracker_url() {
+        assert_eq!(
+            redact_tracker_url("https://tracker.example.com/announce.php?passkey=0123456789abcdef&foo=bar"),
+            "https://tracker.example.com/announce.php?passkey=REDACTED&foo=bar"
+        );
+        assert_eq!(
+            redact_tracker_url("http://tracker.example.com:8080/0123456789abcdef0123/announce"),
+            "http://tracker.example.com:8080/REDACTED/announce"
+        );
+        // Encoded info_hash and other announce params survive, client key does not
+        assert_eq!(
+            redact_tracker_url("http://t.example/announce?info_hash=%12%34&port=6881&key=ABCD1234"),
+            "http://t.example/announce?info_hash=%12%34&port=6881&key=REDACTED"
+        );
+        assert_eq!(
+            redact_tracker_url("http://tracker.example.com/announce"),
+            "http://tracker.example.com/announce"
+        );
+    }
+
+    #[test]
+    fn test_redacted_torrent_hides_passkey() {
+        let passkey = "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6";
+        let info = TorrentInfo {
+            announce_list: Some(vec![vec![format!(
+                "https://backup.example.com/announce?passkey={}",
+                passkey
+            )]]),
+            ..TorrentInfo::fixture(format!("https://private.example.com/{}/announce", passkey))
+        };
+
+        let logged = serde_json::to_string(&info.redacted()).unwrap();
+        assert!(!logged.contains(passkey));
+        assert!(logged.contains(REDACTED));
+
+        // The in-memory torrent keeps the functional URLs for announcing
+        assert!(info.get_tracker_url().contains(passkey));
+        assert!(info.get_all_tracker_urls().iter().all(|url| url.contains(passkey)));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::torrent::{ClientConfig, ClientType};
 use std::fmt::Display;
 use std::path::PathBuf;
 
@@ -12,8 +13,23 @@ pub enum ValidationError {
         max: f64,
         value: f64,
     },
+    NotPositive {
+        field: String,
+        value: f64,
+    },
     InvalidPort(u16),
     MissingField(String),
+    InvalidTrackerUrl(String),
+    UnsupportedTrackerScheme(String),
+    InvalidPeerIdPrefix(String),
+    InvalidUserAgent(String),
+    InvalidSchedule(String),
+    InvalidProxyUrl(String),
+    InvalidAnnounceParam(String),
+    UnknownClientVersion {
+        client: String,
+        version: String,
+    },
 }
 
 impl Display for ValidationError {
@@ -26,10 +42,29 @@ impl Display for ValidationError {
             ValidationError::InvalidRange { field, min, max, value } => {
                 write!(f, "{} must be between {} and {}, got: {}", field, min, max, value)
             }
+            ValidationError::NotPositive { field, value } => {
+                write!(f, "{} must be greater than 0, got: {}", field, value)
+            }
             ValidationError::InvalidPort(port) => {
                 write!(f, "Invalid port number: {}. Must be between 1024 and 65535", port)
             }
             ValidationError::MissingField(field) => write!(f, "Missing required field: {}", field),
+            ValidationError::InvalidTrackerUrl(url) => write!(f, "Invalid tracker URL: {}", url),
+            ValidationError::UnsupportedTrackerScheme(scheme) => {
+                write!(
+                    f,
+                    "Unsupported tracker URL scheme: {}. Only http and https are supported",
+                    scheme
+                )
+            }
+            ValidationError::InvalidPeerIdPrefix(msg) => write!(f, "Invalid peer ID prefix: {}", msg),
+            ValidationError::InvalidUserAgent(msg) => write!(f, "Invalid user agent: {}", msg),
+            ValidationError::InvalidSchedule(msg) => write!(f, "Invalid active schedule: {}", msg),
+            ValidationError::InvalidProxyUrl(msg) => write!(f, "Invalid proxy URL: {}", msg),
+            ValidationError::InvalidAnnounceParam(msg) => write!(f, "Invalid announce parameter: {}", msg),
+            ValidationError::UnknownClientVersion { client, version } => {
+                write!(f, "Unknown {} version: {}", client, version)
+            }
         }
     }
 }
@@ -60,11 +95,13 @@ pub fn validate_torrent_path(path: &str) -> Result<PathBuf, ValidationError> {
     Ok(path_buf)
 }
 
+/// Lowest accepted upload/download rate (KB/s)
+pub const MIN_RATE: f64 = 0.0;
+/// Highest accepted upload/download rate (KB/s), 1 TB/s should be more than enough
+pub const MAX_RATE: f64 = 1_000_000.0;
+
 /// Validate upload/download rate (KB/s)
 pub fn validate_rate(rate: f64, field_name: &str) -> Result<f64, ValidationError> {
-    const MIN_RATE: f64 = 0.0;
-    const MAX_RATE: f64 = 1_000_000.0; // 1 TB/s should be more than enough
-
     if !(MIN_RATE..=MAX_RATE).contains(&rate) {
         return Err(ValidationError::InvalidRange {
             field: field_name.to_string(),
@@ -104,6 +141,213 @@ pub fn validate_update_interval(interval: u64) -> Result<u64, ValidationError> {
     Ok(interval)
 }
 
+/// Validate tracker request timeout (seconds)
+pub fn validate_request_timeout(timeout: u64) -> Result<u64, ValidationError> {
+    const MIN_TIMEOUT: u64 = 1;
+    const MAX_TIMEOUT: u64 = 300; // 5 minutes
+
+    if !(MIN_TIMEOUT..=MAX_TIMEOUT).contains(&timeout) {
+        return Err(ValidationError::InvalidRange {
+            field: "request_timeout_secs".to_string(),
+            min: MIN_TIMEOUT as f64,
+            max: MAX_TIMEOUT as f64,
+            value: timeout as f64,
+        });
+    }
+
+    Ok(timeout)
+}
+
+/// Validate the optional announce interval bounds (seconds), each 1-86400 with min <= max
+pub fn validate_announce_interval_bounds(min: Option<u64>, max: Option<u64>) -> Result<(), ValidationError> {
+    const MIN_BOUND: u64 = 1;
+    const MAX_BOUND: u64 = 86400; // 24 hours
+
+    for (field, value) in [("announce_interval_min_secs", min), ("announce_interval_max_secs", max)] {
+        if let Some(value) = value.filter(|v| !(MIN_BOUND..=MAX_BOUND).contains(v)) {
+            return Err(ValidationError::InvalidRange {
+                field: field.to_string(),
+                min: MIN_BOUND as f64,
+                max: MAX_BOUND as f64,
+                value: value as f64,
+            });
+        }
+    }
+
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(ValidationError::InvalidRange {
+                field: "announce_interval_min_secs".to_string(),
+                min: MIN_BOUND as f64,
+                max: max as f64,
+                value: min as f64,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the announce jitter (percent of the interval)
+pub fn validate_announce_jitter(percent: f64) -> Result<f64, ValidationError> {
+    const MAX_JITTER: f64 = 50.0;
+
+    if !(0.0..=MAX_JITTER).contains(&percent) {
+        return Err(ValidationError::InvalidRange {
+            field: "announce_jitter_percent".to_string(),
+            min: 0.0,
+            max: MAX_JITTER,
+            value: percent,
+        });
+    }
+
+    Ok(percent)
+}
+
+/// Validate the maximum delay before sending `completed` (seconds)
+pub fn validate_completion_jitter(secs: u64) -> Result<u64, ValidationError> {
+    const MAX_JITTER: u64 = 86400; // 24 hours
+
+    if secs > MAX_JITTER {
+        return Err(ValidationError::InvalidRange {
+            field: "completion_jitter_secs".to_string(),
+            min: 0.0,
+            max: MAX_JITTER as f64,
+            value: secs as f64,
+        });
+    }
+
+    Ok(secs)
+}
+
+/// Validate a finite value greater than 0
+pub fn validate_positive(value: f64, field_name: &str) -> Result<f64, ValidationError> {
+    if !value.is_finite() || value <= 0.0 {
+        return Err(ValidationError::NotPositive {
+            field: field_name.to_string(),
+            value,
+        });
+    }
+
+    Ok(value)
+}
+
+/// Validate custom announce parameters appended after the standard ones.
+/// Names and values must be non-empty without control characters, and names can't replace a
+/// parameter the client already sends, in any case. Both are percent-encoded when the URL is built.
+pub fn validate_announce_params(params: &[(String, String)]) -> Result<(), ValidationError> {
+    const STANDARD_PARAMS: &[&str] = &[
+        "info_hash",
+        "peer_id",
+        "port",
+        "uploaded",
+        "downloaded",
+        "left",
+        "compact",
+        "no_peer_id",
+        "event",
+        "ip",
+        "numwant",
+        "key",
+        "trackerid",
+        // Client-specific ones from the announce layouts
+        "supportcrypto",
+        "corrupt",
+        "redundant",
+        "azver",
+    ];
+
+    for (name, value) in params {
+        if name.trim().is_empty() || value.trim().is_empty() {
+            return Err(ValidationError::InvalidAnnounceParam(format!(
+                "'{}={}' needs a name and a value",
+                name, value
+            )));
+        }
+        if name.chars().chain(value.chars()).any(char::is_control) {
+            return Err(ValidationError::InvalidAnnounceParam(format!(
+                "'{}' must not contain control characters",
+                name
+            )));
+        }
+        if STANDARD_PARAMS
+            .iter()
+            .any(|param| param.eq_ignore_ascii_case(name.trim()))
+        {
+            return Err(ValidationError::InvalidAnnounceParam(format!(
+                "'{}' is a standard announce parameter",
+                name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a custom peer ID prefix.
+/// Peer IDs are 20 bytes and sent unescaped, so the prefix must be 1-19 URL-safe ASCII characters
+/// (leaving room for at least one random character).
+pub fn validate_peer_id_prefix(prefix: &str) -> Result<String, ValidationError> {
+    const MAX_PREFIX_LEN: usize = 19;
+
+    if prefix.is_empty() || prefix.len() > MAX_PREFIX_LEN {
+        return Err(ValidationError::InvalidPeerIdPrefix(format!(
+            "must be 1 to {} characters, got {}",
+            MAX_PREFIX_LEN,
+            prefix.len()
+        )));
+    }
+
+    if let Some(c) = prefix
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')))
+    {
+        return Err(ValidationError::InvalidPeerIdPrefix(format!(
+            "'{}' is not allowed, use letters, digits, '-', '.', '_' or '~'",
+            c
+        )));
+    }
+
+    Ok(prefix.to_string())
+}
+
+/// Validate a custom User-Agent header value
+pub fn validate_user_agent(user_agent: &str) -> Result<String, ValidationError> {
+    let user_agent = user_agent.trim();
+
+    if user_agent.is_empty() {
+        return Err(ValidationError::InvalidUserAgent("must not be empty".to_string()));
+    }
+
+    if user_agent.chars().any(|c| c.is_control() || !c.is_ascii()) {
+        return Err(ValidationError::InvalidUserAgent(
+            "must only contain printable ASCII characters".to_string(),
+        ));
+    }
+
+    Ok(user_agent.to_string())
+}
+
+/// Validate active hour ranges.
+/// Each range is `(start, end)` in local hours 0-23, end exclusive; `start > end` wraps past
+/// midnight and `start == end` covers the whole day.
+pub fn validate_active_schedule(schedule: &[(u8, u8)]) -> Result<(), ValidationError> {
+    if schedule.is_empty() {
+        return Err(ValidationError::InvalidSchedule(
+            "at least one hour range is required".to_string(),
+        ));
+    }
+
+    if let Some((start, end)) = schedule.iter().find(|(start, end)| *start > 23 || *end > 23) {
+        return Err(ValidationError::InvalidSchedule(format!(
+            "hours must be between 0 and 23, got {}-{}",
+            start, end
+        )));
+    }
+
+    Ok(())
+}
+
 /// Validate percentage (0-100)
 pub fn validate_percentage(value: f64, field_name: &str) -> Result<f64, ValidationError> {
     if !(0.0..=100.0).contains(&value) {
@@ -118,6 +362,56 @@ pub fn validate_percentage(value: f64, field_name: &str) -> Result<f64, Validati
     Ok(value)
 }
 
+/// Validate that a client version is one of the releases that can be emulated
+pub fn validate_client_version(client_type: &ClientType, version: &str) -> Result<(), ValidationError> {
+    if ClientConfig::available_versions(client_type)
+        .iter()
+        .any(|v| v == version)
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::UnknownClientVersion {
+            client: client_type.name().to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+/// Validate a tracker announce URL (only HTTP(S) trackers are supported)
+pub fn validate_tracker_url(url: &str) -> Result<url::Url, ValidationError> {
+    let parsed = url::Url::parse(url).map_err(|e| ValidationError::InvalidTrackerUrl(format!("{} ({})", url, e)))?;
+
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(ValidationError::UnsupportedTrackerScheme(scheme.to_string())),
+    }
+}
+
+/// Validate a tracker proxy URL (WASM builds route announces through it as `<proxy>?url=<announce>`).
+/// Returns the trimmed URL.
+pub fn validate_proxy_url(url: &str) -> Result<String, ValidationError> {
+    let url = url.trim();
+    let parsed = url::Url::parse(url).map_err(|e| ValidationError::InvalidProxyUrl(format!("{} ({})", url, e)))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ValidationError::InvalidProxyUrl(format!(
+            "{} (only http and https are supported)",
+            url
+        )));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(ValidationError::InvalidProxyUrl(format!("{} (missing host)", url)));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(ValidationError::InvalidProxyUrl(format!(
+            "{} (must not contain a query or fragment)",
+            url
+        )));
+    }
+
+    Ok(url.to_string())
+}
+
 // ClientType validation removed - it's an enum so type-safe by design
 
 #[cfg(test)]
@@ -196,6 +490,126 @@ mod tests {
         assert!(validate_update_interval(10000).is_err());
     }
 
+    #[test]
+    fn test_validate_fingerprint_overrides() {
+        assert_eq!(validate_peer_id_prefix("-XX1234-").unwrap(), "-XX1234-");
+        assert!(validate_peer_id_prefix("M7-2-2--").is_ok());
+        assert!(validate_peer_id_prefix(&"A".repeat(19)).is_ok());
+
+        assert!(validate_peer_id_prefix("").is_err());
+        assert!(validate_peer_id_prefix(&"A".repeat(20)).is_err());
+        assert!(validate_peer_id_prefix("-XX 12-").is_err());
+        assert!(validate_peer_id_prefix("-XX&12-").is_err());
+
+        assert_eq!(validate_user_agent(" MyClient/1.0 ").unwrap(), "MyClient/1.0");
+        assert!(validate_user_agent("").is_err());
+        assert!(validate_user_agent("   ").is_err());
+        assert!(validate_user_agent("Client\r\nX-Injected: 1").is_err());
+    }
+
+    #[test]
+    fn test_validate_request_timeout() {
+        assert!(validate_request_timeout(1).is_ok()); // Min
+        assert!(validate_request_timeout(30).is_ok());
+        assert!(validate_request_timeout(300).is_ok()); // Max
+
+        assert!(validate_request_timeout(0).is_err());
+        assert!(validate_request_timeout(301).is_err());
+    }
+
+    #[test]
+    fn test_validate_announce_interval_bounds() {
+        assert!(validate_announce_interval_bounds(None, None).is_ok());
+        assert!(validate_announce_interval_bounds(Some(60), None).is_ok());
+        assert!(validate_announce_interval_bounds(None, Some(86400)).is_ok()); // Max
+        assert!(validate_announce_interval_bounds(Some(600), Some(600)).is_ok());
+
+        assert!(validate_announce_interval_bounds(Some(0), None).is_err());
+        assert!(validate_announce_interval_bounds(None, Some(86401)).is_err());
+        assert!(validate_announce_interval_bounds(Some(900), Some(600)).is_err());
+    }
+
+    #[test]
+    fn test_validate_positive() {
+        assert!(validate_positive(5.0, "max_ratio_cap").is_ok());
+        assert!(validate_positive(0.5, "max_ratio_cap").is_ok());
+
+        assert!(validate_positive(0.0, "max_ratio_cap").is_err());
+        assert!(validate_positive(-1.0, "max_ratio_cap").is_err());
+        assert!(validate_positive(f64::INFINITY, "max_ratio_cap").is_err());
+    }
+
+    #[test]
+    fn test_validate_announce_jitter() {
+        assert!(validate_announce_jitter(0.0).is_ok());
+        assert!(validate_announce_jitter(5.0).is_ok());
+        assert!(validate_announce_jitter(50.0).is_ok()); // Max
+
+        assert!(validate_announce_jitter(-1.0).is_err());
+        assert!(validate_announce_jitter(50.1).is_err());
+        assert!(validate_announce_jitter(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_validate_completion_jitter() {
+        assert!(validate_completion_jitter(0).is_ok());
+        assert!(validate_completion_jitter(86400).is_ok()); // Max
+
+        assert!(validate_completion_jitter(86401).is_err());
+        assert!(validate_completion_jitter(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_validate_announce_params() {
+        let param = |name: &str, value: &str| (name.to_string(), value.to_string());
+        assert!(validate_announce_params(&[]).is_ok());
+        assert!(validate_announce_params(&[param("uid", "42"), param("auth", "a b&c")]).is_ok());
+
+        assert!(validate_announce_params(&[param("", "42")]).is_err());
+        assert!(validate_announce_params(&[param("uid", " ")]).is_err());
+        assert!(validate_announce_params(&[param("uid", "4\n2")]).is_err());
+        assert!(validate_announce_params(&[param("uploaded", "0")]).is_err());
+        assert!(validate_announce_params(&[param("Uploaded", "0")]).is_err());
+        assert!(validate_announce_params(&[param("supportcrypto", "1")]).is_err());
+        assert!(validate_announce_params(&[param("corrupt", "0")]).is_err());
+    }
+
+    #[test]
+    fn test_validate_active_schedule() {
+        assert!(validate_active_schedule(&[(9, 17)]).is_ok());
+        assert!(validate_active_schedule(&[(22, 6), (12, 13)]).is_ok()); // Wraps past midnight
+        assert!(validate_active_schedule(&[(0, 0)]).is_ok()); // Whole day
+
+        assert!(validate_active_schedule(&[]).is_err());
+        assert!(validate_active_schedule(&[(22, 24)]).is_err());
+        assert!(validate_active_schedule(&[(25, 3)]).is_err());
+    }
+
+    #[test]
+    fn test_validate_tracker_url() {
+        // Supported schemes
+        assert!(validate_tracker_url("http://tracker.example.com/announce").is_ok());
+        assert!(validate_tracker_url("https://tracker.example.com:443/abc/announce?passkey=x").is_ok());
+
+        // Unsupported schemes
+        let result = validate_tracker_url("udp://tracker.example.com:1337/announce");
+        assert!(matches!(result, Err(ValidationError::UnsupportedTrackerScheme(ref s)) if s == "udp"));
+        assert!(matches!(
+            validate_tracker_url("wss://tracker.example.com"),
+            Err(ValidationError::UnsupportedTrackerScheme(_))
+        ));
+
+        // Not a URL
+        assert!(matches!(
+            validate_tracker_url("not a url"),
+            Err(ValidationError::InvalidTrackerUrl(_))
+        ));
+        assert!(matches!(
+            validate_tracker_url(""),
+            Err(ValidationError::InvalidTrackerUrl(_))
+        ));
+    }
+
     #[test]
     fn test_validate_torrent_path_nonexistent() {
         let result = validate_torrent_path("/nonexistent/file.torrent");
@@ -296,4 +710,19 @@ mod tests {
         let err = ValidationError::MissingField("torrent".to_string());
         assert_eq!(format!("{}", err), "Missing required field: torrent");
     }
+
+    #[test]
+    fn test_validate_proxy_url() {
+        assert_eq!(
+            validate_proxy_url(" https://proxy.example.com/announce ").unwrap(),
+            "https://proxy.example.com/announce"
+        );
+        assert!(validate_proxy_url("http://localhost:8787").is_ok());
+
+        assert!(validate_proxy_url("").is_err());
+        assert!(validate_proxy_url("proxy.example.com").is_err());
+        assert!(validate_proxy_url("ftp://proxy.example.com").is_err());
+        assert!(validate_proxy_url("https://proxy.example.com/?url=").is_err());
+        assert!(validate_proxy_url("https://proxy.example.com/#x").is_err());
+    }
 }
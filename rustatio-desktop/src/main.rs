@@ -103,6 +103,8 @@ struct InstanceInfo {
     torrent_name: Option<String>,
     is_running: bool,
     is_paused: bool,
+    /// Why the instance gave up, when it is `Errored`
+    error: Option<String>,
 }
 
 // Application state
@@ -153,8 +155,12 @@ async fn list_instances(state: State<'_, AppState>) -> Result<Vec<InstanceInfo>,
         instances.push(InstanceInfo {
             id: *id,
             torrent_name: Some(instance.torrent_name.clone()),
-            is_running: matches!(stats.state, FakerState::Running | FakerState::Completed),
+            is_running: matches!(
+                stats.state,
+                FakerState::Running | FakerState::Completed | FakerState::Seeding
+            ),
             is_paused: matches!(stats.state, FakerState::Paused),
+            error: stats.error,
         });
     }
 
@@ -492,6 +498,7 @@ async fn get_client_types() -> Vec<String> {
         "qbittorrent".to_string(),
         "transmission".to_string(),
         "deluge".to_string(),
+        "biglybt".to_string(),
     ]
 }
 
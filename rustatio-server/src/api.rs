@@ -1,6 +1,6 @@
 use axum::{
-    extract::{Multipart, Path, Query, State},
-    http::StatusCode,
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
     response::{
         sse::{Event, KeepAlive, Sse},
         IntoResponse, Response,
@@ -8,15 +8,20 @@ use axum::{
     routing::{delete, get, patch, post},
     Json, Router,
 };
+use futures::future::join_all;
 use futures::stream::Stream;
-use rustatio_core::{FakerConfig, TorrentInfo};
+use rustatio_core::{ClientFingerprint, FakerConfig, FakerState, TorrentInfo};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::convert::Infallible;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 
 use crate::auth;
-use crate::state::InstanceInfo;
+use crate::health::{HealthReport, ReadinessReport};
+use crate::replay::{self, ReplayTimeline};
+use crate::state::{sort_instances, InstanceError, InstanceInfo, InstanceSort, LogEvent, LogFilter, SortOrder};
 use crate::watch::{WatchStatus, WatchedFile};
 use crate::ServerState;
 
@@ -45,11 +50,26 @@ impl ApiError {
 struct ApiSuccess<T> {
     success: bool,
     data: T,
+    /// Total number of items before pagination (paginated lists only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<usize>,
 }
 
 impl<T: Serialize> ApiSuccess<T> {
     fn new(data: T) -> Self {
-        Self { success: true, data }
+        Self {
+            success: true,
+            data,
+            total: None,
+        }
+    }
+
+    fn page(data: T, total: usize) -> Self {
+        Self {
+            success: true,
+            data,
+            total: Some(total),
+        }
     }
 
     fn response(data: T) -> Response
@@ -62,15 +82,24 @@ impl<T: Serialize> ApiSuccess<T> {
 
 /// Build the API router
 pub fn router() -> Router<ServerState> {
-    Router::new()
+    let router = Router::new()
         // Instance management
         .route("/instances", get(list_instances).post(create_instance))
-        .route("/instances/{id}", delete(delete_instance))
+        .route("/instances/{id}", get(get_instance).delete(delete_instance))
         .route("/instances/{id}/torrent", post(load_instance_torrent))
         .route("/instances/{id}/config", patch(update_instance_config))
+        .route("/instances/{id}/labels", patch(update_instance_labels))
+        .route("/instances/{id}/profile/{name}", post(apply_profile))
+        .route("/instances/{id}/clone", post(clone_instance))
+        .route("/instances/{id}/logs", get(instance_logs_sse))
         // Torrent loading
         .route("/torrent/load", post(load_torrent))
+        .route("/torrent/load-url", post(load_torrent_url))
         // Faker operations
+        .route("/faker/bulk/start", post(bulk_start))
+        .route("/faker/bulk/stop", post(bulk_stop))
+        .route("/faker/bulk/pause", post(bulk_pause))
+        .route("/faker/bulk/resume", post(bulk_resume))
         .route("/faker/{id}/start", post(start_faker))
         .route("/faker/{id}/stop", post(stop_faker))
         .route("/faker/{id}/pause", post(pause_faker))
@@ -78,8 +107,12 @@ pub fn router() -> Router<ServerState> {
         .route("/faker/{id}/update", post(update_faker))
         .route("/faker/{id}/stats", get(get_stats))
         .route("/faker/{id}/stats-only", post(update_stats_only))
+        // Admin
+        .route("/admin/stop-all", post(admin_stop_all))
+        .route("/admin/resume-all", post(admin_resume_all))
         // Client types
-        .route("/clients", get(get_client_types))
+        .route("/clients", get(get_clients))
+        .route("/profiles", get(list_profiles))
         // Network status (VPN detection)
         .route("/network/status", get(get_network_status))
         // SSE streaming
@@ -91,6 +124,15 @@ pub fn router() -> Router<ServerState> {
         .route("/watch/files/{filename}", delete(delete_watch_file))
         // Auth verification (returns success if token is valid)
         .route("/auth/verify", get(verify_auth))
+        // Detailed health (per-instance liveness)
+        .route("/health", get(detailed_health));
+
+    // Demo replay (only when DEMO_REPLAY is enabled)
+    if replay::is_demo_replay_enabled() {
+        router.route("/demo/replay", post(demo_replay))
+    } else {
+        router
+    }
 }
 
 /// Auth-free router for endpoints that don't require authentication
@@ -130,15 +172,183 @@ struct CreateInstanceResponse {
     id: String,
 }
 
-async fn create_instance(State(state): State<ServerState>) -> Response {
-    let id = state.app.next_instance_id().await;
+/// An `Idempotency-Key` header makes retries of the same request return the same id
+async fn create_instance(State(state): State<ServerState>, headers: HeaderMap) -> Response {
+    let key = headers
+        .get("idempotency-key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty());
+    let id = match key {
+        Some(key) => state.app.instance_id_for_key(key).await,
+        None => state.app.next_instance_id().await,
+    };
     ApiSuccess::response(CreateInstanceResponse { id })
 }
 
+/// Query parameters for listing instances
+#[derive(Deserialize)]
+struct ListInstancesQuery {
+    /// Only return instances carrying this label
+    label: Option<String>,
+    /// Sort key: created (default), ratio, uploaded or name
+    #[serde(default)]
+    sort: InstanceSort,
+    /// Sort direction: asc (default) or desc
+    #[serde(default)]
+    order: SortOrder,
+    /// Number of instances to skip
+    #[serde(default)]
+    offset: usize,
+    /// Maximum number of instances to return (default: all)
+    limit: Option<usize>,
+}
+
 /// List all instances with their current stats
-async fn list_instances(State(state): State<ServerState>) -> Response {
-    let instances: Vec<InstanceInfo> = state.app.list_instances().await;
-    ApiSuccess::response(instances)
+async fn list_instances(
+    State(state): State<ServerState>,
+    Query(query): Query<ListInstancesQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let mut instances: Vec<InstanceInfo> = state.app.list_instances().await;
+    if let Some(label) = query.label {
+        instances.retain(|instance| instance.labels.contains(&label));
+    }
+    sort_instances(&mut instances, query.sort, query.order);
+
+    let total = instances.len();
+    let page: Vec<InstanceInfo> = instances
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+    let tagged: Vec<InstanceTag> = page.iter().map(InstanceTag::from).collect();
+    etag_response(&headers, &(total, tagged), ApiSuccess::page(&page, total))
+}
+
+/// What an instance contributes to the list's ETag. The torrent's file list never changes for
+/// an instance and the background loop's tick time changes without anything to show, so both
+/// are left out.
+#[derive(Serialize)]
+struct InstanceTag<'a> {
+    id: &'a str,
+    info_hash: [u8; 20],
+    announce: &'a str,
+    announce_list: &'a Option<Vec<Vec<String>>>,
+    config: &'a FakerConfig,
+    stats: &'a rustatio_core::FakerStats,
+    labels: &'a [String],
+}
+
+impl<'a> From<&'a InstanceInfo> for InstanceTag<'a> {
+    fn from(instance: &'a InstanceInfo) -> Self {
+        Self {
+            id: &instance.id,
+            info_hash: instance.torrent.info_hash,
+            announce: &instance.torrent.announce,
+            announce_list: &instance.torrent.announce_list,
+            config: &instance.config,
+            stats: &instance.stats,
+            labels: &instance.labels,
+        }
+    }
+}
+
+/// Successful response tagged with an ETag of `tagged`, the parts of the body that matter to clients.
+/// Replies `304 Not Modified` without a body when the client's `If-None-Match` already has it.
+fn etag_response<K: Serialize, T: Serialize>(headers: &HeaderMap, tagged: &K, data: ApiSuccess<T>) -> Response {
+    let tagged = match serde_json::to_vec(tagged) {
+        Ok(tagged) => tagged,
+        Err(e) => return ApiError::response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let mut hasher = DefaultHasher::new();
+    tagged.hash(&mut hasher);
+    let etag = format!("\"{:016x}\"", hasher.finish());
+
+    let cached = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &etag));
+    if cached {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    let body = match serde_json::to_vec(&data) {
+        Ok(body) => body,
+        Err(e) => return ApiError::response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::ETAG, etag),
+            (header::CONTENT_TYPE, "application/json".to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Whether an `If-None-Match` header value (a list of tags or `*`) matches `etag`
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Get a single instance with its torrent, config and current stats
+async fn get_instance(State(state): State<ServerState>, Path(id): Path<String>) -> Response {
+    match state.app.get_instance(&id).await {
+        Some(instance) => ApiSuccess::response(instance),
+        None => ApiError::response(StatusCode::NOT_FOUND, "Instance not found"),
+    }
+}
+
+/// Detailed health report with per-instance background loop liveness
+async fn detailed_health(State(state): State<ServerState>) -> Response {
+    let instances = state.app.list_instances().await;
+    let mut report = HealthReport::from_instances(&instances).with_max_instances(state.app.max_instances);
+    if state.app.vpn_gate.required() {
+        report = report.with_vpn_gate(state.app.vpn_gate.status());
+    }
+    ApiSuccess::response(report)
+}
+
+/// Readiness check, 503 when the saved state or the watch folder service failed to initialize.
+/// Failed steps are retried on each call, so fixing the cause doesn't need a restart.
+pub async fn readiness(State(state): State<ServerState>) -> Response {
+    let mut startup = state.startup.write().await;
+    if startup.state_error.is_some() {
+        startup.state_error = state.app.load_saved_state().await.err();
+        if startup.state_error.is_none() {
+            tracing::info!("Saved state loaded on readiness retry");
+        }
+    }
+    if startup.watch_error.is_some() {
+        startup.watch_error = state.watch.write().await.start().await.err();
+        if startup.watch_error.is_none() {
+            tracing::info!("Watch folder service started on readiness retry");
+        }
+    }
+
+    let instances = state.app.list_instances().await;
+    let watch = state.watch.read().await.get_status().await;
+    let report = ReadinessReport::new(&startup, state.app.persistence_error(), &instances, watch);
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
+
+/// Create a synthetic instance that replays a recorded stats timeline
+async fn demo_replay(State(state): State<ServerState>, Json(timeline): Json<ReplayTimeline>) -> Response {
+    match state.app.create_replay_instance(timeline).await {
+        Ok(id) => ApiSuccess::response(CreateInstanceResponse { id }),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
+    }
 }
 
 /// Return full application config (public endpoint)
@@ -178,6 +388,9 @@ async fn delete_instance(
 struct LoadTorrentResponse {
     torrent_id: String,
     torrent: TorrentInfo,
+    /// Existing instance already using this torrent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_of: Option<String>,
 }
 
 /// Load a torrent file
@@ -191,11 +404,13 @@ async fn load_torrent(State(state): State<ServerState>, mut multipart: Multipart
                         // Generate a temporary ID and store the torrent
                         let torrent_id = uuid::Uuid::new_v4().to_string();
                         let torrent_data = torrent.clone();
+                        let duplicate_of = state.app.find_instance_by_info_hash(&torrent.info_hash).await;
                         state.app.store_torrent(&torrent_id, torrent).await;
 
                         return ApiSuccess::response(LoadTorrentResponse {
                             torrent_id,
                             torrent: torrent_data,
+                            duplicate_of,
                         });
                     }
                     Err(e) => {
@@ -212,6 +427,187 @@ async fn load_torrent(State(state): State<ServerState>, mut multipart: Multipart
     ApiError::response(StatusCode::BAD_REQUEST, "No torrent file provided")
 }
 
+/// Largest .torrent file accepted from a remote URL
+const MAX_TORRENT_URL_BYTES: usize = 10 * 1024 * 1024;
+
+/// Timeout for fetching a .torrent file from a remote URL
+const TORRENT_URL_TIMEOUT_SECS: u64 = 15;
+
+/// Redirects followed when fetching a .torrent file from a remote URL
+const MAX_TORRENT_URL_REDIRECTS: usize = 5;
+
+/// Request body for loading a torrent from a URL
+#[derive(Deserialize)]
+struct LoadTorrentUrlRequest {
+    url: String,
+}
+
+/// Whether an address is reachable on the public internet, so the server can't be used to probe
+/// loopback, private, link-local or other special-purpose networks
+fn is_public_addr(ip: std::net::IpAddr) -> bool {
+    match ip {
+        std::net::IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // Shared address space (100.64.0.0/10) and "this network" (0.0.0.0/8)
+                || (a == 100 && (64..128).contains(&b))
+                || a == 0)
+        }
+        std::net::IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_addr(ip.into()),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Check a torrent URL's scheme and resolve its host, refusing hosts with a non-public address
+/// unless `allow_local` is set. Returns the host name to pin to the checked addresses, if any.
+async fn resolve_torrent_url(
+    url: &reqwest::Url,
+    allow_local: bool,
+) -> Result<Option<(String, Vec<std::net::SocketAddr>)>, (StatusCode, String)> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported URL scheme '{}', expected http or https", url.scheme()),
+        ));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "URL has no host".to_string()))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let (addrs, domain) = match literal.parse::<std::net::IpAddr>() {
+        Ok(ip) => (vec![std::net::SocketAddr::new(ip, port)], None),
+        Err(_) => {
+            let addrs: Vec<_> = tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Failed to resolve {}: {}", host, e)))?
+                .collect();
+            if addrs.is_empty() {
+                return Err((StatusCode::BAD_GATEWAY, format!("Failed to resolve {}", host)));
+            }
+            (addrs, Some(host.to_string()))
+        }
+    };
+    if !allow_local && addrs.iter().any(|addr| !is_public_addr(addr.ip())) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Refusing to fetch {}, it resolves to a local or private address", host),
+        ));
+    }
+    Ok(domain.map(|domain| (domain, addrs)))
+}
+
+/// Download a .torrent file, enforcing the scheme, public addresses, redirect limit, timeout and size limit
+async fn fetch_torrent_url(url: &str, allow_local: bool) -> Result<Vec<u8>, (StatusCode, String)> {
+    let mut url = reqwest::Url::parse(url).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid URL: {}", e)))?;
+
+    let too_large = || {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Torrent file exceeds {} bytes", MAX_TORRENT_URL_BYTES),
+        )
+    };
+    let fetch_error = |e: reqwest::Error| (StatusCode::BAD_GATEWAY, format!("Failed to fetch torrent: {}", e));
+
+    // Redirects are followed by hand so every hop's address is checked, and the client is pinned
+    // to the checked addresses so a second DNS answer can't point it elsewhere
+    let mut redirects = 0;
+    let mut response = loop {
+        let mut client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(TORRENT_URL_TIMEOUT_SECS))
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some((domain, addrs)) = resolve_torrent_url(&url, allow_local).await? {
+            client = client.resolve_to_addrs(&domain, &addrs);
+        }
+        let client = client
+            .build()
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("HTTP client error: {}", e)))?;
+
+        let response = client.get(url.clone()).send().await.map_err(fetch_error)?;
+        if !response.status().is_redirection() {
+            break response.error_for_status().map_err(fetch_error)?;
+        }
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| url.join(location).ok())
+            .ok_or_else(|| (StatusCode::BAD_GATEWAY, "Redirect without a valid location".to_string()))?;
+        redirects += 1;
+        if redirects > MAX_TORRENT_URL_REDIRECTS {
+            return Err((
+                StatusCode::BAD_GATEWAY,
+                format!("Too many redirects (more than {})", MAX_TORRENT_URL_REDIRECTS),
+            ));
+        }
+        url = location;
+    };
+
+    let declared_len = response.content_length().unwrap_or(0);
+    if declared_len > MAX_TORRENT_URL_BYTES as u64 {
+        return Err(too_large());
+    }
+
+    // Content-Length can be missing or wrong, so enforce the limit while reading
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Failed to read torrent: {}", e)))?
+    {
+        if bytes.len() + chunk.len() > MAX_TORRENT_URL_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(bytes)
+}
+
+/// Load a torrent file from a remote http(s) URL
+async fn load_torrent_url(State(state): State<ServerState>, Json(request): Json<LoadTorrentUrlRequest>) -> Response {
+    load_torrent_from_url(&state, &request.url, false).await
+}
+
+/// Download, parse and store a torrent file, `allow_local` permitting non-public addresses
+async fn load_torrent_from_url(state: &ServerState, url: &str, allow_local: bool) -> Response {
+    let bytes = match fetch_torrent_url(url, allow_local).await {
+        Ok(bytes) => bytes,
+        Err((status, e)) => return ApiError::response(status, e),
+    };
+
+    match TorrentInfo::from_bytes(&bytes) {
+        Ok(torrent) => {
+            let torrent_id = uuid::Uuid::new_v4().to_string();
+            let torrent_data = torrent.clone();
+            let duplicate_of = state.app.find_instance_by_info_hash(&torrent.info_hash).await;
+            state.app.store_torrent(&torrent_id, torrent).await;
+
+            ApiSuccess::response(LoadTorrentResponse {
+                torrent_id,
+                torrent: torrent_data,
+                duplicate_of,
+            })
+        }
+        Err(e) => ApiError::response(StatusCode::BAD_REQUEST, format!("Failed to parse torrent: {}", e)),
+    }
+}
+
 /// Load a torrent file for a specific instance (creates idle instance on server)
 /// This allows the instance to persist across page refreshes
 async fn load_instance_torrent(
@@ -225,6 +621,12 @@ async fn load_instance_torrent(
             match field.bytes().await {
                 Ok(bytes) => match TorrentInfo::from_bytes(&bytes) {
                     Ok(torrent) => {
+                        let duplicate_of = state
+                            .app
+                            .find_instance_by_info_hash(&torrent.info_hash)
+                            .await
+                            .filter(|other| *other != id);
+
                         // Check if instance already exists
                         if state.app.instance_exists(&id).await {
                             // Update existing instance with new torrent
@@ -233,13 +635,14 @@ async fn load_instance_torrent(
                             return ApiSuccess::response(LoadTorrentResponse {
                                 torrent_id: id,
                                 torrent,
+                                duplicate_of,
                             });
                         }
 
                         // Create idle instance on server (will persist across refreshes)
                         if let Err(e) = state.app.create_idle_instance(&id, torrent.clone()).await {
                             return ApiError::response(
-                                StatusCode::INTERNAL_SERVER_ERROR,
+                                instance_error_status(&e),
                                 format!("Failed to create instance: {}", e),
                             );
                         }
@@ -247,6 +650,7 @@ async fn load_instance_torrent(
                         return ApiSuccess::response(LoadTorrentResponse {
                             torrent_id: id,
                             torrent,
+                            duplicate_of,
                         });
                     }
                     Err(e) => {
@@ -272,7 +676,104 @@ async fn update_instance_config(
 ) -> Response {
     match state.app.update_instance_config_only(&id, config).await {
         Ok(()) => ApiSuccess::response(()),
-        Err(e) => ApiError::response(StatusCode::NOT_FOUND, e),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
+    }
+}
+
+/// Apply a named profile to an instance config (without starting the faker)
+async fn apply_profile(State(state): State<ServerState>, Path((id, name)): Path<(String, String)>) -> Response {
+    match state.app.apply_profile(&id, &name).await {
+        Ok(config) => ApiSuccess::response(config),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
+    }
+}
+
+/// Status for a typed instance error
+fn instance_error_status(error: &InstanceError) -> StatusCode {
+    match error {
+        InstanceError::NotFound(_) => StatusCode::NOT_FOUND,
+        InstanceError::Invalid(_) => StatusCode::BAD_REQUEST,
+        InstanceError::LimitReached(_) | InstanceError::Conflict(_) => StatusCode::CONFLICT,
+        InstanceError::Failed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Request body for updating instance labels
+#[derive(Deserialize)]
+struct UpdateLabelsRequest {
+    labels: Vec<String>,
+}
+
+/// Replace an instance's labels
+async fn update_instance_labels(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateLabelsRequest>,
+) -> Response {
+    match state.app.update_instance_labels(&id, request.labels).await {
+        Ok(labels) => ApiSuccess::response(labels),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
+    }
+}
+
+/// JSON body for cloning an instance onto a torrent loaded earlier via `/torrent/load`
+#[derive(Deserialize)]
+struct CloneInstanceRequest {
+    torrent_id: String,
+}
+
+/// Create a new instance for another torrent with a copy of an instance's config.
+/// Accepts either a multipart torrent upload (`file` field) or `{"torrent_id": "..."}`.
+/// Returns 404 for an unknown instance or torrent, 400 when the copied config can't run the torrent
+/// and 409 when MAX_INSTANCES is reached.
+async fn clone_instance(State(state): State<ServerState>, Path(id): Path<String>, request: Request) -> Response {
+    let is_multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/form-data"));
+
+    let torrent = if is_multipart {
+        let mut multipart = match Multipart::from_request(request, &()).await {
+            Ok(multipart) => multipart,
+            Err(e) => return ApiError::response(StatusCode::BAD_REQUEST, e.body_text()),
+        };
+        let mut torrent = None;
+        while let Ok(Some(field)) = multipart.next_field().await {
+            if field.name() == Some("file") {
+                let bytes = match field.bytes().await {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        return ApiError::response(StatusCode::BAD_REQUEST, format!("Failed to read file: {}", e));
+                    }
+                };
+                match TorrentInfo::from_bytes(&bytes) {
+                    Ok(parsed) => torrent = Some(parsed),
+                    Err(e) => {
+                        return ApiError::response(StatusCode::BAD_REQUEST, format!("Failed to parse torrent: {}", e));
+                    }
+                }
+                break;
+            }
+        }
+        match torrent {
+            Some(torrent) => torrent,
+            None => return ApiError::response(StatusCode::BAD_REQUEST, "No torrent file provided"),
+        }
+    } else {
+        let Json(body) = match Json::<CloneInstanceRequest>::from_request(request, &()).await {
+            Ok(body) => body,
+            Err(e) => return ApiError::response(StatusCode::BAD_REQUEST, e.body_text()),
+        };
+        match state.app.get_torrent(&body.torrent_id).await {
+            Some(torrent) => torrent,
+            None => return ApiError::response(StatusCode::NOT_FOUND, "Torrent not found"),
+        }
+    };
+
+    match state.app.clone_instance(&id, torrent).await {
+        Ok(id) => ApiSuccess::response(CreateInstanceResponse { id }),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
     }
 }
 
@@ -283,32 +784,68 @@ struct StartFakerRequest {
     config: FakerConfig,
 }
 
+/// Query parameters for starting a faker
+#[derive(Deserialize)]
+struct StartFakerQuery {
+    /// Start even if another instance is already running or paused on the same torrent
+    #[serde(default)]
+    force: bool,
+}
+
 /// Start a faker instance
 ///
 /// If the instance already exists (e.g., from watch folder), it will update the config
 /// and start it. Otherwise, it creates a new instance with the provided torrent and config.
+/// An instance that is already running takes the new config in place and keeps its session.
+/// Returns 400 for an invalid config, and 409 if another instance is already running or paused on the
+/// same torrent unless `force` is set.
 async fn start_faker(
     State(state): State<ServerState>,
     Path(id): Path<String>,
+    Query(query): Query<StartFakerQuery>,
     Json(request): Json<StartFakerRequest>,
 ) -> Response {
+    // Checked before creating the instance, so a refused start leaves nothing behind
+    if !query.force {
+        if let Err(e) = state
+            .app
+            .ensure_no_active_duplicate(&id, &request.torrent.info_hash)
+            .await
+        {
+            return ApiError::response(instance_error_status(&e), e);
+        }
+    }
+
     // Check if instance already exists (e.g., from watch folder)
     if state.app.instance_exists(&id).await {
         // Update config for existing instance
         if let Err(e) = state.app.update_instance_config(&id, request.config).await {
-            return ApiError::response(StatusCode::INTERNAL_SERVER_ERROR, e);
+            return ApiError::response(instance_error_status(&e), e);
+        }
+
+        let running = matches!(
+            state.app.get_stats(&id).await,
+            Ok(stats) if matches!(stats.state, FakerState::Running | FakerState::Seeding)
+        );
+        if running {
+            return ApiSuccess::response(());
         }
     } else {
         // Create new instance with provided torrent and config
         if let Err(e) = state.app.create_instance(&id, request.torrent, request.config).await {
-            return ApiError::response(StatusCode::INTERNAL_SERVER_ERROR, e);
+            return ApiError::response(instance_error_status(&e), e);
         }
     }
 
     // Start the faker
-    match state.app.start_instance(&id).await {
+    let started = if query.force {
+        state.app.force_start_instance(&id).await
+    } else {
+        state.app.start_instance(&id).await
+    };
+    match started {
         Ok(()) => ApiSuccess::response(()),
-        Err(e) => ApiError::response(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
     }
 }
 
@@ -332,10 +869,116 @@ async fn pause_faker(State(state): State<ServerState>, Path(id): Path<String>) -
 async fn resume_faker(State(state): State<ServerState>, Path(id): Path<String>) -> Response {
     match state.app.resume_instance(&id).await {
         Ok(()) => ApiSuccess::response(()),
-        Err(e) => ApiError::response(StatusCode::NOT_FOUND, e),
+        Err(e) => ApiError::response(instance_error_status(&e), e),
+    }
+}
+
+/// Request body for bulk faker operations
+#[derive(Deserialize)]
+struct BulkRequest {
+    #[serde(default)]
+    ids: Vec<String>,
+    /// Apply to every instance instead of `ids`
+    #[serde(default)]
+    all: bool,
+}
+
+/// Outcome of a bulk operation for a single instance
+#[derive(Serialize)]
+struct BulkResult {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl From<Result<(), String>> for BulkResult {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => BulkResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => BulkResult {
+                success: false,
+                error: Some(e),
+            },
+        }
     }
 }
 
+#[derive(Clone, Copy)]
+enum BulkAction {
+    Start,
+    Stop,
+    Pause,
+    Resume,
+}
+
+/// Apply an action to several instances concurrently, collecting a per-id result.
+/// Failures are reported per instance and never abort the rest of the batch.
+async fn run_bulk(state: &ServerState, request: BulkRequest, action: BulkAction) -> Response {
+    let ids = if request.all {
+        state.app.instance_ids().await
+    } else {
+        request.ids
+    };
+
+    let results = join_all(ids.into_iter().map(|id| async move {
+        let result = match action {
+            BulkAction::Start => state.app.start_instance(&id).await.map_err(String::from),
+            BulkAction::Stop => state.app.stop_instance(&id).await.map(|_| ()),
+            BulkAction::Pause => state.app.pause_instance(&id).await,
+            BulkAction::Resume => state.app.resume_instance(&id).await.map_err(String::from),
+        };
+        (id, BulkResult::from(result))
+    }))
+    .await;
+
+    ApiSuccess::response(results.into_iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Stop every running instance without deleting it, e.g. before switching VPN servers
+async fn admin_stop_all(State(state): State<ServerState>) -> Response {
+    let results = state.app.stop_all().await;
+    ApiSuccess::response(
+        results
+            .into_iter()
+            .map(|(id, result)| (id, BulkResult::from(result)))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+/// Resume the instances stopped by `admin_stop_all`
+async fn admin_resume_all(State(state): State<ServerState>) -> Response {
+    let results = state.app.resume_all().await;
+    ApiSuccess::response(
+        results
+            .into_iter()
+            .map(|(id, result)| (id, BulkResult::from(result)))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+/// Start several faker instances
+async fn bulk_start(State(state): State<ServerState>, Json(request): Json<BulkRequest>) -> Response {
+    run_bulk(&state, request, BulkAction::Start).await
+}
+
+/// Stop several faker instances
+async fn bulk_stop(State(state): State<ServerState>, Json(request): Json<BulkRequest>) -> Response {
+    run_bulk(&state, request, BulkAction::Stop).await
+}
+
+/// Pause several faker instances
+async fn bulk_pause(State(state): State<ServerState>, Json(request): Json<BulkRequest>) -> Response {
+    run_bulk(&state, request, BulkAction::Pause).await
+}
+
+/// Resume several faker instances
+async fn bulk_resume(State(state): State<ServerState>, Json(request): Json<BulkRequest>) -> Response {
+    run_bulk(&state, request, BulkAction::Resume).await
+}
+
 /// Update a faker instance (send tracker announce)
 async fn update_faker(State(state): State<ServerState>, Path(id): Path<String>) -> Response {
     match state.app.update_instance(&id).await {
@@ -360,102 +1003,89 @@ async fn get_stats(State(state): State<ServerState>, Path(id): Path<String>) ->
     }
 }
 
-/// Get available client types
-async fn get_client_types() -> Response {
-    let types = vec!["utorrent", "qbittorrent", "transmission", "deluge"];
-    ApiSuccess::response(types)
+/// List the emulated clients with their fingerprint details
+async fn get_clients() -> Response {
+    ApiSuccess::response(ClientFingerprint::all())
 }
 
-/// Network status response from gluetun
-#[derive(Serialize)]
-struct NetworkStatus {
-    ip: String,
-    country: Option<String>,
-    organization: Option<String>,
-    is_vpn: bool,
-}
-
-/// Response from gluetun control server /v1/vpn/status
-#[derive(Deserialize)]
-struct GluetunVpnStatus {
-    status: String,
+/// List configured profiles
+async fn list_profiles(State(state): State<ServerState>) -> Response {
+    ApiSuccess::response(state.app.config.profiles.clone())
 }
 
-/// Response from gluetun control server /v1/publicip/ip
+/// Query parameters for network status
 #[derive(Deserialize)]
-struct GluetunPublicIp {
-    public_ip: String,
-    country: Option<String>,
-    organization: Option<String>,
+struct NetworkStatusQuery {
+    /// Look the status up again instead of using the cached one
+    #[serde(default)]
+    refresh: bool,
 }
 
 /// Get network status (public IP and VPN detection)
-/// Uses gluetun's control server for definitive VPN detection.
+/// Uses gluetun's control server for definitive VPN detection, cached for NETWORK_STATUS_CACHE_SECS.
 /// This endpoint is only available when running with Docker + gluetun.
-async fn get_network_status() -> Response {
-    match try_gluetun_detection().await {
-        Some(status) => ApiSuccess::response(status),
-        None => ApiSuccess::response(NetworkStatus {
-            ip: "unknown".into(),
-            country: None,
-            organization: None,
-            is_vpn: false,
-        }),
-    }
-}
-
-
-/// Try to detect VPN status via gluetun's control server
-async fn try_gluetun_detection() -> Option<NetworkStatus> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_millis(1000))
-        .build()
-        .ok()?;
-
-    // Get VPN status
-    let vpn_status = client
-        .get("http://localhost:8000/v1/vpn/status")
-        .send()
-        .await
-        .ok()?
-        .json::<GluetunVpnStatus>()
-        .await
-        .ok()?;
+async fn get_network_status(State(state): State<ServerState>, Query(query): Query<NetworkStatusQuery>) -> Response {
+    ApiSuccess::response(state.app.network_status.get(query.refresh).await)
+}
 
-    let is_vpn = vpn_status.status == "running";
+/// Query parameters for the log stream
+#[derive(Deserialize)]
+struct LogsQuery {
+    /// Least severe level to send (error, warn, info, debug or trace)
+    level: Option<String>,
+    /// Only send events whose target starts with this prefix
+    target: Option<String>,
+}
 
-    // Get public IP (includes country and organization from geolocation)
-    let public_ip = client
-        .get("http://localhost:8000/v1/publicip/ip")
-        .send()
-        .await
-        .ok()?
-        .json::<GluetunPublicIp>()
-        .await
-        .ok()?;
+/// SSE endpoint for streaming logs to the UI
+async fn logs_sse(State(state): State<ServerState>, Query(query): Query<LogsQuery>) -> Response {
+    match LogFilter::parse(query.level.as_deref(), query.target) {
+        Ok(filter) => log_stream(&state, filter),
+        Err(e) => ApiError::response(StatusCode::BAD_REQUEST, e),
+    }
+}
 
-    Some(NetworkStatus {
-        ip: public_ip.public_ip,
-        country: public_ip.country,
-        organization: public_ip.organization,
-        is_vpn,
-    })
+/// SSE endpoint for streaming a single instance's logs
+async fn instance_logs_sse(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> Response {
+    let mut filter = match LogFilter::parse(query.level.as_deref(), query.target) {
+        Ok(filter) => filter,
+        Err(e) => return ApiError::response(StatusCode::BAD_REQUEST, e),
+    };
+    if !state.app.instance_exists(&id).await {
+        return ApiError::response(StatusCode::NOT_FOUND, "Instance not found");
+    }
+    filter.instance_id = Some(id);
+    log_stream(&state, filter)
 }
 
-/// SSE endpoint for streaming logs to the UI
-async fn logs_sse(State(state): State<ServerState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = state.app.subscribe_logs();
+/// Stream buffered and live log events matching `filter`
+fn log_stream(state: &ServerState, filter: LogFilter) -> Response {
+    let (mut history, rx) = state.app.subscribe_logs();
+    history.retain(|log_event| filter.matches(log_event));
 
-    let stream = BroadcastStream::new(rx).filter_map(|result| {
-        result.ok().map(|log_event| {
-            Ok(Event::default()
-                .event("log")
-                .json_data(&log_event)
-                .unwrap_or_else(|_| Event::default()))
-        })
+    // Replay recent history first so the UI has context, then stream live events
+    let live = BroadcastStream::new(rx).filter_map(move |result| {
+        result
+            .ok()
+            .filter(|log_event| filter.matches(log_event))
+            .map(|log_event| Ok::<_, Infallible>(log_sse_event(&log_event)))
     });
+    let stream = tokio_stream::iter(history)
+        .map(|log_event| Ok(log_sse_event(&log_event)))
+        .chain(live);
 
-    Sse::new(stream).keep_alive(KeepAlive::default())
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+fn log_sse_event(log_event: &LogEvent) -> Event {
+    Event::default()
+        .event("log")
+        .json_data(log_event)
+        .unwrap_or_else(|_| Event::default())
 }
 
 /// SSE endpoint for streaming instance events to the UI (for real-time sync)
@@ -500,3 +1130,544 @@ async fn delete_watch_file(State(state): State<ServerState>, Path(filename): Pat
         Err(e) => ApiError::response(StatusCode::NOT_FOUND, e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_etag_response() {
+        let respond =
+            |headers: &HeaderMap, data: Vec<&str>| etag_response(headers, &data, ApiSuccess::new(data.clone()));
+        let response = respond(&HeaderMap::new(), vec!["a", "b"]);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        // Same data and a matching tag: nothing to send
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, format!("\"other\", W/{}", etag).parse().unwrap());
+        let response = respond(&headers, vec!["a", "b"]);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+        // Changed data gets a new tag and a full response
+        let response = respond(&headers, vec!["a", "c"]);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[header::ETAG], etag.as_str());
+
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"stale\"", &etag));
+    }
+
+    #[tokio::test]
+    async fn test_list_etag_ignores_tick() {
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        state
+            .app
+            .create_idle_instance("a", TorrentInfo::fixture("http://127.0.0.1:1/announce"))
+            .await
+            .unwrap();
+        let instance = state.app.get_instance("a").await.unwrap();
+        let etag = |instance: &InstanceInfo| {
+            let tagged = vec![InstanceTag::from(instance)];
+            let response = etag_response(&HeaderMap::new(), &(1, tagged), ApiSuccess::page(vec![instance], 1));
+            response.headers()[header::ETAG].clone()
+        };
+
+        let mut ticked = instance.clone();
+        ticked.last_tick_unix = Some(1);
+        assert_eq!(etag(&ticked), etag(&instance));
+
+        let mut uploaded = instance.clone();
+        uploaded.stats.uploaded += 1;
+        assert_ne!(etag(&uploaded), etag(&instance));
+    }
+
+    #[test]
+    fn test_instance_error_status() {
+        let status = |e: InstanceError| instance_error_status(&e);
+        assert_eq!(status(InstanceError::NotFound(String::new())), StatusCode::NOT_FOUND);
+        assert_eq!(status(InstanceError::Invalid(String::new())), StatusCode::BAD_REQUEST);
+        assert_eq!(status(InstanceError::LimitReached(String::new())), StatusCode::CONFLICT);
+    }
+
+    fn test_state(data_dir: &std::path::Path) -> ServerState {
+        test_state_with_config(data_dir, rustatio_core::AppConfig::default())
+    }
+
+    fn test_state_with_config(data_dir: &std::path::Path, config: rustatio_core::AppConfig) -> ServerState {
+        let app = crate::state::AppState::new(data_dir.to_str().unwrap(), config);
+        let watch_config = crate::watch::WatchConfig {
+            watch_dir: data_dir.join("watch"),
+            auto_start: false,
+            enabled: false,
+            recursive: false,
+        };
+        ServerState {
+            app: app.clone(),
+            watch: std::sync::Arc::new(tokio::sync::RwLock::new(crate::watch::WatchService::new(
+                watch_config,
+                app,
+            ))),
+            startup: std::sync::Arc::new(tokio::sync::RwLock::new(crate::health::StartupStatus {
+                started_at: std::time::Instant::now(),
+                state_error: None,
+                watch_error: None,
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_rejects_invalid_config() {
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+
+        let invalid = [
+            FakerConfig {
+                port: 80,
+                ..FakerConfig::default()
+            },
+            FakerConfig {
+                completion_percent: 500.0,
+                ..FakerConfig::default()
+            },
+        ];
+        for config in invalid {
+            let response = start_faker(
+                State(state.clone()),
+                Path("a".to_string()),
+                Query(StartFakerQuery { force: false }),
+                Json(StartFakerRequest {
+                    torrent: TorrentInfo::fixture("http://127.0.0.1:1/announce"),
+                    config,
+                }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        assert!(!state.app.instance_exists("a").await, "rejected start creates nothing");
+    }
+
+    #[tokio::test]
+    async fn test_start_duplicate_requires_force() {
+        let announce = test_support::spawn_tracker().await;
+
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        let app = state.app.clone();
+        let torrent = TorrentInfo::fixture(announce);
+        let start = |id: &str, force: bool| {
+            start_faker(
+                State(state.clone()),
+                Path(id.to_string()),
+                Query(StartFakerQuery { force }),
+                Json(StartFakerRequest {
+                    torrent: torrent.clone(),
+                    config: FakerConfig::default(),
+                }),
+            )
+        };
+
+        assert_eq!(start("a", false).await.status(), StatusCode::OK);
+        assert_eq!(start("b", false).await.status(), StatusCode::CONFLICT);
+        assert!(!app.instance_exists("b").await, "rejected start creates nothing");
+        assert_eq!(start("b", true).await.status(), StatusCode::OK);
+
+        app.stop_instance("a").await.unwrap();
+        app.stop_instance("b").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bulk_actions() {
+        let announce = test_support::spawn_tracker().await;
+
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        for (id, info_hash) in [("a", [1u8; 20]), ("b", [2u8; 20])] {
+            let torrent = TorrentInfo {
+                info_hash,
+                ..TorrentInfo::fixture(announce.clone())
+            };
+            state.app.create_idle_instance(id, torrent).await.unwrap();
+        }
+        let ids = |ids: &[&str]| {
+            Json(BulkRequest {
+                ids: ids.iter().map(|id| id.to_string()).collect(),
+                all: false,
+            })
+        };
+        let all = || {
+            Json(BulkRequest {
+                ids: Vec::new(),
+                all: true,
+            })
+        };
+        let outcome = |response: Response| async move {
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            body["data"]
+                .as_object()
+                .unwrap()
+                .iter()
+                .map(|(id, result)| (id.clone(), result["success"].as_bool().unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let faker_state = |id: &'static str| {
+            let app = state.app.clone();
+            async move { app.get_stats(id).await.unwrap().state }
+        };
+
+        // A missing instance fails on its own, the rest of the batch still runs
+        let started = outcome(bulk_start(State(state.clone()), ids(&["a", "b", "missing"])).await).await;
+        assert_eq!(
+            started,
+            vec![("a".into(), true), ("b".into(), true), ("missing".into(), false)]
+        );
+        assert_eq!(faker_state("a").await, FakerState::Running);
+        assert_eq!(faker_state("b").await, FakerState::Running);
+
+        let paused = outcome(bulk_pause(State(state.clone()), ids(&["a"])).await).await;
+        assert_eq!(paused, vec![("a".into(), true)]);
+        assert_eq!(faker_state("a").await, FakerState::Paused);
+        assert_eq!(faker_state("b").await, FakerState::Running);
+
+        let resumed = outcome(bulk_resume(State(state.clone()), ids(&["a", "missing"])).await).await;
+        assert_eq!(resumed, vec![("a".into(), true), ("missing".into(), false)]);
+        assert_eq!(faker_state("a").await, FakerState::Running);
+
+        let stopped = outcome(bulk_stop(State(state.clone()), all()).await).await;
+        assert_eq!(stopped, vec![("a".into(), true), ("b".into(), true)]);
+        assert_eq!(faker_state("a").await, FakerState::Stopped);
+        assert_eq!(faker_state("b").await, FakerState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_apply_profile_status() {
+        let data_dir = test_support::temp_dir();
+        let config_path = data_dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+            version = 1
+
+            [[profiles]]
+            name = "slow"
+            [profiles.faker]
+            default_upload_rate = 10.0
+
+            [[profiles]]
+            name = "broken"
+            [profiles.faker]
+            default_upload_rate = "fast"
+
+            [[profiles]]
+            name = "unknown-version"
+            client_version = "0.0.1"
+            "#,
+        )
+        .unwrap();
+        let config = rustatio_core::AppConfig::load(&config_path).unwrap();
+        let state = test_state_with_config(data_dir.path(), config);
+        state
+            .app
+            .create_idle_instance("a", TorrentInfo::fixture("http://127.0.0.1:1/announce"))
+            .await
+            .unwrap();
+        let apply =
+            |id: &str, name: &str| apply_profile(State(state.clone()), Path((id.to_string(), name.to_string())));
+
+        assert_eq!(apply("a", "slow").await.status(), StatusCode::OK);
+        assert_eq!(apply("a", "broken").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(apply("a", "unknown-version").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(apply("a", "missing").await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(apply("b", "slow").await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_config_update_validated() {
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        state
+            .app
+            .create_idle_instance("a", TorrentInfo::fixture("http://127.0.0.1:1/announce"))
+            .await
+            .unwrap();
+        let update = |id: &str, config: FakerConfig| {
+            update_instance_config(State(state.clone()), Path(id.to_string()), Json(config))
+        };
+
+        let invalid = FakerConfig {
+            port: 80,
+            ..FakerConfig::default()
+        };
+        assert_eq!(update("a", invalid).await.status(), StatusCode::BAD_REQUEST);
+        assert_ne!(state.app.get_instance("a").await.unwrap().config.port, 80);
+        assert_eq!(update("a", FakerConfig::default()).await.status(), StatusCode::OK);
+        assert_eq!(
+            update("b", FakerConfig::default()).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clone_status() {
+        let data_dir = test_support::temp_dir();
+        let mut state = test_state(data_dir.path());
+        state.app.max_instances = Some(2);
+        state
+            .app
+            .create_idle_instance("a", TorrentInfo::fixture("http://127.0.0.1:1/announce"))
+            .await
+            .unwrap();
+        let mut other = TorrentInfo::fixture("http://127.0.0.1:1/announce");
+        other.info_hash = [9u8; 20];
+        state.app.store_torrent("ok", other).await;
+        state
+            .app
+            .store_torrent("udp", TorrentInfo::fixture("udp://127.0.0.1:1"))
+            .await;
+        let clone = |id: &str, torrent_id: &str| {
+            let request = Request::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(format!("{{\"torrent_id\":\"{}\"}}", torrent_id)))
+                .unwrap();
+            clone_instance(State(state.clone()), Path(id.to_string()), request)
+        };
+
+        assert_eq!(clone("missing", "ok").await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(clone("a", "missing").await.status(), StatusCode::NOT_FOUND);
+        // A torrent the faker can't announce for is a bad request, not a missing instance
+        assert_eq!(clone("a", "udp").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(clone("a", "ok").await.status(), StatusCode::OK);
+        assert_eq!(clone("a", "ok").await.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_load_torrent_url() {
+        let announce = "http://tracker.example/announce";
+        let torrent = format!(
+            "d8:announce{}:{}4:infod6:lengthi1024e4:name8:test.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee",
+            announce.len(),
+            announce
+        )
+        .into_bytes();
+        let files = Router::new()
+            .route("/test.torrent", get(move || std::future::ready(torrent.clone())))
+            .route(
+                "/redirect",
+                get(|| async { axum::response::Redirect::temporary("/test.torrent") }),
+            )
+            .route("/loop", get(|| async { axum::response::Redirect::temporary("/loop") }))
+            .route("/garbage.torrent", get(|| async { "not a torrent" }))
+            .route("/missing.torrent", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/large.torrent", get(|| async { vec![0u8; MAX_TORRENT_URL_BYTES + 1] }))
+            .route(
+                "/streamed.torrent",
+                get(|| async {
+                    // No Content-Length, the limit has to hold while reading
+                    let chunk = vec![0u8; MAX_TORRENT_URL_BYTES / 4];
+                    let chunks = futures::stream::iter((0..5).map(move |_| Ok::<_, Infallible>(chunk.clone())));
+                    axum::body::Body::from_stream(chunks)
+                }),
+            );
+        let base = test_support::serve(files).await;
+
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        // The test server is local, so it's only reachable with local addresses allowed
+        let load = |url: String| {
+            let state = state.clone();
+            async move { load_torrent_from_url(&state, &url, true).await }
+        };
+
+        assert_eq!(load(format!("{}/test.torrent", base)).await.status(), StatusCode::OK);
+        assert_eq!(load(format!("{}/redirect", base)).await.status(), StatusCode::OK);
+        assert_eq!(load(format!("{}/loop", base)).await.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            load(format!("{}/garbage.torrent", base)).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            load(format!("{}/missing.torrent", base)).await.status(),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            load(format!("{}/large.torrent", base)).await.status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            load(format!("{}/streamed.torrent", base)).await.status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // Only http(s) is fetched, local files and other protocols are refused up front
+        for url in ["file:///etc/passwd", "ftp://127.0.0.1/test.torrent", "not a url"] {
+            assert_eq!(load(url.to_string()).await.status(), StatusCode::BAD_REQUEST, "{}", url);
+        }
+
+        // The endpoint refuses local and private addresses, including names resolving to them
+        let port = base.rsplit(':').next().unwrap();
+        for url in [
+            format!("{}/test.torrent", base),
+            format!("http://localhost:{}/test.torrent", port),
+            format!("http://[::ffff:127.0.0.1]:{}/test.torrent", port),
+            "http://169.254.169.254/latest/meta-data".to_string(),
+            "http://[::1]/test.torrent".to_string(),
+        ] {
+            let response =
+                load_torrent_url(State(state.clone()), Json(LoadTorrentUrlRequest { url: url.clone() })).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_is_public_addr() {
+        for ip in [
+            "93.184.215.14",
+            "2606:2800:21f:cb07:6820:80da:af6b:8b2c",
+            "::ffff:93.184.215.14",
+        ] {
+            assert!(is_public_addr(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:192.168.1.1",
+        ] {
+            assert!(!is_public_addr(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readiness_retries_failed_startup() {
+        let data_dir = test_support::temp_dir();
+        let mut state = test_state(data_dir.path());
+        // A file in the way of the watch directory, like a missing mount
+        let blocker = data_dir.path().join("blocker");
+        std::fs::write(&blocker, b"").unwrap();
+        let watch_config = crate::watch::WatchConfig {
+            watch_dir: blocker.join("watch"),
+            auto_start: false,
+            enabled: true,
+            recursive: false,
+        };
+        let mut watch = crate::watch::WatchService::new(watch_config, state.app.clone());
+        let watch_error = watch.start().await.err();
+        assert!(watch_error.is_some());
+        state.watch = std::sync::Arc::new(tokio::sync::RwLock::new(watch));
+        {
+            let mut startup = state.startup.write().await;
+            startup.state_error = Some("state.json is unreadable".to_string());
+            startup.watch_error = watch_error;
+        }
+        state
+            .app
+            .create_idle_instance("a", TorrentInfo::fixture("http://127.0.0.1:1/announce"))
+            .await
+            .unwrap();
+
+        // The state loads now, the watch directory still can't be created
+        assert_eq!(
+            readiness(State(state.clone())).await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert!(state.startup.read().await.state_error.is_none());
+        assert!(state.startup.read().await.watch_error.is_some());
+        assert!(state.app.get_instance("a").await.is_some());
+
+        std::fs::remove_file(&blocker).unwrap();
+        assert_eq!(readiness(State(state.clone())).await.status(), StatusCode::OK);
+        assert!(state.startup.read().await.watch_error.is_none());
+        state.watch.write().await.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_instance_logs_sse() {
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        for (id, info_hash) in [("a", [1u8; 20]), ("b", [2u8; 20])] {
+            let torrent = TorrentInfo {
+                info_hash,
+                ..TorrentInfo::fixture("http://127.0.0.1:1/announce")
+            };
+            state.app.create_idle_instance(id, torrent).await.unwrap();
+        }
+        let publish = |id: &str, message: &str| {
+            let event = LogEvent::new("info", message.to_string()).with_instance_id(id);
+            state.app.log_history.publish(&state.app.log_sender, event);
+        };
+        let logs = |id: &str| {
+            let query = LogsQuery {
+                level: None,
+                target: None,
+            };
+            instance_logs_sse(State(state.clone()), Path(id.to_string()), Query(query))
+        };
+
+        assert_eq!(logs("missing").await.status(), StatusCode::NOT_FOUND);
+
+        publish("a", "a buffered");
+        publish("b", "b buffered");
+        let response = logs("a").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        publish("b", "b live");
+        publish("a", "a live");
+
+        // The buffered lines come first, then live ones, each filtered to the instance
+        let mut body = response.into_body().into_data_stream();
+        let mut text = String::new();
+        while !text.contains("a live") {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), body.next())
+                .await
+                .expect("live line arrives")
+                .unwrap()
+                .unwrap();
+            text.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        assert!(text.find("a buffered").unwrap() < text.find("a live").unwrap());
+        assert!(!text.contains("b buffered"));
+        assert!(!text.contains("b live"));
+    }
+
+    #[tokio::test]
+    async fn test_get_instance() {
+        let data_dir = test_support::temp_dir();
+        let state = test_state(data_dir.path());
+        let torrent = TorrentInfo::fixture("http://127.0.0.1:1/announce");
+        state.app.create_idle_instance("a", torrent).await.unwrap();
+        state
+            .app
+            .update_instance_labels("a", vec!["movies".to_string()])
+            .await
+            .unwrap();
+
+        let response = get_instance(State(state.clone()), Path("a".to_string())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["id"], "a");
+        assert_eq!(body["data"]["torrent"]["name"], "test");
+        assert_eq!(body["data"]["labels"], serde_json::json!(["movies"]));
+        assert_eq!(body["data"]["stats"]["state"], "Idle");
+
+        let response = get_instance(State(state.clone()), Path("missing".to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+    }
+}
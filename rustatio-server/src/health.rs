@@ -0,0 +1,290 @@
+//! Detailed health reporting for external monitoring.
+//!
+//! Each running instance's background loop records a `last_tick_unix` timestamp on every
+//! iteration. An instance that is Running but hasn't ticked for longer than
+//! `HEALTH_STALE_AFTER_SECS` (default: twice `UPDATE_INTERVAL_SECS`, at least 60) is reported
+//! as stale, and the overall status becomes `degraded`.
+//!
+//! The readiness report (`/ready`) additionally covers startup: it is not ready when the
+//! saved state couldn't be loaded or the watch folder service failed to start, and retries
+//! those steps on each call. Persistence failures (unwritable data directory, failed saves)
+//! are reported as degraded.
+
+use crate::network::VpnGateStatus;
+use crate::persistence::{now_timestamp, InstanceSource};
+use crate::state::{update_interval_secs, InstanceInfo};
+use crate::watch::WatchStatus;
+use rustatio_core::FakerState;
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Smallest default number of seconds without a tick before a running instance is considered stale
+const MIN_STALE_AFTER_SECS: u64 = 60;
+
+/// Cached staleness threshold from environment
+static STALE_AFTER_SECS: OnceLock<u64> = OnceLock::new();
+
+/// Get the staleness threshold in seconds, caching the result
+pub fn stale_after_secs() -> u64 {
+    *STALE_AFTER_SECS.get_or_init(|| {
+        std::env::var("HEALTH_STALE_AFTER_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or_else(|| default_stale_after_secs(update_interval_secs()))
+    })
+}
+
+/// Default staleness threshold: two missed background ticks, but never less than a minute so a
+/// slow announce doesn't flag short intervals
+fn default_stale_after_secs(update_interval_secs: u64) -> u64 {
+    update_interval_secs.saturating_mul(2).max(MIN_STALE_AFTER_SECS)
+}
+
+/// Overall server health status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// All running instances are ticking
+    Ok,
+    /// At least one running instance has a stale background loop, or the VPN gate is blocking announces
+    Degraded,
+}
+
+/// Liveness details for a single instance
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceHealth {
+    pub id: String,
+    pub name: String,
+    pub state: FakerState,
+    pub last_tick_unix: Option<u64>,
+    /// True when the instance is Running but its background loop hasn't ticked recently
+    pub stale: bool,
+}
+
+/// Detailed health report
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub now_unix: u64,
+    pub stale_after_secs: u64,
+    pub instances: Vec<InstanceHealth>,
+    pub instance_count: usize,
+    /// MAX_INSTANCES (only when set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_instances: Option<usize>,
+    /// VPN gate state (only when REQUIRE_VPN is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vpn_gate: Option<VpnGateStatus>,
+}
+
+impl HealthReport {
+    /// Build a health report from the current instance list
+    pub fn from_instances(instances: &[InstanceInfo]) -> Self {
+        let now = now_timestamp();
+        let stale_after = stale_after_secs();
+
+        let instances: Vec<InstanceHealth> = instances
+            .iter()
+            .map(|instance| {
+                // Replayed demo instances have no background loop once their timeline ends
+                let running = matches!(instance.stats.state, FakerState::Running | FakerState::Seeding)
+                    && instance.source != InstanceSource::Replay;
+                let stale = running
+                    && instance
+                        .last_tick_unix
+                        .is_none_or(|tick| now.saturating_sub(tick) > stale_after);
+
+                InstanceHealth {
+                    id: instance.id.clone(),
+                    name: instance.torrent.name.clone(),
+                    state: instance.stats.state.clone(),
+                    last_tick_unix: instance.last_tick_unix,
+                    stale,
+                }
+            })
+            .collect();
+
+        let status = if instances.iter().any(|i| i.stale) {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Ok
+        };
+
+        Self {
+            status,
+            now_unix: now,
+            stale_after_secs: stale_after,
+            instance_count: instances.len(),
+            instances,
+            max_instances: None,
+            vpn_gate: None,
+        }
+    }
+
+    /// Include the MAX_INSTANCES cap next to the instance count
+    pub fn with_max_instances(mut self, max_instances: Option<usize>) -> Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    /// Include the VPN gate, degrading the status while it blocks announces
+    pub fn with_vpn_gate(mut self, gate: VpnGateStatus) -> Self {
+        if gate.is_blocking() {
+            self.status = HealthStatus::Degraded;
+        }
+        self.vpn_gate = Some(gate);
+        self
+    }
+}
+
+/// Outcome of server startup, updated when the readiness check retries a failed step
+#[derive(Debug, Clone)]
+pub struct StartupStatus {
+    pub started_at: Instant,
+    /// Why the saved state couldn't be loaded
+    pub state_error: Option<String>,
+    /// Why the watch folder service failed to start
+    pub watch_error: Option<String>,
+}
+
+/// Number of instances in each state
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InstanceCounts {
+    pub total: usize,
+    pub running: usize,
+    pub paused: usize,
+    pub stopped: usize,
+    pub errored: usize,
+}
+
+impl InstanceCounts {
+    fn from_instances(instances: &[InstanceInfo]) -> Self {
+        let mut counts = Self {
+            total: instances.len(),
+            ..Self::default()
+        };
+        for instance in instances {
+            match instance.stats.state {
+                FakerState::Running | FakerState::Seeding => counts.running += 1,
+                FakerState::Paused => counts.paused += 1,
+                FakerState::Idle | FakerState::Stopped | FakerState::Completed => counts.stopped += 1,
+                FakerState::Errored => counts.errored += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// Readiness report, served with 503 when `ready` is false
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub uptime_secs: u64,
+    pub state_loaded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_error: Option<String>,
+    /// True when the state can't currently be saved
+    pub persistence_degraded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistence_error: Option<String>,
+    pub instances: InstanceCounts,
+    pub watch: WatchStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch_error: Option<String>,
+}
+
+impl ReadinessReport {
+    pub fn new(
+        startup: &StartupStatus,
+        persistence_error: Option<String>,
+        instances: &[InstanceInfo],
+        watch: WatchStatus,
+    ) -> Self {
+        Self {
+            ready: startup.state_error.is_none() && startup.watch_error.is_none(),
+            uptime_secs: startup.started_at.elapsed().as_secs(),
+            state_loaded: startup.state_error.is_none(),
+            state_error: startup.state_error.clone(),
+            persistence_degraded: persistence_error.is_some(),
+            persistence_error,
+            instances: InstanceCounts::from_instances(instances),
+            watch,
+            watch_error: startup.watch_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch_status() -> WatchStatus {
+        WatchStatus {
+            enabled: true,
+            watch_dir: "/torrents".to_string(),
+            auto_start: false,
+            file_count: 0,
+            loaded_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_default_stale_after_follows_update_interval() {
+        assert_eq!(default_stale_after_secs(5), 60);
+        assert_eq!(default_stale_after_secs(30), 60);
+        assert_eq!(default_stale_after_secs(120), 240);
+        assert_eq!(default_stale_after_secs(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_readiness_report() {
+        let mut startup = StartupStatus {
+            started_at: Instant::now(),
+            state_error: None,
+            watch_error: None,
+        };
+        let report = ReadinessReport::new(&startup, None, &[], watch_status());
+        assert!(report.ready);
+        assert!(report.state_loaded);
+        assert_eq!(report.instances.total, 0);
+
+        startup.watch_error = Some("permission denied".to_string());
+        let report = ReadinessReport::new(&startup, None, &[], watch_status());
+        assert!(!report.ready);
+        assert!(report.state_loaded);
+
+        startup.watch_error = None;
+        startup.state_error = Some("state.json is unreadable".to_string());
+        let report = ReadinessReport::new(&startup, None, &[], watch_status());
+        assert!(!report.ready);
+        assert!(!report.state_loaded);
+
+        // Failing saves degrade the report without making the server unready
+        startup.state_error = None;
+        let error = Some("data directory is not writable".to_string());
+        let report = ReadinessReport::new(&startup, error, &[], watch_status());
+        assert!(report.ready);
+        assert!(report.persistence_degraded);
+    }
+
+    #[test]
+    fn test_vpn_gate_degrades_health() {
+        let gate = |vpn_detected| VpnGateStatus {
+            required: true,
+            vpn_detected,
+            last_checked_unix: None,
+            held_instances: Vec::new(),
+        };
+
+        let report = HealthReport::from_instances(&[]).with_vpn_gate(gate(Some(true)));
+        assert_eq!(report.status, HealthStatus::Ok);
+        assert!(report.vpn_gate.is_some());
+
+        let report = HealthReport::from_instances(&[]).with_vpn_gate(gate(Some(false)));
+        assert_eq!(report.status, HealthStatus::Degraded);
+        let report = HealthReport::from_instances(&[]).with_vpn_gate(gate(None));
+        assert_eq!(report.status, HealthStatus::Degraded);
+    }
+}
@@ -1,20 +1,24 @@
-use crate::state::LogEvent;
+use crate::state::{LogEvent, LogHistory};
+use rustatio_core::logger::instance_context;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::field::{Field, Visit};
 use tracing::{Event, Level, Subscriber};
+use tracing_log::NormalizeEvent;
 use tracing_subscriber::layer::Context;
 use tracing_subscriber::Layer;
 
-/// Custom tracing layer that forwards logs to a broadcast channel
+/// Custom tracing layer that forwards logs to a broadcast channel and the recent log history
 pub struct BroadcastLayer {
     sender: Arc<broadcast::Sender<LogEvent>>,
+    history: LogHistory,
 }
 
 impl BroadcastLayer {
-    pub fn new(sender: broadcast::Sender<LogEvent>) -> Self {
+    pub fn new(sender: broadcast::Sender<LogEvent>, history: LogHistory) -> Self {
         Self {
             sender: Arc::new(sender),
+            history,
         }
     }
 }
@@ -77,7 +81,57 @@ impl<S: Subscriber> Layer<S> for BroadcastLayer {
             Level::TRACE => "trace",
         };
 
-        // Send to broadcast channel (ignore errors - no subscribers is fine)
-        let _ = self.sender.send(LogEvent::new(level, visitor.message));
+        // Bridged log crate events carry their real target (e.g. "rustatio_core::faker") in the normalized metadata
+        let normalized = event.normalized_metadata();
+        let source_target = normalized.as_ref().map_or(target, |meta| meta.target());
+
+        // Events are dispatched on the logging thread, so its instance context still applies.
+        // The instance ID becomes a field and its "[Instance ...] " prefix is dropped from the message.
+        let log_event = match instance_context() {
+            Some(id) => {
+                let prefix = format!("[Instance {}] ", id);
+                let message = visitor
+                    .message
+                    .strip_prefix(&prefix)
+                    .map_or_else(|| visitor.message.clone(), str::to_string);
+                LogEvent::new(level, message).with_instance_id(id)
+            }
+            None => LogEvent::new(level, visitor.message),
+        }
+        .with_target(source_target);
+
+        // Buffer and send to broadcast channel
+        self.history.publish(&self.sender, log_event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustatio_core::logger::set_instance_context_str;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_instance_context_becomes_field() {
+        let (sender, _) = broadcast::channel(16);
+        let history = LogHistory::new(10);
+        let subscriber = tracing_subscriber::registry().with(BroadcastLayer::new(sender.clone(), history.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            set_instance_context_str(Some("abc123"));
+            tracing::info!(target: "rustatio_core::faker", "[Instance abc123] Announce successful");
+            set_instance_context_str(None);
+            tracing::warn!(target: "rustatio_core::tracker", "No instance");
+            // Other targets are not forwarded
+            tracing::info!(target: "hyper", "ignored");
+        });
+
+        let (events, _) = history.subscribe(&sender);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message, "Announce successful");
+        assert_eq!(events[0].instance_id.as_deref(), Some("abc123"));
+        assert_eq!(events[0].target.as_deref(), Some("rustatio_core::faker"));
+        assert_eq!(events[1].message, "No instance");
+        assert_eq!(events[1].instance_id, None);
     }
 }
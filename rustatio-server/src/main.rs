@@ -1,9 +1,15 @@
 mod api;
 mod auth;
+mod health;
 mod log_layer;
+mod network;
 mod persistence;
+mod rate_limit;
+mod replay;
 mod state;
 mod static_files;
+#[cfg(test)]
+mod test_support;
 mod watch;
 
 use axum::{middleware, routing::get, Router};
@@ -16,6 +22,7 @@ use tower_http::trace::TraceLayer;
 use tracing_subscriber::layer::SubscriberExt;
 use rustatio_core::AppConfig;
 
+use crate::health::StartupStatus;
 use crate::log_layer::BroadcastLayer;
 use crate::state::AppState;
 use crate::watch::{WatchConfig, WatchDisabledReason, WatchService};
@@ -25,10 +32,13 @@ use crate::watch::{WatchConfig, WatchDisabledReason, WatchService};
 pub struct ServerState {
     pub app: AppState,
     pub watch: Arc<RwLock<WatchService>>,
+    pub startup: Arc<RwLock<StartupStatus>>,
 }
 
 #[tokio::main]
 async fn main() {
+    let started_at = std::time::Instant::now();
+
     // Bridge log crate to tracing FIRST (before any subscriber)
     tracing_log::LogTracer::init().expect("Failed to set logger");
 
@@ -47,23 +57,48 @@ async fn main() {
     let default_filter = "rustatio_server=info,rustatio_core=trace,log=trace,tower_http=info,hyper=info,reqwest=info";
     let subscriber = tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| default_filter.into()))
-        .with(BroadcastLayer::new(state.log_sender.clone()))
+        .with(BroadcastLayer::new(state.log_sender.clone(), state.log_history.clone()))
         .with(tracing_subscriber::fmt::layer());
 
     // Set as global default
     tracing::subscriber::set_global_default(subscriber).expect("Failed to set subscriber");
 
+    // Check for a VPN before restored instances get a chance to announce
+    if state.vpn_gate.required() {
+        tracing::info!("REQUIRE_VPN is set, announcing only while a VPN is detected");
+        state.check_vpn_gate().await;
+    }
+
+    // Make sure state can be saved before restored instances start changing it
+    if let Err(e) = state.check_persistence().await {
+        tracing::error!(
+            "PERSISTENCE UNAVAILABLE: {}. Instance changes will NOT be saved and will be lost on restart. \
+            Make sure DATA_DIR ({}) is mounted and writable.",
+            e,
+            data_dir
+        );
+        if persistence::is_persistence_required() {
+            tracing::error!("REQUIRE_PERSISTENCE is set, exiting");
+            std::process::exit(1);
+        }
+    }
+
     // Load saved state and restore instances
-    match state.load_saved_state().await {
+    let state_error = match state.load_saved_state().await {
         Ok(count) => {
             if count > 0 {
                 tracing::info!("Restored {} instance(s) from saved state", count);
             }
+            None
         }
         Err(e) => {
             tracing::error!("Failed to load saved state: {}", e);
+            Some(e)
         }
-    }
+    };
+
+    state.spawn_vpn_gate_monitor();
+    state.spawn_scrape_monitor();
 
     // Initialize and start watch folder service
     let (watch_config, disabled_reason) = WatchConfig::from_env();
@@ -86,15 +121,24 @@ async fn main() {
     }
 
     let mut watch_service = WatchService::new(watch_config.clone(), state.clone());
-    if let Err(e) = watch_service.start().await {
-        tracing::error!("Failed to start watch folder service: {}", e);
-    }
+    let watch_error = match watch_service.start().await {
+        Ok(()) => None,
+        Err(e) => {
+            tracing::error!("Failed to start watch folder service: {}", e);
+            Some(e)
+        }
+    };
     let watch_service = Arc::new(RwLock::new(watch_service));
 
     // Create combined server state
     let server_state = ServerState {
         app: state.clone(),
         watch: watch_service.clone(),
+        startup: Arc::new(RwLock::new(StartupStatus {
+            started_at,
+            state_error,
+            watch_error,
+        })),
     };
 
     // Get port from environment or use default
@@ -107,10 +151,20 @@ async fn main() {
     let app = Router::new()
         // Health check (no auth required)
         .route("/health", get(|| async { "OK" }))
-        // Public API routes (no auth required)
-        .nest("/api", api::public_router())
-        // Protected API routes (auth required when AUTH_TOKEN is set)
-        .nest("/api", api::router().layer(middleware::from_fn(auth::auth_middleware)))
+        // Readiness check with startup and instance details (no auth required)
+        .route("/ready", get(api::readiness))
+        // Public API routes (no auth required, rate limited when RATE_LIMIT_RPM is set)
+        .nest(
+            "/api",
+            api::public_router().layer(middleware::from_fn(rate_limit::rate_limit_middleware)),
+        )
+        // Protected API routes (auth required when AUTH_TOKEN is set, rate limited when RATE_LIMIT_RPM is set)
+        .nest(
+            "/api",
+            api::router()
+                .layer(middleware::from_fn(auth::auth_middleware))
+                .layer(middleware::from_fn(rate_limit::rate_limit_middleware)),
+        )
         // Static files (web UI) - must be last as it catches all other routes (no auth)
         .fallback(static_files::static_handler)
         .layer(cors)
@@ -129,6 +183,21 @@ async fn main() {
         tracing::warn!("Authentication disabled - API is open to all. Set AUTH_TOKEN to enable.");
     }
 
+    // Log rate limiting status
+    if let Some(rpm) = rate_limit::get_rate_limit_rpm() {
+        tracing::info!("Rate limiting enabled: {} requests/minute per client IP", rpm);
+    }
+
+    if let Some(secs) = state::tracker_timeout_secs() {
+        tracing::info!("Tracker request timeout: {}s", secs);
+    }
+
+    tracing::info!(
+        "Background updates every {}s, state saved every {}s",
+        state::update_interval_secs(),
+        state::save_interval_secs()
+    );
+
     // Create shutdown signal channel
     let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
     let state_for_shutdown = state.clone();
@@ -158,7 +227,8 @@ async fn main() {
     });
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app)
+    // Serve with connect info so the rate limiter can key buckets by client IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(async {
             let _ = shutdown_rx.await;
         })
@@ -0,0 +1,893 @@
+//! Network status (public IP and VPN detection) and the optional VPN gate.
+//!
+//! VPN detection asks gluetun's control server (`GLUETUN_CONTROL_URL`, default
+//! `http://localhost:8000`). Without gluetun, `/api/network/status` can fall back to public IP
+//! lookup services over HTTPS (`IP_LOOKUP_PROVIDERS`, tried in order, none by default since
+//! they see the real IP) and guesses the VPN from the network's ASN or organization name.
+//! Extra `organization=Provider` or `AS12345=Provider` entries can be added via
+//! `VPN_PROVIDER_PATTERNS` or a `VPN_PROVIDERS_FILE`. Results are cached for
+//! `NETWORK_STATUS_CACHE_SECS` (default: 60).
+//!
+//! When `REQUIRE_VPN` is set, the server refuses to announce without a detected VPN: starting,
+//! resuming or manually announcing an instance fails, periodic announces are deferred and stops
+//! don't send `stopped`. Announcing re-checks the network when the last check is more than a few
+//! seconds old, and the network is re-checked every `VPN_CHECK_INTERVAL_SECS` (default: 30).
+//! Running instances are paused when the VPN goes away and resumed once it's back.
+
+use crate::persistence::now_timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Default gluetun control server address
+const DEFAULT_GLUETUN_CONTROL_URL: &str = "http://localhost:8000";
+
+/// Default seconds between VPN gate checks
+const DEFAULT_VPN_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// How long a VPN gate check is reused before announcing checks the network again
+const VPN_GATE_CHECK_REUSE: Duration = Duration::from_secs(5);
+
+/// Default seconds a network status lookup is reused
+const DEFAULT_NETWORK_STATUS_CACHE_SECS: u64 = 60;
+
+/// Longest a failed network status lookup is reused, so a short outage isn't reported for the full TTL
+const FAILED_NETWORK_STATUS_CACHE: Duration = Duration::from_secs(5);
+
+/// Default time a single IP lookup provider gets to answer
+const DEFAULT_IP_LOOKUP_TIMEOUT_MS: u64 = 2000;
+
+/// Default time all IP lookup providers get together
+const DEFAULT_IP_LOOKUP_DEADLINE_MS: u64 = 5000;
+
+/// Known VPN provider patterns, matched against the network's organization name. Hosting
+/// networks that VPNs rent servers from are left out, they'd flag every VPS as a VPN.
+const VPN_PROVIDERS: &[(&str, &str)] = &[
+    ("proton", "ProtonVPN"),
+    ("mullvad", "Mullvad"),
+    ("31173 services", "Mullvad"),
+    ("nordvpn", "NordVPN"),
+    ("packethub", "NordVPN"),
+    ("expressvpn", "ExpressVPN"),
+    ("surfshark", "Surfshark"),
+    ("private internet access", "Private Internet Access"),
+    ("windscribe", "Windscribe"),
+    ("cyberghost", "CyberGhost"),
+    ("ipvanish", "IPVanish"),
+    ("tunnelbear", "TunnelBear"),
+    ("hotspot shield", "Hotspot Shield"),
+    ("vyprvpn", "VyprVPN"),
+    ("hide.me", "Hide.me"),
+    ("perfect privacy", "Perfect Privacy"),
+    ("airvpn", "AirVPN"),
+    ("privatevpn", "PrivateVPN"),
+    ("torguard", "TorGuard"),
+    ("ivpn", "IVPN"),
+    ("ovpn", "OVPN"),
+    ("azirevpn", "AzireVPN"),
+    ("purevpn", "PureVPN"),
+    ("privado", "PrivadoVPN"),
+    ("strongvpn", "StrongVPN"),
+];
+
+/// ASNs run by VPN providers themselves, for lookups that report one
+const VPN_PROVIDER_ASNS: &[(u32, &str)] = &[(39351, "Mullvad"), (62371, "ProtonVPN"), (207137, "NordVPN")];
+
+/// Error returned for announces refused by the VPN gate
+pub const VPN_REQUIRED: &str = "REQUIRE_VPN is set and no VPN is detected, refusing to announce";
+
+/// Cached gluetun control server address from environment
+static GLUETUN_CONTROL_URL: OnceLock<String> = OnceLock::new();
+
+/// Get the gluetun control server address, caching the result
+pub fn gluetun_control_url() -> &'static str {
+    GLUETUN_CONTROL_URL.get_or_init(|| {
+        std::env::var("GLUETUN_CONTROL_URL")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_GLUETUN_CONTROL_URL.to_string())
+    })
+}
+
+/// Whether announcing requires a detected VPN (REQUIRE_VPN)
+pub fn is_vpn_required() -> bool {
+    std::env::var("REQUIRE_VPN")
+        .map(|v| v.trim().eq_ignore_ascii_case("true") || v.trim() == "1")
+        .unwrap_or(false)
+}
+
+/// Seconds between VPN gate checks (VPN_CHECK_INTERVAL_SECS)
+pub fn vpn_check_interval() -> Duration {
+    let secs = std::env::var("VPN_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(DEFAULT_VPN_CHECK_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// How long a network status lookup is reused (NETWORK_STATUS_CACHE_SECS, 0 disables caching)
+pub fn network_status_cache_ttl() -> Duration {
+    let secs = std::env::var("NETWORK_STATUS_CACHE_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_NETWORK_STATUS_CACHE_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Network status from gluetun or an IP lookup provider
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkStatus {
+    pub ip: String,
+    pub country: Option<String>,
+    pub organization: Option<String>,
+    pub is_vpn: bool,
+    /// Autonomous system number of the network, when the lookup reports one
+    pub asn: Option<u32>,
+    /// VPN provider guessed from the ASN or organization (IP lookup fallback only)
+    pub vpn_provider: Option<String>,
+}
+
+impl NetworkStatus {
+    /// Status reported when neither gluetun nor any IP lookup provider answered
+    pub fn unknown() -> Self {
+        Self {
+            ip: "unknown".into(),
+            country: None,
+            organization: None,
+            is_vpn: false,
+            asn: None,
+            vpn_provider: None,
+        }
+    }
+
+    /// Status from an IP lookup, with the VPN guessed from the ASN or organization
+    fn from_lookup(
+        ip: String,
+        country: Option<String>,
+        organization: Option<String>,
+        asn: Option<u32>,
+        vpn_patterns: &VpnPatterns,
+    ) -> Self {
+        let vpn_provider = vpn_patterns.detect(organization.as_deref(), asn);
+        Self {
+            ip,
+            country,
+            organization,
+            is_vpn: vpn_provider.is_some(),
+            asn,
+            vpn_provider,
+        }
+    }
+}
+
+/// ASN at the start of a string like `AS9009 M247 Ltd`
+fn parse_asn(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let digits = value
+        .get(..2)
+        .filter(|p| p.eq_ignore_ascii_case("as"))
+        .map(|_| &value[2..])?;
+    let end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+    digits[..end].parse().ok()
+}
+
+/// Organization name without a leading ASN (`AS9009 M247 Ltd` -> `M247 Ltd`)
+fn strip_asn(organization: &str) -> &str {
+    let organization = organization.trim();
+    if parse_asn(organization).is_none() {
+        return organization;
+    }
+    organization
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, name)| name.trim())
+}
+
+/// VPN provider patterns: the built-in list plus user entries
+#[derive(Debug, Clone)]
+pub struct VpnPatterns {
+    /// Lowercase organization name patterns, matched as substrings
+    organizations: Vec<(String, String)>,
+    asns: Vec<(u32, String)>,
+}
+
+impl VpnPatterns {
+    pub fn builtin() -> Self {
+        Self {
+            organizations: VPN_PROVIDERS
+                .iter()
+                .map(|(pattern, provider)| (pattern.to_string(), provider.to_string()))
+                .collect(),
+            asns: VPN_PROVIDER_ASNS
+                .iter()
+                .map(|(asn, provider)| (*asn, provider.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Built-in list plus the entries from VPN_PROVIDER_PATTERNS and VPN_PROVIDERS_FILE
+    pub fn from_env() -> Self {
+        let mut patterns = Self::builtin();
+        if let Ok(entries) = std::env::var("VPN_PROVIDER_PATTERNS") {
+            patterns.add_entries(&entries, "VPN_PROVIDER_PATTERNS");
+        }
+        if let Ok(path) = std::env::var("VPN_PROVIDERS_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(entries) => patterns.add_entries(&entries, &path),
+                Err(e) => tracing::warn!("Failed to read VPN_PROVIDERS_FILE {}: {}", path, e),
+            }
+        }
+        patterns
+    }
+
+    /// Add `pattern=Provider` entries separated by commas or newlines (`#` starts a comment).
+    /// A pattern like `AS12345` matches that ASN, anything else part of the organization name.
+    /// User entries take precedence over built-in ones.
+    pub fn add_entries(&mut self, entries: &str, source: &str) {
+        let mut organizations = Vec::new();
+        let mut asns = Vec::new();
+        for entry in entries.split(['\n', ',']) {
+            let entry = entry.split('#').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let Some((pattern, provider)) = entry.split_once('=') else {
+                tracing::warn!(
+                    "Ignoring VPN provider entry '{}' from {} (expected pattern=Provider)",
+                    entry,
+                    source
+                );
+                continue;
+            };
+            let (pattern, provider) = (pattern.trim(), provider.trim().to_string());
+            if pattern.is_empty() || provider.is_empty() {
+                tracing::warn!(
+                    "Ignoring VPN provider entry '{}' from {} (empty pattern or provider)",
+                    entry,
+                    source
+                );
+                continue;
+            }
+            match parse_asn(pattern).filter(|_| pattern[2..].chars().all(|c| c.is_ascii_digit())) {
+                Some(asn) => asns.push((asn, provider)),
+                None => organizations.push((strip_asn(pattern).to_lowercase(), provider)),
+            }
+        }
+        if !organizations.is_empty() || !asns.is_empty() {
+            tracing::info!(
+                "Loaded {} custom VPN provider pattern(s) from {}",
+                organizations.len() + asns.len(),
+                source
+            );
+        }
+        organizations.append(&mut self.organizations);
+        self.organizations = organizations;
+        asns.append(&mut self.asns);
+        self.asns = asns;
+    }
+
+    /// VPN provider matching the ASN, or whose pattern appears in the organization name (case-insensitive)
+    pub fn detect(&self, organization: Option<&str>, asn: Option<u32>) -> Option<String> {
+        if let Some(asn) = asn {
+            if let Some((_, provider)) = self.asns.iter().find(|(known, _)| *known == asn) {
+                return Some(provider.clone());
+            }
+        }
+        let organization = strip_asn(organization?).to_lowercase();
+        self.organizations
+            .iter()
+            .find(|(pattern, _)| organization.contains(pattern.as_str()))
+            .map(|(_, provider)| provider.clone())
+    }
+}
+
+/// Response from gluetun control server /v1/vpn/status
+#[derive(Deserialize)]
+struct GluetunVpnStatus {
+    status: String,
+}
+
+/// Response from gluetun control server /v1/publicip/ip
+#[derive(Deserialize)]
+struct GluetunPublicIp {
+    public_ip: String,
+    country: Option<String>,
+    organization: Option<String>,
+}
+
+/// Try to detect VPN status via gluetun's control server at `control_url`
+pub async fn detect_network_status(control_url: &str) -> Option<NetworkStatus> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(1000))
+        .build()
+        .ok()?;
+
+    // Get VPN status
+    let vpn_status = client
+        .get(format!("{}/v1/vpn/status", control_url))
+        .send()
+        .await
+        .ok()?
+        .json::<GluetunVpnStatus>()
+        .await
+        .ok()?;
+
+    let is_vpn = vpn_status.status == "running";
+
+    // Get public IP (includes country and organization from geolocation)
+    let public_ip = client
+        .get(format!("{}/v1/publicip/ip", control_url))
+        .send()
+        .await
+        .ok()?
+        .json::<GluetunPublicIp>()
+        .await
+        .ok()?;
+
+    Some(NetworkStatus {
+        ip: public_ip.public_ip,
+        country: public_ip.country,
+        organization: public_ip.organization,
+        is_vpn,
+        asn: None,
+        vpn_provider: None,
+    })
+}
+
+/// Public IP lookup service used when gluetun isn't available. Only services reachable over HTTPS,
+/// the lookup reveals the real IP to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpLookupProvider {
+    /// ipinfo.io: IP, country and organization
+    IpInfo,
+    /// ipify.org: IP only
+    Ipify,
+}
+
+/// Response from ipinfo.io and ipify.org
+#[derive(Deserialize)]
+struct IpInfoResponse {
+    ip: String,
+    country: Option<String>,
+    /// e.g. `AS9009 M247 Europe SRL`
+    org: Option<String>,
+}
+
+impl IpLookupProvider {
+    pub const ALL: [IpLookupProvider; 2] = [Self::IpInfo, Self::Ipify];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IpInfo => "ipinfo",
+            Self::Ipify => "ipify",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn url(self) -> &'static str {
+        match self {
+            Self::IpInfo => "https://ipinfo.io/json",
+            Self::Ipify => "https://api.ipify.org?format=json",
+        }
+    }
+
+    async fn lookup(self, client: &reqwest::Client, url: &str, vpn_patterns: &VpnPatterns) -> Option<NetworkStatus> {
+        let response = client.get(url).send().await.ok()?.error_for_status().ok()?;
+        let data = response.json::<IpInfoResponse>().await.ok()?;
+        let asn = data.org.as_deref().and_then(parse_asn);
+        Some(NetworkStatus::from_lookup(
+            data.ip,
+            data.country,
+            data.org,
+            asn,
+            vpn_patterns,
+        ))
+    }
+}
+
+/// IP lookup providers in the order to try them (IP_LOOKUP_PROVIDERS, e.g. `ipinfo,ipify`), none when unset
+pub fn ip_lookup_providers() -> Vec<IpLookupProvider> {
+    let Ok(value) = std::env::var("IP_LOOKUP_PROVIDERS") else {
+        return Vec::new();
+    };
+    let mut providers = Vec::new();
+    for name in value.split(',').filter(|name| !name.trim().is_empty()) {
+        match IpLookupProvider::from_name(name) {
+            Some(provider) if !providers.contains(&provider) => providers.push(provider),
+            Some(_) => {}
+            None => tracing::warn!(
+                "Ignoring unknown IP lookup provider '{}' in IP_LOOKUP_PROVIDERS",
+                name.trim()
+            ),
+        }
+    }
+    providers
+}
+
+fn env_millis(name: &str, default: u64) -> Duration {
+    let millis = std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&ms| ms > 0)
+        .unwrap_or(default);
+    Duration::from_millis(millis)
+}
+
+/// Public IP lookup through a list of providers, tried in order
+#[derive(Debug, Clone)]
+pub struct IpLookup {
+    /// Providers and the URL each is queried at
+    providers: Vec<(IpLookupProvider, String)>,
+    /// Time a single provider gets before the next one is tried
+    provider_timeout: Duration,
+    /// Time all providers get together
+    deadline: Duration,
+    vpn_patterns: VpnPatterns,
+    /// Refuse plain HTTP, the lookup reveals the real IP
+    https_only: bool,
+}
+
+impl IpLookup {
+    pub fn new(providers: Vec<(IpLookupProvider, String)>, provider_timeout: Duration, deadline: Duration) -> Self {
+        Self {
+            providers,
+            provider_timeout,
+            deadline,
+            vpn_patterns: VpnPatterns::builtin(),
+            https_only: true,
+        }
+    }
+
+    /// Allow plain HTTP, for local test servers
+    #[cfg(test)]
+    fn allow_http(mut self) -> Self {
+        self.https_only = false;
+        self
+    }
+
+    pub fn with_vpn_patterns(mut self, vpn_patterns: VpnPatterns) -> Self {
+        self.vpn_patterns = vpn_patterns;
+        self
+    }
+
+    /// Lookup configured from IP_LOOKUP_PROVIDERS, IP_LOOKUP_TIMEOUT_MS, IP_LOOKUP_DEADLINE_MS
+    /// and the custom VPN provider patterns
+    pub fn from_env() -> Self {
+        let providers = ip_lookup_providers()
+            .into_iter()
+            .map(|provider| (provider, provider.url().to_string()))
+            .collect();
+        Self::new(
+            providers,
+            env_millis("IP_LOOKUP_TIMEOUT_MS", DEFAULT_IP_LOOKUP_TIMEOUT_MS),
+            env_millis("IP_LOOKUP_DEADLINE_MS", DEFAULT_IP_LOOKUP_DEADLINE_MS),
+        )
+        .with_vpn_patterns(VpnPatterns::from_env())
+    }
+
+    /// First provider's answer, even an IP-only one, so a slow provider doesn't hold up the result.
+    /// None when no provider answered before the deadline.
+    pub async fn run(&self) -> Option<NetworkStatus> {
+        let client = reqwest::Client::builder().https_only(self.https_only).build().ok()?;
+        let deadline = tokio::time::Instant::now() + self.deadline;
+
+        for (provider, url) in &self.providers {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                tracing::warn!("IP lookup deadline of {:?} reached", self.deadline);
+                break;
+            }
+            let timeout = self.provider_timeout.min(remaining);
+            match tokio::time::timeout(timeout, provider.lookup(&client, url, &self.vpn_patterns)).await {
+                Ok(Some(status)) => return Some(status),
+                Ok(None) => tracing::debug!("IP lookup via {} failed, trying the next provider", provider.name()),
+                Err(_) => tracing::debug!("IP lookup via {} timed out after {:?}", provider.name(), timeout),
+            }
+        }
+        None
+    }
+}
+
+/// Last network status lookup, reused until it expires so polling clients don't hit gluetun every time
+#[derive(Debug)]
+pub struct NetworkStatusCache {
+    control_url: String,
+    /// Used when gluetun doesn't answer
+    fallback: IpLookup,
+    ttl: Duration,
+    /// Held across a lookup so concurrent requests share it, with when it expires
+    cached: tokio::sync::Mutex<Option<(NetworkStatus, Instant)>>,
+}
+
+impl NetworkStatusCache {
+    pub fn new(control_url: impl Into<String>, fallback: IpLookup, ttl: Duration) -> Self {
+        Self {
+            control_url: control_url.into(),
+            fallback,
+            ttl,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Cache configured from GLUETUN_CONTROL_URL, the IP lookup variables and NETWORK_STATUS_CACHE_SECS
+    pub fn from_env() -> Self {
+        Self::new(gluetun_control_url(), IpLookup::from_env(), network_status_cache_ttl())
+    }
+
+    /// Current network status, looked up again when the cached one expired or `refresh` is set.
+    /// A failed lookup (`unknown`) expires after a few seconds at most.
+    pub async fn get(&self, refresh: bool) -> NetworkStatus {
+        let mut cached = self.cached.lock().await;
+        if let Some((status, expires_at)) = cached.as_ref() {
+            if !refresh && Instant::now() < *expires_at {
+                return status.clone();
+            }
+        }
+
+        let (status, ttl) = match detect_network_status(&self.control_url).await {
+            Some(status) => (status, self.ttl),
+            None => match self.fallback.run().await {
+                Some(status) => (status, self.ttl),
+                None => (NetworkStatus::unknown(), self.ttl.min(FAILED_NETWORK_STATUS_CACHE)),
+            },
+        };
+        *cached = Some((status.clone(), Instant::now() + ttl));
+        status
+    }
+}
+
+/// VPN gate status, reported by the health endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct VpnGateStatus {
+    /// Whether REQUIRE_VPN is set
+    pub required: bool,
+    /// Result of the last check (None until the first check)
+    pub vpn_detected: Option<bool>,
+    pub last_checked_unix: Option<u64>,
+    /// Instances paused (or not auto-started) until the VPN is back
+    pub held_instances: Vec<String>,
+}
+
+impl VpnGateStatus {
+    /// True when announcing is currently refused
+    pub fn is_blocking(&self) -> bool {
+        self.required && self.vpn_detected != Some(true)
+    }
+}
+
+/// Last VPN check result
+#[derive(Debug, Default)]
+struct GateCheck {
+    vpn_detected: Option<bool>,
+    last_checked_unix: Option<u64>,
+    checked_at: Option<Instant>,
+}
+
+/// What happens to an instance held by the gate once the VPN is back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeldAction {
+    /// Paused when the VPN went away: resume it
+    Resume,
+    /// Auto-start refused: start it, giving up after `max_failed_announces` failed announces (0 = never)
+    Start { max_failed_announces: u32 },
+}
+
+/// Kill-switch that blocks announces while no VPN is detected (REQUIRE_VPN)
+#[derive(Debug)]
+pub struct VpnGate {
+    required: bool,
+    control_url: String,
+    /// How long a check is reused by `allows_announce`
+    check_reuse: Duration,
+    last: Mutex<GateCheck>,
+    /// Serializes the checks of concurrent announces, so they share one lookup
+    checking: tokio::sync::Mutex<()>,
+    /// Instances held back by the gate, resumed or started when the VPN returns
+    held: Mutex<HashMap<String, HeldAction>>,
+}
+
+impl VpnGate {
+    pub fn new(required: bool, control_url: impl Into<String>, check_reuse: Duration) -> Self {
+        Self {
+            required,
+            control_url: control_url.into(),
+            check_reuse,
+            last: Mutex::new(GateCheck::default()),
+            checking: tokio::sync::Mutex::new(()),
+            held: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gate configured from REQUIRE_VPN and GLUETUN_CONTROL_URL
+    pub fn from_env() -> Self {
+        Self::new(is_vpn_required(), gluetun_control_url(), VPN_GATE_CHECK_REUSE)
+    }
+
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    /// Query the network and record whether a VPN is up, warning when it goes away.
+    /// Returns true when a VPN is detected.
+    pub async fn check(&self) -> bool {
+        let is_vpn = detect_network_status(&self.control_url)
+            .await
+            .is_some_and(|status| status.is_vpn);
+
+        let mut last = self.last.lock().unwrap();
+        let previous = last.vpn_detected.replace(is_vpn);
+        last.last_checked_unix = Some(now_timestamp());
+        last.checked_at = Some(Instant::now());
+        drop(last);
+
+        match (previous, is_vpn) {
+            (Some(true) | None, false) => {
+                tracing::error!(
+                    "NO VPN DETECTED via {}: REQUIRE_VPN is set, announcing is blocked until the VPN is back",
+                    self.control_url
+                );
+            }
+            (Some(false) | None, true) => tracing::info!("VPN detected, announcing is allowed"),
+            _ => {}
+        }
+        is_vpn
+    }
+
+    /// Whether an announce may go out now: always without REQUIRE_VPN, otherwise when a VPN is
+    /// detected. A check younger than `check_reuse` is reused, an older one is done again.
+    pub async fn allows_announce(&self) -> bool {
+        if !self.required {
+            return true;
+        }
+        let _checking = self.checking.lock().await;
+        let recent = {
+            let last = self.last.lock().unwrap();
+            last.checked_at
+                .filter(|at| at.elapsed() < self.check_reuse)
+                .and(last.vpn_detected)
+        };
+        match recent {
+            Some(is_vpn) => is_vpn,
+            None => self.check().await,
+        }
+    }
+
+    /// Hold an instance until the VPN is back
+    pub fn hold(&self, id: &str, action: HeldAction) {
+        self.held.lock().unwrap().insert(id.to_string(), action);
+    }
+
+    /// Take the instances held by the gate
+    pub fn take_held(&self) -> Vec<(String, HeldAction)> {
+        self.held.lock().unwrap().drain().collect()
+    }
+
+    pub fn status(&self) -> VpnGateStatus {
+        let last = self.last.lock().unwrap();
+        let mut held_instances: Vec<String> = self.held.lock().unwrap().keys().cloned().collect();
+        held_instances.sort();
+        VpnGateStatus {
+            required: self.required,
+            vpn_detected: last.vpn_detected,
+            last_checked_unix: last.last_checked_unix,
+            held_instances,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Spawn a fake gluetun control server counting VPN status lookups
+    async fn spawn_gluetun(lookups: Arc<AtomicUsize>) -> String {
+        let app = Router::new()
+            .route(
+                "/v1/vpn/status",
+                get(move || {
+                    lookups.fetch_add(1, Ordering::Relaxed);
+                    async { r#"{"status":"running"}"# }
+                }),
+            )
+            .route(
+                "/v1/publicip/ip",
+                get(|| async { r#"{"public_ip":"10.0.0.1","country":"Sweden","organization":"Mullvad"}"# }),
+            );
+        test_support::serve(app).await
+    }
+
+    #[tokio::test]
+    async fn test_network_status_cache() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let no_fallback = IpLookup::new(Vec::new(), Duration::from_secs(1), Duration::from_secs(1));
+        let cache = NetworkStatusCache::new(
+            spawn_gluetun(lookups.clone()).await,
+            no_fallback.clone(),
+            Duration::from_secs(60),
+        );
+
+        let status = cache.get(false).await;
+        assert!(status.is_vpn);
+        assert_eq!(status.ip, "10.0.0.1");
+        assert_eq!(cache.get(false).await.ip, "10.0.0.1");
+        assert_eq!(
+            lookups.load(Ordering::Relaxed),
+            1,
+            "second call is served from the cache"
+        );
+
+        cache.get(true).await;
+        assert_eq!(lookups.load(Ordering::Relaxed), 2, "refresh bypasses the cache");
+
+        // A zero TTL disables caching
+        let cache = NetworkStatusCache::new(
+            spawn_gluetun(lookups.clone()).await,
+            no_fallback.clone(),
+            Duration::ZERO,
+        );
+        cache.get(false).await;
+        cache.get(false).await;
+        assert_eq!(lookups.load(Ordering::Relaxed), 4);
+
+        // A failed lookup is only kept for a few seconds
+        let cache = NetworkStatusCache::new("http://127.0.0.1:1", no_fallback, Duration::from_secs(60));
+        assert_eq!(cache.get(false).await.ip, "unknown");
+        let (_, expires_at) = cache.cached.lock().await.clone().unwrap();
+        assert!(expires_at <= Instant::now() + FAILED_NETWORK_STATUS_CACHE);
+    }
+
+    #[tokio::test]
+    async fn test_vpn_gate_reuses_recent_check() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let control_url = spawn_gluetun(lookups.clone()).await;
+
+        let gate = VpnGate::new(true, control_url.clone(), Duration::from_secs(60));
+        let allowed = futures::future::join_all((0..5).map(|_| gate.allows_announce())).await;
+        assert!(allowed.into_iter().all(|allowed| allowed));
+        assert_eq!(
+            lookups.load(Ordering::Relaxed),
+            1,
+            "concurrent announces share one check"
+        );
+
+        // Without REQUIRE_VPN the network is never asked
+        let gate = VpnGate::new(false, control_url, Duration::ZERO);
+        assert!(gate.allows_announce().await);
+        assert_eq!(lookups.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ip_lookup_skips_hanging_provider() {
+        let app = Router::new()
+            .route(
+                "/hang",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    "{}"
+                }),
+            )
+            .route(
+                "/ipinfo",
+                get(|| async { r#"{"ip":"10.0.0.2","country":"SE","org":"AS39351 31173 Services AB"}"# }),
+            );
+        let base = test_support::serve(app).await;
+
+        let providers = vec![
+            (IpLookupProvider::Ipify, format!("{}/hang", base)),
+            (IpLookupProvider::IpInfo, format!("{}/ipinfo", base)),
+        ];
+        let lookup = IpLookup::new(providers.clone(), Duration::from_millis(200), Duration::from_secs(2)).allow_http();
+        let started = Instant::now();
+        let status = lookup.run().await.expect("second provider answers");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(status.ip, "10.0.0.2");
+        assert!(status.is_vpn);
+        assert_eq!(status.vpn_provider.as_deref(), Some("Mullvad"));
+
+        // The deadline caps the total wait even with generous per-provider timeouts
+        let lookup = IpLookup::new(
+            vec![(IpLookupProvider::Ipify, format!("{}/hang", base))],
+            Duration::from_secs(10),
+            Duration::from_millis(300),
+        )
+        .allow_http();
+        let started = Instant::now();
+        assert!(lookup.run().await.is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // Plain HTTP is refused outside tests
+        let lookup = IpLookup::new(providers, Duration::from_millis(200), Duration::from_secs(2));
+        assert!(lookup.run().await.is_none());
+    }
+
+    #[test]
+    fn test_custom_vpn_patterns() {
+        let mut patterns = VpnPatterns::builtin();
+        assert_eq!(patterns.detect(Some("Niche Networks AB"), None), None);
+
+        patterns.add_entries(
+            "# extra VPNs\nNiche Networks AB=NicheVPN  # relays\nAS64500 = Other VPN, bad entry, AS39351=Mullvad Relay",
+            "test",
+        );
+        assert_eq!(
+            patterns.detect(Some("AS64511 NICHE NETWORKS AB"), None).as_deref(),
+            Some("NicheVPN")
+        );
+        assert_eq!(
+            patterns.detect(Some("Niche Networks AB Broadband"), None).as_deref(),
+            Some("NicheVPN")
+        );
+        assert_eq!(
+            patterns.detect(Some("Unlisted ISP"), Some(64500)).as_deref(),
+            Some("Other VPN")
+        );
+        // User entries win over built-in ones
+        assert_eq!(
+            patterns.detect(Some("31173 Services AB"), Some(39351)).as_deref(),
+            Some("Mullvad Relay")
+        );
+        // Built-in ASNs still match when the organization name is unhelpful
+        assert_eq!(
+            patterns.detect(Some("Some Ltd"), Some(62371)).as_deref(),
+            Some("ProtonVPN")
+        );
+        assert_eq!(patterns.detect(None, None), None);
+
+        let mut patterns = VpnPatterns::builtin();
+        patterns.add_entries("azirevpn=Azire", "test");
+        assert_eq!(patterns.detect(Some("AzireVPN AB"), None).as_deref(), Some("Azire"));
+
+        // Built-in organization patterns match part of the name
+        let builtin = VpnPatterns::builtin();
+        for (organization, provider) in [
+            ("AzireVPN AB", "AzireVPN"),
+            ("AS9009 Proton AG", "ProtonVPN"),
+            ("31173 Services AB", "Mullvad"),
+            ("Packethub S.A.", "NordVPN"),
+        ] {
+            assert_eq!(builtin.detect(Some(organization), None).as_deref(), Some(provider));
+        }
+
+        // Ordinary ISPs and hosting networks aren't VPNs
+        for (organization, asn) in [
+            ("Express Internet Co", None),
+            ("Nordnet AS", None),
+            ("AS14061 DigitalOcean, LLC", Some(14061)),
+            ("AS63949 Akamai Connected Cloud", Some(63949)),
+            ("AS9009 M247 Europe SRL", Some(9009)),
+        ] {
+            assert_eq!(builtin.detect(Some(organization), asn), None, "{}", organization);
+        }
+
+        assert_eq!(strip_asn("AS9009 M247 Ltd"), "M247 Ltd");
+        assert_eq!(strip_asn(" Asgard Telecom "), "Asgard Telecom");
+
+        assert_eq!(parse_asn("AS9009 M247 Ltd"), Some(9009));
+        assert_eq!(parse_asn("as39351"), Some(39351));
+        assert_eq!(parse_asn("Mullvad"), None);
+    }
+
+    #[test]
+    fn test_ip_lookup_provider_names() {
+        for provider in IpLookupProvider::ALL {
+            assert_eq!(IpLookupProvider::from_name(provider.name()), Some(provider));
+        }
+        assert_eq!(IpLookupProvider::from_name(" IPinfo "), Some(IpLookupProvider::IpInfo));
+        assert_eq!(IpLookupProvider::from_name("whatismyip"), None);
+        assert_eq!(IpLookupProvider::from_name("ip-api"), None, "plain HTTP only");
+        assert!(IpLookupProvider::ALL
+            .into_iter()
+            .all(|provider| provider.url().starts_with("https://")));
+    }
+}
@@ -1,9 +1,16 @@
-use rustatio_core::{FakerConfig, FakerState, TorrentInfo};
+use rustatio_core::faker::HISTORY_MAX_POINTS;
+use rustatio_core::{FakerConfig, FakerState, FakerStats, TorrentInfo};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Mutex;
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
+
+/// Current version of the persisted state format
+/// - v1: instances with cumulative stats
+/// - v2: adds stats history (graphs)
+pub const STATE_VERSION: u32 = 2;
 
 /// Source of an instance - where it was created from
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -14,6 +21,8 @@ pub enum InstanceSource {
     Manual,
     /// Created automatically from watch folder
     WatchFolder,
+    /// Synthetic instance replaying a recorded stats timeline (never persisted)
+    Replay,
 }
 
 /// Persisted state for a single faker instance
@@ -25,6 +34,9 @@ pub struct PersistedInstance {
     pub cumulative_uploaded: u64,
     pub cumulative_downloaded: u64,
     pub state: FakerState,
+    /// Why an `Errored` instance gave up
+    #[serde(default)]
+    pub error: Option<String>,
     /// Timestamp when this instance was created
     pub created_at: u64,
     /// Timestamp of last update
@@ -32,6 +44,38 @@ pub struct PersistedInstance {
     /// Source of this instance (manual or watch folder)
     #[serde(default)]
     pub source: InstanceSource,
+    /// Stats history for graphs (added in v2, empty when loading v1 files)
+    #[serde(default)]
+    pub history: PersistedHistory,
+    /// User-defined labels for grouping and filtering (empty when missing)
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// Stats history saved with an instance so graphs continue after a restart
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PersistedHistory {
+    pub upload_rate: Vec<f64>,
+    pub download_rate: Vec<f64>,
+    pub ratio: Vec<f64>,
+    /// Unix timestamps in milliseconds
+    pub timestamps: Vec<u64>,
+}
+
+impl PersistedHistory {
+    /// Build from live stats history, keeping only the most recent `HISTORY_MAX_POINTS` points
+    pub fn from_stats(stats: &FakerStats) -> Self {
+        fn recent<T: Clone>(history: &[T]) -> Vec<T> {
+            history[history.len().saturating_sub(HISTORY_MAX_POINTS)..].to_vec()
+        }
+
+        Self {
+            upload_rate: recent(&stats.upload_rate_history),
+            download_rate: recent(&stats.download_rate_history),
+            ratio: recent(&stats.ratio_history),
+            timestamps: recent(&stats.history_timestamps),
+        }
+    }
 }
 
 /// Full application state that gets persisted to disk
@@ -46,64 +90,219 @@ impl PersistedState {
     pub fn new() -> Self {
         Self {
             instances: HashMap::new(),
-            version: 1,
+            version: STATE_VERSION,
+        }
+    }
+
+    /// Upgrade state loaded from an older format to the current version.
+    /// Fields added in later versions are filled with serde defaults while parsing.
+    pub fn migrate(mut self) -> Self {
+        if self.version < STATE_VERSION {
+            tracing::info!("Migrating saved state from v{} to v{}", self.version, STATE_VERSION);
+            self.version = STATE_VERSION;
+        } else if self.version > STATE_VERSION {
+            tracing::warn!(
+                "Saved state version v{} is newer than supported v{}, unknown fields will be ignored",
+                self.version,
+                STATE_VERSION
+            );
+        }
+        self
+    }
+}
+
+/// Previous state files kept by default, see [`state_backups`]
+const DEFAULT_STATE_BACKUPS: usize = 3;
+
+/// Number of previous state files kept as `<state file>.1` (newest) to `.N`, from STATE_BACKUPS (0 disables)
+pub fn state_backups() -> usize {
+    std::env::var("STATE_BACKUPS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_STATE_BACKUPS)
+}
+
+/// Whether the server should refuse to start when the data directory isn't writable (REQUIRE_PERSISTENCE)
+pub fn is_persistence_required() -> bool {
+    std::env::var("REQUIRE_PERSISTENCE")
+        .map(|v| v.trim().eq_ignore_ascii_case("true") || v.trim() == "1")
+        .unwrap_or(false)
+}
+
+fn backup_file(state_file: &str, n: usize) -> String {
+    format!("{}.{}", state_file, n)
+}
+
+/// Header written at the start of MessagePack state files, used to tell them apart from JSON on load
+const MSGPACK_MAGIC: &[u8] = b"RSTMSGP1";
+
+/// On-disk format of the saved state, selected with PERSIST_FORMAT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistFormat {
+    /// Pretty-printed JSON, easy to inspect (default)
+    Json,
+    /// Compact MessagePack, smaller and faster to parse with many instances
+    MessagePack,
+}
+
+impl PersistFormat {
+    /// Read the format from PERSIST_FORMAT (`json` or `msgpack`), defaulting to JSON
+    pub fn from_env() -> Self {
+        match std::env::var("PERSIST_FORMAT") {
+            Ok(value) => match value.trim().to_lowercase().as_str() {
+                "" | "json" => Self::Json,
+                "msgpack" | "messagepack" | "binary" => Self::MessagePack,
+                other => {
+                    tracing::warn!("Unknown PERSIST_FORMAT '{}', using json", other);
+                    Self::Json
+                }
+            },
+            Err(_) => Self::Json,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "state.json",
+            Self::MessagePack => "state.msgpack",
+        }
+    }
+
+    fn encode(self, state: &PersistedState) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec_pretty(state).map_err(|e| e.to_string()),
+            Self::MessagePack => {
+                // Named fields keep serde defaults and skipped fields working, like JSON
+                let body = rmp_serde::to_vec_named(state).map_err(|e| e.to_string())?;
+                Ok([MSGPACK_MAGIC, &body].concat())
+            }
+        }
+    }
+
+    /// Decode a state file, detecting its format from the header rather than the configured format
+    fn decode(contents: &[u8]) -> Result<PersistedState, String> {
+        match contents.strip_prefix(MSGPACK_MAGIC) {
+            Some(body) => rmp_serde::from_slice(body).map_err(|e| e.to_string()),
+            None => serde_json::from_slice(contents).map_err(|e| e.to_string()),
         }
     }
 }
 
 /// Persistence manager for saving/loading state
 pub struct Persistence {
+    /// File the state is saved to
     state_file: String,
+    /// State file of the other format, loaded when the format was switched since the last save
+    fallback_file: String,
+    format: PersistFormat,
+    /// Number of previous state files to keep
+    backups: usize,
+    /// Error from the last save or writability check
+    last_error: Mutex<Option<String>>,
 }
 
 impl Persistence {
     pub fn new(data_dir: &str) -> Self {
+        Self::with_format(data_dir, PersistFormat::from_env())
+    }
+
+    pub fn with_format(data_dir: &str, format: PersistFormat) -> Self {
+        let other = match format {
+            PersistFormat::Json => PersistFormat::MessagePack,
+            PersistFormat::MessagePack => PersistFormat::Json,
+        };
         Self {
-            state_file: format!("{}/state.json", data_dir),
+            state_file: format!("{}/{}", data_dir, format.file_name()),
+            fallback_file: format!("{}/{}", data_dir, other.file_name()),
+            format,
+            backups: state_backups(),
+            last_error: Mutex::new(None),
         }
     }
 
-    /// Load state from disk, returns default state if file doesn't exist
-    pub async fn load(&self) -> PersistedState {
-        let path = Path::new(&self.state_file);
-
-        if !path.exists() {
+    /// Load state from disk, returns default state if file doesn't exist.
+    /// Falls back to the newest valid backup if the state file can't be parsed, and errors if none is usable.
+    pub async fn load(&self) -> Result<PersistedState, String> {
+        let state_file = [&self.state_file, &self.fallback_file]
+            .into_iter()
+            .find(|file| Path::new(file).exists());
+        let Some(state_file) = state_file else {
             tracing::info!("No saved state found at {}, starting fresh", self.state_file);
-            return PersistedState::new();
-        }
+            return Ok(PersistedState::new());
+        };
 
-        match fs::File::open(path).await {
-            Ok(mut file) => {
-                let mut contents = String::new();
-                if let Err(e) = file.read_to_string(&mut contents).await {
-                    tracing::error!("Failed to read state file: {}", e);
-                    return PersistedState::new();
+        match fs::read(state_file).await {
+            Ok(contents) => match PersistFormat::decode(&contents) {
+                Ok(state) => {
+                    tracing::info!("Loaded saved state from {}", state_file);
+                    return Ok(state.migrate());
                 }
+                Err(e) => {
+                    tracing::error!("Failed to parse state file: {}", e);
+                    // Backup corrupted file
+                    let backup = format!("{}.corrupted", state_file);
+                    let _ = fs::rename(state_file, &backup).await;
+                    tracing::warn!("Backed up corrupted state to {}", backup);
+                }
+            },
+            Err(e) => tracing::error!("Failed to read state file: {}", e),
+        }
 
-                match serde_json::from_str(&contents) {
-                    Ok(state) => {
-                        tracing::info!("Loaded saved state from {}", self.state_file);
-                        state
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to parse state file: {}", e);
-                        // Backup corrupted file
-                        let backup = format!("{}.corrupted", self.state_file);
-                        let _ = fs::rename(path, &backup).await;
-                        tracing::warn!("Backed up corrupted state to {}", backup);
-                        PersistedState::new()
-                    }
+        for n in 1..=self.backups {
+            let backup = backup_file(state_file, n);
+            let Ok(contents) = fs::read(&backup).await else {
+                continue;
+            };
+            match PersistFormat::decode(&contents) {
+                Ok(state) => {
+                    tracing::warn!("Recovered saved state from backup {}", backup);
+                    return Ok(state.migrate());
                 }
-            }
-            Err(e) => {
-                tracing::error!("Failed to open state file: {}", e);
-                PersistedState::new()
+                Err(e) => tracing::warn!("Skipping unreadable state backup {}: {}", backup, e),
             }
         }
+
+        Err(format!("{} is unreadable and no valid backup was found", state_file))
     }
 
     /// Save state to disk
     pub async fn save(&self, state: &PersistedState) -> Result<(), String> {
+        let result = self.write_state(state).await;
+        self.record(&result);
+        result
+    }
+
+    /// Check that the data directory is writable by creating and removing a probe file
+    pub async fn check_writable(&self) -> Result<(), String> {
+        let result = self.write_probe().await;
+        self.record(&result);
+        result
+    }
+
+    /// Error from the last save or writability check, if it failed
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn record(&self, result: &Result<(), String>) {
+        *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = result.as_ref().err().cloned();
+    }
+
+    async fn write_probe(&self) -> Result<(), String> {
+        let dir = Path::new(&self.state_file).parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Failed to create data directory {}: {}", dir.display(), e))?;
+
+        let probe = dir.join(".write-test");
+        fs::write(&probe, b"ok")
+            .await
+            .map_err(|e| format!("Data directory {} is not writable: {}", dir.display(), e))?;
+        let _ = fs::remove_file(&probe).await;
+        Ok(())
+    }
+
+    async fn write_state(&self, state: &PersistedState) -> Result<(), String> {
         // Ensure directory exists
         if let Some(parent) = Path::new(&self.state_file).parent() {
             if let Err(e) = fs::create_dir_all(parent).await {
@@ -111,8 +310,10 @@ impl Persistence {
             }
         }
 
-        // Serialize to JSON with pretty printing for debugging
-        let json = serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize state: {}", e))?;
+        let contents = self
+            .format
+            .encode(state)
+            .map_err(|e| format!("Failed to serialize state: {}", e))?;
 
         // Write to temp file first, then rename (atomic)
         let temp_file = format!("{}.tmp", self.state_file);
@@ -121,7 +322,7 @@ impl Persistence {
             .await
             .map_err(|e| format!("Failed to create temp file: {}", e))?;
 
-        file.write_all(json.as_bytes())
+        file.write_all(&contents)
             .await
             .map_err(|e| format!("Failed to write state: {}", e))?;
 
@@ -129,14 +330,44 @@ impl Persistence {
             .await
             .map_err(|e| format!("Failed to sync state file: {}", e))?;
 
+        self.rotate_backups().await;
+
         // Atomic rename
         fs::rename(&temp_file, &self.state_file)
             .await
             .map_err(|e| format!("Failed to rename state file: {}", e))?;
 
+        // Drop the state saved in the previous format so it can't be loaded later as stale state
+        if Path::new(&self.fallback_file).exists() {
+            if let Err(e) = fs::remove_file(&self.fallback_file).await {
+                tracing::warn!("Failed to remove old state file {}: {}", self.fallback_file, e);
+            }
+        }
+
         tracing::debug!("State saved to {}", self.state_file);
         Ok(())
     }
+
+    /// Shift existing backups up by one and copy the current state file to `.1`.
+    /// Failures are logged but never fail the save.
+    async fn rotate_backups(&self) {
+        if self.backups == 0 || !Path::new(&self.state_file).exists() {
+            return;
+        }
+
+        for n in (1..self.backups).rev() {
+            let from = backup_file(&self.state_file, n);
+            if Path::new(&from).exists() {
+                if let Err(e) = fs::rename(&from, backup_file(&self.state_file, n + 1)).await {
+                    tracing::warn!("Failed to rotate state backup {}: {}", from, e);
+                }
+            }
+        }
+
+        if let Err(e) = fs::copy(&self.state_file, backup_file(&self.state_file, 1)).await {
+            tracing::warn!("Failed to back up state file: {}", e);
+        }
+    }
 }
 
 /// Get current timestamp in seconds since UNIX epoch
@@ -146,3 +377,158 @@ pub fn now_timestamp() -> u64 {
         .unwrap_or_default()
         .as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_migrate_v1_state_without_history() {
+        let torrent = TorrentInfo::fixture("http://tracker.example/announce");
+
+        // v1 instances were saved without the history field
+        let v1 = serde_json::json!({
+            "version": 1,
+            "instances": {
+                "abc": {
+                    "id": "abc",
+                    "torrent": torrent,
+                    "config": FakerConfig::default(),
+                    "cumulative_uploaded": 100,
+                    "cumulative_downloaded": 50,
+                    "state": "Running",
+                    "created_at": 1,
+                    "updated_at": 2
+                }
+            }
+        });
+
+        let state = serde_json::from_value::<PersistedState>(v1).unwrap().migrate();
+        assert_eq!(state.version, STATE_VERSION);
+
+        let instance = &state.instances["abc"];
+        assert_eq!(instance.cumulative_uploaded, 100);
+        assert!(instance.history.ratio.is_empty());
+        assert!(instance.history.timestamps.is_empty());
+        assert!(instance.labels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_keeps_existing_state() {
+        let data_dir = test_support::temp_dir();
+        let persistence = Persistence::new(data_dir.path().to_str().unwrap());
+
+        let mut state = PersistedState::new();
+        persistence.save(&state).await.unwrap();
+
+        // A temp file that can't be written stands in for a save interrupted mid-write
+        let temp_file = data_dir.path().join("state.json.tmp");
+        std::fs::create_dir(&temp_file).unwrap();
+        state.version = 99;
+        assert!(persistence.save(&state).await.is_err());
+        assert_eq!(persistence.load().await.unwrap().version, STATE_VERSION);
+
+        // A truncated temp file left by a crash is simply overwritten by the next save
+        std::fs::remove_dir(&temp_file).unwrap();
+        std::fs::write(&temp_file, "{\"version\": 2, \"inst").unwrap();
+        assert_eq!(persistence.load().await.unwrap().version, STATE_VERSION);
+        persistence.save(&state).await.unwrap();
+        assert!(!temp_file.exists());
+    }
+
+    fn test_instance(id: &str, uploaded: u64) -> PersistedInstance {
+        PersistedInstance {
+            id: id.to_string(),
+            torrent: TorrentInfo {
+                info_hash: [uploaded as u8; 20],
+                announce_list: Some(vec![vec!["http://backup.example/announce".to_string()]]),
+                name: format!("torrent {}", id),
+                comment: Some("comment".to_string()),
+                ..TorrentInfo::fixture("http://tracker.example/announce")
+            },
+            config: FakerConfig::default(),
+            cumulative_uploaded: uploaded,
+            cumulative_downloaded: 0,
+            state: FakerState::Running,
+            error: None,
+            created_at: 1,
+            updated_at: 2,
+            source: InstanceSource::WatchFolder,
+            history: PersistedHistory {
+                upload_rate: vec![1.5, 2.5],
+                download_rate: vec![0.0, 0.0],
+                ratio: vec![0.1, 0.2],
+                timestamps: vec![1000, 2000],
+            },
+            labels: vec!["movies".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_both_formats() {
+        let mut state = PersistedState::new();
+        for (id, uploaded) in [("a", 10), ("b", 20), ("c", 30)] {
+            state.instances.insert(id.to_string(), test_instance(id, uploaded));
+        }
+
+        for format in [PersistFormat::Json, PersistFormat::MessagePack] {
+            let data_dir = test_support::temp_dir();
+            let persistence = Persistence::with_format(data_dir.path().to_str().unwrap(), format);
+            persistence.save(&state).await.unwrap();
+
+            let loaded = persistence.load().await.unwrap();
+            assert_eq!(loaded.instances.len(), 3);
+            for (id, instance) in &state.instances {
+                let restored = &loaded.instances[id];
+                assert_eq!(restored.cumulative_uploaded, instance.cumulative_uploaded);
+                assert_eq!(restored.torrent.info_hash, instance.torrent.info_hash);
+                assert_eq!(restored.torrent.announce_list, instance.torrent.announce_list);
+                assert_eq!(restored.state, FakerState::Running);
+                assert_eq!(restored.source, InstanceSource::WatchFolder);
+                assert_eq!(restored.history.timestamps, vec![1000, 2000]);
+                assert_eq!(restored.labels, vec!["movies"]);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switching_format_keeps_state() {
+        let data_dir = test_support::temp_dir();
+        let dir = data_dir.path().to_str().unwrap();
+        let mut state = PersistedState::new();
+        state.instances.insert("a".to_string(), test_instance("a", 10));
+
+        // Existing JSON state is still loaded after switching to MessagePack
+        Persistence::with_format(dir, PersistFormat::Json)
+            .save(&state)
+            .await
+            .unwrap();
+        let binary = Persistence::with_format(dir, PersistFormat::MessagePack);
+        assert_eq!(binary.load().await.unwrap().instances.len(), 1);
+
+        // The first save in the new format replaces the old file
+        binary.save(&state).await.unwrap();
+        assert!(!data_dir.path().join("state.json").exists());
+        assert!(std::fs::read(data_dir.path().join("state.msgpack"))
+            .unwrap()
+            .starts_with(MSGPACK_MAGIC));
+        assert_eq!(binary.load().await.unwrap().instances.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_writable() {
+        let data_dir = test_support::temp_dir();
+        let persistence = Persistence::new(data_dir.path().to_str().unwrap());
+        persistence.check_writable().await.unwrap();
+        assert!(persistence.last_error().is_none());
+        assert!(!data_dir.path().join(".write-test").exists());
+
+        // A data directory below a regular file can never be created
+        let blocked = Persistence::new(data_dir.path().join(".write-blocker").join("data").to_str().unwrap());
+        std::fs::write(data_dir.path().join(".write-blocker"), b"").unwrap();
+        assert!(blocked.check_writable().await.is_err());
+        assert!(blocked.save(&PersistedState::new()).await.is_err());
+        assert!(blocked.last_error().is_some());
+    }
+}
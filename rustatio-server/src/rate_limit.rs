@@ -0,0 +1,190 @@
+//! Rate limiting middleware for API requests.
+//!
+//! When `RATE_LIMIT_RPM` environment variable is set to a positive number, each client IP
+//! may make at most that many API requests per minute. Limiting uses a token bucket per IP
+//! that refills continuously, so short bursts up to the limit are allowed.
+//! Requests over the limit receive `429 Too Many Requests` with a `Retry-After` header.
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Cached requests-per-minute limit from environment (None = rate limiting disabled)
+static RATE_LIMIT_RPM: OnceLock<Option<u32>> = OnceLock::new();
+
+/// Token buckets keyed by client IP
+static BUCKETS: OnceLock<Mutex<HashMap<IpAddr, TokenBucket>>> = OnceLock::new();
+
+/// Number of tracked IPs above which idle (full) buckets are pruned
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Get the configured requests-per-minute limit, caching the result
+pub fn get_rate_limit_rpm() -> Option<u32> {
+    *RATE_LIMIT_RPM.get_or_init(|| {
+        std::env::var("RATE_LIMIT_RPM")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|&rpm| rpm > 0)
+    })
+}
+
+/// Token bucket allowing `capacity` requests per minute
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rpm: u32, now: Instant) -> Self {
+        let capacity = rpm as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: now,
+        }
+    }
+
+    /// Add tokens for the time elapsed since the last refill, up to capacity
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Try to consume one token.
+    /// Returns the time until a token becomes available if the bucket is empty.
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - self.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+
+    /// Whether the bucket has fully refilled (client has been idle)
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens + elapsed * self.refill_per_sec >= self.capacity
+    }
+}
+
+/// Rate limit error response
+#[derive(Serialize)]
+struct RateLimitError {
+    success: bool,
+    error: String,
+}
+
+impl RateLimitError {
+    fn too_many_requests(retry_after: Duration) -> Response {
+        // Round up so clients never retry before a token is available
+        let retry_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(Self {
+                success: false,
+                error: format!("Rate limit exceeded. Retry in {} second(s).", retry_secs),
+            }),
+        )
+            .into_response();
+
+        if let Ok(value) = HeaderValue::from_str(&retry_secs.to_string()) {
+            response.headers_mut().insert(RETRY_AFTER, value);
+        }
+
+        response
+    }
+}
+
+/// Middleware that limits API requests per client IP according to RATE_LIMIT_RPM.
+///
+/// If RATE_LIMIT_RPM is not set, all requests are allowed (rate limiting disabled).
+/// The client IP comes from the connection info, so the router must be served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`.
+pub async fn rate_limit_middleware(request: Request, next: Next) -> Response {
+    let rpm = match get_rate_limit_rpm() {
+        Some(rpm) => rpm,
+        None => return next.run(request).await,
+    };
+
+    let ip = match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => addr.ip(),
+        None => return next.run(request).await,
+    };
+
+    let result = {
+        let now = Instant::now();
+        let mut buckets = BUCKETS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+
+        buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::new(rpm, now))
+            .try_acquire(now)
+    };
+
+    match result {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            tracing::debug!("Rate limit exceeded for {}", ip);
+            RateLimitError::too_many_requests(retry_after)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(60, start);
+
+        // Full bucket allows a burst up to capacity
+        for _ in 0..60 {
+            assert!(bucket.try_acquire(start).is_ok());
+        }
+
+        // Empty bucket reports time until next token (60 rpm = 1 token/s)
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert!((wait.as_secs_f64() - 1.0).abs() < 1e-6);
+
+        // Half a second is not enough for a full token
+        assert!(bucket.try_acquire(start + Duration::from_millis(500)).is_err());
+
+        // After a full second one token has been refilled
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_ok());
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_err());
+
+        // Refill never exceeds capacity
+        let later = start + Duration::from_secs(3600);
+        bucket.refill(later);
+        assert_eq!(bucket.tokens, 60.0);
+        assert!(bucket.is_full(later));
+    }
+}
@@ -0,0 +1,217 @@
+//! Stats replay for UI development and demos.
+//!
+//! When the `DEMO_REPLAY` environment variable is set to `true`, `POST /api/demo/replay`
+//! accepts a recorded timeline of `FakerStats` snapshots (for example collected by polling
+//! `GET /api/faker/{id}/stats`) and creates a synthetic instance that steps through it on a
+//! timer. Replayed instances never contact a tracker and are not persisted.
+
+use crate::persistence::now_timestamp;
+use crate::state::{AppState, InstanceEvent};
+use rustatio_core::{FakerStats, RatioFaker, TorrentInfo};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+
+/// Cached demo replay flag from environment
+static DEMO_REPLAY: OnceLock<bool> = OnceLock::new();
+
+/// Check if the demo replay endpoint is enabled, caching the result
+pub fn is_demo_replay_enabled() -> bool {
+    *DEMO_REPLAY.get_or_init(|| {
+        std::env::var("DEMO_REPLAY")
+            .map(|v| v.trim().eq_ignore_ascii_case("true") || v.trim() == "1")
+            .unwrap_or(false)
+    })
+}
+
+fn default_name() -> String {
+    "Demo replay".to_string()
+}
+
+fn default_interval_ms() -> u64 {
+    1000
+}
+
+/// Recorded stats timeline to replay
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayTimeline {
+    /// Torrent name shown for the synthetic instance
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Torrent size in bytes
+    pub total_size: u64,
+    /// Delay between frames in milliseconds
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Start over from the first frame after the last one
+    #[serde(default)]
+    pub repeat: bool,
+    /// Stats snapshots, replayed in order
+    pub frames: Vec<FakerStats>,
+}
+
+impl ReplayTimeline {
+    /// Check that the timeline can be replayed
+    pub fn validate(&self) -> Result<(), String> {
+        if self.frames.is_empty() {
+            return Err("Replay timeline has no frames".to_string());
+        }
+        if self.interval_ms == 0 {
+            return Err("Replay interval_ms must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Synthetic torrent for the replayed instance
+    pub fn torrent(&self, id: &str) -> TorrentInfo {
+        // Derive a stable, unique-enough info_hash from the instance ID
+        let mut info_hash = [0u8; 20];
+        for (i, b) in id.bytes().take(20).enumerate() {
+            info_hash[i] = b;
+        }
+
+        TorrentInfo {
+            info_hash,
+            // Never contacted: replayed instances can't be started
+            announce: "http://replay.invalid/announce".to_string(),
+            announce_list: None,
+            name: self.name.clone(),
+            total_size: self.total_size,
+            piece_length: 256 * 1024,
+            num_pieces: self.total_size.div_ceil(256 * 1024) as usize,
+            creation_date: None,
+            comment: Some("Demo replay".to_string()),
+            created_by: None,
+            source: None,
+            is_single_file: true,
+            files: Vec::new(),
+        }
+    }
+}
+
+/// Drive a replayed instance through its timeline, emitting a stats event per frame
+pub async fn replay_loop(
+    id: String,
+    faker: Arc<RwLock<RatioFaker>>,
+    state: AppState,
+    timeline: ReplayTimeline,
+    last_tick: Arc<AtomicU64>,
+    mut shutdown_rx: mpsc::Receiver<()>,
+) {
+    let interval = Duration::from_millis(timeline.interval_ms);
+    let mut index = 0;
+
+    tracing::info!("Replaying {} frames for demo instance {}", timeline.frames.len(), id);
+
+    loop {
+        if !state.instance_exists(&id).await {
+            tracing::info!("Demo instance {} no longer exists, stopping replay", id);
+            break;
+        }
+
+        let stats = timeline.frames[index].clone();
+        faker.write().await.replace_stats(stats.clone()).await;
+        last_tick.store(now_timestamp(), Ordering::Relaxed);
+        state.emit_instance_event(InstanceEvent::Stats {
+            id: id.clone(),
+            stats: Box::new(stats),
+        });
+
+        index += 1;
+        if index == timeline.frames.len() {
+            if !timeline.repeat {
+                tracing::info!("Replay finished for demo instance {}", id);
+                break;
+            }
+            index = 0;
+        }
+
+        tokio::select! {
+            _ = shutdown_rx.recv() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::InstanceSource;
+    use crate::test_support;
+    use rustatio_core::{AppConfig, FakerConfig, FakerState};
+    use tokio::sync::broadcast;
+
+    async fn frames(uploaded: &[u64]) -> Vec<FakerStats> {
+        let timeline = ReplayTimeline {
+            name: default_name(),
+            total_size: 1024,
+            interval_ms: default_interval_ms(),
+            repeat: false,
+            frames: Vec::new(),
+        };
+        let faker = RatioFaker::new(timeline.torrent("frames"), FakerConfig::default()).unwrap();
+        let base = faker.get_stats().await;
+
+        uploaded
+            .iter()
+            .map(|&uploaded| FakerStats {
+                uploaded,
+                state: FakerState::Running,
+                ..base.clone()
+            })
+            .collect()
+    }
+
+    async fn next_event(rx: &mut broadcast::Receiver<InstanceEvent>) -> InstanceEvent {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for instance event")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replay_emits_frames_in_order() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let mut events = state.subscribe_instance_events();
+
+        let timeline = ReplayTimeline {
+            name: "Recorded".to_string(),
+            total_size: 1024,
+            interval_ms: 10,
+            repeat: false,
+            frames: frames(&[100, 200, 300]).await,
+        };
+        let id = state.create_replay_instance(timeline).await.unwrap();
+
+        match next_event(&mut events).await {
+            InstanceEvent::Created { id: created, .. } => assert_eq!(created, id),
+            other => panic!("expected created event, got {:?}", other),
+        }
+
+        let mut replayed = Vec::new();
+        for _ in 0..3 {
+            match next_event(&mut events).await {
+                InstanceEvent::Stats { id: stats_id, stats } => {
+                    assert_eq!(stats_id, id);
+                    replayed.push(stats.uploaded);
+                }
+                other => panic!("expected stats event, got {:?}", other),
+            }
+        }
+        assert_eq!(replayed, vec![100, 200, 300]);
+
+        // The instance keeps reporting the last frame and ignores tracker updates
+        let stats = state.update_instance(&id).await.unwrap();
+        assert_eq!(stats.uploaded, 300);
+        assert_eq!(stats.state, FakerState::Running);
+        assert!(state.start_instance(&id).await.is_err());
+
+        let info = state.list_instances().await;
+        assert_eq!(info[0].source, InstanceSource::Replay);
+
+        state.delete_instance(&id, true).await.unwrap();
+    }
+}
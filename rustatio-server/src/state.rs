@@ -1,19 +1,279 @@
-use crate::persistence::{now_timestamp, InstanceSource, PersistedInstance, PersistedState, Persistence};
+use crate::network::{self, HeldAction, NetworkStatusCache, VpnGate, VPN_REQUIRED};
+use crate::persistence::{
+    now_timestamp, InstanceSource, PersistedHistory, PersistedInstance, PersistedState, Persistence, STATE_VERSION,
+};
+use crate::replay::{self, ReplayTimeline};
+use futures::future::join_all;
 use rustatio_core::logger::set_instance_context_str;
-use rustatio_core::{FakerConfig, FakerState, FakerStats, RatioFaker, TorrentInfo, AppConfig};
-use serde::Serialize;
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{broadcast, mpsc, RwLock};
+use rustatio_core::validation::{validate_request_timeout, validate_update_interval};
+use rustatio_core::{
+    redact_tracker_url, AppConfig, FakerConfig, FakerState, FakerStats, RatioFaker, TorrentInfo, DEFAULT_RATE_DECIMALS,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Mutex, OwnedMutexGuard, RwLock, Semaphore};
 use tokio::task::JoinHandle;
 
+/// How long an `Idempotency-Key` on instance creation keeps returning the same id
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(600);
+
+/// Most idempotency keys remembered at once, the oldest is forgotten first
+const MAX_IDEMPOTENCY_KEYS: usize = 10_000;
+
+/// Cached tracker request timeout from environment (None = use each instance's config)
+static TRACKER_TIMEOUT_SECS: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Get the server-wide tracker request timeout from TRACKER_TIMEOUT_SECS, caching the result
+pub fn tracker_timeout_secs() -> Option<u64> {
+    *TRACKER_TIMEOUT_SECS.get_or_init(|| {
+        let value = std::env::var("TRACKER_TIMEOUT_SECS").ok()?;
+        match value.trim().parse::<u64>() {
+            Ok(secs) if validate_request_timeout(secs).is_ok() => Some(secs),
+            _ => {
+                tracing::warn!(
+                    "Ignoring invalid TRACKER_TIMEOUT_SECS '{}' (expected 1-300 seconds)",
+                    value
+                );
+                None
+            }
+        }
+    })
+}
+
+const DEFAULT_UPDATE_INTERVAL_SECS: u64 = 5;
+const DEFAULT_SAVE_INTERVAL_SECS: u64 = 30;
+
+/// Cached background update and save intervals from environment
+static UPDATE_INTERVAL_SECS: OnceLock<u64> = OnceLock::new();
+static SAVE_INTERVAL_SECS: OnceLock<u64> = OnceLock::new();
+
+/// Read an interval in seconds from `var`, falling back to `default` when unset or outside 1-3600 seconds
+fn interval_secs_env(var: &str, default: u64) -> u64 {
+    let Ok(value) = std::env::var(var) else {
+        return default;
+    };
+    match value.trim().parse::<u64>() {
+        Ok(secs) if validate_update_interval(secs).is_ok() => secs,
+        _ => {
+            tracing::warn!(
+                "Ignoring invalid {} '{}' (expected 1-3600 seconds), using {}s",
+                var,
+                value,
+                default
+            );
+            default
+        }
+    }
+}
+
+/// Get how often running instances are updated in the background from UPDATE_INTERVAL_SECS (default 5), caching the result
+pub fn update_interval_secs() -> u64 {
+    *UPDATE_INTERVAL_SECS.get_or_init(|| interval_secs_env("UPDATE_INTERVAL_SECS", DEFAULT_UPDATE_INTERVAL_SECS))
+}
+
+/// Get how often running instances save the state in the background from SAVE_INTERVAL_SECS (default 30), caching the result
+pub fn save_interval_secs() -> u64 {
+    *SAVE_INTERVAL_SECS.get_or_init(|| interval_secs_env("SAVE_INTERVAL_SECS", DEFAULT_SAVE_INTERVAL_SECS))
+}
+
+/// Time until the next background tick: the update interval, cut short when the faker's next announce is due
+/// sooner. Announces are only scheduled by the faker (never earlier than the tracker's min interval), so ticking
+/// more often can't make them faster; this only keeps long update intervals from delaying them.
+fn next_tick_delay(update_interval: Duration, until_announce: Option<Duration>) -> Duration {
+    match until_announce {
+        Some(until) => update_interval.min(until.max(Duration::from_secs(1))),
+        None => update_interval,
+    }
+}
+
+/// Longest startup jitter, larger settings are clamped to it
+const MAX_STARTUP_JITTER_SECS: u64 = 3600;
+
+/// Cached startup jitter from environment (None = use the config file setting)
+static STARTUP_JITTER_SECS: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Get the startup jitter override from STARTUP_JITTER_SECS (at most 3600), caching the result
+fn startup_jitter_secs_env() -> Option<u64> {
+    *STARTUP_JITTER_SECS.get_or_init(|| {
+        std::env::var("STARTUP_JITTER_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(clamp_startup_jitter)
+    })
+}
+
+/// Limit a startup jitter setting to `MAX_STARTUP_JITTER_SECS`
+fn clamp_startup_jitter(secs: u64) -> u64 {
+    if secs > MAX_STARTUP_JITTER_SECS {
+        tracing::warn!(
+            "Startup jitter of {}s is too long, using {}s",
+            secs,
+            MAX_STARTUP_JITTER_SECS
+        );
+    }
+    secs.min(MAX_STARTUP_JITTER_SECS)
+}
+
+/// Cached restore give-up threshold from environment (None = use the config file setting)
+static RESTORE_MAX_FAILED_ANNOUNCES: OnceLock<Option<u32>> = OnceLock::new();
+
+/// Get the restore give-up threshold override from RESTORE_MAX_FAILED_ANNOUNCES, caching the result
+fn restore_max_failed_announces_env() -> Option<u32> {
+    *RESTORE_MAX_FAILED_ANNOUNCES.get_or_init(|| {
+        std::env::var("RESTORE_MAX_FAILED_ANNOUNCES")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+    })
+}
+
+/// Pick a random delay in `0..jitter_secs` seconds (at most `MAX_STARTUP_JITTER_SECS`) before an
+/// auto-started instance's first announce
+pub fn startup_delay(jitter_secs: u64) -> Duration {
+    let jitter_secs = jitter_secs.min(MAX_STARTUP_JITTER_SECS);
+    if jitter_secs == 0 {
+        return Duration::ZERO;
+    }
+    rand::random_range(Duration::ZERO..Duration::from_secs(jitter_secs))
+}
+
+/// Shortest allowed background scrape interval, to keep tracker load reasonable
+const MIN_SCRAPE_INTERVAL_SECS: u64 = 60;
+
+/// Cached background scrape interval from environment (None = disabled)
+static SCRAPE_INTERVAL_SECS: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Get how often running instances scrape their tracker between announces from SCRAPE_INTERVAL_SECS.
+/// Unset or 0 disables background scrapes; shorter intervals are raised to 60 seconds.
+pub fn scrape_interval_secs() -> Option<u64> {
+    *SCRAPE_INTERVAL_SECS.get_or_init(|| {
+        std::env::var("SCRAPE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .map(|secs| secs.max(MIN_SCRAPE_INTERVAL_SECS))
+    })
+}
+
+const DEFAULT_MAX_CONCURRENT_ANNOUNCES: usize = 4;
+
+/// Get how many instances may update (and announce) at the same time from MAX_CONCURRENT_ANNOUNCES (default 4)
+pub fn max_concurrent_announces() -> usize {
+    std::env::var("MAX_CONCURRENT_ANNOUNCES")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_ANNOUNCES)
+}
+
+/// Cached number of decimals for rates reported in instance info
+static RATE_DECIMALS: OnceLock<u32> = OnceLock::new();
+
+/// Get the number of decimals reported rates are rounded to from RATE_DECIMALS (default 1), caching the result
+pub fn rate_decimals() -> u32 {
+    *RATE_DECIMALS.get_or_init(|| {
+        std::env::var("RATE_DECIMALS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .map(|d| d.min(6))
+            .unwrap_or(DEFAULT_RATE_DECIMALS)
+    })
+}
+
+/// Apply TRACKER_TIMEOUT_SECS to configs that don't set their own timeout
+fn apply_tracker_timeout(config: &mut FakerConfig) {
+    if config.request_timeout_secs.is_none() {
+        config.request_timeout_secs = tracker_timeout_secs();
+    }
+}
+
+/// Copy the settings a running faker keeps until it's recreated (see `RatioFaker::apply_config`),
+/// so the stored config shows the port and client actually announced
+fn keep_running_settings(config: &mut FakerConfig, running: &FakerConfig) {
+    config.port = running.port;
+    config.client_type = running.client_type.clone();
+    config.client_version = running.client_version.clone();
+    config.completion_percent = running.completion_percent;
+    config.user_agent_override = running.user_agent_override.clone();
+    config.peer_id_prefix_override = running.peer_id_prefix_override.clone();
+}
+
+/// Get the maximum number of instances from MAX_INSTANCES (unset or 0 = unlimited)
+pub fn max_instances() -> Option<usize> {
+    std::env::var("MAX_INSTANCES")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+}
+
+/// Start of the error returned when creating an instance would exceed MAX_INSTANCES
+const INSTANCE_LIMIT_REACHED: &str = "Instance limit reached";
+
+const DEFAULT_LOG_BUFFER_SIZE: usize = 500;
+
+/// Get how many recent log events are kept for new SSE subscribers from LOG_BUFFER_SIZE (default 500, 0 disables)
+pub fn log_buffer_size() -> usize {
+    std::env::var("LOG_BUFFER_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_LOG_BUFFER_SIZE)
+}
+
+/// Why an instance operation failed, so the API can answer with a matching status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstanceError {
+    /// The instance or profile doesn't exist
+    NotFound(String),
+    /// The request or the config it produces is invalid
+    Invalid(String),
+    /// Creating another instance would exceed MAX_INSTANCES
+    LimitReached(String),
+    /// Another instance is already active on the same torrent
+    Conflict(String),
+    /// The operation itself failed, e.g. the `started` announce
+    Failed(String),
+}
+
+impl std::fmt::Display for InstanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(message)
+            | Self::Invalid(message)
+            | Self::LimitReached(message)
+            | Self::Conflict(message)
+            | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<InstanceError> for String {
+    fn from(error: InstanceError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Check the user-facing fields of a config before an instance stores or runs it
+fn validate_config(config: &FakerConfig) -> Result<(), InstanceError> {
+    config.validate().map_err(|e| InstanceError::Invalid(e.to_string()))
+}
+
+/// Error returned for lifecycle operations on replayed demo instances
+const REPLAY_NOT_CONTROLLABLE: &str = "Replayed demo instances follow their timeline and can only be deleted";
+
 /// Log event sent to UI via SSE
 #[derive(Clone, Debug, Serialize)]
 pub struct LogEvent {
     pub timestamp: u64,
     pub level: String,
     pub message: String,
+    /// Instance the event was logged for, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    /// Module the event was logged from (e.g. `rustatio_core::faker`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
 }
 
 impl LogEvent {
@@ -26,7 +286,104 @@ impl LogEvent {
             timestamp,
             level: level.to_string(),
             message,
+            instance_id: None,
+            target: None,
+        }
+    }
+
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+}
+
+/// Server-side filter for the log SSE stream
+#[derive(Debug, Default)]
+pub struct LogFilter {
+    /// Least severe level to keep (e.g. `warn` keeps warnings and errors)
+    pub min_level: Option<tracing::Level>,
+    /// Target prefix to keep (e.g. `rustatio_core::faker`)
+    pub target: Option<String>,
+    /// Instance whose events to keep
+    pub instance_id: Option<String>,
+}
+
+impl LogFilter {
+    /// Build a filter from `?level=` and `?target=` query values
+    pub fn parse(level: Option<&str>, target: Option<String>) -> Result<Self, String> {
+        let min_level = level
+            .map(|l| {
+                l.parse::<tracing::Level>()
+                    .map_err(|_| format!("Invalid log level '{}' (expected error, warn, info, debug or trace)", l))
+            })
+            .transpose()?;
+        Ok(Self {
+            min_level,
+            target: target.filter(|t| !t.is_empty()),
+            instance_id: None,
+        })
+    }
+
+    pub fn matches(&self, event: &LogEvent) -> bool {
+        if let Some(min_level) = self.min_level {
+            // tracing orders levels by verbosity, so ERROR < WARN < ... < TRACE
+            match event.level.parse::<tracing::Level>() {
+                Ok(level) if level <= min_level => {}
+                _ => return false,
+            }
+        }
+        if let Some(target) = &self.target {
+            if !event.target.as_deref().is_some_and(|t| t.starts_with(target.as_str())) {
+                return false;
+            }
+        }
+        if let Some(instance_id) = &self.instance_id {
+            if event.instance_id.as_ref() != Some(instance_id) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Bounded ring buffer of recent log events, replayed to SSE subscribers when they connect
+#[derive(Clone)]
+pub struct LogHistory {
+    events: Arc<std::sync::Mutex<VecDeque<LogEvent>>>,
+    capacity: usize,
+}
+
+impl LogHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: Arc::new(std::sync::Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Record an event and broadcast it to live subscribers
+    pub fn publish(&self, sender: &broadcast::Sender<LogEvent>, event: LogEvent) {
+        // Hold the lock while sending so a concurrent subscribe() never sees an event twice or misses it
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if self.capacity > 0 {
+            if events.len() >= self.capacity {
+                events.pop_front();
+            }
+            events.push_back(event.clone());
         }
+        // Ignore errors - no subscribers is fine
+        let _ = sender.send(event);
+    }
+
+    /// Subscribe to live events, returning the buffered history (oldest first) alongside the receiver
+    pub fn subscribe(&self, sender: &broadcast::Sender<LogEvent>) -> (Vec<LogEvent>, broadcast::Receiver<LogEvent>) {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        (events.iter().cloned().collect(), sender.subscribe())
     }
 }
 
@@ -43,6 +400,8 @@ pub enum InstanceEvent {
     },
     /// An instance was deleted
     Deleted { id: String },
+    /// New stats for an instance (emitted by demo replays)
+    Stats { id: String, stats: Box<FakerStats> },
 }
 
 /// Instance data with cumulative stats tracking
@@ -56,10 +415,16 @@ pub struct FakerInstance {
     pub created_at: u64,
     /// Source of this instance (manual or watch folder)
     pub source: InstanceSource,
+    /// User-defined labels for grouping and filtering
+    pub labels: Vec<String>,
     /// Background task handle (if running)
     task_handle: Option<JoinHandle<()>>,
     /// Shutdown signal sender for background task
     shutdown_tx: Option<mpsc::Sender<()>>,
+    /// Serializes lifecycle operations (start/stop/pause/resume/delete) on this instance
+    op_lock: Arc<Mutex<()>>,
+    /// Unix timestamp of the last background loop tick (0 = never ticked)
+    last_tick_unix: Arc<AtomicU64>,
 }
 
 /// Shared application state
@@ -71,10 +436,24 @@ pub struct AppState {
     pub torrents: Arc<RwLock<HashMap<String, TorrentInfo>>>,
     /// Broadcast channel for log events (SSE)
     pub log_sender: broadcast::Sender<LogEvent>,
+    /// Recent log events replayed to new log subscribers
+    pub log_history: LogHistory,
     /// Broadcast channel for instance events (SSE)
     pub instance_sender: broadcast::Sender<InstanceEvent>,
     /// Persistence manager
     persistence: Arc<Persistence>,
+    /// Permits shared by all background loops so only a few instances hit trackers at once
+    announce_permits: Arc<Semaphore>,
+    /// Instance ids handed out per idempotency key, with when they expire
+    idempotency_keys: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+    /// Instances stopped by `stop_all`, waiting for `resume_all` (not persisted)
+    quiesced: Arc<Mutex<HashSet<String>>>,
+    /// Kill-switch blocking announces without a VPN (REQUIRE_VPN)
+    pub vpn_gate: Arc<VpnGate>,
+    /// Last network status lookup, served to polling clients
+    pub network_status: Arc<NetworkStatusCache>,
+    /// Cap on the number of instances (MAX_INSTANCES, None = unlimited)
+    pub max_instances: Option<usize>,
     /// Core Config
     pub config: AppConfig,
 }
@@ -92,6 +471,9 @@ impl AppState {
         if config.num_want == base.num_want {
             config.num_want = c.default_num_want;
         }
+        if config.num_want_steady.is_none() {
+            config.num_want_steady = c.default_num_want_steady;
+        }
         if config.client_type == base.client_type {
             config.client_type = c.default_type.clone();
         }
@@ -158,6 +540,7 @@ impl AppState {
             (f.default_progressive_duration_hours * 3600.0) as u64;
 
         // Randomization
+        config.randomize_rates = f.default_randomize;
         config.random_range_percent = f.default_random_range_percent;
 
         // Announce / update intervals (actuellement ignorés)
@@ -185,20 +568,42 @@ impl AppState {
             instances: Arc::new(RwLock::new(HashMap::new())),
             torrents: Arc::new(RwLock::new(HashMap::new())),
             log_sender,
+            log_history: LogHistory::new(log_buffer_size()),
             instance_sender,
             persistence: Arc::new(Persistence::new(data_dir)),
+            announce_permits: Arc::new(Semaphore::new(max_concurrent_announces())),
+            idempotency_keys: Arc::new(Mutex::new(HashMap::new())),
+            quiesced: Arc::new(Mutex::new(HashSet::new())),
+            vpn_gate: Arc::new(VpnGate::from_env()),
+            network_status: Arc::new(NetworkStatusCache::from_env()),
+            max_instances: max_instances(),
             config,
         }
     }
 
+    /// Probe the data directory so an unwritable one is reported at startup instead of on every save
+    pub async fn check_persistence(&self) -> Result<(), String> {
+        self.persistence.check_writable().await
+    }
+
+    /// Error from the last failed save or writability check, cleared by the next successful save
+    pub fn persistence_error(&self) -> Option<String> {
+        self.persistence.last_error()
+    }
+
     /// Load saved state and restore instances
     pub async fn load_saved_state(&self) -> Result<usize, String> {
-        let saved = self.persistence.load().await;
+        let saved = self.persistence.load().await?;
 
         let mut restored_count = 0;
 
         // Restore all instances (including Idle ones so they persist across refreshes)
         for (id, persisted) in saved.instances {
+            // A load retried after a failed one must not replace instances created since
+            if self.instances.read().await.contains_key(&id) {
+                continue;
+            }
+
             tracing::info!(
                 "Restoring instance {} ({}) - state: {:?}",
                 id,
@@ -211,9 +616,27 @@ impl AppState {
             let mut faker_config = persisted.config.clone();
             faker_config.initial_uploaded = persisted.cumulative_uploaded;
             faker_config.initial_downloaded = persisted.cumulative_downloaded;
+            apply_tracker_timeout(&mut faker_config);
 
             match RatioFaker::new(persisted.torrent.clone(), faker_config) {
-                Ok(faker) => {
+                Ok(mut faker) => {
+                    // Continue graphs from where they left off
+                    let history = persisted.history;
+                    faker
+                        .restore_history(
+                            history.upload_rate,
+                            history.download_rate,
+                            history.ratio,
+                            history.timestamps,
+                        )
+                        .await;
+
+                    // Still shows why it gave up, until started again
+                    if persisted.state == FakerState::Errored {
+                        let reason = persisted.error.unwrap_or_else(|| "Errored before restart".to_string());
+                        faker.mark_errored(reason).await;
+                    }
+
                     let instance = FakerInstance {
                         faker: Arc::new(RwLock::new(faker)),
                         torrent: persisted.torrent.clone(),
@@ -223,17 +646,19 @@ impl AppState {
                         cumulative_downloaded: persisted.cumulative_downloaded,
                         created_at: persisted.created_at,
                         source: persisted.source,
+                        labels: persisted.labels,
                         task_handle: None,
                         shutdown_tx: None,
+                        op_lock: Arc::new(Mutex::new(())),
+                        last_tick_unix: Arc::new(AtomicU64::new(0)),
                     };
 
                     self.instances.write().await.insert(id.clone(), instance);
 
                     // Auto-start if it was running
-                    if matches!(persisted.state, FakerState::Running) {
-                        if let Err(e) = self.start_instance(&id).await {
-                            tracing::warn!("Failed to auto-start instance {}: {}", id, e);
-                        }
+                    if matches!(persisted.state, FakerState::Running | FakerState::Seeding) {
+                        self.auto_start_instance(id.clone(), self.restore_max_failed_announces())
+                            .await;
                     }
 
                     restored_count += 1;
@@ -251,17 +676,78 @@ impl AppState {
         Ok(restored_count)
     }
 
+    /// Maximum startup jitter in seconds, STARTUP_JITTER_SECS taking precedence over the config file
+    fn startup_jitter_secs(&self) -> u64 {
+        startup_jitter_secs_env().unwrap_or_else(|| clamp_startup_jitter(self.config.faker.startup_jitter_secs))
+    }
+
+    /// Consecutive failed announces before a restored instance gives up,
+    /// RESTORE_MAX_FAILED_ANNOUNCES taking precedence over the config file
+    fn restore_max_failed_announces(&self) -> u32 {
+        restore_max_failed_announces_env().unwrap_or(self.config.faker.restore_max_failed_announces)
+    }
+
+    /// Start an instance that wasn't started by hand (restored or watch folder), after a random
+    /// delay when startup jitter is configured so that many instances don't all announce to the
+    /// tracker at the same moment. It gives up after `max_failed_announces` consecutive failed
+    /// announces (0 = never), moving to `Errored` instead of retrying forever.
+    pub async fn auto_start_instance(&self, id: String, max_failed_announces: u32) {
+        let delay = startup_delay(self.startup_jitter_secs());
+        if delay.is_zero() {
+            self.start_or_hold(&id, max_failed_announces).await;
+            return;
+        }
+
+        tracing::info!("Auto-starting instance {} in {:.1}s", id, delay.as_secs_f64());
+        let state = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+
+            // Skip if the instance was deleted or started manually in the meantime
+            let pending = state
+                .instances
+                .read()
+                .await
+                .get(&id)
+                .is_some_and(|instance| instance.task_handle.is_none());
+            if !pending {
+                return;
+            }
+            state.start_or_hold(&id, max_failed_announces).await;
+        });
+    }
+
+    /// Auto-start an instance now; without a VPN it waits for the gate to start it.
+    /// A failed `started` announce leaves it `Errored`.
+    async fn start_or_hold(&self, id: &str, max_failed_announces: u32) {
+        if !self.vpn_gate.allows_announce().await {
+            tracing::warn!("Not auto-starting instance {} until a VPN is detected", id);
+            self.vpn_gate.hold(id, HeldAction::Start { max_failed_announces });
+            return;
+        }
+        match self.start_instance_with_limit(id, max_failed_announces, false).await {
+            Ok(()) => tracing::info!("Auto-started instance {}", id),
+            Err(e) => tracing::warn!("Failed to auto-start instance {}: {}", id, e),
+        }
+    }
+
     /// Save current state to disk
     pub async fn save_state(&self) -> Result<(), String> {
         let instances = self.instances.read().await;
 
         let mut persisted = PersistedState {
             instances: HashMap::new(),
-            version: 1,
+            version: STATE_VERSION,
         };
 
         for (id, instance) in instances.iter() {
+            // Demo replays are synthetic and don't survive a restart
+            if instance.source == InstanceSource::Replay {
+                continue;
+            }
+
             let stats = instance.faker.read().await.get_stats().await;
+            let history = PersistedHistory::from_stats(&stats);
 
             persisted.instances.insert(
                 id.clone(),
@@ -272,9 +758,12 @@ impl AppState {
                     cumulative_uploaded: stats.uploaded,
                     cumulative_downloaded: stats.downloaded,
                     state: stats.state,
+                    error: stats.error,
                     created_at: instance.created_at,
                     updated_at: now_timestamp(),
                     source: instance.source,
+                    history,
+                    labels: instance.labels.clone(),
                 },
             );
         }
@@ -282,9 +771,9 @@ impl AppState {
         self.persistence.save(&persisted).await
     }
 
-    /// Subscribe to log events
-    pub fn subscribe_logs(&self) -> broadcast::Receiver<LogEvent> {
-        self.log_sender.subscribe()
+    /// Subscribe to log events, returning the buffered recent history to replay first
+    pub fn subscribe_logs(&self) -> (Vec<LogEvent>, broadcast::Receiver<LogEvent>) {
+        self.log_history.subscribe(&self.log_sender)
     }
 
     /// Subscribe to instance events (for real-time sync with frontend)
@@ -303,22 +792,100 @@ impl AppState {
         nanoid::nanoid!(10) // 10 chars is short but collision-resistant enough
     }
 
+    /// Instance ID for a creation request carrying an idempotency key.
+    /// Retries with the same key within the TTL get the id handed out the first time,
+    /// as long as fewer than MAX_IDEMPOTENCY_KEYS newer keys came in since.
+    pub async fn instance_id_for_key(&self, key: &str) -> String {
+        let mut keys = self.idempotency_keys.lock().await;
+        let now = Instant::now();
+        keys.retain(|_, (_, expires)| *expires > now);
+
+        if let Some((id, _)) = keys.get(key) {
+            return id.clone();
+        }
+        if keys.len() >= MAX_IDEMPOTENCY_KEYS {
+            let oldest = keys
+                .iter()
+                .min_by_key(|(_, (_, expires))| *expires)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                keys.remove(&oldest);
+            }
+        }
+        let id = self.next_instance_id().await;
+        keys.insert(key.to_string(), (id.clone(), now + IDEMPOTENCY_KEY_TTL));
+        id
+    }
+
     /// Check if an instance exists
     pub async fn instance_exists(&self, id: &str) -> bool {
         self.instances.read().await.contains_key(id)
     }
 
+    /// IDs of all instances
+    pub async fn instance_ids(&self) -> Vec<String> {
+        self.instances.read().await.keys().cloned().collect()
+    }
+
     /// Update an existing instance's config (used when starting an existing instance with new config)
-    pub async fn update_instance_config(&self, id: &str, config: FakerConfig) -> Result<(), String> {
+    ///
+    /// Running, paused and idle-seeding fakers take the new config in place, keeping their session and history.
+    /// Idle and stopped ones are recreated from scratch.
+    pub async fn update_instance_config(&self, id: &str, config: FakerConfig) -> Result<(), InstanceError> {
+        validate_config(&config)?;
+        let _op_guard = self.lock_instance_ops(id).await.map_err(InstanceError::NotFound)?;
+
+        let faker = {
+            let instances = self.instances.read().await;
+            instances
+                .get(id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?
+                .faker
+                .clone()
+        };
+        let state = faker.read().await.get_stats().await.state;
+        if matches!(state, FakerState::Running | FakerState::Paused | FakerState::Seeding) {
+            let mut faker_config = config.clone();
+            apply_tracker_timeout(&mut faker_config);
+            let mut config = config;
+            {
+                let mut faker = faker.write().await;
+                faker
+                    .apply_config(faker_config)
+                    .await
+                    .map_err(|e| InstanceError::Invalid(e.to_string()))?;
+                keep_running_settings(&mut config, faker.get_config());
+            }
+
+            if let Some(instance) = self.instances.write().await.get_mut(id) {
+                instance.config = config;
+            }
+            return Ok(());
+        }
+
+        // The background task holds the old faker, stop it before replacing
+        let (shutdown_tx, task_handle) = {
+            let mut instances = self.instances.write().await;
+            let instance = instances
+                .get_mut(id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
+            (instance.shutdown_tx.take(), instance.task_handle.take())
+        };
+        Self::stop_background_task(shutdown_tx, task_handle).await;
+
         let mut instances = self.instances.write().await;
-        let instance = instances.get_mut(id).ok_or("Instance not found")?;
+        let instance = instances
+            .get_mut(id)
+            .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
 
         // Create a separate config for RatioFaker with cumulative stats as initial values
         let mut faker_config = config.clone();
         faker_config.initial_uploaded = instance.cumulative_uploaded;
         faker_config.initial_downloaded = instance.cumulative_downloaded;
+        apply_tracker_timeout(&mut faker_config);
 
-        let faker = RatioFaker::new(instance.torrent.clone(), faker_config).map_err(|e| e.to_string())?;
+        let faker = RatioFaker::new(instance.torrent.clone(), faker_config)
+            .map_err(|e| InstanceError::Invalid(e.to_string()))?;
 
         instance.faker = Arc::new(RwLock::new(faker));
         instance.config = config.clone(); // Store original user config (not modified)
@@ -328,9 +895,27 @@ impl AppState {
 
     /// Update only the config for an instance (without recreating the faker)
     /// Used to persist form changes before the faker is started
-    pub async fn update_instance_config_only(&self, id: &str, config: FakerConfig) -> Result<(), String> {
+    pub async fn update_instance_config_only(&self, id: &str, mut config: FakerConfig) -> Result<(), InstanceError> {
+        validate_config(&config)?;
+        let faker = self
+            .instances
+            .read()
+            .await
+            .get(id)
+            .map(|instance| instance.faker.clone())
+            .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
+        {
+            let faker = faker.read().await;
+            let state = faker.get_stats().await.state;
+            if matches!(state, FakerState::Running | FakerState::Paused | FakerState::Seeding) {
+                keep_running_settings(&mut config, faker.get_config());
+            }
+        }
+
         let mut instances = self.instances.write().await;
-        let instance = instances.get_mut(id).ok_or("Instance not found")?;
+        let instance = instances
+            .get_mut(id)
+            .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
 
         // Just update the stored config, don't recreate the faker
         instance.config = config;
@@ -344,15 +929,41 @@ impl AppState {
         Ok(())
     }
 
+    /// Replace an instance's config with a profile merged over the defaults, keeping its counters
+    pub async fn apply_profile(&self, id: &str, name: &str) -> Result<FakerConfig, InstanceError> {
+        let mut config = self.config.profile_faker_config(name).map_err(|e| match e {
+            rustatio_core::ConfigError::UnknownProfile(_) => InstanceError::NotFound(e.to_string()),
+            _ => InstanceError::Invalid(format!("Invalid profile '{}': {}", name, e)),
+        })?;
+
+        {
+            let instances = self.instances.read().await;
+            let instance = instances
+                .get(id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
+            config.initial_uploaded = instance.config.initial_uploaded;
+            config.initial_downloaded = instance.config.initial_downloaded;
+        }
+
+        self.update_instance_config_only(id, config.clone()).await?;
+        Ok(config)
+    }
+
     /// Create a new faker instance (manual creation via API)
-    pub async fn create_instance(&self, id: &str, torrent: TorrentInfo, config: FakerConfig) -> Result<(), String> {
+    pub async fn create_instance(
+        &self,
+        id: &str,
+        torrent: TorrentInfo,
+        config: FakerConfig,
+    ) -> Result<(), InstanceError> {
         let config = self.apply_faker_defaults(config);
-        self.create_instance_internal(id, torrent, config, InstanceSource::Manual).await
+        self.create_instance_internal(id, torrent, config, InstanceSource::Manual)
+            .await
     }
 
     /// Create a new idle faker instance (torrent loaded but not started)
     /// Used when user loads a torrent via UI - creates server-side instance so it persists on refresh
-    pub async fn create_idle_instance(&self, id: &str, torrent: TorrentInfo) -> Result<(), String> {
+    pub async fn create_idle_instance(&self, id: &str, torrent: TorrentInfo) -> Result<(), InstanceError> {
         // Use default config for idle instance
         let config = self.apply_faker_defaults(FakerConfig::default());
         self.create_instance_internal(id, torrent.clone(), config, InstanceSource::Manual)
@@ -369,6 +980,35 @@ impl AppState {
         Ok(())
     }
 
+    /// Create a new instance for `torrent` using a copy of another instance's config.
+    /// The new instance gets a fresh id and starts from zero cumulative stats.
+    pub async fn clone_instance(&self, source_id: &str, torrent: TorrentInfo) -> Result<String, InstanceError> {
+        let mut config = {
+            let instances = self.instances.read().await;
+            instances
+                .get(source_id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?
+                .config
+                .clone()
+        };
+        config.initial_uploaded = 0;
+        config.initial_downloaded = 0;
+
+        let id = self.next_instance_id().await;
+        self.create_instance_internal(&id, torrent.clone(), config, InstanceSource::Manual)
+            .await?;
+
+        // Emit event for real-time sync
+        self.emit_instance_event(InstanceEvent::Created {
+            id: id.clone(),
+            torrent_name: torrent.name,
+            info_hash: hex::encode(torrent.info_hash),
+            auto_started: false,
+        });
+
+        Ok(id)
+    }
+
     /// Create a new faker instance and emit an event for real-time sync
     /// Used by watch folder to notify connected frontends
     pub async fn create_instance_with_event(
@@ -377,7 +1017,7 @@ impl AppState {
         torrent: TorrentInfo,
         mut config: FakerConfig,
         auto_started: bool,
-    ) -> Result<(), String> {
+    ) -> Result<(), InstanceError> {
         config = self.apply_faker_defaults(config);
         self.create_instance_internal(id, torrent.clone(), config, InstanceSource::WatchFolder)
             .await?;
@@ -400,14 +1040,18 @@ impl AppState {
         torrent: TorrentInfo,
         config: FakerConfig,
         source: InstanceSource,
-    ) -> Result<(), String> {
+    ) -> Result<(), InstanceError> {
         // Set instance context for logging
         set_instance_context_str(Some(id));
+        validate_config(&config)?;
 
         let torrent_info_hash = torrent.info_hash;
 
-        // Check if instance exists and has same torrent - preserve cumulative stats and source
-        let (cumulative_uploaded, cumulative_downloaded, created_at, existing_source) = {
+        // Wait for any in-flight lifecycle operation on an instance being replaced
+        let op_guard = self.lock_instance_ops(id).await.ok();
+
+        // Check if instance exists and has same torrent - preserve cumulative stats, source and labels
+        let (cumulative_uploaded, cumulative_downloaded, created_at, existing_source, labels) = {
             let instances = self.instances.read().await;
             if let Some(existing) = instances.get(id) {
                 if existing.torrent_info_hash == torrent_info_hash {
@@ -416,12 +1060,13 @@ impl AppState {
                         existing.cumulative_downloaded,
                         existing.created_at,
                         Some(existing.source),
+                        existing.labels.clone(),
                     )
                 } else {
-                    (0, 0, now_timestamp(), None)
+                    (0, 0, now_timestamp(), None, Vec::new())
                 }
             } else {
-                (0, 0, now_timestamp(), None)
+                (0, 0, now_timestamp(), None, Vec::new())
             }
         };
 
@@ -434,8 +1079,10 @@ impl AppState {
         let mut faker_config = config.clone();
         faker_config.initial_uploaded = cumulative_uploaded;
         faker_config.initial_downloaded = cumulative_downloaded;
+        apply_tracker_timeout(&mut faker_config);
 
-        let faker = RatioFaker::new(torrent.clone(), faker_config).map_err(|e| e.to_string())?;
+        let faker =
+            RatioFaker::new(torrent.clone(), faker_config).map_err(|e| InstanceError::Invalid(e.to_string()))?;
 
         let instance = FakerInstance {
             faker: Arc::new(RwLock::new(faker)),
@@ -446,11 +1093,30 @@ impl AppState {
             cumulative_downloaded,
             created_at,
             source: final_source,
+            labels,
             task_handle: None,
             shutdown_tx: None,
+            // Keep sharing the lock with the replaced instance so waiting operations stay serialized
+            op_lock: op_guard
+                .as_ref()
+                .map(|guard| OwnedMutexGuard::mutex(guard).clone())
+                .unwrap_or_default(),
+            last_tick_unix: Arc::new(AtomicU64::new(0)),
+        };
+
+        let replaced = {
+            let mut instances = self.instances.write().await;
+            if !instances.contains_key(id) {
+                self.check_instance_limit(instances.len())?;
+            }
+            instances.insert(id.to_string(), instance)
         };
 
-        self.instances.write().await.insert(id.to_string(), instance);
+        // Don't leave a background task running for a replaced instance
+        if let Some(mut old) = replaced {
+            Self::stop_background_task(old.shutdown_tx.take(), old.task_handle.take()).await;
+        }
+        drop(op_guard);
 
         // Save state after creating instance
         if let Err(e) = self.save_state().await {
@@ -460,28 +1126,178 @@ impl AppState {
         Ok(())
     }
 
-    /// Start a faker instance
-    pub async fn start_instance(&self, id: &str) -> Result<(), String> {
+    /// Create a synthetic instance that replays a recorded stats timeline (demo mode)
+    pub async fn create_replay_instance(&self, timeline: ReplayTimeline) -> Result<String, InstanceError> {
+        timeline.validate().map_err(InstanceError::Invalid)?;
+        self.ensure_instance_capacity().await?;
+
+        let id = self.next_instance_id().await;
+        let torrent = timeline.torrent(&id);
+
+        let mut faker = RatioFaker::new(torrent.clone(), FakerConfig::default())
+            .map_err(|e| InstanceError::Invalid(e.to_string()))?;
+        faker.replace_stats(timeline.frames[0].clone()).await;
+        let faker = Arc::new(RwLock::new(faker));
+        let last_tick = Arc::new(AtomicU64::new(0));
+
+        let instance = FakerInstance {
+            faker: faker.clone(),
+            torrent_info_hash: torrent.info_hash,
+            torrent: torrent.clone(),
+            config: FakerConfig::default(),
+            cumulative_uploaded: 0,
+            cumulative_downloaded: 0,
+            created_at: now_timestamp(),
+            source: InstanceSource::Replay,
+            labels: Vec::new(),
+            task_handle: None,
+            shutdown_tx: None,
+            op_lock: Arc::default(),
+            last_tick_unix: last_tick.clone(),
+        };
+        self.instances.write().await.insert(id.clone(), instance);
+
+        self.emit_instance_event(InstanceEvent::Created {
+            id: id.clone(),
+            torrent_name: torrent.name,
+            info_hash: hex::encode(torrent.info_hash),
+            auto_started: true,
+        });
+
+        let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let task_handle = tokio::spawn(replay::replay_loop(
+            id.clone(),
+            faker,
+            self.clone(),
+            timeline,
+            last_tick,
+            shutdown_rx,
+        ));
+
+        if let Some(instance) = self.instances.write().await.get_mut(&id) {
+            instance.task_handle = Some(task_handle);
+            instance.shutdown_tx = Some(shutdown_tx);
+        }
+
+        Ok(id)
+    }
+
+    /// Refuse a new instance when `count` instances already reach MAX_INSTANCES
+    fn check_instance_limit(&self, count: usize) -> Result<(), InstanceError> {
+        match self.max_instances {
+            Some(max) if count >= max => Err(InstanceError::LimitReached(format!(
+                "{} ({} of {} instances, raise MAX_INSTANCES to allow more)",
+                INSTANCE_LIMIT_REACHED, count, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that another instance can be created without exceeding MAX_INSTANCES
+    pub async fn ensure_instance_capacity(&self) -> Result<(), InstanceError> {
+        self.check_instance_limit(self.instances.read().await.len())
+    }
+
+    /// Whether an instance replays recorded stats instead of talking to a tracker
+    async fn is_replay(&self, id: &str) -> bool {
+        self.instances
+            .read()
+            .await
+            .get(id)
+            .is_some_and(|instance| instance.source == InstanceSource::Replay)
+    }
+
+    /// Acquire the lifecycle operation lock for an instance.
+    /// Held for the whole duration of start/stop/pause/resume/delete so that concurrent
+    /// operations on the same instance run one after another instead of interleaving.
+    async fn lock_instance_ops(&self, id: &str) -> Result<OwnedMutexGuard<()>, String> {
+        let op_lock = {
+            let instances = self.instances.read().await;
+            instances.get(id).ok_or("Instance not found")?.op_lock.clone()
+        };
+        Ok(op_lock.lock_owned().await)
+    }
+
+    /// Signal a background task to stop and wait for it to finish.
+    /// The task is aborted if it doesn't exit in time, so it never outlives the instance's handle.
+    async fn stop_background_task(shutdown_tx: Option<mpsc::Sender<()>>, task_handle: Option<JoinHandle<()>>) {
+        if let Some(tx) = shutdown_tx {
+            let _ = tx.send(()).await;
+        }
+        if let Some(handle) = task_handle {
+            let abort_handle = handle.abort_handle();
+            if tokio::time::timeout(Duration::from_secs(2), handle).await.is_err() {
+                abort_handle.abort();
+            }
+        }
+    }
+
+    /// `ensure_no_active_duplicate` for an existing instance
+    async fn ensure_instance_not_duplicate(&self, id: &str) -> Result<(), InstanceError> {
+        let info_hash = {
+            let instances = self.instances.read().await;
+            instances
+                .get(id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?
+                .torrent_info_hash
+        };
+        self.ensure_no_active_duplicate(id, &info_hash).await
+    }
+
+    /// Start a faker instance, refused while another instance of the same torrent is active
+    pub async fn start_instance(&self, id: &str) -> Result<(), InstanceError> {
+        self.start_instance_with_limit(id, 0, false).await
+    }
+
+    /// Start a faker instance even if another instance of the same torrent is active
+    pub async fn force_start_instance(&self, id: &str) -> Result<(), InstanceError> {
+        self.start_instance_with_limit(id, 0, true).await
+    }
+
+    /// Start an instance whose background loop gives up after `max_failed_announces`
+    /// consecutive failed announces (0 = never)
+    async fn start_instance_with_limit(
+        &self,
+        id: &str,
+        max_failed_announces: u32,
+        allow_duplicate: bool,
+    ) -> Result<(), InstanceError> {
         // Set instance context for logging
         set_instance_context_str(Some(id));
 
-        let faker_arc = {
-            let mut instances = self.instances.write().await;
-            let instance = instances.get_mut(id).ok_or("Instance not found")?;
+        if self.is_replay(id).await {
+            return Err(InstanceError::Invalid(REPLAY_NOT_CONTROLLABLE.to_string()));
+        }
+        self.ensure_vpn().await.map_err(InstanceError::Failed)?;
 
-            // Stop existing background task if any
-            if let Some(tx) = instance.shutdown_tx.take() {
-                let _ = tx.send(()).await;
-            }
-            if let Some(handle) = instance.task_handle.take() {
-                handle.abort();
-            }
+        let _op_guard = self.lock_instance_ops(id).await.map_err(InstanceError::NotFound)?;
+        if !allow_duplicate {
+            self.ensure_instance_not_duplicate(id).await?;
+        }
 
-            instance.faker.clone()
+        let (faker_arc, shutdown_tx, task_handle, last_tick) = {
+            let mut instances = self.instances.write().await;
+            let instance = instances
+                .get_mut(id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
+            (
+                instance.faker.clone(),
+                instance.shutdown_tx.take(),
+                instance.task_handle.take(),
+                instance.last_tick_unix.clone(),
+            )
         };
 
+        // Stop existing background task if any
+        Self::stop_background_task(shutdown_tx, task_handle).await;
+
         // Start the faker (sends "started" announce)
-        faker_arc.write().await.start().await.map_err(|e| e.to_string())?;
+        faker_arc
+            .write()
+            .await
+            .start()
+            .await
+            .map_err(|e| InstanceError::Failed(e.to_string()))?;
 
         if let Err(e) = self.save_state().await {
             tracing::warn!("Failed to save state after start: {}", e);
@@ -494,8 +1310,20 @@ impl AppState {
         let instances_clone = self.instances.clone();
         let persistence_self = self.clone();
 
+        // A freshly (re)started instance counts as alive until its first tick
+        last_tick.store(now_timestamp(), Ordering::Relaxed);
+
         let task_handle = tokio::spawn(async move {
-            Self::background_update_loop(id_clone, faker_clone, instances_clone, persistence_self, shutdown_rx).await;
+            Self::background_update_loop(
+                id_clone,
+                faker_clone,
+                instances_clone,
+                persistence_self,
+                last_tick,
+                max_failed_announces,
+                shutdown_rx,
+            )
+            .await;
         });
 
         // Store task handle and shutdown sender
@@ -521,22 +1349,28 @@ impl AppState {
         faker: Arc<RwLock<RatioFaker>>,
         instances: Arc<RwLock<HashMap<String, FakerInstance>>>,
         state: AppState,
+        last_tick: Arc<AtomicU64>,
+        max_failed_announces: u32,
         mut shutdown_rx: mpsc::Receiver<()>,
     ) {
-        let update_interval = Duration::from_secs(5);
-        let save_interval = Duration::from_secs(30);
+        let update_interval = Duration::from_secs(update_interval_secs());
+        let save_interval = Duration::from_secs(save_interval_secs());
         let mut last_save = std::time::Instant::now();
         let mut last_state: Option<FakerState> = None;
+        let mut failed_announces: u32 = 0;
 
         tracing::info!("Background update loop started for instance {}", id);
 
         loop {
+            let next_announce = faker.read().await.get_stats().await.next_announce;
+            let until_announce = next_announce.map(|at| at.saturating_duration_since(std::time::Instant::now()));
+
             tokio::select! {
                 _ = shutdown_rx.recv() => {
                     tracing::info!("Background update loop received shutdown signal for instance {}", id);
                     break;
                 }
-                _ = tokio::time::sleep(update_interval) => {
+                _ = tokio::time::sleep(next_tick_delay(update_interval, until_announce)) => {
                     // 🔥 Check if instance still exists
                     let exists = {
                         let guard = instances.read().await;
@@ -548,22 +1382,51 @@ impl AppState {
                         break;
                     }
 
-                    // Update the faker
-                    if let Err(e) = faker.write().await.update().await {
-                        tracing::warn!("Background update failed for instance {}: {}", id, e);
+                    // Without a VPN nothing goes to the tracker until the gate pauses the instance
+                    if !state.vpn_gate.allows_announce().await {
+                        tracing::debug!("No VPN detected, deferring the update of instance {}", id);
+                        last_tick.store(now_timestamp(), Ordering::Relaxed);
+                        continue;
                     }
 
-                    // Detect state change
-                    let stats = faker.read().await.get_stats().await;
-                    if last_state != Some(stats.state.clone()) {
-                        last_state = Some(stats.state.clone());
-                        if let Err(e) = state.save_state().await {
-                            tracing::warn!("Failed to save state after state change: {}", e);
+                    // Wait for an announce permit, but never past a shutdown request
+                    let permit = tokio::select! {
+                        permit = state.announce_permits.clone().acquire_owned() => permit,
+                        _ = shutdown_rx.recv() => {
+                            tracing::info!("Background update loop received shutdown signal for instance {}", id);
+                            break;
+                        }
+                    };
+
+                    // Update the faker, giving up on a tracker that keeps failing
+                    let update = faker.write().await.update().await;
+                    match update {
+                        Ok(()) => failed_announces = 0,
+                        Err(e) => {
+                            tracing::warn!("Background update failed for instance {}: {}", id, e);
+                            failed_announces += 1;
+                            if max_failed_announces > 0 && failed_announces >= max_failed_announces {
+                                let reason = format!("{} consecutive announces failed, last error: {}", failed_announces, e);
+                                faker.write().await.mark_errored(reason).await;
+                            }
+                        }
+                    }
+                    drop(permit);
+
+                    // Record liveness so stuck loops can be detected externally
+                    last_tick.store(now_timestamp(), Ordering::Relaxed);
+
+                    // Detect state change
+                    let stats = faker.read().await.get_stats().await;
+                    if last_state != Some(stats.state.clone()) {
+                        last_state = Some(stats.state.clone());
+                        if let Err(e) = state.save_state().await {
+                            tracing::warn!("Failed to save state after state change: {}", e);
                         }
                     }
 
                     // Stop loop if no longer running
-                    if stats.state != FakerState::Running {
+                    if !matches!(stats.state, FakerState::Running | FakerState::Seeding) {
                         tracing::info!("Instance {} no longer running, stopping background loop", id);
 
                         if stats.state == FakerState::Stopped {
@@ -584,6 +1447,10 @@ impl AppState {
                             }
                         }
 
+                        // Given up: wait for a manual retry instead of ticking on
+                        if stats.state == FakerState::Errored {
+                            break;
+                        }
                     }
 
                     // Periodically save state
@@ -605,6 +1472,12 @@ impl AppState {
         // Set instance context for logging
         set_instance_context_str(Some(id));
 
+        if self.is_replay(id).await {
+            return Err(REPLAY_NOT_CONTROLLABLE.to_string());
+        }
+
+        let _op_guard = self.lock_instance_ops(id).await?;
+
         let (faker_arc, shutdown_tx, task_handle) = {
             let mut instances = self.instances.write().await;
             let instance = instances.get_mut(id).ok_or("Instance not found")?;
@@ -615,20 +1488,19 @@ impl AppState {
             )
         };
 
-        // Signal background task to stop
-        if let Some(tx) = shutdown_tx {
-            let _ = tx.send(()).await;
-        }
-        // Wait for task to finish (with timeout)
-        if let Some(handle) = task_handle {
-            let _ = tokio::time::timeout(Duration::from_secs(2), handle).await;
-        }
+        // Signal background task to stop and wait for it to finish
+        Self::stop_background_task(shutdown_tx, task_handle).await;
 
         // Get final stats before stopping
         let stats = faker_arc.read().await.get_stats().await;
 
-        // Stop the faker (sends "stopped" announce)
-        faker_arc.write().await.stop().await.map_err(|e| e.to_string())?;
+        // Stop the faker (sends "stopped" announce), without a word to the tracker when there's no VPN
+        if self.vpn_gate.allows_announce().await {
+            faker_arc.write().await.stop().await.map_err(|e| e.to_string())?;
+        } else {
+            tracing::warn!("No VPN detected, stopping instance {} without announcing", id);
+            faker_arc.write().await.stop_without_announce().await;
+        }
 
         if let Err(e) = self.save_state().await {
             tracing::warn!("Failed to save state after stop: {}", e);
@@ -656,6 +1528,12 @@ impl AppState {
         // Set instance context for logging
         set_instance_context_str(Some(id));
 
+        if self.is_replay(id).await {
+            return Err(REPLAY_NOT_CONTROLLABLE.to_string());
+        }
+
+        let _op_guard = self.lock_instance_ops(id).await?;
+
         let (faker_arc, shutdown_tx, task_handle) = {
             let mut instances = self.instances.write().await;
             let instance = instances.get_mut(id).ok_or("Instance not found")?;
@@ -666,14 +1544,8 @@ impl AppState {
             )
         };
 
-        // Signal background task to stop
-        if let Some(tx) = shutdown_tx {
-            let _ = tx.send(()).await;
-        }
-        // Wait for task to finish (with timeout)
-        if let Some(handle) = task_handle {
-            let _ = tokio::time::timeout(Duration::from_secs(2), handle).await;
-        }
+        // Signal background task to stop and wait for it to finish
+        Self::stop_background_task(shutdown_tx, task_handle).await;
 
         // Pause the faker
         faker_arc.write().await.pause().await.map_err(|e| e.to_string())?;
@@ -690,28 +1562,42 @@ impl AppState {
         Ok(())
     }
 
-    /// Resume a faker instance
-    pub async fn resume_instance(&self, id: &str) -> Result<(), String> {
+    /// Resume a faker instance, refused while another instance of the same torrent is active
+    pub async fn resume_instance(&self, id: &str) -> Result<(), InstanceError> {
         // Set instance context for logging
         set_instance_context_str(Some(id));
 
-        let faker_arc = {
-            let mut instances = self.instances.write().await;
-            let instance = instances.get_mut(id).ok_or("Instance not found")?;
+        if self.is_replay(id).await {
+            return Err(InstanceError::Invalid(REPLAY_NOT_CONTROLLABLE.to_string()));
+        }
+        self.ensure_vpn().await.map_err(InstanceError::Failed)?;
 
-            // Stop existing background task if any (shouldn't have one when paused, but be safe)
-            if let Some(tx) = instance.shutdown_tx.take() {
-                let _ = tx.send(()).await;
-            }
-            if let Some(handle) = instance.task_handle.take() {
-                handle.abort();
-            }
+        let _op_guard = self.lock_instance_ops(id).await.map_err(InstanceError::NotFound)?;
+        self.ensure_instance_not_duplicate(id).await?;
 
-            instance.faker.clone()
+        let (faker_arc, shutdown_tx, task_handle, last_tick) = {
+            let mut instances = self.instances.write().await;
+            let instance = instances
+                .get_mut(id)
+                .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
+            (
+                instance.faker.clone(),
+                instance.shutdown_tx.take(),
+                instance.task_handle.take(),
+                instance.last_tick_unix.clone(),
+            )
         };
 
+        // Stop existing background task if any (shouldn't have one when paused, but be safe)
+        Self::stop_background_task(shutdown_tx, task_handle).await;
+
         // Resume the faker
-        faker_arc.write().await.resume().await.map_err(|e| e.to_string())?;
+        faker_arc
+            .write()
+            .await
+            .resume()
+            .await
+            .map_err(|e| InstanceError::Failed(e.to_string()))?;
 
         if let Err(e) = self.save_state().await {
             tracing::warn!("Failed to save state after resume: {}", e);
@@ -724,8 +1610,20 @@ impl AppState {
         let instances_clone = self.instances.clone();
         let persistence_self = self.clone();
 
+        // A freshly (re)started instance counts as alive until its first tick
+        last_tick.store(now_timestamp(), Ordering::Relaxed);
+
         let task_handle = tokio::spawn(async move {
-            Self::background_update_loop(id_clone, faker_clone, instances_clone, persistence_self, shutdown_rx).await;
+            Self::background_update_loop(
+                id_clone,
+                faker_clone,
+                instances_clone,
+                persistence_self,
+                last_tick,
+                0,
+                shutdown_rx,
+            )
+            .await;
         });
 
         // Store task handle and shutdown sender
@@ -745,11 +1643,210 @@ impl AppState {
         Ok(())
     }
 
+    /// IDs of running or seeding instances (demo replays excluded)
+    async fn running_instance_ids(&self) -> Vec<String> {
+        let fakers: Vec<_> = {
+            let instances = self.instances.read().await;
+            instances
+                .iter()
+                .filter(|(_, instance)| instance.source != InstanceSource::Replay)
+                .map(|(id, instance)| (id.clone(), instance.faker.clone()))
+                .collect()
+        };
+        let mut running = Vec::new();
+        for (id, faker) in fakers {
+            let state = faker.read().await.get_stats().await.state;
+            if matches!(state, FakerState::Running | FakerState::Seeding) {
+                running.push(id);
+            }
+        }
+        running
+    }
+
+    /// Refuse to announce while REQUIRE_VPN is set and no VPN is detected
+    async fn ensure_vpn(&self) -> Result<(), String> {
+        if !self.vpn_gate.allows_announce().await {
+            return Err(VPN_REQUIRED.to_string());
+        }
+        Ok(())
+    }
+
+    /// Re-check the VPN gate: pause running instances when the VPN is gone, resume the held ones once it's back
+    pub async fn check_vpn_gate(&self) {
+        if !self.vpn_gate.required() {
+            return;
+        }
+
+        if self.vpn_gate.check().await {
+            let held = self.vpn_gate.take_held();
+            if !held.is_empty() {
+                tracing::info!("VPN is back, resuming {} held instance(s)", held.len());
+            }
+            for (id, action) in held {
+                // Skip instances stopped or deleted in the meantime
+                let waiting = self
+                    .get_stats(&id)
+                    .await
+                    .is_ok_and(|stats| matches!(stats.state, FakerState::Paused | FakerState::Idle));
+                if !waiting {
+                    continue;
+                }
+                let result = match action {
+                    HeldAction::Resume => self.resume_instance(&id).await,
+                    HeldAction::Start { max_failed_announces } => {
+                        self.start_instance_with_limit(&id, max_failed_announces, false).await
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::warn!("Failed to resume instance {} after the VPN came back: {}", id, e);
+                }
+            }
+            return;
+        }
+
+        let running = self.running_instance_ids().await;
+        if !running.is_empty() {
+            tracing::error!("No VPN detected, pausing {} running instance(s)", running.len());
+        }
+        for id in running {
+            match self.pause_instance(&id).await {
+                Ok(()) => self.vpn_gate.hold(&id, HeldAction::Resume),
+                Err(e) => tracing::warn!("Failed to pause instance {} without a VPN: {}", id, e),
+            }
+        }
+    }
+
+    /// Re-check the VPN gate in the background every VPN_CHECK_INTERVAL_SECS (REQUIRE_VPN only)
+    pub fn spawn_vpn_gate_monitor(&self) {
+        if !self.vpn_gate.required() {
+            return;
+        }
+        let state = self.clone();
+        let interval = network::vpn_check_interval();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                state.check_vpn_gate().await;
+            }
+        });
+    }
+
+    /// Refresh the swarm counts of running instances between announces, never announcing or changing
+    /// transfer stats. Instances on the same tracker share one scrape request.
+    async fn scrape_running_instances(&self) {
+        if !self.vpn_gate.allows_announce().await {
+            return;
+        }
+
+        let running: HashSet<String> = self.running_instance_ids().await.into_iter().collect();
+        let fakers: Vec<_> = {
+            let instances = self.instances.read().await;
+            instances
+                .iter()
+                .filter(|(id, _)| running.contains(*id))
+                .map(|(_, instance)| (instance.torrent_info_hash, instance.faker.clone()))
+                .collect()
+        };
+        let mut by_tracker: HashMap<String, Vec<_>> = HashMap::new();
+        for (info_hash, faker) in fakers {
+            let tracker_url = faker.read().await.tracker_url().to_string();
+            by_tracker.entry(tracker_url).or_default().push((info_hash, faker));
+        }
+
+        join_all(by_tracker.into_iter().map(|(tracker_url, fakers)| async move {
+            let Ok(_permit) = self.announce_permits.acquire().await else {
+                return;
+            };
+            let info_hashes: Vec<[u8; 20]> = fakers
+                .iter()
+                .map(|(info_hash, _)| *info_hash)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            let scrape = fakers[0].1.read().await.scrape_many(&info_hashes).await;
+            match scrape {
+                Ok(responses) => {
+                    for (info_hash, faker) in fakers {
+                        if let Some(response) = responses.get(&info_hash) {
+                            faker.write().await.apply_scrape(response).await;
+                        }
+                    }
+                }
+                Err(e) => tracing::debug!(
+                    "Background scrape of {} failed: {}",
+                    redact_tracker_url(&tracker_url),
+                    e
+                ),
+            }
+        }))
+        .await;
+    }
+
+    /// Scrape the trackers of running instances every SCRAPE_INTERVAL_SECS (opt-in)
+    pub fn spawn_scrape_monitor(&self) {
+        let Some(interval) = scrape_interval_secs() else {
+            return;
+        };
+        let state = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+                state.scrape_running_instances().await;
+            }
+        });
+    }
+
+    /// Stop every running instance (sending `stopped` announces) but keep them, so `resume_all` can bring
+    /// them back. Returns the outcome per stopped instance.
+    pub async fn stop_all(&self) -> Vec<(String, Result<(), String>)> {
+        let running = self.running_instance_ids().await;
+        let results = join_all(running.into_iter().map(|id| async move {
+            // Within MAX_CONCURRENT_ANNOUNCES like the background updates, instead of flooding the trackers
+            let result = match self.announce_permits.acquire().await {
+                Ok(_permit) => self.stop_instance(&id).await.map(|_| ()),
+                Err(e) => Err(e.to_string()),
+            };
+            (id, result)
+        }))
+        .await;
+
+        let stopped: Vec<String> = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(id, _)| id.clone())
+            .collect();
+        tracing::info!("Stopped {} instance(s) until resume-all", stopped.len());
+        self.quiesced.lock().await.extend(stopped);
+        results
+    }
+
+    /// Resume the instances stopped by `stop_all`. Returns the outcome per instance.
+    pub async fn resume_all(&self) -> Vec<(String, Result<(), String>)> {
+        let ids: Vec<String> = self.quiesced.lock().await.drain().collect();
+        join_all(ids.into_iter().map(|id| async move {
+            let result = match self.announce_permits.acquire().await {
+                Ok(_permit) => self.resume_instance(&id).await.map_err(String::from),
+                Err(e) => Err(e.to_string()),
+            };
+            (id, result)
+        }))
+        .await
+    }
+
     /// Update faker (send tracker announce)
     pub async fn update_instance(&self, id: &str) -> Result<FakerStats, String> {
         // Set instance context for logging
         set_instance_context_str(Some(id));
 
+        // Replays own their stats, just report the current frame
+        if self.is_replay(id).await {
+            return self.get_stats(id).await;
+        }
+
+        if !self.vpn_gate.allows_announce().await {
+            return Err(VPN_REQUIRED.to_string());
+        }
+
         let faker_arc = {
             let instances = self.instances.read().await;
             let instance = instances.get(id).ok_or("Instance not found")?;
@@ -766,6 +1863,16 @@ impl AppState {
         // Set instance context for logging
         set_instance_context_str(Some(id));
 
+        // Replays own their stats, just report the current frame
+        if self.is_replay(id).await {
+            return self.get_stats(id).await;
+        }
+
+        // A completion or stop condition would announce, so without a VPN the stats stay as they are
+        if !self.vpn_gate.allows_announce().await {
+            return self.get_stats(id).await;
+        }
+
         let faker_arc = {
             let instances = self.instances.read().await;
             let instance = instances.get(id).ok_or("Instance not found")?;
@@ -810,6 +1917,9 @@ impl AppState {
             }
         }
 
+        // Wait for any in-flight lifecycle operation (not found is fine, delete is idempotent)
+        let _op_guard = self.lock_instance_ops(id).await.ok();
+
         // Stop background task if running
         let (shutdown_tx, task_handle) = {
             let mut instances = self.instances.write().await;
@@ -820,14 +1930,8 @@ impl AppState {
             }
         };
 
-        // Signal background task to stop
-        if let Some(tx) = shutdown_tx {
-            let _ = tx.send(()).await;
-        }
-        // Wait for task to finish (with timeout)
-        if let Some(handle) = task_handle {
-            let _ = tokio::time::timeout(Duration::from_secs(2), handle).await;
-        }
+        // Signal background task to stop and wait for it to finish
+        Self::stop_background_task(shutdown_tx, task_handle).await;
 
         // Remove instance
         let removed = self.instances.write().await.remove(id);
@@ -851,7 +1955,6 @@ impl AppState {
     }
 
     /// Get a stored torrent
-    #[allow(dead_code)]
     pub async fn get_torrent(&self, id: &str) -> Option<TorrentInfo> {
         self.torrents.read().await.get(id).cloned()
     }
@@ -862,30 +1965,117 @@ impl AppState {
         let mut result = Vec::new();
 
         for (id, instance) in instances.iter() {
-            let stats = instance.faker.read().await.get_stats().await;
-
-            result.push(InstanceInfo {
-                id: id.clone(),
-                torrent: instance.torrent.clone(),
-                config: instance.config.clone(),
-                stats,
-                created_at: instance.created_at,
-                source: instance.source,
-            });
+            result.push(Self::instance_info(id, instance).await);
         }
 
+        // HashMap order changes between calls, creation order gives clients a stable list
+        sort_instances(&mut result, InstanceSort::Created, SortOrder::Asc);
         result
     }
 
+    /// Get a single instance with its current stats
+    pub async fn get_instance(&self, id: &str) -> Option<InstanceInfo> {
+        let instances = self.instances.read().await;
+        let instance = instances.get(id)?;
+        Some(Self::instance_info(id, instance).await)
+    }
+
+    /// Project an instance into the shape returned by the API
+    async fn instance_info(id: &str, instance: &FakerInstance) -> InstanceInfo {
+        let stats = instance
+            .faker
+            .read()
+            .await
+            .get_stats()
+            .await
+            .with_rounded_rates(rate_decimals());
+
+        InstanceInfo {
+            id: id.to_string(),
+            torrent: instance.torrent.clone(),
+            config: instance.config.clone(),
+            stats,
+            created_at: instance.created_at,
+            source: instance.source,
+            labels: instance.labels.clone(),
+            last_tick_unix: match instance.last_tick_unix.load(Ordering::Relaxed) {
+                0 => None,
+                tick => Some(tick),
+            },
+        }
+    }
+
     /// Find instance ID by info_hash
     pub async fn find_instance_by_info_hash(&self, info_hash: &[u8; 20]) -> Option<String> {
+        self.instances_by_info_hash(info_hash)
+            .await
+            .into_iter()
+            .next()
+            .map(|(id, _)| id)
+    }
+
+    /// Find the watch folder instance of the torrent `info_hash`
+    pub async fn find_watch_folder_instance(&self, info_hash: &[u8; 20]) -> Option<String> {
         let instances = self.instances.read().await;
-        for (id, instance) in instances.iter() {
-            if &instance.torrent_info_hash == info_hash {
-                return Some(id.clone());
+        instances
+            .iter()
+            .find(|(_, instance)| {
+                &instance.torrent_info_hash == info_hash && instance.source == InstanceSource::WatchFolder
+            })
+            .map(|(id, _)| id.clone())
+    }
+
+    /// Every instance of the torrent `info_hash`, with its faker
+    async fn instances_by_info_hash(&self, info_hash: &[u8; 20]) -> Vec<(String, Arc<RwLock<RatioFaker>>)> {
+        let instances = self.instances.read().await;
+        instances
+            .iter()
+            .filter(|(_, instance)| &instance.torrent_info_hash == info_hash)
+            .map(|(id, instance)| (id.clone(), instance.faker.clone()))
+            .collect()
+    }
+
+    /// Refuse to run `id` on the torrent `info_hash` while another instance of it is running, seeding or
+    /// paused. Two instances announcing one info_hash from the same IP look like a cheating client to trackers.
+    pub async fn ensure_no_active_duplicate(&self, id: &str, info_hash: &[u8; 20]) -> Result<(), InstanceError> {
+        for (other, faker) in self.instances_by_info_hash(info_hash).await {
+            if other == id {
+                continue;
+            }
+            let state = faker.read().await.get_stats().await.state;
+            if matches!(state, FakerState::Running | FakerState::Seeding | FakerState::Paused) {
+                return Err(InstanceError::Conflict(format!(
+                    "Torrent is already active in instance {}",
+                    other
+                )));
             }
         }
-        None
+        Ok(())
+    }
+
+    /// Replace an instance's labels (trimmed, empty and duplicate labels dropped)
+    pub async fn update_instance_labels(&self, id: &str, labels: Vec<String>) -> Result<Vec<String>, InstanceError> {
+        let _op_guard = self.lock_instance_ops(id).await.map_err(InstanceError::NotFound)?;
+        let mut normalized: Vec<String> = Vec::new();
+        for label in labels {
+            let label = label.trim();
+            if !label.is_empty() && !normalized.iter().any(|l| l == label) {
+                normalized.push(label.to_string());
+            }
+        }
+
+        let mut instances = self.instances.write().await;
+        let instance = instances
+            .get_mut(id)
+            .ok_or_else(|| InstanceError::NotFound("Instance not found".to_string()))?;
+        instance.labels = normalized.clone();
+        drop(instances);
+
+        if let Err(e) = self.save_state().await {
+            tracing::warn!("Failed to save state after updating instance labels: {}", e);
+        }
+
+        Ok(normalized)
     }
 
     /// Update an instance's source
@@ -916,6 +2106,62 @@ impl AppState {
         self.update_instance_source(&id, source).await
     }
 
+    /// Swap an instance's torrent for an updated file (e.g. new trackers), keeping its config.
+    /// Used when a watch folder file is replaced. A running or paused instance is stopped on the old
+    /// torrent and comes back in the same state on the new one. The counters are kept for the same
+    /// torrent and start over for a different one.
+    pub async fn replace_instance_torrent(&self, id: &str, torrent: TorrentInfo) -> Result<(), String> {
+        let faker = {
+            let instances = self.instances.read().await;
+            instances.get(id).ok_or("Instance not found")?.faker.clone()
+        };
+        let faker_state = faker.read().await.get_stats().await.state;
+        let was_running = matches!(faker_state, FakerState::Running | FakerState::Seeding);
+        let was_paused = faker_state == FakerState::Paused;
+        if was_running || was_paused {
+            // Tells the old trackers we're gone and updates the cumulative counters
+            self.stop_instance(id).await?;
+        }
+
+        {
+            let _op_guard = self.lock_instance_ops(id).await?;
+            let mut instances = self.instances.write().await;
+            let instance = instances.get_mut(id).ok_or("Instance not found")?;
+
+            // Another torrent's totals don't belong to this one
+            if instance.torrent_info_hash != torrent.info_hash {
+                instance.cumulative_uploaded = 0;
+                instance.cumulative_downloaded = 0;
+                instance.config.initial_uploaded = 0;
+                instance.config.initial_downloaded = 0;
+            }
+
+            let mut faker_config = instance.config.clone();
+            faker_config.initial_uploaded = instance.cumulative_uploaded;
+            faker_config.initial_downloaded = instance.cumulative_downloaded;
+            apply_tracker_timeout(&mut faker_config);
+            let faker = RatioFaker::new(torrent.clone(), faker_config).map_err(|e| e.to_string())?;
+
+            instance.faker = Arc::new(RwLock::new(faker));
+            instance.torrent_info_hash = torrent.info_hash;
+            instance.torrent = torrent.clone();
+        }
+        if let Some(stored) = self.torrents.write().await.get_mut(id) {
+            *stored = torrent;
+        }
+
+        if was_running || was_paused {
+            self.start_instance(id).await?;
+            if was_paused {
+                self.pause_instance(id).await?;
+            }
+        } else if let Err(e) = self.save_state().await {
+            tracing::warn!("Failed to save state after replacing torrent: {}", e);
+        }
+
+        Ok(())
+    }
+
     /// Delete an instance by info_hash (internal use - bypasses source check)
     /// Used when torrent file is removed from watch folder
     pub async fn delete_instance_by_info_hash(&self, info_hash: &[u8; 20]) -> Result<(), String> {
@@ -925,6 +2171,9 @@ impl AppState {
             None => return Ok(()), // No instance found, nothing to delete
         };
 
+        // Wait for any in-flight lifecycle operation
+        let _op_guard = self.lock_instance_ops(&id).await.ok();
+
         // Stop background task if running
         let (shutdown_tx, task_handle) = {
             let mut instances = self.instances.write().await;
@@ -935,14 +2184,8 @@ impl AppState {
             }
         };
 
-        // Signal background task to stop
-        if let Some(tx) = shutdown_tx {
-            let _ = tx.send(()).await;
-        }
-        // Wait for task to finish (with timeout)
-        if let Some(handle) = task_handle {
-            let _ = tokio::time::timeout(Duration::from_secs(2), handle).await;
-        }
+        // Signal background task to stop and wait for it to finish
+        Self::stop_background_task(shutdown_tx, task_handle).await;
 
         // Remove instance
         let removed = self.instances.write().await.remove(&id);
@@ -968,9 +2211,46 @@ pub struct InstanceInfo {
     pub id: String,
     pub torrent: TorrentInfo,
     pub config: FakerConfig,
+    /// Current stats, with rates rounded to RATE_DECIMALS
     pub stats: FakerStats,
     pub created_at: u64,
     pub source: InstanceSource,
+    pub labels: Vec<String>,
+    /// Unix timestamp of the last background loop tick (None if never started)
+    pub last_tick_unix: Option<u64>,
+}
+
+/// Sort key for listing instances
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceSort {
+    #[default]
+    Created,
+    Ratio,
+    Uploaded,
+    Name,
+}
+
+/// Sort direction for listing instances
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Sort instances in place. Ties keep their existing (creation) order when ascending.
+pub fn sort_instances(instances: &mut [InstanceInfo], sort: InstanceSort, order: SortOrder) {
+    match sort {
+        InstanceSort::Created => instances.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id))),
+        InstanceSort::Ratio => instances.sort_by(|a, b| a.stats.ratio.total_cmp(&b.stats.ratio)),
+        InstanceSort::Uploaded => instances.sort_by_key(|i| i.stats.uploaded),
+        InstanceSort::Name => instances.sort_by_cached_key(|i| i.torrent.name.to_lowercase()),
+    }
+    if order == SortOrder::Desc {
+        instances.reverse();
+    }
 }
 
 impl AppState {
@@ -1004,3 +2284,848 @@ impl AppState {
         tracing::info!("All background tasks stopped");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{routing::get, Router};
+
+    /// Spawn a minimal HTTP tracker that accepts every announce
+    #[test]
+    fn test_log_history_keeps_most_recent() {
+        let (sender, _) = broadcast::channel(16);
+        let history = LogHistory::new(3);
+
+        for i in 0..5 {
+            history.publish(&sender, LogEvent::new("info", format!("message {}", i)));
+        }
+
+        let (buffered, mut rx) = history.subscribe(&sender);
+        let messages: Vec<_> = buffered.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["message 2", "message 3", "message 4"]);
+
+        // Events published after subscribing only arrive live
+        history.publish(&sender, LogEvent::new("warn", "live".to_string()));
+        assert_eq!(rx.try_recv().unwrap().message, "live");
+
+        let disabled = LogHistory::new(0);
+        disabled.publish(&sender, LogEvent::new("info", "dropped".to_string()));
+        assert!(disabled.subscribe(&sender).0.is_empty());
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let warn = LogEvent::new("warn", "w".to_string()).with_target("rustatio_core::faker");
+        let info = LogEvent::new("info", "i".to_string()).with_target("rustatio_server::state");
+        let error = LogEvent::new("error", "e".to_string());
+
+        let all = LogFilter::parse(None, None).unwrap();
+        assert!(all.matches(&warn) && all.matches(&info) && all.matches(&error));
+
+        let by_level = LogFilter::parse(Some("WARN"), None).unwrap();
+        assert!(by_level.matches(&warn));
+        assert!(by_level.matches(&error));
+        assert!(!by_level.matches(&info));
+
+        let by_target = LogFilter::parse(None, Some("rustatio_core".to_string())).unwrap();
+        assert!(by_target.matches(&warn));
+        assert!(!by_target.matches(&info));
+        assert!(!by_target.matches(&error));
+
+        assert!(LogFilter::parse(Some("loud"), None).is_err());
+
+        let instance = warn.clone().with_instance_id("abc");
+        let other = info.clone().with_instance_id("xyz");
+        let mut by_instance = LogFilter::parse(None, None).unwrap();
+        by_instance.instance_id = Some("abc".to_string());
+        assert!(by_instance.matches(&instance));
+        assert!(!by_instance.matches(&other));
+        assert!(!by_instance.matches(&warn));
+    }
+
+    /// Spawn an HTTP tracker counting the announces it gets
+    async fn spawn_counting_tracker() -> (String, Arc<AtomicU64>) {
+        let announces = Arc::new(AtomicU64::new(0));
+        let counter = announces.clone();
+        let app = Router::new().route(
+            "/announce",
+            get(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                async { test_support::ANNOUNCE_RESPONSE }
+            }),
+        );
+        (format!("{}/announce", test_support::serve(app).await), announces)
+    }
+
+    #[test]
+    fn test_startup_delay_bounds() {
+        assert_eq!(startup_delay(0), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(startup_delay(3) < Duration::from_secs(3));
+            assert!(startup_delay(u64::MAX) < Duration::from_secs(MAX_STARTUP_JITTER_SECS));
+        }
+        assert_eq!(clamp_startup_jitter(u64::MAX), MAX_STARTUP_JITTER_SECS);
+        assert_eq!(clamp_startup_jitter(30), 30);
+    }
+
+    #[tokio::test]
+    async fn test_restore_with_startup_jitter() {
+        let data_dir = test_support::temp_dir();
+        let (announce_url, announces) = spawn_counting_tracker().await;
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(announce_url);
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+        state.start_instance("a").await.unwrap();
+        let announced_before = announces.load(Ordering::Relaxed);
+
+        // Kept short of the announce timeout, the paused clock may jump ahead while an announce is in flight
+        let jitter_secs = 2;
+        let mut config = AppConfig::default();
+        config.faker.startup_jitter_secs = jitter_secs;
+        let restored = AppState::new(data_dir.path().to_str().unwrap(), config);
+
+        // Restoring doesn't wait for the delayed start. The clock only moves when the test sleeps.
+        tokio::time::pause();
+        let restore_began = tokio::time::Instant::now();
+        assert_eq!(restored.load_saved_state().await.unwrap(), 1);
+        assert!(restore_began.elapsed() < Duration::from_secs(1));
+
+        // Once the jitter bound has passed the delayed start has fired, its announce runs on real time
+        tokio::time::sleep(Duration::from_secs(jitter_secs)).await;
+        tokio::time::resume();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while announces.load(Ordering::Relaxed) == announced_before {
+            assert!(std::time::Instant::now() < deadline, "no announce after restore");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        restored.stop_instance("a").await.unwrap();
+        state.stop_instance("a").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restored_instance_gives_up_on_dead_tracker() {
+        let data_dir = test_support::temp_dir();
+
+        // Tracker asking for the next announce right away, until it goes away
+        let up = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let tracker_up = up.clone();
+        let app = Router::new().route(
+            "/announce",
+            get(move || {
+                let up = tracker_up.load(Ordering::Relaxed);
+                async move {
+                    if up {
+                        Ok("d8:completei1e10:incompletei1e8:intervali0e5:peers0:e")
+                    } else {
+                        Err(axum::http::StatusCode::SERVICE_UNAVAILABLE)
+                    }
+                }
+            }),
+        );
+        let announce_url = format!("{}/announce", test_support::serve(app).await);
+
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let config = FakerConfig {
+            announce_max_retries: 0,
+            ..FakerConfig::default()
+        };
+        state
+            .create_instance_internal("a", TorrentInfo::fixture(announce_url), config, InstanceSource::Manual)
+            .await
+            .unwrap();
+        state.start_instance("a").await.unwrap();
+
+        let mut config = AppConfig::default();
+        config.faker.restore_max_failed_announces = 1;
+        let restored = AppState::new(data_dir.path().to_str().unwrap(), config);
+        assert_eq!(restored.load_saved_state().await.unwrap(), 1);
+        up.store(false, Ordering::Relaxed);
+
+        // Skip the background loop's one second floor between ticks, then let the announce run on real time
+        tokio::time::pause();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        tokio::time::resume();
+
+        // The first failed periodic announce is enough to give up
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let stats = loop {
+            let stats = restored.get_instance("a").await.unwrap().stats;
+            if stats.state == FakerState::Errored {
+                break stats;
+            }
+            assert!(std::time::Instant::now() < deadline, "restored instance never gave up");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        };
+        assert!(stats.error.unwrap().contains("announces failed"));
+
+        // The background loop exits instead of retrying forever
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let instances = restored.instances.read().await;
+        assert!(instances["a"].task_handle.as_ref().unwrap().is_finished());
+        drop(instances);
+
+        let _ = state.stop_instance("a").await;
+    }
+
+    #[tokio::test]
+    async fn test_errored_instance_restored_as_errored() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture("http://127.0.0.1:1/announce");
+        let config = FakerConfig {
+            announce_max_retries: 0,
+            ..FakerConfig::default()
+        };
+        state
+            .create_instance_internal("a", torrent, config, InstanceSource::Manual)
+            .await
+            .unwrap();
+        assert!(state.start_instance("a").await.is_err());
+        let stats = state.get_instance("a").await.unwrap().stats;
+        assert_eq!(stats.state, FakerState::Errored);
+        let reason = stats.error.unwrap();
+        state.save_state().await.unwrap();
+
+        // Not auto-started, and the reason survives the restart
+        let restored = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        assert_eq!(restored.load_saved_state().await.unwrap(), 1);
+        let stats = restored.get_instance("a").await.unwrap().stats;
+        assert_eq!(stats.state, FakerState::Errored);
+        assert_eq!(stats.error, Some(reason));
+        assert!(restored.instances.read().await["a"].task_handle.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_state_falls_back_to_backup() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture("http://127.0.0.1:1/announce");
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+        // The second save rotates the first one into state.json.1
+        state.save_state().await.unwrap();
+        state.save_state().await.unwrap();
+        assert!(data_dir.path().join("state.json.1").exists());
+
+        std::fs::write(data_dir.path().join("state.json"), b"{ not json").unwrap();
+
+        let restored = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        assert_eq!(restored.load_saved_state().await.unwrap(), 1);
+        assert!(restored.get_instance("a").await.is_some());
+        assert!(data_dir.path().join("state.json.corrupted").exists());
+    }
+
+    #[tokio::test]
+    async fn test_stop_while_waiting_for_announce_permit() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(test_support::spawn_tracker().await);
+
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+        state.start_instance("a").await.unwrap();
+
+        // Hold every permit so the loop blocks on its first update tick
+        let permits = state.announce_permits.available_permits() as u32;
+        let held = state.announce_permits.acquire_many(permits).await.unwrap();
+        tokio::time::pause();
+        tokio::time::sleep(Duration::from_secs(update_interval_secs() + 1)).await;
+
+        let started_tick = state.list_instances().await[0].last_tick_unix.unwrap();
+        let (shutdown_tx, handle) = {
+            let mut instances = state.instances.write().await;
+            let instance = instances.get_mut("a").unwrap();
+            (
+                instance.shutdown_tx.take().unwrap(),
+                instance.task_handle.take().unwrap(),
+            )
+        };
+        shutdown_tx.send(()).await.unwrap();
+
+        // The loop exits on its own instead of waiting for a permit
+        let exited = tokio::time::timeout(Duration::from_secs(1), handle).await;
+        assert!(matches!(exited, Ok(Ok(()))));
+        assert_eq!(state.list_instances().await[0].last_tick_unix, Some(started_tick));
+
+        drop(held);
+    }
+
+    #[tokio::test]
+    async fn test_sort_instances() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        for (id, name) in [("b", "Zebra"), ("a", "apple"), ("c", "Mango")] {
+            let mut torrent = TorrentInfo::fixture("http://tracker.example/announce");
+            torrent.name = name.to_string();
+            torrent.info_hash = [id.as_bytes()[0]; 20];
+            state
+                .create_instance_internal(id, torrent, FakerConfig::default(), InstanceSource::Manual)
+                .await
+                .unwrap();
+        }
+
+        let ids = |instances: &[InstanceInfo]| instances.iter().map(|i| i.id.clone()).collect::<Vec<_>>();
+
+        // Default listing is stable (created_at, then id for instances created in the same second)
+        for instance in state.instances.write().await.values_mut() {
+            instance.created_at = 1_700_000_000;
+        }
+        let mut instances = state.list_instances().await;
+        assert_eq!(ids(&instances), vec!["a", "b", "c"]);
+
+        sort_instances(&mut instances, InstanceSort::Name, SortOrder::Asc);
+        assert_eq!(ids(&instances), vec!["a", "c", "b"]);
+        sort_instances(&mut instances, InstanceSort::Name, SortOrder::Desc);
+        assert_eq!(ids(&instances), vec!["b", "c", "a"]);
+        sort_instances(&mut instances, InstanceSort::Created, SortOrder::Desc);
+        assert_eq!(ids(&instances), vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_next_tick_delay() {
+        let update_interval = Duration::from_secs(60);
+
+        assert_eq!(next_tick_delay(update_interval, None), update_interval);
+        assert_eq!(
+            next_tick_delay(update_interval, Some(Duration::from_secs(1800))),
+            update_interval
+        );
+        // A long update interval wakes up for a due announce instead of delaying it
+        assert_eq!(
+            next_tick_delay(update_interval, Some(Duration::from_secs(20))),
+            Duration::from_secs(20)
+        );
+        // An overdue announce doesn't turn the loop into a busy loop
+        assert_eq!(
+            next_tick_delay(update_interval, Some(Duration::ZERO)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            next_tick_delay(Duration::from_secs(5), Some(Duration::from_secs(20))),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_instances() {
+        let data_dir = test_support::temp_dir();
+        let mut state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        state.max_instances = Some(2);
+        let create = |id: &'static str| {
+            let mut torrent = TorrentInfo::fixture("http://tracker.example/announce");
+            torrent.info_hash = [id.as_bytes()[0]; 20];
+            state.create_instance_internal(id, torrent, FakerConfig::default(), InstanceSource::Manual)
+        };
+
+        create("a").await.unwrap();
+        create("b").await.unwrap();
+        let err = create("c").await.unwrap_err();
+        assert!(matches!(err, InstanceError::LimitReached(_)), "{}", err);
+        assert!(state.ensure_instance_capacity().await.is_err());
+        assert!(!state.instance_exists("c").await);
+
+        // Recreating an existing instance doesn't count against the cap
+        create("a").await.unwrap();
+
+        state.delete_instance("b", true).await.unwrap();
+        create("c").await.unwrap();
+        assert_eq!(state.instance_ids().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_clone_instance() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let config = FakerConfig {
+            upload_rate: 1234.0,
+            initial_uploaded: 5000,
+            ..FakerConfig::default()
+        };
+        let torrent = TorrentInfo::fixture("http://tracker.example/announce");
+        state
+            .create_instance_internal("a", torrent, config, InstanceSource::WatchFolder)
+            .await
+            .unwrap();
+
+        let mut other = TorrentInfo::fixture("http://other.example/announce");
+        other.info_hash = [9u8; 20];
+        let id = state.clone_instance("a", other).await.unwrap();
+        assert_ne!(id, "a");
+
+        let clone = state.get_instance(&id).await.unwrap();
+        assert_eq!(clone.config.upload_rate, 1234.0);
+        assert_eq!(clone.config.initial_uploaded, 0);
+        assert_eq!(clone.stats.uploaded, 0);
+        assert_eq!(clone.source, InstanceSource::Manual);
+        assert_eq!(clone.torrent.info_hash, [9u8; 20]);
+
+        let missing = TorrentInfo::fixture("http://tracker.example/announce");
+        assert!(state.clone_instance("missing", missing).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_start_stop_is_consistent() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(test_support::spawn_tracker().await);
+
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+
+        // Senders of every background task ever spawned; a closed sender means its task has exited
+        let mut senders: Vec<mpsc::Sender<()>> = Vec::new();
+
+        for _ in 0..10 {
+            let (start, stop, start_again) = tokio::join!(
+                state.start_instance("a"),
+                state.stop_instance("a"),
+                state.start_instance("a"),
+            );
+            assert!(start.is_ok() && stop.is_ok() && start_again.is_ok());
+
+            let instances = state.instances.read().await;
+            let instance = instances.get("a").unwrap();
+            let running = instance.faker.read().await.get_stats().await.state == FakerState::Running;
+
+            // A background task exists exactly when the faker is running
+            assert_eq!(instance.task_handle.is_some(), running);
+            assert_eq!(instance.shutdown_tx.is_some(), running);
+            if let Some(handle) = &instance.task_handle {
+                assert!(!handle.is_finished());
+            }
+            senders.extend(instance.shutdown_tx.clone());
+        }
+
+        state.stop_instance("a").await.unwrap();
+
+        // No background task outlives its instance handle
+        assert!(senders.iter().all(|tx| tx.is_closed()));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_config_update_keeps_running_faker() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(test_support::spawn_tracker().await);
+
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+        state.start_instance("a").await.unwrap();
+        let faker = state.instances.read().await.get("a").unwrap().faker.clone();
+
+        let config = FakerConfig {
+            upload_rate: 1234.0,
+            ..FakerConfig::default()
+        };
+        state.update_instance_config("a", config).await.unwrap();
+
+        let instances = state.instances.read().await;
+        let instance = instances.get("a").unwrap();
+        assert!(
+            Arc::ptr_eq(&instance.faker, &faker),
+            "running faker is updated in place"
+        );
+        assert!(instance.task_handle.is_some());
+        assert_eq!(instance.config.upload_rate, 1234.0);
+        assert_eq!(faker.read().await.get_stats().await.state, FakerState::Running);
+        drop(instances);
+
+        // The running faker keeps its port, and so does the stored config
+        let running_port = faker.read().await.get_config().port;
+        let config = FakerConfig {
+            port: running_port + 1,
+            ..FakerConfig::default()
+        };
+        state.update_instance_config("a", config.clone()).await.unwrap();
+        assert_eq!(state.get_instance("a").await.unwrap().config.port, running_port);
+        state.update_instance_config_only("a", config.clone()).await.unwrap();
+        assert_eq!(state.get_instance("a").await.unwrap().config.port, running_port);
+
+        state.stop_instance("a").await.unwrap();
+        // Recreated on the next update, with the new port
+        state.update_instance_config("a", config).await.unwrap();
+        let instance = state.get_instance("a").await.unwrap();
+        assert_eq!(instance.config.port, running_port + 1);
+        let faker = state.instances.read().await.get("a").unwrap().faker.clone();
+        assert_eq!(faker.read().await.get_config().port, running_port + 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_replace_torrent_keeps_paused() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(test_support::spawn_tracker().await);
+
+        state
+            .create_instance_internal(
+                "a",
+                torrent.clone(),
+                FakerConfig::default(),
+                InstanceSource::WatchFolder,
+            )
+            .await
+            .unwrap();
+        state.start_instance("a").await.unwrap();
+        let faker = state.instances.read().await.get("a").unwrap().faker.clone();
+        let stats = faker.read().await.get_stats().await;
+        faker
+            .write()
+            .await
+            .replace_stats(FakerStats {
+                uploaded: 5000,
+                downloaded: 3000,
+                ..stats
+            })
+            .await;
+        state.pause_instance("a").await.unwrap();
+
+        let replacement = TorrentInfo {
+            info_hash: [8u8; 20],
+            ..torrent
+        };
+        state.replace_instance_torrent("a", replacement).await.unwrap();
+
+        let instances = state.instances.read().await;
+        let instance = instances.get("a").unwrap();
+        assert_eq!(instance.torrent_info_hash, [8u8; 20]);
+        assert_eq!(instance.faker.read().await.get_stats().await.state, FakerState::Paused);
+        // The old torrent's totals aren't carried over to the new one
+        assert_eq!((instance.cumulative_uploaded, instance.cumulative_downloaded), (0, 0));
+        assert_eq!(instance.faker.read().await.get_stats().await.uploaded, 0);
+        drop(instances);
+
+        state.stop_instance("a").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_instance_id_for_key() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+
+        let first = state.instance_id_for_key("retry-1").await;
+        assert_eq!(state.instance_id_for_key("retry-1").await, first);
+        assert_ne!(state.instance_id_for_key("retry-2").await, first);
+
+        // Expired keys hand out a fresh id
+        state.idempotency_keys.lock().await.get_mut("retry-1").unwrap().1 = Instant::now();
+        let second = state.instance_id_for_key("retry-1").await;
+        assert_ne!(second, first);
+
+        // A flood of keys evicts the oldest instead of growing the map
+        for i in 0..MAX_IDEMPOTENCY_KEYS {
+            state.instance_id_for_key(&format!("flood-{}", i)).await;
+        }
+        let keys = state.idempotency_keys.lock().await;
+        assert_eq!(keys.len(), MAX_IDEMPOTENCY_KEYS);
+        assert!(!keys.contains_key("retry-1"));
+    }
+
+    #[tokio::test]
+    async fn test_vpn_gate() {
+        let data_dir = test_support::temp_dir();
+        let mut state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+
+        // Tracker counting the announces it gets
+        let announces = Arc::new(AtomicU64::new(0));
+        let counter = announces.clone();
+        let tracker = Router::new().route(
+            "/announce",
+            get(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                async { test_support::ANNOUNCE_RESPONSE }
+            }),
+        );
+        let torrent = TorrentInfo::fixture(format!("{}/announce", test_support::serve(tracker).await));
+
+        // Fake gluetun control server whose VPN can be switched off and on
+        let vpn_up = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let up = vpn_up.clone();
+        let gluetun = Router::new()
+            .route(
+                "/v1/vpn/status",
+                get(move || {
+                    let status = if up.load(Ordering::Relaxed) {
+                        "running"
+                    } else {
+                        "stopped"
+                    };
+                    async move { format!(r#"{{"status":"{}"}}"#, status) }
+                }),
+            )
+            .route("/v1/publicip/ip", get(|| async { r#"{"public_ip":"10.0.0.1"}"# }));
+        let control_url = test_support::serve(gluetun).await;
+        state.vpn_gate = Arc::new(VpnGate::new(true, control_url, Duration::ZERO));
+
+        for (id, info_hash) in [("a", [1u8; 20]), ("b", [2u8; 20]), ("c", [3u8; 20])] {
+            let torrent = TorrentInfo {
+                info_hash,
+                ..torrent.clone()
+            };
+            state
+                .create_instance_internal(id, torrent, FakerConfig::default(), InstanceSource::Manual)
+                .await
+                .unwrap();
+        }
+        state.start_instance("a").await.unwrap();
+
+        // VPN gone: new announces are refused, auto-starts held and running instances paused
+        vpn_up.store(false, Ordering::Relaxed);
+        assert_eq!(
+            state.start_instance("b").await,
+            Err(InstanceError::Failed(VPN_REQUIRED.to_string()))
+        );
+        assert_eq!(state.update_instance("a").await.unwrap_err(), VPN_REQUIRED);
+        state.start_or_hold("c", 3).await;
+        state.check_vpn_gate().await;
+        assert_eq!(state.get_stats("a").await.unwrap().state, FakerState::Paused);
+        let status = state.vpn_gate.status();
+        assert!(status.is_blocking());
+        assert_eq!(status.held_instances, vec!["a".to_string(), "c".to_string()]);
+
+        // VPN back: held instances resume or start
+        vpn_up.store(true, Ordering::Relaxed);
+        state.check_vpn_gate().await;
+        assert_eq!(state.get_stats("a").await.unwrap().state, FakerState::Running);
+        assert_eq!(state.get_stats("b").await.unwrap().state, FakerState::Idle);
+        assert_eq!(state.get_stats("c").await.unwrap().state, FakerState::Running);
+        assert!(!state.vpn_gate.status().is_blocking());
+        assert!(state.vpn_gate.status().held_instances.is_empty());
+
+        // Stopping without a VPN doesn't announce `stopped`
+        let sent = announces.load(Ordering::Relaxed);
+        vpn_up.store(false, Ordering::Relaxed);
+        state.stop_instance("a").await.unwrap();
+        assert_eq!(state.get_stats("a").await.unwrap().state, FakerState::Stopped);
+        assert_eq!(announces.load(Ordering::Relaxed), sent);
+
+        vpn_up.store(true, Ordering::Relaxed);
+        state.stop_instance("c").await.unwrap();
+        assert_eq!(announces.load(Ordering::Relaxed), sent + 1);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_grouped_by_tracker() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+
+        // Tracker answering scrapes for both torrents at once, counting the requests
+        let scrapes = Arc::new(AtomicU64::new(0));
+        let counter = scrapes.clone();
+        let mut files = b"d5:filesd".to_vec();
+        for (info_hash, seeders) in [([1u8; 20], 5), ([2u8; 20], 7)] {
+            files.extend_from_slice(b"20:");
+            files.extend_from_slice(&info_hash);
+            files.extend_from_slice(format!("d8:completei{}e10:downloadedi0e10:incompletei2ee", seeders).as_bytes());
+        }
+        files.extend_from_slice(b"ee");
+        let tracker = Router::new()
+            .route("/announce", get(|| async { test_support::ANNOUNCE_RESPONSE }))
+            .route(
+                "/scrape",
+                get(move || {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    let files = files.clone();
+                    async move { files }
+                }),
+            );
+        let torrent = TorrentInfo::fixture(format!("{}/announce", test_support::serve(tracker).await));
+
+        for (id, info_hash) in [("a", [1u8; 20]), ("b", [2u8; 20])] {
+            let torrent = TorrentInfo {
+                info_hash,
+                ..torrent.clone()
+            };
+            state
+                .create_instance_internal(id, torrent, FakerConfig::default(), InstanceSource::Manual)
+                .await
+                .unwrap();
+            state.start_instance(id).await.unwrap();
+        }
+
+        state.scrape_running_instances().await;
+        assert_eq!(scrapes.load(Ordering::Relaxed), 1, "one request per tracker");
+        assert_eq!(state.get_stats("a").await.unwrap().seeders, 5);
+        assert_eq!(state.get_stats("b").await.unwrap().seeders, 7);
+
+        state.stop_all().await;
+    }
+
+    #[tokio::test]
+    async fn test_stop_all_and_resume_all() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let announce = test_support::spawn_tracker().await;
+
+        for id in ["a", "b", "idle"] {
+            // Separate torrents, so both can run
+            let mut torrent = TorrentInfo::fixture(announce.clone());
+            torrent.info_hash = [id.as_bytes()[0]; 20];
+            state
+                .create_instance_internal(id, torrent, FakerConfig::default(), InstanceSource::Manual)
+                .await
+                .unwrap();
+        }
+        state.start_instance("a").await.unwrap();
+        state.start_instance("b").await.unwrap();
+
+        // Stops wait for announce permits like any other announce
+        let permits = state.announce_permits.available_permits() as u32;
+        let held = state.announce_permits.acquire_many(permits).await.unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(200), state.stop_all())
+            .await
+            .is_err());
+        assert_eq!(state.get_stats("a").await.unwrap().state, FakerState::Running);
+        drop(held);
+
+        let mut stopped = state.stop_all().await;
+        stopped.sort_by(|x, y| x.0.cmp(&y.0));
+        assert_eq!(stopped, vec![("a".to_string(), Ok(())), ("b".to_string(), Ok(()))]);
+        for id in ["a", "b"] {
+            assert_eq!(state.get_stats(id).await.unwrap().state, FakerState::Stopped);
+        }
+        assert!(state.instance_exists("a").await, "stopped instances are kept");
+
+        let resumed = state.resume_all().await;
+        assert_eq!(resumed.len(), 2);
+        assert!(resumed.iter().all(|(_, result)| result.is_ok()));
+        for id in ["a", "b"] {
+            assert_eq!(state.get_stats(id).await.unwrap().state, FakerState::Running);
+        }
+        assert_eq!(state.get_stats("idle").await.unwrap().state, FakerState::Idle);
+        assert!(state.resume_all().await.is_empty());
+
+        state.stop_instance("a").await.unwrap();
+        state.stop_instance("b").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_active_duplicate_refused() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(test_support::spawn_tracker().await);
+
+        for id in ["a", "b"] {
+            state
+                .create_instance_internal(id, torrent.clone(), FakerConfig::default(), InstanceSource::Manual)
+                .await
+                .unwrap();
+        }
+        // Idle copies don't conflict
+        assert!(state.ensure_no_active_duplicate("b", &torrent.info_hash).await.is_ok());
+
+        state.start_instance("a").await.unwrap();
+        assert!(state.ensure_no_active_duplicate("a", &torrent.info_hash).await.is_ok());
+        assert!(state.ensure_no_active_duplicate("c", &[9u8; 20]).await.is_ok());
+        assert!(matches!(
+            state.start_instance("b").await,
+            Err(InstanceError::Conflict(_))
+        ));
+
+        // A paused copy still blocks, for resume as well as start
+        state.pause_instance("a").await.unwrap();
+        assert!(matches!(
+            state.start_instance("b").await,
+            Err(InstanceError::Conflict(_))
+        ));
+        state.force_start_instance("b").await.unwrap();
+        assert!(matches!(
+            state.resume_instance("a").await,
+            Err(InstanceError::Conflict(_))
+        ));
+
+        state.stop_instance("a").await.unwrap();
+        state.stop_instance("b").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_labels_persisted() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture("http://tracker.example/announce");
+
+        state
+            .create_instance_internal("a", torrent.clone(), FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+        let labels = [" movies ", "", "movies", "hd"].map(String::from).to_vec();
+        let labels = state.update_instance_labels("a", labels).await.unwrap();
+        assert_eq!(labels, vec!["movies", "hd"]);
+
+        // Re-creating the same torrent keeps its labels
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+        assert_eq!(state.list_instances().await[0].labels, vec!["movies", "hd"]);
+
+        let info = state.get_instance("a").await.unwrap();
+        assert_eq!(info.id, "a");
+        assert_eq!(info.labels, vec!["movies", "hd"]);
+        assert!(state.get_instance("missing").await.is_none());
+
+        // Labels survive a restart
+        let restored = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        assert_eq!(restored.load_saved_state().await.unwrap(), 1);
+        assert_eq!(restored.list_instances().await[0].labels, vec!["movies", "hd"]);
+
+        assert!(matches!(
+            state.update_instance_labels("missing", Vec::new()).await,
+            Err(InstanceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_last_tick_advances() {
+        let data_dir = test_support::temp_dir();
+        let state = AppState::new(data_dir.path().to_str().unwrap(), AppConfig::default());
+        let torrent = TorrentInfo::fixture(test_support::spawn_tracker().await);
+
+        state
+            .create_instance_internal("a", torrent, FakerConfig::default(), InstanceSource::Manual)
+            .await
+            .unwrap();
+
+        let last_tick = |state: AppState| async move {
+            let instances = state.list_instances().await;
+            instances[0].last_tick_unix
+        };
+
+        // Never started: no tick yet
+        assert_eq!(last_tick(state.clone()).await, None);
+
+        // Freshly started: tick is recent
+        state.start_instance("a").await.unwrap();
+        let started_tick = last_tick(state.clone()).await.unwrap();
+        assert!(now_timestamp().saturating_sub(started_tick) <= 1);
+
+        // Pretend the last tick was long ago, the next update tick must advance it
+        {
+            let instances = state.instances.read().await;
+            instances["a"]
+                .last_tick_unix
+                .store(started_tick - 100, Ordering::Relaxed);
+        }
+        // With the clock paused, this returns as soon as the loop's next update tick has run
+        tokio::time::pause();
+        tokio::time::sleep(Duration::from_secs(update_interval_secs() + 1)).await;
+        let ticked = last_tick(state.clone()).await.unwrap();
+        assert!(ticked >= started_tick);
+
+        // The stopped announce goes over the network, where a paused clock fires timeouts early
+        tokio::time::resume();
+        state.stop_instance("a").await.unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+//! Helpers shared by the server's unit tests
+
+use axum::{routing::get, Router};
+
+/// Tracker announce answer: one seeder, one leecher, no peers and a 30 minute interval
+pub const ANNOUNCE_RESPONSE: &str = "d8:completei1e10:incompletei1e8:intervali1800e5:peers0:e";
+
+/// Fresh temporary directory, removed when dropped even if the test panics
+pub fn temp_dir() -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .prefix("rustatio-test-")
+        .tempdir()
+        .expect("create temp dir")
+}
+
+/// Serve `app` on a free local port, returning its base URL (`http://127.0.0.1:<port>`)
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    base
+}
+
+/// Spawn a tracker answering every announce with `ANNOUNCE_RESPONSE`, returning its announce URL
+pub async fn spawn_tracker() -> String {
+    let base = serve(Router::new().route("/announce", get(|| async { ANNOUNCE_RESPONSE }))).await;
+    format!("{}/announce", base)
+}
@@ -11,7 +11,9 @@ use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
+use tokio::time::Instant;
 
 /// Configuration for the watch folder service
 #[derive(Debug, Clone)]
@@ -22,8 +24,17 @@ pub struct WatchConfig {
     pub auto_start: bool,
     /// Whether the watch service is enabled
     pub enabled: bool,
+    /// Whether to also pick up torrents in subdirectories
+    pub recursive: bool,
 }
 
+/// Name of the directory imported torrents are moved into
+const ARCHIVED_DIR: &str = "archived";
+
+/// Quiet period after the last event for a file before it is imported.
+/// Writers that flush in chunks emit several events; they collapse into one import.
+const EVENT_DEBOUNCE: Duration = Duration::from_secs(1);
+
 /// Reason why watch folder is disabled
 #[derive(Debug, Clone)]
 pub enum WatchDisabledReason {
@@ -43,6 +54,10 @@ impl WatchConfig {
             .map(|v| v.to_lowercase() == "true" || v == "1")
             .unwrap_or(false);
 
+        let recursive = std::env::var("WATCH_RECURSIVE")
+            .map(|v| v.to_lowercase() == "true" || v == "1")
+            .unwrap_or(false);
+
         // Determine enabled status with reason tracking
         let (enabled, disabled_reason) = match std::env::var("WATCH_ENABLED") {
             Ok(val) => {
@@ -80,6 +95,7 @@ impl WatchConfig {
                 watch_dir: watch_path,
                 auto_start,
                 enabled,
+                recursive,
             },
             disabled_reason,
         )
@@ -193,22 +209,22 @@ impl WatchService {
         let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
         self.shutdown_tx = Some(shutdown_tx);
 
-        let watch_dir = self.config.watch_dir.clone();
-        let auto_start = self.config.auto_start;
+        let config = self.config.clone();
         let state = self.state.clone();
         let loaded_hashes = self.loaded_hashes.clone();
         let path_to_hash = self.path_to_hash.clone();
 
         tokio::spawn(async move {
-            if let Err(e) = run_watcher(watch_dir, auto_start, state, loaded_hashes, path_to_hash, shutdown_rx).await {
+            if let Err(e) = run_watcher(config, state, loaded_hashes, path_to_hash, shutdown_rx).await {
                 tracing::error!("Watch service error: {}", e);
             }
         });
 
         tracing::info!(
-            "Watch folder service started: {:?} (auto_start={})",
+            "Watch folder service started: {:?} (auto_start={}, recursive={})",
             self.config.watch_dir,
-            self.config.auto_start
+            self.config.auto_start,
+            self.config.recursive
         );
 
         Ok(())
@@ -224,8 +240,8 @@ impl WatchService {
 
     /// Scan directory for existing .torrent files
     async fn scan_directory(&self) {
-        let entries = match std::fs::read_dir(&self.config.watch_dir) {
-            Ok(entries) => entries,
+        let paths = match find_torrent_files(&self.config.watch_dir, self.config.recursive) {
+            Ok(paths) => paths,
             Err(e) => {
                 tracing::warn!("Failed to scan watch directory: {}", e);
                 return;
@@ -233,22 +249,19 @@ impl WatchService {
         };
 
         let mut count = 0;
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if is_torrent_file(&path) {
-                if let Err(e) = process_torrent_file(
-                    &path,
-                    self.config.auto_start,
-                    &self.state,
-                    &self.loaded_hashes,
-                    &self.path_to_hash,
-                )
-                .await
-                {
-                    tracing::warn!("Failed to process {:?}: {}", path, e);
-                } else {
-                    count += 1;
-                }
+        for path in paths {
+            if let Err(e) = process_torrent_file(
+                &path,
+                self.config.auto_start,
+                &self.state,
+                &self.loaded_hashes,
+                &self.path_to_hash,
+            )
+            .await
+            {
+                tracing::warn!("Failed to process {:?}: {}", path, e);
+            } else {
+                count += 1;
             }
         }
 
@@ -260,12 +273,8 @@ impl WatchService {
     /// Get status of the watch service
     pub async fn get_status(&self) -> WatchStatus {
         let loaded_count = self.loaded_hashes.read().await.len();
-        let file_count = std::fs::read_dir(&self.config.watch_dir)
-            .map(|entries| {
-                entries
-                    .filter(|e| e.as_ref().map(|e| is_torrent_file(&e.path())).unwrap_or(false))
-                    .count()
-            })
+        let file_count = find_torrent_files(&self.config.watch_dir, self.config.recursive)
+            .map(|paths| paths.len())
             .unwrap_or(0);
 
         WatchStatus {
@@ -282,23 +291,20 @@ impl WatchService {
         let mut files = Vec::new();
         let loaded_hashes = self.loaded_hashes.read().await;
 
-        let entries = match std::fs::read_dir(&self.config.watch_dir) {
-            Ok(entries) => entries,
+        let paths = match find_torrent_files(&self.config.watch_dir, self.config.recursive) {
+            Ok(paths) => paths,
             Err(_) => return files,
         };
 
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if !is_torrent_file(&path) {
-                continue;
-            }
-
+        for path in paths {
+            // Relative to the watch dir so nested files stay distinguishable (e.g. "movies/a.torrent")
             let filename = path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_default();
+                .strip_prefix(&self.config.watch_dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
 
-            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
 
             // Try to parse the torrent to get info
             let (status, info_hash, name) = match std::fs::read(&path) {
@@ -395,6 +401,36 @@ fn is_torrent_file(path: &Path) -> bool {
     path.is_file() && path.extension().map(|e| e == "torrent").unwrap_or(false)
 }
 
+/// Check if a path lies inside an `archived` directory below the watch dir
+fn is_archived(watch_dir: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(watch_dir).unwrap_or(path);
+    relative
+        .parent()
+        .map(|parent| parent.components().any(|c| c.as_os_str() == ARCHIVED_DIR))
+        .unwrap_or(false)
+}
+
+/// Collect the .torrent files in the watch dir, walking subdirectories (except `archived`) when recursive
+fn find_torrent_files(watch_dir: &Path, recursive: bool) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![watch_dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if recursive && entry.file_name() != ARCHIVED_DIR {
+                    pending.push(path);
+                }
+            } else if is_torrent_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
 /// Process a torrent file - load it and optionally start faking
 async fn process_torrent_file(
     path: &Path,
@@ -411,6 +447,43 @@ async fn process_torrent_file(
 
     let info_hash = torrent.info_hash;
 
+    // A file replacing one we imported earlier (same name, e.g. new trackers) updates that instance.
+    // Checked before duplicates: new trackers keep the info_hash. The same file dropped again is a duplicate.
+    let archived_path = archived_path(path)?;
+    let canonical = archived_path.canonicalize().unwrap_or(archived_path);
+    let archived = std::fs::read(&canonical).ok();
+    let mapped_hash = path_to_hash.read().await.get(&canonical).copied();
+    // The mapping doesn't survive restarts, the archived file itself still tells the old torrent
+    let previous_hash = mapped_hash.or_else(|| {
+        let data = archived.as_ref()?;
+        TorrentInfo::from_bytes(data).ok().map(|previous| previous.info_hash)
+    });
+    let unchanged = archived.is_some_and(|archived| archived == data);
+    if let Some(previous_hash) = previous_hash.filter(|_| !unchanged) {
+        if let Some(instance_id) = state.find_watch_folder_instance(&previous_hash).await {
+            state.replace_instance_torrent(&instance_id, torrent.clone()).await?;
+
+            {
+                let mut hashes = loaded_hashes.write().await;
+                hashes.remove(&previous_hash);
+                hashes.insert(info_hash);
+            }
+            path_to_hash.write().await.remove(&canonical);
+            if let Some(canonical) = archive_torrent_file(path) {
+                path_to_hash.write().await.insert(canonical, info_hash);
+            }
+
+            tracing::info!(
+                "Updated instance {} with replaced torrent file '{}' (info_hash: {} -> {})",
+                instance_id,
+                torrent.name,
+                hex::encode(previous_hash),
+                hex::encode(info_hash)
+            );
+            return Ok(());
+        }
+    }
+
     // Check for duplicates
     {
         let hashes = loaded_hashes.read().await;
@@ -424,6 +497,9 @@ async fn process_torrent_file(
         }
     }
 
+    // Fails before archiving, so the file stays in the folder and is retried on the next event or startup
+    state.ensure_instance_capacity().await?;
+
     // Create instance with event emission for real-time sync
     let instance_id = state.next_instance_id().await;
     let config = FakerConfig::default();
@@ -434,31 +510,7 @@ async fn process_torrent_file(
         .await?;
 
     // 🔥 Déplacer le fichier torrent dans /archived après importation
-    let archived_dir = path.parent().unwrap().join("archived");
-    if !archived_dir.exists() {
-        if let Err(e) = std::fs::create_dir_all(&archived_dir) {
-            tracing::warn!("Failed to create archived directory: {}", e);
-        }
-    }
-    
-    let filename = path.file_name().unwrap();
-    let archived_path = archived_dir.join(filename);
-
-    // Déclarer ici pour qu'il soit visible partout
-    let mut canonical_archived: Option<PathBuf> = None;
-
-    if let Err(e) = std::fs::rename(path, &archived_path) {
-        tracing::warn!("Failed to archive torrent file {:?}: {}", path, e);
-    } else {
-        tracing::info!("Archived torrent file to {:?}", archived_path);
-
-        let canonical = archived_path
-            .canonicalize()
-            .unwrap_or_else(|_| archived_path.clone());
-
-        path_to_hash.write().await.insert(canonical.clone(), info_hash);
-        canonical_archived = Some(canonical);
-    }
+    let canonical_archived = archive_torrent_file(path);
 
     // Track as loaded
     loaded_hashes.write().await.insert(info_hash);
@@ -474,22 +526,44 @@ async fn process_torrent_file(
         instance_id
     );
 
-    // Auto-start if enabled
+    // Auto-start if enabled, with the same startup jitter and VPN gate as restored instances
     if auto_start {
-        if let Err(e) = state.start_instance(&instance_id).await {
-            tracing::warn!("Failed to auto-start instance {}: {}", instance_id, e);
-        } else {
-            tracing::info!("Auto-started instance {}", instance_id);
-        }
+        state.auto_start_instance(instance_id, 0).await;
     }
 
     Ok(())
 }
 
+/// Where an imported torrent file ends up: `archived/` next to it
+fn archived_path(path: &Path) -> Result<PathBuf, String> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(filename)) => Ok(parent.join(ARCHIVED_DIR).join(filename)),
+        _ => Err(format!("Invalid torrent file path: {:?}", path)),
+    }
+}
+
+/// Move an imported torrent file into `archived/`, replacing an earlier file with the same name.
+/// Returns the canonical archived path, or None if the file couldn't be moved.
+fn archive_torrent_file(path: &Path) -> Option<PathBuf> {
+    let archived_path = archived_path(path).ok()?;
+    if let Some(archived_dir) = archived_path.parent().filter(|dir| !dir.exists()) {
+        if let Err(e) = std::fs::create_dir_all(archived_dir) {
+            tracing::warn!("Failed to create archived directory: {}", e);
+        }
+    }
+
+    if let Err(e) = std::fs::rename(path, &archived_path) {
+        tracing::warn!("Failed to archive torrent file {:?}: {}", path, e);
+        return None;
+    }
+    tracing::info!("Archived torrent file to {:?}", archived_path);
+
+    Some(archived_path.canonicalize().unwrap_or(archived_path))
+}
+
 /// Run the file watcher in a background task
 async fn run_watcher(
-    watch_dir: PathBuf,
-    auto_start: bool,
+    config: WatchConfig,
     state: AppState,
     loaded_hashes: Arc<RwLock<HashSet<[u8; 20]>>>,
     path_to_hash: Arc<RwLock<HashMap<PathBuf, [u8; 20]>>>,
@@ -508,36 +582,61 @@ async fn run_watcher(
     )
     .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
+    let WatchConfig {
+        watch_dir,
+        auto_start,
+        recursive,
+        ..
+    } = config;
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+
     // Start watching
     watcher
-        .watch(&watch_dir, RecursiveMode::NonRecursive)
+        .watch(&watch_dir, mode)
         .map_err(|e| format!("Failed to watch directory: {}", e))?;
 
     tracing::debug!("File watcher started for {:?}", watch_dir);
 
+    // Files waiting for their debounce deadline before being imported
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
     loop {
+        let next_deadline = pending.values().min().copied();
+
         tokio::select! {
             _ = shutdown_rx.recv() => {
                 tracing::debug!("File watcher received shutdown signal");
                 break;
             }
+            _ = tokio::time::sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                for path in take_due(&mut pending, Instant::now()) {
+                    // The file may have been moved or deleted while we waited
+                    if !is_torrent_file(&path) {
+                        continue;
+                    }
+
+                    if let Err(e) = process_torrent_file(
+                        &path,
+                        auto_start,
+                        &state,
+                        &loaded_hashes,
+                        &path_to_hash,
+                    ).await {
+                        tracing::warn!("Failed to process {:?}: {}", path, e);
+                    }
+                }
+            }
             Some(event) = rx.recv() => {
-                // Process create and modify events for .torrent files
+                // Queue create and modify events for .torrent files, restarting the quiet period
                 if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
                     for path in event.paths {
-                        if is_torrent_file(&path) {
-                            // Small delay to ensure file is fully written
-                            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-
-                            if let Err(e) = process_torrent_file(
-                                &path,
-                                auto_start,
-                                &state,
-                                &loaded_hashes,
-                                &path_to_hash,
-                            ).await {
-                                tracing::warn!("Failed to process {:?}: {}", path, e);
-                            }
+                        // Files we moved into `archived` were already imported
+                        if is_torrent_file(&path) && !is_archived(&watch_dir, &path) {
+                            pending.insert(path, Instant::now() + EVENT_DEBOUNCE);
                         }
                     }
                 }
@@ -552,9 +651,21 @@ async fn run_watcher(
                         let (info_hash, matched_path) = {
                             let mapping = path_to_hash.read().await;
 
+                            // The file is gone but its directory usually isn't, which tells
+                            // same-named files in different subdirectories apart
+                            let canonical = path
+                                .parent()
+                                .and_then(|parent| parent.canonicalize().ok())
+                                .zip(path.file_name())
+                                .map(|(parent, name)| parent.join(name));
+
                             // First try exact match with the path as given
                             if let Some(&hash) = mapping.get(&path) {
                                 (Some(hash), Some(path.clone()))
+                            } else if let Some((canonical, &hash)) =
+                                canonical.and_then(|c| mapping.get(&c).map(|hash| (c, hash)))
+                            {
+                                (Some(hash), Some(canonical))
                             } else {
                                 // Try to find by matching filename in watch directory
                                 // This handles the case where notify gives us a non-canonical path
@@ -611,3 +722,176 @@ async fn run_watcher(
 
     Ok(())
 }
+
+/// Remove and return the pending paths whose debounce deadline has passed
+fn take_due(pending: &mut HashMap<PathBuf, Instant>, now: Instant) -> Vec<PathBuf> {
+    let due: Vec<PathBuf> = pending
+        .iter()
+        .filter(|(_, &deadline)| deadline <= now)
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in &due {
+        pending.remove(path);
+    }
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_find_torrent_files_recursive() {
+        let dir = test_support::temp_dir();
+        std::fs::create_dir_all(dir.path().join("movies/hd")).unwrap();
+        std::fs::create_dir_all(dir.path().join(ARCHIVED_DIR)).unwrap();
+        std::fs::create_dir_all(dir.path().join("movies").join(ARCHIVED_DIR)).unwrap();
+        for file in [
+            "top.torrent",
+            "notes.txt",
+            "movies/a.torrent",
+            "movies/hd/b.torrent",
+            "archived/old.torrent",
+            "movies/archived/old.torrent",
+        ] {
+            std::fs::write(dir.path().join(file), b"").unwrap();
+        }
+
+        let relative = |recursive| {
+            let mut files: Vec<String> = find_torrent_files(dir.path(), recursive)
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(relative(false), vec!["top.torrent"]);
+        assert_eq!(
+            relative(true),
+            vec!["movies/a.torrent", "movies/hd/b.torrent", "top.torrent"]
+        );
+
+        assert!(is_archived(dir.path(), &dir.path().join("movies/archived/old.torrent")));
+        assert!(!is_archived(dir.path(), &dir.path().join("movies/a.torrent")));
+    }
+
+    #[test]
+    fn test_repeated_events_coalesce() {
+        let start = Instant::now();
+        let path = PathBuf::from("/torrents/a.torrent");
+        let mut pending = HashMap::new();
+
+        // Three chunked writes in quick succession keep pushing the deadline back
+        for offset_ms in [0, 200, 400] {
+            pending.insert(path.clone(), start + Duration::from_millis(offset_ms) + EVENT_DEBOUNCE);
+        }
+
+        assert!(take_due(&mut pending, start + EVENT_DEBOUNCE).is_empty());
+        assert_eq!(
+            take_due(&mut pending, start + Duration::from_millis(400) + EVENT_DEBOUNCE),
+            vec![path]
+        );
+        assert!(pending.is_empty());
+    }
+
+    /// Single-file torrent; a different length gives a different info_hash
+    fn torrent_file(announce: &str, length: u64) -> Vec<u8> {
+        format!(
+            "d8:announce{}:{}4:infod6:lengthi{}e4:name8:test.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee",
+            announce.len(),
+            announce,
+            length
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_auto_start_uses_startup_jitter() {
+        let dir = test_support::temp_dir();
+        let mut config = rustatio_core::AppConfig::default();
+        config.faker.startup_jitter_secs = 3600;
+        let state = AppState::new(dir.path().join("data").to_str().unwrap(), config);
+        let path = dir.path().join("a.torrent");
+        std::fs::write(&path, torrent_file("http://127.0.0.1:1/announce", 1024)).unwrap();
+
+        let loaded_hashes = Arc::new(RwLock::new(HashSet::new()));
+        let path_to_hash = Arc::new(RwLock::new(HashMap::new()));
+        process_torrent_file(&path, true, &state, &loaded_hashes, &path_to_hash)
+            .await
+            .unwrap();
+
+        // The start waits for its random delay instead of announcing right away
+        let instances = state.list_instances().await;
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].stats.state, rustatio_core::FakerState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_replaced_file_updates_instance() {
+        let dir = test_support::temp_dir();
+        let state = AppState::new(
+            dir.path().join("data").to_str().unwrap(),
+            rustatio_core::AppConfig::default(),
+        );
+        let loaded_hashes = Arc::new(RwLock::new(HashSet::new()));
+        let path_to_hash = Arc::new(RwLock::new(HashMap::new()));
+        let path = dir.path().join("a.torrent");
+
+        let import = |data: Vec<u8>| {
+            std::fs::write(&path, data).unwrap();
+            process_torrent_file(&path, false, &state, &loaded_hashes, &path_to_hash)
+        };
+
+        import(torrent_file("http://old.example.com/announce", 1024))
+            .await
+            .unwrap();
+        let instances = state.list_instances().await;
+        assert_eq!(instances.len(), 1);
+        let (id, old_hash) = (instances[0].id.clone(), instances[0].torrent.info_hash);
+
+        // Same filename, new trackers and info_hash
+        import(torrent_file("http://new.example.com/announce", 2048))
+            .await
+            .unwrap();
+        let instances = state.list_instances().await;
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].id, id);
+        assert_eq!(instances[0].torrent.announce, "http://new.example.com/announce");
+        let new_hash = instances[0].torrent.info_hash;
+        assert_ne!(new_hash, old_hash);
+        assert_eq!(state.find_instance_by_info_hash(&new_hash).await, Some(id.clone()));
+        assert_eq!(*loaded_hashes.read().await, HashSet::from([new_hash]));
+        assert_eq!(path_to_hash.read().await.values().collect::<Vec<_>>(), vec![&new_hash]);
+        assert!(!path.exists());
+
+        // After a restart the mapping is gone, the archived file still matches the instance
+        path_to_hash.write().await.clear();
+        import(torrent_file("http://newer.example.com/announce", 4096))
+            .await
+            .unwrap();
+        let instances = state.list_instances().await;
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].id, id);
+        assert_eq!(instances[0].torrent.announce, "http://newer.example.com/announce");
+        let hash = instances[0].torrent.info_hash;
+
+        // New trackers only keep the info_hash, that's still a replacement and not a duplicate
+        import(torrent_file("http://other.example.com/announce", 4096))
+            .await
+            .unwrap();
+        let instances = state.list_instances().await;
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].id, id);
+        assert_eq!(instances[0].torrent.info_hash, hash);
+        assert_eq!(instances[0].torrent.announce, "http://other.example.com/announce");
+
+        // The same file dropped again is a duplicate
+        import(torrent_file("http://other.example.com/announce", 4096))
+            .await
+            .unwrap();
+        assert_eq!(state.list_instances().await.len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 use rustatio_core::*;
+use serde::Serialize;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use wasm_bindgen::prelude::*;
 
 // Re-export the set_log_callback function from rustatio_core (WASM only)
@@ -187,6 +188,50 @@ pub async fn update_stats_only(id: u32) -> Result<JsValue, JsValue> {
     .await
 }
 
+/// Update several instances in one call.
+/// Takes an array of instance IDs and returns an object mapping each ID to its stats.
+/// Unknown IDs and instances whose update fails are left out of the result.
+#[wasm_bindgen]
+pub async fn update_all(ids: JsValue) -> Result<JsValue, JsValue> {
+    let ids: Vec<u32> = serde_wasm_bindgen::from_value(ids).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let results = update_instances(&ids).await;
+
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    results
+        .serialize(&serializer)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// Update the requested instances, skipping unknown IDs, and return the stats of
+// the ones that updated successfully. Every taken instance is put back.
+async fn update_instances(ids: &[u32]) -> BTreeMap<String, FakerStats> {
+    // Take every requested instance out in a single borrow
+    let taken: Vec<(u32, WasmFakerInstance)> = INSTANCES.with(|instances| {
+        let mut instances = instances.borrow_mut();
+        ids.iter()
+            .filter_map(|id| instances.remove(id).map(|instance| (*id, instance)))
+            .collect()
+    });
+
+    let mut results = BTreeMap::new();
+    let mut updated = Vec::with_capacity(taken.len());
+    for (id, mut instance) in taken {
+        rustatio_core::logger::set_instance_context(Some(id));
+        match instance.faker.update().await {
+            Ok(()) => {
+                results.insert(id.to_string(), instance.faker.get_stats().await);
+            }
+            Err(e) => rustatio_core::log_error!("Update failed: {}", e),
+        }
+        updated.push((id, instance));
+    }
+
+    // Put them all back in a single borrow
+    INSTANCES.with(|instances| instances.borrow_mut().extend(updated));
+
+    results
+}
+
 #[wasm_bindgen]
 pub async fn get_stats(id: u32) -> Result<JsValue, JsValue> {
     with_instance(id, |instance| async move {
@@ -197,6 +242,20 @@ pub async fn get_stats(id: u32) -> Result<JsValue, JsValue> {
     .await
 }
 
+/// Synchronous counterpart of `get_stats` for render loops.
+/// Fails with "Instance not found" while an async call on the same instance is in flight.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn peek_stats(id: u32) -> Result<JsValue, JsValue> {
+    INSTANCES.with(|instances| {
+        let instances = instances.borrow();
+        let instance = instances
+            .get(&id)
+            .ok_or_else(|| JsValue::from_str("Instance not found"))?;
+        serde_wasm_bindgen::to_value(&instance.faker.stats_snapshot()).map_err(|e| JsValue::from_str(&e.to_string()))
+    })
+}
+
 #[wasm_bindgen]
 pub async fn stop_faker(id: u32) -> Result<(), JsValue> {
     rustatio_core::logger::set_instance_context(Some(id));
@@ -272,6 +331,116 @@ pub async fn scrape_tracker(id: u32) -> Result<JsValue, JsValue> {
 
 #[wasm_bindgen]
 pub fn get_client_types() -> JsValue {
-    let types = vec!["utorrent", "qbittorrent", "transmission", "deluge"];
+    let types: Vec<&str> = ClientType::all().iter().map(ClientType::id).collect();
     serde_wasm_bindgen::to_value(&types).unwrap()
 }
+
+/// Emulated clients with their fingerprint details (peer ID prefix, User-Agent, crypto support, versions)
+#[wasm_bindgen]
+pub fn get_clients() -> JsValue {
+    serde_wasm_bindgen::to_value(&ClientFingerprint::all()).unwrap()
+}
+
+// Helper function to get the browser's localStorage
+fn local_storage() -> Result<web_sys::Storage, JsValue> {
+    web_sys::window()
+        .ok_or_else(|| JsValue::from_str("No window available"))?
+        .local_storage()?
+        .ok_or_else(|| JsValue::from_str("localStorage is not available"))
+}
+
+/// Set the proxy URL tracker requests are routed through
+#[wasm_bindgen]
+pub fn set_proxy_url(url: &str) -> Result<(), JsValue> {
+    let url = validate_proxy_url(url).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    local_storage()?.set_item(protocol::PROXY_URL_STORAGE_KEY, &url)?;
+    rustatio_core::log_info!("Tracker proxy set to {}", url);
+    Ok(())
+}
+
+/// Get the configured proxy URL, if any
+#[wasm_bindgen]
+pub fn get_proxy_url() -> Option<String> {
+    local_storage()
+        .ok()?
+        .get_item(protocol::PROXY_URL_STORAGE_KEY)
+        .ok()
+        .flatten()
+        .filter(|url| !url.is_empty())
+}
+
+/// Remove the proxy URL so tracker requests go out directly
+#[wasm_bindgen]
+pub fn clear_proxy_url() -> Result<(), JsValue> {
+    local_storage()?.remove_item(protocol::PROXY_URL_STORAGE_KEY)?;
+    rustatio_core::log_info!("Tracker proxy cleared");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEAD_TRACKER: &str = "http://127.0.0.1:1/announce";
+
+    fn insert_instance(id: u32) {
+        let config = FakerConfig {
+            announce_max_retries: 0,
+            ..FakerConfig::default()
+        };
+        let torrent = TorrentInfo::fixture(DEAD_TRACKER);
+        put_instance(
+            id,
+            WasmFakerInstance {
+                faker: RatioFaker::new(torrent, config).unwrap(),
+                torrent_name: "test".to_string(),
+                torrent_info_hash: [1u8; 20],
+                cumulative_uploaded: 0,
+                cumulative_downloaded: 0,
+            },
+        );
+    }
+
+    fn stored_ids() -> Vec<u32> {
+        INSTANCES.with(|instances| {
+            let mut ids: Vec<u32> = instances.borrow().keys().copied().collect();
+            ids.sort_unstable();
+            ids
+        })
+    }
+
+    #[tokio::test]
+    async fn test_update_instances_filters_ids() {
+        for id in [1, 2, 3] {
+            insert_instance(id);
+        }
+
+        // Unknown IDs are skipped, unrequested instances are left alone
+        let results = update_instances(&[1, 3, 42]).await;
+        assert_eq!(results.keys().collect::<Vec<_>>(), ["1", "3"]);
+        assert_eq!(results["1"].state, FakerState::Idle);
+        assert_eq!(stored_ids(), [1, 2, 3]);
+
+        assert!(update_instances(&[]).await.is_empty());
+        assert!(update_instances(&[42]).await.is_empty());
+        assert_eq!(stored_ids(), [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_update_instances_puts_back_failed_updates() {
+        insert_instance(1);
+        insert_instance(2);
+
+        // Instance 2 is due to announce to a tracker that refuses connections
+        let mut instance = take_instance(2).unwrap();
+        let mut stats = instance.faker.get_stats().await;
+        stats.state = FakerState::Running;
+        stats.next_announce = Some(instant::Instant::now());
+        instance.faker.replace_stats(stats).await;
+        put_instance(2, instance);
+
+        let results = update_instances(&[1, 2]).await;
+        assert_eq!(results.keys().collect::<Vec<_>>(), ["1"]);
+        assert_eq!(stored_ids(), [1, 2]);
+    }
+}
@@ -192,13 +192,24 @@ export async function initWasm() {
 }
 
 // Proxy configuration helpers
+// WASM validates and stores the URL; fall back to localStorage before it is loaded
 export function getProxyUrl() {
+  if (initialized && wasm) {
+    return wasm.get_proxy_url() || '';
+  }
   return localStorage.getItem('rustatio-proxy-url') || '';
 }
 
+// Throws with a readable message if the URL is malformed
 export function setProxyUrl(url) {
   if (url && url.trim()) {
-    localStorage.setItem('rustatio-proxy-url', url.trim());
+    if (initialized && wasm) {
+      wasm.set_proxy_url(url);
+    } else {
+      localStorage.setItem('rustatio-proxy-url', url.trim());
+    }
+  } else if (initialized && wasm) {
+    wasm.clear_proxy_url();
   } else {
     localStorage.removeItem('rustatio-proxy-url');
   }
@@ -231,6 +242,10 @@ export async function listenToLogs(callback) {
       eventSource.addEventListener('log', event => {
         try {
           const logEvent = JSON.parse(event.data);
+          // Server logs carry the instance as a field; display it like frontend-generated logs
+          if (logEvent.instance_id) {
+            logEvent.message = `[Instance ${logEvent.instance_id}] ${logEvent.message}`;
+          }
           callback(logEvent);
         } catch (e) {
           console.error('Failed to parse log event:', e);
@@ -347,7 +362,9 @@ async function serverFetch(endpoint, options = {}, logMessage = null) {
 
     if (!data.success) {
       emitLog('error', `API error: ${data.error || 'Unknown error'}`);
-      throw new Error(data.error || 'Unknown error');
+      const error = new Error(data.error || 'Unknown error');
+      error.statusCode = response.status;
+      throw error;
     }
 
     if (logMessage) {
@@ -454,15 +471,23 @@ const serverApi = {
       throw new Error(data.error || 'Failed to load torrent');
     }
 
+    if (data.data.duplicate_of) {
+      emitLog(
+        'warn',
+        `[Instance ${id}] This torrent is already loaded in instance ${data.data.duplicate_of}`
+      );
+    }
+
     emitLog(
       'info',
       `[Instance ${id}] Torrent loaded: ${data.data.torrent.name} (${formatBytes(data.data.torrent.total_size)})`
     );
     return data.data.torrent;
   },
-  startFaker: async (id, torrent, config) => {
+  startFaker: async (id, torrent, config, force = false) => {
     emitLog('info', `[Instance ${id}] Starting faker for ${torrent.name}`);
-    await serverFetch(`/faker/${id}/start`, {
+    const query = force ? '?force=true' : '';
+    await serverFetch(`/faker/${id}/start${query}`, {
       method: 'POST',
       body: JSON.stringify({ torrent, config }),
     });
@@ -505,8 +530,9 @@ const serverApi = {
   getClientTypes: async () => {
     return serverFetch('/clients', { method: 'GET' });
   },
-  getNetworkStatus: async () => {
-    return serverFetch('/network/status', { method: 'GET' });
+  getNetworkStatus: async (refresh = false) => {
+    const query = refresh ? '?refresh=true' : '';
+    return serverFetch(`/network/status${query}`, { method: 'GET' });
   },
   getConfig: async () => {
     try {
@@ -727,7 +753,7 @@ const tauriApi = {
     return invoke('scrape_tracker', { instanceId: id });
   },
   getClientTypes: async () => {
-    return ['utorrent', 'qbittorrent', 'transmission', 'deluge'];
+    return ['utorrent', 'qbittorrent', 'transmission', 'deluge', 'biglybt'];
   },
   getNetworkStatus: async () => {
     // Use fallback function for desktop
@@ -335,7 +335,7 @@ export const instanceActions = {
 
               // Set running state based on server state
               const state = serverInst.stats.state;
-              instance.isRunning = state === 'Running';
+              instance.isRunning = state === 'Running' || state === 'Seeding';
               instance.isPaused = state === 'Paused';
 
               if (instance.isRunning) {
@@ -344,6 +344,9 @@ export const instanceActions = {
               } else if (instance.isPaused) {
                 instance.statusMessage = 'Paused - restored from server';
                 instance.statusType = 'idle';
+              } else if (state === 'Errored') {
+                instance.statusMessage = 'Error: ' + (serverInst.stats.error || 'instance gave up');
+                instance.statusType = 'error';
               } else {
                 instance.statusMessage = 'Ready to start faking';
                 instance.statusType = 'idle';
@@ -620,7 +623,7 @@ export const instanceActions = {
 
     // Set running state based on server state
     const state = serverInst.stats.state;
-    instance.isRunning = state === 'Running';
+    instance.isRunning = state === 'Running' || state === 'Seeding';
     instance.isPaused = state === 'Paused';
 
     if (instance.isRunning) {
@@ -629,6 +632,9 @@ export const instanceActions = {
     } else if (instance.isPaused) {
       instance.statusMessage = 'Paused - added from watch folder';
       instance.statusType = 'idle';
+    } else if (state === 'Errored') {
+      instance.statusMessage = 'Error: ' + (serverInst.stats.error || 'instance gave up');
+      instance.statusType = 'error';
     } else {
       instance.statusMessage = 'Ready to start - added from watch folder';
       instance.statusType = 'idle';